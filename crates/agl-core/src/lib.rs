@@ -21,6 +21,8 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub mod rw_sync;
 pub mod buffer;
+pub mod version;
+pub mod vdf;
 
 pub mod tasks;
 
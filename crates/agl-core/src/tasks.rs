@@ -18,7 +18,7 @@
 
 pub use tokio::{fs, io, net, sync, join, try_join, select};
 pub use tokio::time::{sleep, sleep_until};
-pub use tokio::task::{JoinHandle, JoinError};
+pub use tokio::task::{JoinHandle, JoinError, AbortHandle};
 
 use tokio::runtime::{Runtime, Builder};
 
@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-core
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Steam's binary VDF format used by files like `shortcuts.vdf`.
+
+const VDF_MAP: u8    = 0x00;
+const VDF_STRING: u8 = 0x01;
+const VDF_INT: u8    = 0x02;
+const VDF_END: u8    = 0x08;
+
+/// Maximal nesting depth of the VDF maps.
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, thiserror::Error)]
+pub enum VdfError {
+    #[error("unexpected end of file")]
+    UnexpectedEof,
+
+    #[error("unterminated string at offset {0}")]
+    UnterminatedString(usize),
+
+    #[error("unsupported value type {kind:#04x} at offset {offset}")]
+    UnsupportedType {
+        kind: u8,
+        offset: usize
+    },
+
+    #[error("maps nesting is deeper than {MAX_DEPTH} levels")]
+    TooDeep
+}
+
+/// Value of the binary VDF file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VdfValue {
+    Map(Vec<(String, VdfValue)>),
+    String(String),
+    Int(u32)
+}
+
+impl VdfValue {
+    /// Get value of the map's key. Keys are case insensitive.
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&VdfValue> {
+        let Self::Map(entries) = self else {
+            return None;
+        };
+
+        entries.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key.as_ref()))
+            .map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    #[inline]
+    pub fn as_int(&self) -> Option<u32> {
+        match self {
+            Self::Int(value) => Some(*value),
+            _ => None
+        }
+    }
+}
+
+/// Decode entries of the binary VDF file.
+pub fn decode(bytes: &[u8]) -> Result<Vec<(String, VdfValue)>, VdfError> {
+    fn read_cstring(bytes: &[u8], offset: &mut usize) -> Result<String, VdfError> {
+        let len = bytes.get(*offset..)
+            .and_then(|bytes| bytes.iter().position(|byte| *byte == 0))
+            .ok_or(VdfError::UnterminatedString(*offset))?;
+
+        let value = String::from_utf8_lossy(&bytes[*offset..*offset + len]).to_string();
+
+        *offset += len + 1;
+
+        Ok(value)
+    }
+
+    fn read_map(
+        bytes: &[u8],
+        offset: &mut usize,
+        depth: usize
+    ) -> Result<Vec<(String, VdfValue)>, VdfError> {
+        if depth > MAX_DEPTH {
+            return Err(VdfError::TooDeep);
+        }
+
+        let mut entries = Vec::new();
+
+        loop {
+            let kind_offset = *offset;
+
+            let Some(kind) = bytes.get(kind_offset).copied() else {
+                return Err(VdfError::UnexpectedEof);
+            };
+
+            *offset += 1;
+
+            if kind == VDF_END {
+                return Ok(entries);
+            }
+
+            let key = read_cstring(bytes, offset)?;
+
+            let value = match kind {
+                VDF_MAP => VdfValue::Map(read_map(bytes, offset, depth + 1)?),
+                VDF_STRING => VdfValue::String(read_cstring(bytes, offset)?),
+
+                VDF_INT => {
+                    let Some(value) = bytes.get(*offset..*offset + 4) else {
+                        return Err(VdfError::UnexpectedEof);
+                    };
+
+                    *offset += 4;
+
+                    VdfValue::Int(u32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+                }
+
+                _ => return Err(VdfError::UnsupportedType {
+                    kind,
+                    offset: kind_offset
+                })
+            };
+
+            entries.push((key, value));
+        }
+    }
+
+    read_map(bytes, &mut 0, 0)
+}
+
+/// Encode entries to the binary VDF file.
+pub fn encode(entries: &[(String, VdfValue)]) -> Result<Vec<u8>, VdfError> {
+    fn write_map(
+        buf: &mut Vec<u8>,
+        entries: &[(String, VdfValue)],
+        depth: usize
+    ) -> Result<(), VdfError> {
+        if depth > MAX_DEPTH {
+            return Err(VdfError::TooDeep);
+        }
+
+        for (key, value) in entries {
+            match value {
+                VdfValue::Map(entries) => {
+                    buf.push(VDF_MAP);
+                    buf.extend_from_slice(key.as_bytes());
+                    buf.push(0);
+
+                    write_map(buf, entries, depth + 1)?;
+                }
+
+                VdfValue::String(value) => {
+                    buf.push(VDF_STRING);
+                    buf.extend_from_slice(key.as_bytes());
+                    buf.push(0);
+                    buf.extend_from_slice(value.as_bytes());
+                    buf.push(0);
+                }
+
+                VdfValue::Int(value) => {
+                    buf.push(VDF_INT);
+                    buf.extend_from_slice(key.as_bytes());
+                    buf.push(0);
+                    buf.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+
+        buf.push(VDF_END);
+
+        Ok(())
+    }
+
+    let mut buf = Vec::new();
+
+    write_map(&mut buf, entries, 0)?;
+
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORTCUTS: &[u8] = include_bytes!("../tests/shortcuts.vdf");
+
+    #[test]
+    fn decode_shortcuts() -> Result<(), VdfError> {
+        let entries = decode(SHORTCUTS)?;
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "shortcuts");
+
+        let shortcuts = &entries[0].1;
+
+        let flatpak = shortcuts.get("0").unwrap();
+        let lutris = shortcuts.get("1").unwrap();
+
+        assert_eq!(flatpak.get("AppName").and_then(VdfValue::as_str), Some("Genshin Impact"));
+        assert_eq!(flatpak.get("exe").and_then(VdfValue::as_str), Some("\"/usr/bin/flatpak\""));
+        assert_eq!(flatpak.get("tags").and_then(|tags| tags.get("0")).and_then(VdfValue::as_str), Some("favorite"));
+        assert_eq!(flatpak.get("LastPlayTime").and_then(VdfValue::as_int), Some(1729300000));
+
+        assert_eq!(lutris.get("appid").and_then(VdfValue::as_int), Some(0xc527428a));
+        assert_eq!(lutris.get("tags"), Some(&VdfValue::Map(vec![])));
+
+        assert!(shortcuts.get("2").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), VdfError> {
+        assert_eq!(encode(&decode(SHORTCUTS)?)?, SHORTCUTS);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(matches!(decode(&SHORTCUTS[..SHORTCUTS.len() - 1]), Err(VdfError::UnexpectedEof)));
+        assert!(matches!(decode(b"\x01key\x00value"), Err(VdfError::UnterminatedString(5))));
+        assert!(matches!(decode(b"\x07key\x00\x08"), Err(VdfError::UnsupportedType { kind: 0x07, offset: 0 })));
+    }
+
+    #[test]
+    fn too_deep() {
+        let nested = b"\x00k\x00".repeat(100_000);
+
+        assert!(matches!(decode(&nested), Err(VdfError::TooDeep)));
+
+        let mut value = VdfValue::Map(vec![]);
+
+        for _ in 1..MAX_DEPTH {
+            value = VdfValue::Map(vec![(String::from("k"), value)]);
+        }
+
+        assert!(encode(&[(String::from("k"), value.clone())]).is_ok());
+
+        let value = VdfValue::Map(vec![(String::from("k"), value)]);
+
+        assert!(matches!(encode(&[(String::from("k"), value)]), Err(VdfError::TooDeep)));
+    }
+}
@@ -0,0 +1,545 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-core
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Versions parsing and comparison. Supports semantic versions as well as
+//! looser dotted schemes used by games (`1.2.3.45`, `v2.1`, `1.0rc1`).
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[derive(Debug, thiserror::Error)]
+pub enum VersionError {
+    #[error("empty version string")]
+    Empty,
+
+    #[error("invalid version: {0}")]
+    InvalidVersion(String),
+
+    #[error("invalid version range: {0}")]
+    InvalidRange(String)
+}
+
+/// Pre-release version identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String)
+}
+
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Numeric(a), Self::Numeric(b)) => a.cmp(b),
+            (Self::Alphanumeric(a), Self::Alphanumeric(b)) => a.cmp(b),
+
+            // Numeric identifiers always have lower precedence.
+            (Self::Numeric(_), Self::Alphanumeric(_)) => Ordering::Less,
+            (Self::Alphanumeric(_), Self::Numeric(_)) => Ordering::Greater
+        }
+    }
+}
+
+impl PartialOrd for Identifier {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Numeric(value) => write!(f, "{value}"),
+            Self::Alphanumeric(value) => f.write_str(value)
+        }
+    }
+}
+
+/// Parsed version. Unlike semver it can have any amount of numeric components,
+/// and missing components are considered to be zeros, so `1.2` equals
+/// `1.2.0`. Build metadata is ignored in comparisons.
+#[derive(Debug, Clone)]
+pub struct Version {
+    numbers: Vec<u64>,
+    pre: Vec<Identifier>,
+    build: Option<String>
+}
+
+impl Version {
+    /// Create new version from its numeric components.
+    pub fn new(numbers: impl Into<Vec<u64>>) -> Self {
+        Self {
+            numbers: numbers.into(),
+            pre: vec![],
+            build: None
+        }
+    }
+
+    #[inline]
+    pub fn numbers(&self) -> &[u64] {
+        &self.numbers
+    }
+
+    #[inline]
+    pub fn major(&self) -> u64 {
+        self.numbers.first().copied().unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn minor(&self) -> u64 {
+        self.numbers.get(1).copied().unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn patch(&self) -> u64 {
+        self.numbers.get(2).copied().unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn pre(&self) -> &[Identifier] {
+        &self.pre
+    }
+
+    #[inline]
+    pub fn build(&self) -> Option<&str> {
+        self.build.as_deref()
+    }
+
+    #[inline]
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Get the lowest version which is greater than all the versions starting
+    /// with the first `len` components of the current one. For example, for
+    /// `1.2.3` and 2 components it's `1.3`.
+    fn bump(&self, len: usize) -> Self {
+        let mut numbers = self.numbers.clone();
+
+        numbers.resize(len.max(1), 0);
+
+        if let Some(last) = numbers.last_mut() {
+            *last = last.saturating_add(1);
+        }
+
+        Self::new(numbers)
+    }
+}
+
+impl FromStr for Version {
+    type Err = VersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s.trim();
+
+        let version = version.strip_prefix(['v', 'V'])
+            .unwrap_or(version);
+
+        if version.is_empty() {
+            return Err(VersionError::Empty);
+        }
+
+        let (version, build) = match version.split_once('+') {
+            Some((version, build)) => (version, Some(build.to_string())),
+            None => (version, None)
+        };
+
+        // Numeric components end with the first character which is not a
+        // digit or a dot (`1.0-rc1`, `1.0rc1`).
+        let numbers_len = version.find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+
+        let numbers = version[..numbers_len].trim_end_matches('.')
+            .split('.')
+            .map(u64::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| VersionError::InvalidVersion(s.to_string()))?;
+
+        let pre = version[numbers_len..].trim_start_matches(['-', '_', '.']);
+
+        let pre = if pre.is_empty() {
+            vec![]
+        } else {
+            pre.split('.')
+                .map(|identifier| {
+                    if identifier.is_empty() {
+                        return Err(VersionError::InvalidVersion(s.to_string()));
+                    }
+
+                    Ok(match identifier.parse::<u64>() {
+                        Ok(value) => Identifier::Numeric(value),
+                        Err(_) => Identifier::Alphanumeric(identifier.to_string())
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        Ok(Self {
+            numbers,
+            pre,
+            build
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+
+        for i in 0..len {
+            let a = self.numbers.get(i).copied().unwrap_or_default();
+            let b = other.numbers.get(i).copied().unwrap_or_default();
+
+            match a.cmp(&b) {
+                Ordering::Equal => (),
+                ordering => return ordering
+            }
+        }
+
+        // Pre-release versions have lower precedence than the release one.
+        match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.pre.cmp(&other.pre)
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let numbers = self.numbers.iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>();
+
+        f.write_str(&numbers.join("."))?;
+
+        if !self.pre.is_empty() {
+            let pre = self.pre.iter()
+                .map(Identifier::to_string)
+                .collect::<Vec<_>>();
+
+            write!(f, "-{}", pre.join("."))?;
+        }
+
+        if let Some(build) = &self.build {
+            write!(f, "+{build}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    operator: Operator,
+    version: Version
+}
+
+impl Comparator {
+    fn matches(&self, version: &Version) -> bool {
+        match self.operator {
+            Operator::Equal        => version == &self.version,
+            Operator::NotEqual     => version != &self.version,
+            Operator::Greater      => version > &self.version,
+            Operator::GreaterEqual => version >= &self.version,
+            Operator::Less         => version < &self.version,
+            Operator::LessEqual    => version <= &self.version
+        }
+    }
+}
+
+/// Versions range. Supports comparison operators (`=`, `!=`, `>`, `>=`, `<`,
+/// `<=`), tilde (`~1.2.3` allows changes of the last component) and caret
+/// (`^1.2.3` allows changes which don't modify the first non-zero component)
+/// requirements, wildcards (`1.2.*`, `1.x`, `*`) and inclusive hyphen ranges
+/// (`1.2 - 1.4`). Requirements separated by spaces or commas must all match,
+/// and alternatives are separated by `||`. Version without operator must be
+/// equal to the given one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq(Vec<Vec<Comparator>>);
+
+impl VersionReq {
+    /// Check if the version matches the range.
+    pub fn matches(&self, version: &Version) -> bool {
+        self.0.iter().any(|comparators| {
+            comparators.iter().all(|comparator| comparator.matches(version))
+        })
+    }
+
+    fn parse_requirement(
+        requirement: &str,
+        comparators: &mut Vec<Comparator>
+    ) -> Result<(), VersionError> {
+        let invalid = || VersionError::InvalidRange(requirement.to_string());
+
+        let operators = [
+            (">=", Operator::GreaterEqual),
+            ("<=", Operator::LessEqual),
+            ("!=", Operator::NotEqual),
+            ("==", Operator::Equal),
+            (">",  Operator::Greater),
+            ("<",  Operator::Less),
+            ("=",  Operator::Equal)
+        ];
+
+        for (prefix, operator) in operators {
+            if let Some(version) = requirement.strip_prefix(prefix) {
+                comparators.push(Comparator {
+                    operator,
+                    version: Version::from_str(version)?
+                });
+
+                return Ok(());
+            }
+        }
+
+        if let Some(version) = requirement.strip_prefix('~') {
+            let version = Version::from_str(version)?;
+
+            let len = version.numbers.len();
+            let len = if len <= 2 { len - 1 } else { len - 2 };
+
+            comparators.push(Comparator {
+                operator: Operator::Less,
+                version: version.bump(len + 1)
+            });
+
+            comparators.push(Comparator {
+                operator: Operator::GreaterEqual,
+                version
+            });
+
+            return Ok(());
+        }
+
+        if let Some(version) = requirement.strip_prefix('^') {
+            let version = Version::from_str(version)?;
+
+            let len = version.numbers.iter()
+                .position(|number| *number != 0)
+                .unwrap_or(version.numbers.len() - 1);
+
+            comparators.push(Comparator {
+                operator: Operator::Less,
+                version: version.bump(len + 1)
+            });
+
+            comparators.push(Comparator {
+                operator: Operator::GreaterEqual,
+                version
+            });
+
+            return Ok(());
+        }
+
+        // Wildcards.
+        let parts = requirement.split('.').collect::<Vec<_>>();
+
+        if let Some(len) = parts.iter().position(|part| ["*", "x", "X"].contains(part)) {
+            if len == 0 {
+                return Ok(());
+            }
+
+            let numbers = parts[..len].iter()
+                .map(|part| part.parse::<u64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+
+            let version = Version::new(numbers);
+
+            comparators.push(Comparator {
+                operator: Operator::Less,
+                version: version.bump(len)
+            });
+
+            comparators.push(Comparator {
+                operator: Operator::GreaterEqual,
+                version
+            });
+
+            return Ok(());
+        }
+
+        comparators.push(Comparator {
+            operator: Operator::Equal,
+            version: Version::from_str(requirement)?
+        });
+
+        Ok(())
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = VersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut alternatives = Vec::new();
+
+        for alternative in s.split("||") {
+            let mut comparators = Vec::new();
+
+            let mut tokens = alternative.split([' ', '\t', ','])
+                .filter(|token| !token.is_empty())
+                .peekable();
+
+            while let Some(token) = tokens.next() {
+                // Merge operators separated from their versions (`>= 1.2`).
+                let token = if token.chars().all(|c| "<>=!~^".contains(c)) {
+                    let Some(version) = tokens.next() else {
+                        return Err(VersionError::InvalidRange(s.to_string()));
+                    };
+
+                    format!("{token}{version}")
+                } else {
+                    token.to_string()
+                };
+
+                // Hyphen ranges (`1.2 - 1.4`).
+                if tokens.peek() == Some(&"-") {
+                    tokens.next();
+
+                    let Some(upper) = tokens.next() else {
+                        return Err(VersionError::InvalidRange(s.to_string()));
+                    };
+
+                    comparators.push(Comparator {
+                        operator: Operator::GreaterEqual,
+                        version: Version::from_str(&token)?
+                    });
+
+                    comparators.push(Comparator {
+                        operator: Operator::LessEqual,
+                        version: Version::from_str(upper)?
+                    });
+
+                    continue;
+                }
+
+                Self::parse_requirement(&token, &mut comparators)?;
+            }
+
+            alternatives.push(comparators);
+        }
+
+        Ok(Self(alternatives))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(value: &str) -> Version {
+        Version::from_str(value).unwrap()
+    }
+
+    fn matches(range: &str, value: &str) -> bool {
+        VersionReq::from_str(range).unwrap()
+            .matches(&version(value))
+    }
+
+    #[test]
+    fn parse() {
+        let value = version("v1.2.3-rc.1+build.5");
+
+        assert_eq!(value.numbers(), &[1, 2, 3]);
+        assert_eq!(value.pre(), &[Identifier::Alphanumeric(String::from("rc")), Identifier::Numeric(1)]);
+        assert_eq!(value.build(), Some("build.5"));
+        assert_eq!(value.to_string(), "1.2.3-rc.1+build.5");
+
+        assert_eq!(version("1.2.3.45").numbers(), &[1, 2, 3, 45]);
+        assert_eq!(version("1.0rc1").pre(), &[Identifier::Alphanumeric(String::from("rc1"))]);
+        assert_eq!(version("2").major(), 2);
+        assert_eq!(version("2").patch(), 0);
+
+        assert!(Version::from_str("").is_err());
+        assert!(Version::from_str("abc").is_err());
+        assert!(Version::from_str("1..2").is_err());
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(version("1.2"), version("1.2.0"));
+        assert_eq!(version("1.2.0+build"), version("1.2.0"));
+
+        assert!(version("1.2.3.45") > version("1.2.3.9"));
+        assert!(version("1.10") > version("1.9"));
+        assert!(version("1.0.0-alpha") < version("1.0.0-alpha.1"));
+        assert!(version("1.0.0-alpha.1") < version("1.0.0-alpha.beta"));
+        assert!(version("1.0.0-beta.2") < version("1.0.0-beta.11"));
+        assert!(version("1.0.0-rc.1") < version("1.0.0"));
+    }
+
+    #[test]
+    fn ranges() {
+        assert!(matches("1.2.3", "1.2.3"));
+        assert!(!matches("1.2.3", "1.2.4"));
+        assert!(matches(">= 1.2, < 2", "1.9.9"));
+        assert!(!matches(">=1.2 <2", "2.0"));
+        assert!(matches("!=1.2", "1.3"));
+
+        assert!(matches("~1.2.3", "1.2.9"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches("~1.2", "1.2.9"));
+        assert!(matches("~1.2.3.4", "1.2.3.9"));
+        assert!(!matches("~1.2.3.4", "1.2.4.0"));
+
+        assert!(matches("^1.2.3", "1.9.0"));
+        assert!(!matches("^1.2.3", "2.0.0"));
+        assert!(matches("^0.2.3", "0.2.9"));
+        assert!(!matches("^0.2.3", "0.3.0"));
+
+        assert!(matches("1.2.*", "1.2.3.4"));
+        assert!(!matches("1.x", "2.0"));
+        assert!(matches("*", "100.0"));
+
+        assert!(matches("1.2 - 1.4", "1.4"));
+        assert!(!matches("1.2 - 1.4", "1.4.1"));
+
+        assert!(matches("<1 || >=2.1", "2.1"));
+        assert!(!matches("<1 || >=2.1", "1.5"));
+    }
+}
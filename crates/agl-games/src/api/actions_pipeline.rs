@@ -26,6 +26,7 @@ use super::PipelineAction;
 pub struct ActionsPipeline {
     title: LocalizableString,
     description: Option<LocalizableString>,
+    interactive: bool,
     pipeline: Box<[PipelineAction]>
 }
 
@@ -45,6 +46,9 @@ impl ActionsPipeline {
                 })
                 .unwrap_or(Ok(None))?,
 
+            interactive: table.get::<Option<bool>>("interactive")?
+                .unwrap_or(false),
+
             pipeline: table.get::<Vec<LuaTable>>("pipeline")
                 .and_then(|pipeline| {
                     pipeline.iter()
@@ -66,6 +70,13 @@ impl ActionsPipeline {
         self.description.as_ref()
     }
 
+    /// Whether the pipeline needs user interaction. Such pipelines are never
+    /// performed unattended.
+    #[inline(always)]
+    pub const fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
     /// List of actions which will be executed to apply the pipeline.
     #[inline(always)]
     pub const fn actions(&self) -> &[PipelineAction] {
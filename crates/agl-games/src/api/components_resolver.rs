@@ -0,0 +1,477 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-games
+// Copyright (C) 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+
+use super::GameComponentsGroup;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ComponentsResolveError {
+    #[error("unknown component: {0}")]
+    UnknownComponent(String),
+
+    #[error("component {0} is locked and can't be uninstalled")]
+    LockedComponent(String),
+
+    #[error("component {component} requires {dependency}")]
+    RequiredComponent {
+        component: String,
+        dependency: String
+    },
+
+    #[error("component {component} conflicts with {conflict}")]
+    ConflictingComponents {
+        component: String,
+        conflict: String
+    },
+
+    #[error("components dependency cycle: {}", .0.join(" -> "))]
+    DependencyCycle(Vec<String>)
+}
+
+/// Ordered list of components changes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ComponentsPlan {
+    /// Components which should be uninstalled, in order. Dependent components
+    /// go before their dependencies.
+    pub uninstall: Vec<String>,
+
+    /// Components which should be installed, in order. Dependencies go before
+    /// the components which require them.
+    pub install: Vec<String>
+}
+
+impl ComponentsPlan {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.uninstall.is_empty() && self.install.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ComponentNode<'a> {
+    locked: bool,
+    requires: &'a [String],
+    conflicts: &'a [String],
+    exclusive: Option<&'a str>
+}
+
+/// Resolver of the game components changes. Uses dependencies, conflicts and
+/// mutually exclusive groups of the components layout to build a plan of
+/// components installation and uninstallation.
+#[derive(Debug, Clone)]
+pub struct ComponentsResolver<'a> {
+    /// Names of components in the layout order.
+    names: Vec<&'a str>,
+
+    nodes: HashMap<&'a str, ComponentNode<'a>>
+}
+
+impl<'a> ComponentsResolver<'a> {
+    pub fn new(layout: &'a [GameComponentsGroup]) -> Self {
+        let mut names = Vec::new();
+        let mut nodes = HashMap::new();
+
+        for group in layout {
+            for entry in group.entries() {
+                names.push(entry.name());
+
+                nodes.insert(entry.name(), ComponentNode {
+                    locked: entry.is_locked(),
+                    requires: entry.requires(),
+                    conflicts: entry.conflicts(),
+                    exclusive: entry.exclusive()
+                });
+            }
+        }
+
+        Self {
+            names,
+            nodes
+        }
+    }
+
+    /// Resolve plan of enabling and disabling given components.
+    ///
+    /// Dependencies of enabled components are enabled as well, components
+    /// which require disabled ones are disabled, and components conflicting
+    /// with enabled ones (or from the same exclusive group) are disabled with
+    /// their dependents. Enabled locked components are kept enabled, while
+    /// not installed ones are not installed by the plan.
+    pub fn resolve(
+        &self,
+        enabled: &HashSet<String>,
+        enable: &[String],
+        disable: &[String]
+    ) -> Result<ComponentsPlan, ComponentsResolveError> {
+        for name in enable.iter().chain(disable) {
+            if !self.nodes.contains_key(name.as_str()) {
+                return Err(ComponentsResolveError::UnknownComponent(name.clone()));
+            }
+        }
+
+        let locked = self.nodes.iter()
+            .filter(|(name, node)| node.locked && enabled.contains(**name))
+            .map(|(name, _)| *name);
+
+        // Components which must be enabled after applying the plan.
+        let mut keep = enable.iter()
+            .filter_map(|name| self.nodes.get_key_value(name.as_str()))
+            .map(|(name, _)| *name)
+            .chain(locked.clone())
+            .collect::<HashSet<&str>>();
+
+        // Components which must be disabled after applying the plan.
+        let drop = disable.iter()
+            .map(String::as_str)
+            .collect::<HashSet<&str>>();
+
+        let mut target = enabled.iter()
+            .filter_map(|name| self.nodes.get_key_value(name.as_str()))
+            .map(|(name, _)| *name)
+            .collect::<HashSet<&str>>();
+
+        for name in disable {
+            if self.nodes[name.as_str()].locked {
+                return Err(ComponentsResolveError::LockedComponent(name.clone()));
+            }
+
+            self.remove_with_dependents(name, &keep, &mut target)?;
+        }
+
+        let mut stack = Vec::new();
+
+        for name in enable {
+            self.add_with_dependencies(name, &drop, &mut keep, &mut target, &mut stack)?;
+        }
+
+        let order = self.topological_order();
+
+        let install = order.iter()
+            .filter(|name| target.contains(*name) && !enabled.contains(**name))
+            .map(|name| name.to_string())
+            .collect();
+
+        let uninstall = order.iter()
+            .rev()
+            .filter(|name| !target.contains(*name) && enabled.contains(**name))
+            .map(|name| name.to_string())
+            .collect();
+
+        Ok(ComponentsPlan {
+            uninstall,
+            install
+        })
+    }
+
+    /// Sort given components so dependent ones go before their dependencies.
+    /// Unknown components are placed at the end of the list.
+    pub fn uninstall_order(&self, components: &[String]) -> Vec<String> {
+        let components = components.iter()
+            .map(String::as_str)
+            .collect::<HashSet<&str>>();
+
+        let mut order = self.topological_order()
+            .into_iter()
+            .rev()
+            .filter(|name| components.contains(name))
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        for name in components {
+            if !self.nodes.contains_key(name) {
+                order.push(name.to_string());
+            }
+        }
+
+        order
+    }
+
+    /// Disable component and every enabled component which requires it.
+    fn remove_with_dependents(
+        &self,
+        name: &str,
+        keep: &HashSet<&'a str>,
+        target: &mut HashSet<&'a str>
+    ) -> Result<(), ComponentsResolveError> {
+        if !target.remove(name) {
+            return Ok(());
+        }
+
+        let dependents = target.iter()
+            .copied()
+            .filter(|dependent| {
+                self.nodes[dependent].requires.iter().any(|dependency| dependency == name)
+            })
+            .collect::<Vec<_>>();
+
+        for dependent in dependents {
+            if keep.contains(dependent) {
+                return Err(ComponentsResolveError::RequiredComponent {
+                    component: dependent.to_string(),
+                    dependency: name.to_string()
+                });
+            }
+
+            self.remove_with_dependents(dependent, keep, target)?;
+        }
+
+        Ok(())
+    }
+
+    /// Enable component with all its dependencies, disabling conflicting
+    /// components.
+    fn add_with_dependencies(
+        &self,
+        name: &str,
+        drop: &HashSet<&str>,
+        keep: &mut HashSet<&'a str>,
+        target: &mut HashSet<&'a str>,
+        stack: &mut Vec<&'a str>
+    ) -> Result<(), ComponentsResolveError> {
+        let Some((&name, node)) = self.nodes.get_key_value(name) else {
+            return Err(ComponentsResolveError::UnknownComponent(name.to_string()));
+        };
+
+        if stack.contains(&name) {
+            let mut cycle = stack.iter()
+                .skip_while(|component| **component != name)
+                .map(|component| component.to_string())
+                .collect::<Vec<_>>();
+
+            cycle.push(name.to_string());
+
+            return Err(ComponentsResolveError::DependencyCycle(cycle));
+        }
+
+        stack.push(name);
+
+        for dependency in node.requires {
+            if !self.nodes.contains_key(dependency.as_str()) || drop.contains(dependency.as_str()) {
+                return Err(ComponentsResolveError::RequiredComponent {
+                    component: name.to_string(),
+                    dependency: dependency.clone()
+                });
+            }
+
+            self.add_with_dependencies(dependency, drop, keep, target, stack)?;
+        }
+
+        stack.pop();
+
+        keep.insert(name);
+        target.insert(name);
+
+        // Disable components conflicting with this one.
+        let conflicts = target.iter()
+            .copied()
+            .filter(|other| *other != name)
+            .filter(|other| {
+                let other_node = &self.nodes[other];
+
+                node.conflicts.iter().any(|conflict| conflict == other)
+                    || other_node.conflicts.iter().any(|conflict| conflict == name)
+                    || node.exclusive.is_some() && node.exclusive == other_node.exclusive
+            })
+            .collect::<Vec<_>>();
+
+        for conflict in conflicts {
+            if keep.contains(conflict) {
+                return Err(ComponentsResolveError::ConflictingComponents {
+                    component: name.to_string(),
+                    conflict: conflict.to_string()
+                });
+            }
+
+            self.remove_with_dependents(conflict, keep, target)?;
+        }
+
+        Ok(())
+    }
+
+    /// List components so dependencies go before the components which
+    /// require them. Otherwise the layout order is kept.
+    fn topological_order(&self) -> Vec<&'a str> {
+        fn visit<'a>(
+            resolver: &ComponentsResolver<'a>,
+            name: &'a str,
+            visited: &mut HashSet<&'a str>,
+            order: &mut Vec<&'a str>
+        ) {
+            if !visited.insert(name) {
+                return;
+            }
+
+            for dependency in resolver.nodes[name].requires {
+                if let Some((dependency, _)) = resolver.nodes.get_key_value(dependency.as_str()) {
+                    visit(resolver, dependency, visited, order);
+                }
+            }
+
+            order.push(name);
+        }
+
+        let mut visited = HashSet::with_capacity(self.names.len());
+        let mut order = Vec::with_capacity(self.names.len());
+
+        for name in &self.names {
+            visit(self, name, &mut visited, &mut order);
+        }
+
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::prelude::*;
+
+    use super::*;
+
+    fn layout() -> Box<[GameComponentsGroup]> {
+        let lua = Lua::new();
+
+        let groups = lua.load(r#"
+            return {
+                {
+                    entries = {
+                        { name = "base", title = "Base game", locked = true },
+                        { name = "dxvk", title = "DXVK", exclusive = "translation" },
+                        { name = "dxvk-async", title = "DXVK async", exclusive = "translation" },
+                        { name = "vkd3d", title = "VKD3D", requires = { "dxvk" } },
+                        { name = "hud", title = "HUD", requires = { "vkd3d" }, conflicts = { "en" } }
+                    }
+                },
+                {
+                    entries = {
+                        { name = "en", title = "English" },
+                        { name = "ja", title = "Japanese", conflicts = { "en" } }
+                    }
+                }
+            }
+        "#).eval::<Vec<LuaTable>>().unwrap();
+
+        groups.iter()
+            .map(GameComponentsGroup::from_lua)
+            .collect::<Result<Box<[_]>, _>>()
+            .unwrap()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn enabled(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn install_dependencies() {
+        let layout = layout();
+        let resolver = ComponentsResolver::new(&layout);
+
+        let plan = resolver.resolve(&enabled(&["base"]), &names(&["hud"]), &[]).unwrap();
+
+        assert!(plan.uninstall.is_empty());
+        assert_eq!(plan.install, names(&["dxvk", "vkd3d", "hud"]));
+    }
+
+    #[test]
+    fn uninstall_dependents() {
+        let layout = layout();
+        let resolver = ComponentsResolver::new(&layout);
+
+        let plan = resolver.resolve(
+            &enabled(&["base", "dxvk", "vkd3d", "hud"]),
+            &[],
+            &names(&["dxvk"])
+        ).unwrap();
+
+        assert_eq!(plan.uninstall, names(&["hud", "vkd3d", "dxvk"]));
+        assert!(plan.install.is_empty());
+    }
+
+    #[test]
+    fn replace_conflicts() {
+        let layout = layout();
+        let resolver = ComponentsResolver::new(&layout);
+
+        let plan = resolver.resolve(
+            &enabled(&["base", "dxvk", "vkd3d", "en"]),
+            &names(&["dxvk-async", "ja"]),
+            &[]
+        ).unwrap();
+
+        assert_eq!(plan.uninstall, names(&["en", "vkd3d", "dxvk"]));
+        assert_eq!(plan.install, names(&["dxvk-async", "ja"]));
+    }
+
+    #[test]
+    fn skip_not_installed_locked() {
+        let layout = layout();
+        let resolver = ComponentsResolver::new(&layout);
+
+        let plan = resolver.resolve(&enabled(&["en"]), &names(&["dxvk"]), &[]).unwrap();
+
+        assert!(plan.uninstall.is_empty());
+        assert_eq!(plan.install, names(&["dxvk"]));
+    }
+
+    #[test]
+    fn resolve_errors() {
+        let layout = layout();
+        let resolver = ComponentsResolver::new(&layout);
+
+        assert_eq!(
+            resolver.resolve(&enabled(&[]), &[], &names(&["base"])),
+            Err(ComponentsResolveError::LockedComponent(String::from("base")))
+        );
+
+        assert_eq!(
+            resolver.resolve(&enabled(&[]), &names(&["hud", "en"]), &[]),
+            Err(ComponentsResolveError::ConflictingComponents {
+                component: String::from("en"),
+                conflict: String::from("hud")
+            })
+        );
+
+        assert_eq!(
+            resolver.resolve(&enabled(&[]), &names(&["vkd3d"]), &names(&["dxvk"])),
+            Err(ComponentsResolveError::RequiredComponent {
+                component: String::from("vkd3d"),
+                dependency: String::from("dxvk")
+            })
+        );
+
+        assert_eq!(
+            resolver.resolve(&enabled(&[]), &names(&["unknown"]), &[]),
+            Err(ComponentsResolveError::UnknownComponent(String::from("unknown")))
+        );
+    }
+
+    #[test]
+    fn uninstall_order() {
+        let layout = layout();
+        let resolver = ComponentsResolver::new(&layout);
+
+        assert_eq!(
+            resolver.uninstall_order(&names(&["base", "dxvk", "hud", "vkd3d"])),
+            names(&["hud", "vkd3d", "dxvk", "base"])
+        );
+    }
+}
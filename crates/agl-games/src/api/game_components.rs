@@ -20,6 +20,7 @@ use std::str::FromStr;
 
 use mlua::prelude::*;
 
+use agl_core::version::Version;
 use agl_locale::string::LocalizableString;
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -116,6 +117,11 @@ pub struct GameComponentsEntry {
     title: LocalizableString,
     description: Option<LocalizableString>,
     locked: bool,
+    version: Option<String>,
+    latest_version: Option<String>,
+    requires: Box<[String]>,
+    conflicts: Box<[String]>,
+    exclusive: Option<String>,
     values: Box<[GameComponentsEntryValue]>
 }
 
@@ -141,6 +147,19 @@ impl GameComponentsEntry {
                 .flatten()
                 .unwrap_or(false),
 
+            version: value.get::<Option<String>>("version")?,
+            latest_version: value.get::<Option<String>>("latest_version")?,
+
+            requires: value.get::<Option<Vec<String>>>("requires")?
+                .unwrap_or_default()
+                .into_boxed_slice(),
+
+            conflicts: value.get::<Option<Vec<String>>>("conflicts")?
+                .unwrap_or_default()
+                .into_boxed_slice(),
+
+            exclusive: value.get::<Option<String>>("exclusive")?,
+
             values: value.get::<Vec<LuaTable>>("values")
                 .map(|values| {
                     values.iter()
@@ -171,6 +190,48 @@ impl GameComponentsEntry {
         self.locked
     }
 
+    #[inline(always)]
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    #[inline(always)]
+    pub fn latest_version(&self) -> Option<&str> {
+        self.latest_version.as_deref()
+    }
+
+    /// Check if latest version of the component is greater than the installed
+    /// one. Returns `false` if any of the versions is unknown or invalid.
+    pub fn has_update(&self) -> bool {
+        let (Some(version), Some(latest_version)) = (&self.version, &self.latest_version) else {
+            return false;
+        };
+
+        match (Version::from_str(version), Version::from_str(latest_version)) {
+            (Ok(version), Ok(latest_version)) => latest_version > version,
+            _ => false
+        }
+    }
+
+    /// Names of components which must be enabled for this one to work.
+    #[inline(always)]
+    pub const fn requires(&self) -> &[String] {
+        &self.requires
+    }
+
+    /// Names of components which can't be enabled together with this one.
+    #[inline(always)]
+    pub const fn conflicts(&self) -> &[String] {
+        &self.conflicts
+    }
+
+    /// Name of the mutually exclusive group of this component. Only one
+    /// component of the same group can be enabled.
+    #[inline(always)]
+    pub fn exclusive(&self) -> Option<&str> {
+        self.exclusive.as_deref()
+    }
+
     #[inline(always)]
     pub const fn values(&self) -> &[GameComponentsEntryValue] {
         &self.values
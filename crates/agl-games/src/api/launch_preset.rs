@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-games
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value as Json};
+use mlua::prelude::*;
+
+use agl_locale::string::LocalizableString;
+
+use super::GameLaunchInfo;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchPreset {
+    /// Unique name of the preset.
+    pub name: String,
+
+    /// Title used in UI.
+    pub title: LocalizableString,
+
+    /// Optional description of the preset.
+    pub description: Option<LocalizableString>,
+
+    /// Name of the game edition used by this preset.
+    pub edition: Option<String>,
+
+    /// Arguments appended to the game launch arguments.
+    pub args: Option<Vec<String>>,
+
+    /// Environment variables applied on top of the game launch environment.
+    pub env: Option<HashMap<String, String>>,
+
+    /// Wrapper commands used instead of the game's own wrappers.
+    pub wrappers: Option<Vec<String>>,
+
+    /// Whether the global wrappers should be used.
+    pub global_wrappers: Option<bool>
+}
+
+impl LaunchPreset {
+    /// Merge game integration presets with the user ones. User presets
+    /// override integration presets with the same name, keeping their
+    /// position in the list.
+    pub fn merge(
+        presets: impl IntoIterator<Item = Self>,
+        user_presets: impl IntoIterator<Item = Self>
+    ) -> Vec<Self> {
+        let mut presets = presets.into_iter().collect::<Vec<_>>();
+
+        for user_preset in user_presets {
+            match presets.iter_mut().find(|preset| preset.name == user_preset.name) {
+                Some(preset) => *preset = user_preset,
+                None => presets.push(user_preset)
+            }
+        }
+
+        presets
+    }
+
+    /// Apply preset overrides to the game launch info.
+    pub fn apply(&self, info: &mut GameLaunchInfo) {
+        if let Some(args) = &self.args {
+            info.args.get_or_insert_default()
+                .extend(args.iter().cloned());
+        }
+
+        if let Some(env) = &self.env {
+            info.env.get_or_insert_default()
+                .extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        json!({
+            "name": self.name,
+            "title": self.title.to_json(),
+            "description": self.description.as_ref()
+                .map(LocalizableString::to_json),
+            "edition": self.edition,
+            "args": self.args,
+            "env": self.env,
+            "wrappers": self.wrappers,
+            "global_wrappers": self.global_wrappers
+        })
+    }
+
+    pub fn from_json(value: &Json) -> Option<Self> {
+        let strings = |value: &Json| -> Option<Vec<String>> {
+            value.as_array()?
+                .iter()
+                .map(|value| value.as_str().map(String::from))
+                .collect()
+        };
+
+        Some(Self {
+            name: value.get("name")?
+                .as_str()?
+                .to_string(),
+
+            title: value.get("title")
+                .and_then(LocalizableString::from_json)?,
+
+            description: value.get("description")
+                .and_then(LocalizableString::from_json),
+
+            edition: value.get("edition")
+                .and_then(Json::as_str)
+                .map(String::from),
+
+            args: value.get("args")
+                .and_then(strings),
+
+            env: value.get("env")
+                .and_then(Json::as_object)
+                .and_then(|env| {
+                    env.iter()
+                        .map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                        .collect::<Option<HashMap<_, _>>>()
+                }),
+
+            wrappers: value.get("wrappers")
+                .and_then(strings),
+
+            global_wrappers: value.get("global_wrappers")
+                .and_then(Json::as_bool)
+        })
+    }
+
+    pub fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        let strings = |name: &str| -> Result<Option<Vec<String>>, LuaError> {
+            Ok(value.get::<Option<Vec<LuaString>>>(name)?
+                .map(|values| {
+                    values.into_iter()
+                        .map(|value| value.to_string_lossy().to_string())
+                        .collect()
+                }))
+        };
+
+        Ok(Self {
+            name: value.get::<String>("name")?,
+
+            title: value.get::<LuaValue>("title")
+                .and_then(|title| LocalizableString::from_lua(&title))?,
+
+            description: value.get::<LuaValue>("description")
+                .map(|description| {
+                    if description.is_nil() || description.is_null() {
+                        Ok(None)
+                    } else {
+                        LocalizableString::from_lua(&description).map(Some)
+                    }
+                })
+                .unwrap_or(Ok(None))?,
+
+            edition: value.get::<Option<String>>("edition")
+                .context("invalid preset edition format")?,
+
+            args: strings("args")
+                .context("invalid preset args format")?,
+
+            env: value.get::<Option<LuaTable>>("env")?
+                .map(|env| {
+                    env.pairs::<LuaString, LuaString>()
+                        .map(|pair| {
+                            pair.map(|(key, value)| {
+                                let key = key.to_string_lossy().to_string();
+                                let value = value.to_string_lossy().to_string();
+
+                                (key, value)
+                            })
+                        })
+                        .collect::<Result<HashMap<_, _>, LuaError>>()
+                })
+                .transpose()
+                .context("invalid preset env format")?,
+
+            wrappers: strings("wrappers")
+                .context("invalid preset wrappers format")?,
+
+            global_wrappers: value.get::<Option<bool>>("global_wrappers")
+                .context("invalid preset global_wrappers format")?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(name: &str, edition: Option<&str>) -> LaunchPreset {
+        LaunchPreset {
+            name: name.to_string(),
+            title: LocalizableString::raw(name),
+            description: None,
+            edition: edition.map(String::from),
+            args: None,
+            env: None,
+            wrappers: None,
+            global_wrappers: None
+        }
+    }
+
+    #[test]
+    fn parse() -> Result<(), LuaError> {
+        let lua = Lua::new();
+
+        let value = lua.load(r#"
+            return {
+                name = "dxvk",
+                title = "DXVK",
+                edition = "global",
+                args = { "-dx11" },
+                env = { DXVK_HUD = "fps" },
+                wrappers = { "gamemoderun" },
+                global_wrappers = false
+            }
+        "#).eval::<LuaTable>()?;
+
+        let preset = LaunchPreset::from_lua(&value)?;
+
+        assert_eq!(preset.name, "dxvk");
+        assert_eq!(preset.description, None);
+        assert_eq!(preset.edition.as_deref(), Some("global"));
+        assert_eq!(preset.args, Some(vec![String::from("-dx11")]));
+        assert_eq!(preset.env.as_ref().and_then(|env| env.get("DXVK_HUD")).map(String::as_str), Some("fps"));
+        assert_eq!(preset.wrappers, Some(vec![String::from("gamemoderun")]));
+        assert_eq!(preset.global_wrappers, Some(false));
+
+        assert_eq!(LaunchPreset::from_json(&preset.to_json()), Some(preset));
+
+        let value = lua.load(r#"
+            return { name = "broken", title = "Broken", args = "-dx11" }
+        "#).eval::<LuaTable>()?;
+
+        assert!(LaunchPreset::from_lua(&value).is_err());
+        assert!(LaunchPreset::from_json(&json!({ "title": "Nameless" })).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge() {
+        let presets = LaunchPreset::merge(
+            [preset("a", None), preset("b", None)],
+            [preset("b", Some("china")), preset("c", None)]
+        );
+
+        assert_eq!(presets, [
+            preset("a", None),
+            preset("b", Some("china")),
+            preset("c", None)
+        ]);
+    }
+
+    #[test]
+    fn apply() {
+        let mut info = GameLaunchInfo {
+            args: Some(vec![String::from("-a")]),
+            ..GameLaunchInfo::default()
+        };
+
+        let mut preset = preset("a", None);
+
+        preset.args = Some(vec![String::from("-b")]);
+        preset.env = Some(HashMap::from([(String::from("A"), String::from("B"))]));
+
+        preset.apply(&mut info);
+
+        assert_eq!(info.args, Some(vec![String::from("-a"), String::from("-b")]));
+        assert_eq!(info.env.and_then(|env| env.get("A").cloned()).as_deref(), Some("B"));
+    }
+}
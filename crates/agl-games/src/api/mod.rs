@@ -16,6 +16,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::path::{Path, PathBuf};
+
 use mlua::prelude::*;
 
 mod game_edition;
@@ -25,8 +27,13 @@ mod actions_pipeline;
 mod pipeline_action;
 mod progress_report;
 mod game_components;
+mod components_resolver;
 mod tools_buttons;
 mod game_settings;
+mod saves_directory;
+mod mods_directory;
+mod verify_manifest;
+mod launch_preset;
 
 pub use game_edition::*;
 pub use game_variant::*;
@@ -35,8 +42,13 @@ pub use actions_pipeline::*;
 pub use pipeline_action::*;
 pub use progress_report::*;
 pub use game_components::*;
+pub use components_resolver::*;
 pub use tools_buttons::*;
 pub use game_settings::*;
+pub use saves_directory::*;
+pub use mods_directory::*;
+pub use verify_manifest::*;
+pub use launch_preset::*;
 
 use crate::platform::Platform;
 
@@ -52,6 +64,7 @@ pub struct GameIntegration {
     game_get_editions: Option<LuaFunction>,
     game_get_launch_info: LuaFunction,
     game_get_actions_pipeline: LuaFunction,
+    game_get_launch_presets: Option<LuaFunction>,
 
     components_get_layout: Option<LuaFunction>,
     components_get_enabled: Option<LuaFunction>,
@@ -63,7 +76,17 @@ pub struct GameIntegration {
 
     settings_get_layout: Option<LuaFunction>,
     settings_get_property: Option<LuaFunction>,
-    settings_set_property: Option<LuaFunction>
+    settings_set_property: Option<LuaFunction>,
+
+    saves_get_directories: Option<LuaFunction>,
+
+    mods_get_directories: Option<LuaFunction>,
+
+    verify_get_manifest: Option<LuaFunction>,
+    verify_repair: Option<LuaFunction>,
+
+    storage_get_path: Option<LuaFunction>,
+    storage_set_path: Option<LuaFunction>
 }
 
 impl GameIntegration {
@@ -97,6 +120,10 @@ impl GameIntegration {
         let components = integration.get::<LuaTable>("components").ok();
         let tools = integration.get::<LuaTable>("tools").ok();
         let settings = integration.get::<LuaTable>("settings").ok();
+        let saves = integration.get::<LuaTable>("saves").ok();
+        let mods = integration.get::<LuaTable>("mods").ok();
+        let verify = integration.get::<LuaTable>("verify").ok();
+        let storage = integration.get::<LuaTable>("storage").ok();
 
         Ok(Self {
             lua,
@@ -109,6 +136,8 @@ impl GameIntegration {
             game_get_actions_pipeline: game.get("get_actions_pipeline")
                 .context("game.get_actions_pipeline API function must be specified")?,
 
+            game_get_launch_presets: game.get("get_launch_presets").ok(),
+
             components_get_layout: components.as_ref()
                 .map(|components| components.get("get_layout"))
                 .transpose()
@@ -162,7 +191,37 @@ impl GameIntegration {
             settings_set_property: settings.as_ref()
                 .map(|settings| settings.get("set_property"))
                 .transpose()
-                .context("settings.set_property API function must be specified")?
+                .context("settings.set_property API function must be specified")?,
+
+            saves_get_directories: saves.as_ref()
+                .map(|saves| saves.get("get_directories"))
+                .transpose()
+                .context("saves.get_directories API function must be specified")?,
+
+            mods_get_directories: mods.as_ref()
+                .map(|mods| mods.get("get_directories"))
+                .transpose()
+                .context("mods.get_directories API function must be specified")?,
+
+            verify_get_manifest: verify.as_ref()
+                .map(|verify| verify.get("get_manifest"))
+                .transpose()
+                .context("verify.get_manifest API function must be specified")?,
+
+            verify_repair: verify.as_ref()
+                .map(|verify| verify.get::<Option<LuaFunction>>("repair"))
+                .transpose()?
+                .flatten(),
+
+            storage_get_path: storage.as_ref()
+                .map(|storage| storage.get("get_path"))
+                .transpose()
+                .context("storage.get_path API function must be specified")?,
+
+            storage_set_path: storage.as_ref()
+                .map(|storage| storage.get("set_path"))
+                .transpose()
+                .context("storage.set_path API function must be specified")?
         })
     }
 
@@ -219,6 +278,30 @@ impl GameIntegration {
             })
     }
 
+    /// Try to get list of default launch presets.
+    ///
+    /// Return `Ok(None)` if integration module doesn't provide any presets.
+    pub fn get_launch_presets(
+        &self,
+        variant: impl AsRef<GameVariant>
+    ) -> Result<Option<Box<[LaunchPreset]>>, LuaError> {
+        let Some(get_launch_presets) = &self.game_get_launch_presets else {
+            return Ok(None);
+        };
+
+        let variant = variant.as_ref()
+            .to_lua(&self.lua)?;
+
+        get_launch_presets.call::<Option<Vec<LuaTable>>>(variant)
+            .and_then(|presets| {
+                presets.map(|presets| {
+                    presets.iter()
+                        .map(LaunchPreset::from_lua)
+                        .collect::<Result<Box<[_]>, LuaError>>()
+                }).transpose()
+            })
+    }
+
     /// Get game components layout.
     ///
     /// Return `Ok(None)` if components are not specified.
@@ -281,11 +364,26 @@ impl GameIntegration {
     }
 
     /// Install a game component.
+    #[inline]
     pub fn install_component(
         &self,
         variant: impl AsRef<GameVariant>,
         component: impl AsRef<str>,
         progress: impl Fn(ProgressReport) + Send + 'static
+    ) -> Result<(), LuaError> {
+        self.install_component_cancellable(variant, component, progress, LuaNil)
+    }
+
+    /// Install a game component with a cancellation token.
+    ///
+    /// The `cancel` value is passed to the integration as the last argument.
+    /// It should be a cancellation token of the packages runtime or `nil`.
+    pub fn install_component_cancellable(
+        &self,
+        variant: impl AsRef<GameVariant>,
+        component: impl AsRef<str>,
+        progress: impl Fn(ProgressReport) + Send + 'static,
+        cancel: impl IntoLua
     ) -> Result<(), LuaError> {
         let Some(install_component) = &self.components_install else {
             return Ok(());
@@ -300,18 +398,34 @@ impl GameIntegration {
         install_component.call::<()>((
             variant.as_ref().to_lua(&self.lua)?,
             component.as_ref(),
-            progress
+            progress,
+            cancel
         ))?;
 
         Ok(())
     }
 
     /// Uninstall a game component.
+    #[inline]
     pub fn uninstall_component(
         &self,
         variant: impl AsRef<GameVariant>,
         component: impl AsRef<str>,
         progress: impl Fn(ProgressReport) + Send + 'static
+    ) -> Result<(), LuaError> {
+        self.uninstall_component_cancellable(variant, component, progress, LuaNil)
+    }
+
+    /// Uninstall a game component with a cancellation token.
+    ///
+    /// The `cancel` value is passed to the integration as the last argument.
+    /// It should be a cancellation token of the packages runtime or `nil`.
+    pub fn uninstall_component_cancellable(
+        &self,
+        variant: impl AsRef<GameVariant>,
+        component: impl AsRef<str>,
+        progress: impl Fn(ProgressReport) + Send + 'static,
+        cancel: impl IntoLua
     ) -> Result<(), LuaError> {
         let Some(uninstall_component) = &self.components_uninstall else {
             return Ok(());
@@ -326,7 +440,8 @@ impl GameIntegration {
         uninstall_component.call::<()>((
             variant.as_ref().to_lua(&self.lua)?,
             component.as_ref(),
-            progress
+            progress,
+            cancel
         ))?;
 
         Ok(())
@@ -407,4 +522,160 @@ impl GameIntegration {
 
         set_property.call::<()>((name.as_ref(), value.into_lua(&self.lua)?))
     }
+
+    /// Get list of game saves directories which should be synchronized.
+    ///
+    /// Return `Ok(None)` if saves are not specified.
+    pub fn get_saves_directories(
+        &self,
+        variant: impl AsRef<GameVariant>
+    ) -> Result<Option<Box<[SavesDirectory]>>, LuaError> {
+        let Some(get_directories) = &self.saves_get_directories else {
+            return Ok(None);
+        };
+
+        let variant = variant.as_ref()
+            .to_lua(&self.lua)?;
+
+        get_directories.call::<Vec<LuaTable>>(variant)
+            .and_then(|directories| {
+                directories.iter()
+                    .map(SavesDirectory::from_lua)
+                    .collect::<Result<Box<[_]>, LuaError>>()
+            })
+            .map(Some)
+    }
+
+    /// Get list of game directories where mods can be deployed.
+    ///
+    /// Return `Ok(None)` if mods are not specified.
+    pub fn get_mods_directories(
+        &self,
+        variant: impl AsRef<GameVariant>
+    ) -> Result<Option<Box<[ModsDirectory]>>, LuaError> {
+        let Some(get_directories) = &self.mods_get_directories else {
+            return Ok(None);
+        };
+
+        let variant = variant.as_ref()
+            .to_lua(&self.lua)?;
+
+        get_directories.call::<Vec<LuaTable>>(variant)
+            .and_then(|directories| {
+                directories.iter()
+                    .map(ModsDirectory::from_lua)
+                    .collect::<Result<Box<[_]>, LuaError>>()
+            })
+            .map(Some)
+    }
+
+    /// Check if the game integration supports game files verification.
+    #[inline(always)]
+    pub const fn can_verify(&self) -> bool {
+        self.verify_get_manifest.is_some()
+    }
+
+    /// Get manifest of the game files which should be verified.
+    ///
+    /// Return `Ok(None)` if verification is not specified or the game is not
+    /// installed.
+    pub fn get_verify_manifest(
+        &self,
+        variant: impl AsRef<GameVariant>
+    ) -> Result<Option<VerifyManifest>, LuaError> {
+        let Some(get_manifest) = &self.verify_get_manifest else {
+            return Ok(None);
+        };
+
+        let variant = variant.as_ref()
+            .to_lua(&self.lua)?;
+
+        get_manifest.call::<Option<LuaTable>>(variant)
+            .and_then(|manifest| {
+                manifest.map(|manifest| {
+                    VerifyManifest::from_lua(&manifest)
+                }).transpose()
+            })
+    }
+
+    /// Check if the game integration can repair broken game files.
+    #[inline(always)]
+    pub const fn can_repair(&self) -> bool {
+        self.verify_repair.is_some()
+    }
+
+    /// Repair (re-download) given game files. Paths are relative to the
+    /// verify manifest's game directory.
+    pub fn repair_files(
+        &self,
+        variant: impl AsRef<GameVariant>,
+        files: &[PathBuf],
+        progress: impl Fn(ProgressReport) + Send + 'static
+    ) -> Result<(), LuaError> {
+        let Some(repair) = &self.verify_repair else {
+            return Ok(());
+        };
+
+        let files = files.iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        let progress = self.lua.create_function(move |_, report: LuaTable| {
+            progress(ProgressReport::from_lua(&report)?);
+
+            Ok(())
+        })?;
+
+        repair.call::<()>((
+            variant.as_ref().to_lua(&self.lua)?,
+            files,
+            progress
+        ))?;
+
+        Ok(())
+    }
+
+    /// Check if the game integration allows to move the game installation.
+    #[inline(always)]
+    pub const fn can_relocate(&self) -> bool {
+        self.storage_get_path.is_some()
+    }
+
+    /// Get path to the game installation folder.
+    ///
+    /// Return `Ok(None)` if storage is not specified or the game is not
+    /// installed.
+    pub fn get_storage_path(
+        &self,
+        variant: impl AsRef<GameVariant>
+    ) -> Result<Option<PathBuf>, LuaError> {
+        let Some(get_path) = &self.storage_get_path else {
+            return Ok(None);
+        };
+
+        let variant = variant.as_ref()
+            .to_lua(&self.lua)?;
+
+        get_path.call::<Option<LuaString>>(variant)
+            .map(|path| {
+                path.map(|path| PathBuf::from(path.to_string_lossy().to_string()))
+            })
+    }
+
+    /// Update path to the game installation folder after its files were
+    /// moved to the new location. Do nothing if storage is not specified.
+    pub fn set_storage_path(
+        &self,
+        variant: impl AsRef<GameVariant>,
+        path: impl AsRef<Path>
+    ) -> Result<(), LuaError> {
+        let Some(set_path) = &self.storage_set_path else {
+            return Ok(());
+        };
+
+        set_path.call::<()>((
+            variant.as_ref().to_lua(&self.lua)?,
+            path.as_ref().to_string_lossy().to_string()
+        ))
+    }
 }
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-games
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::PathBuf;
+
+use mlua::prelude::*;
+
+use agl_locale::string::LocalizableString;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModsDirectory {
+    /// Unique name of the mods directory.
+    pub name: String,
+
+    /// Optional title used in UI.
+    pub title: Option<LocalizableString>,
+
+    /// Path to the game directory where mods are deployed.
+    pub path: PathBuf,
+
+    /// Optional list of extensions of mod files which can be deployed. Other
+    /// files of the mods are ignored.
+    pub extensions: Option<Box<[String]>>
+}
+
+impl ModsDirectory {
+    pub fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        Ok(Self {
+            name: value.get::<String>("name")?,
+
+            title: value.get::<LuaValue>("title")
+                .map(|title| {
+                    if title.is_nil() || title.is_null() {
+                        Ok(None)
+                    } else {
+                        LocalizableString::from_lua(&title).map(Some)
+                    }
+                })
+                .unwrap_or(Ok(None))?,
+
+            path: value.get::<LuaString>("path")
+                .map(|path| PathBuf::from(path.to_string_lossy().to_string()))?,
+
+            extensions: value.get::<Option<Vec<String>>>("extensions")?
+                .map(|extensions| {
+                    extensions.into_iter()
+                        .map(|extension| extension.trim_start_matches('.').to_lowercase())
+                        .collect()
+                })
+        })
+    }
+
+    /// Check whether the file with given path can be deployed to this
+    /// directory according to its extensions list.
+    pub fn is_allowed(&self, path: impl AsRef<std::path::Path>) -> bool {
+        let Some(extensions) = &self.extensions else {
+            return true;
+        };
+
+        path.as_ref()
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .is_some_and(|extension| extensions.contains(&extension))
+    }
+}
@@ -16,17 +16,42 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::path::PathBuf;
+
 use mlua::prelude::*;
 
 use agl_locale::string::LocalizableString;
 
 use super::ProgressReport;
 
+/// Disk space needed to perform a pipeline action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceEstimate {
+    /// Path to the folder where the space is needed. The folder itself may
+    /// not exist yet.
+    pub path: PathBuf,
+
+    /// Amount of bytes needed.
+    pub size: u64
+}
+
+impl SpaceEstimate {
+    pub fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        Ok(Self {
+            path: value.get::<LuaString>("path")
+                .map(|path| PathBuf::from(path.to_string_lossy().to_string()))?,
+
+            size: value.get::<u64>("size")?
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PipelineAction {
     lua: Lua,
     title: LocalizableString,
     description: Option<LocalizableString>,
+    estimate: Option<LuaFunction>,
     before: Option<LuaFunction>,
     perform: LuaFunction
 }
@@ -49,6 +74,7 @@ impl PipelineAction {
                 })
                 .unwrap_or(Ok(None))?,
 
+            estimate: table.get::<Option<LuaFunction>>("estimate").ok().flatten(),
             before: table.get::<Option<LuaFunction>>("before").ok().flatten(),
             perform: table.get::<LuaFunction>("perform")?
         })
@@ -66,6 +92,23 @@ impl PipelineAction {
         self.description.as_ref()
     }
 
+    /// Try to call `estimate` function if it's specified or return `None`.
+    ///
+    /// Returned value is used to check that there's enough free disk space
+    /// before the action is performed.
+    pub fn estimate(&self) -> Result<Option<SpaceEstimate>, LuaError> {
+        let Some(estimate) = &self.estimate else {
+            return Ok(None);
+        };
+
+        estimate.call::<Option<LuaTable>>(())
+            .and_then(|estimate| {
+                estimate.map(|estimate| {
+                    SpaceEstimate::from_lua(&estimate)
+                }).transpose()
+            })
+    }
+
     /// Try to call `before` function if it's specified or return `None`.
     ///
     /// If `Some(true)` is returned, then the action should be started.
@@ -93,9 +136,22 @@ impl PipelineAction {
     /// If `true` is returned, then the next action should be executed.
     /// Otherwise, if `false` is returned, then current action has failed and
     /// pipeline should be stopped.
+    #[inline]
     pub fn perform(
         &self,
         progress: impl Fn(ProgressReport) + Send + 'static
+    ) -> Result<(), LuaError> {
+        self.perform_cancellable(progress, LuaNil)
+    }
+
+    /// Perform the action with a cancellation token.
+    ///
+    /// The `cancel` value is passed to the action as the second argument. It
+    /// should be a cancellation token of the packages runtime or `nil`.
+    pub fn perform_cancellable(
+        &self,
+        progress: impl Fn(ProgressReport) + Send + 'static,
+        cancel: impl IntoLua
     ) -> Result<(), LuaError> {
         let progress = self.lua.create_function(move |_, report: LuaTable| {
             progress(ProgressReport::from_lua(&report)?);
@@ -103,6 +159,6 @@ impl PipelineAction {
             Ok(())
         })?;
 
-        self.perform.call(progress)
+        self.perform.call((progress, cancel))
     }
 }
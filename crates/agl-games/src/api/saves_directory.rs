@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-games
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::PathBuf;
+
+use mlua::prelude::*;
+
+use agl_locale::string::LocalizableString;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavesDirectory {
+    /// Unique name of the saves directory.
+    pub name: String,
+
+    /// Optional title used in UI.
+    pub title: Option<LocalizableString>,
+
+    /// Path to the saves directory.
+    pub path: PathBuf
+}
+
+impl SavesDirectory {
+    pub fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        Ok(Self {
+            name: value.get::<String>("name")?,
+
+            title: value.get::<LuaValue>("title")
+                .map(|title| {
+                    if title.is_nil() || title.is_null() {
+                        Ok(None)
+                    } else {
+                        LocalizableString::from_lua(&title).map(Some)
+                    }
+                })
+                .unwrap_or(Ok(None))?,
+
+            path: value.get::<LuaString>("path")
+                .map(|path| PathBuf::from(path.to_string_lossy().to_string()))?
+        })
+    }
+}
@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-games
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf, Component};
+use std::str::FromStr;
+
+use mlua::prelude::*;
+
+use agl_core::hashes::HashAlgorithm;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyFile {
+    /// Path to the file relative to the manifest's root directory.
+    pub path: PathBuf,
+
+    /// Expected size of the file in bytes.
+    pub size: Option<u64>,
+
+    /// Expected lowercase hex-encoded hash of the file.
+    pub hash: Option<String>
+}
+
+impl VerifyFile {
+    pub fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        let path = value.get::<LuaString>("path")
+            .map(|path| PathBuf::from(path.to_string_lossy().to_string()))?;
+
+        if !is_relative_path(&path) {
+            return Err(LuaError::external(format!(
+                "verify file path must be relative to the game directory: {path:?}"
+            )));
+        }
+
+        Ok(Self {
+            path,
+
+            size: value.get::<Option<u64>>("size")?,
+
+            hash: value.get::<Option<String>>("hash")?
+                .map(|hash| hash.trim().to_lowercase())
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyManifest {
+    /// Path to the game directory which should be verified.
+    pub path: PathBuf,
+
+    /// Algorithm used to calculate the files hashes.
+    pub algorithm: HashAlgorithm,
+
+    /// List of expected game files.
+    pub files: Box<[VerifyFile]>,
+
+    /// List of relative paths to files and folders which are not part of the
+    /// game and should not be reported as extra (e.g. configs or logs).
+    pub ignore: Box<[PathBuf]>
+}
+
+impl VerifyManifest {
+    pub fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        Ok(Self {
+            path: value.get::<LuaString>("path")
+                .map(|path| PathBuf::from(path.to_string_lossy().to_string()))?,
+
+            algorithm: value.get::<LuaString>("algorithm")
+                .and_then(|algorithm| {
+                    HashAlgorithm::from_str(&algorithm.to_string_lossy())
+                        .map_err(LuaError::external)
+                })?,
+
+            files: value.get::<Vec<LuaTable>>("files")?
+                .iter()
+                .map(VerifyFile::from_lua)
+                .collect::<Result<Box<[_]>, LuaError>>()?,
+
+            ignore: value.get::<Option<Vec<LuaString>>>("ignore")?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|path| PathBuf::from(path.to_string_lossy().to_string()))
+                .collect()
+        })
+    }
+
+    /// Check whether given relative path is listed in the ignore list or
+    /// is stored inside of an ignored folder.
+    pub fn is_ignored(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+
+        self.ignore.iter().any(|ignored| path.starts_with(ignored))
+    }
+}
+
+/// Check that the path is relative and doesn't point outside of its root.
+fn is_relative_path(path: &Path) -> bool {
+    path.components().all(|component| {
+        matches!(component, Component::Normal(_) | Component::CurDir)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(files: &str) -> Result<VerifyManifest, LuaError> {
+        let lua = Lua::new();
+
+        let manifest = lua.load(format!(r#"
+            return {{
+                path = "/games/test",
+                algorithm = "md5",
+                files = {{ {files} }},
+                ignore = {{ "logs", "config.ini" }}
+            }}
+        "#)).eval::<LuaTable>()?;
+
+        VerifyManifest::from_lua(&manifest)
+    }
+
+    #[test]
+    fn parse() -> Result<(), LuaError> {
+        let manifest = manifest(r#"
+            { path = "game.exe", size = 1024, hash = "0123ABCD" },
+            { path = "data/file.bin" }
+        "#)?;
+
+        assert_eq!(manifest.algorithm, HashAlgorithm::Md5);
+        assert_eq!(manifest.files.len(), 2);
+
+        assert_eq!(manifest.files[0].size, Some(1024));
+        assert_eq!(manifest.files[0].hash.as_deref(), Some("0123abcd"));
+
+        assert_eq!(manifest.files[1].size, None);
+        assert_eq!(manifest.files[1].hash, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ignore() -> Result<(), LuaError> {
+        let manifest = manifest("")?;
+
+        assert!(manifest.is_ignored("logs/latest.log"));
+        assert!(manifest.is_ignored("config.ini"));
+        assert!(!manifest.is_ignored("config.ini.bak"));
+        assert!(!manifest.is_ignored("game.exe"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn outside_paths() {
+        assert!(manifest(r#"{ path = "../game.exe" }"#).is_err());
+        assert!(manifest(r#"{ path = "/etc/passwd" }"#).is_err());
+        assert!(manifest(r#"{ path = "data/../../game.exe" }"#).is_err());
+    }
+}
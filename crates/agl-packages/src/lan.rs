@@ -0,0 +1,746 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-packages
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Sharing packages resources between launchers in the local network.
+//!
+//! Launchers announce themselves using local service discovery multicast
+//! messages (BEP 14) and serve resources of their storages over a minimal
+//! read-only HTTP server. Resources are requested by their hashes and verified
+//! after downloading, so peers don't have to be trusted.
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener, TcpStream, UdpSocket};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value as Json};
+
+use crate::hash::Hash;
+use crate::storage::Storage;
+
+/// Local service discovery multicast group address.
+const LSD_MULTICAST_ADDR: Ipv4Addr = Ipv4Addr::new(239, 192, 152, 143);
+
+/// Local service discovery port.
+const LSD_PORT: u16 = 6771;
+
+/// Interval between the launcher's announcements.
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Peers which didn't announce themselves for this time are forgotten.
+const PEER_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Timeout of connecting to a peer.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Timeout of reading and writing data from and to a peer.
+const IO_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Maximal amount of requests served at the same time. Other connections
+/// are answered with `503 Service Unavailable`.
+const MAX_CONNECTIONS: usize = 16;
+
+/// Maximal size of a folder resource's entries list.
+const MAX_LISTING_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Maximal size of request and response status lines and headers.
+const MAX_HEADERS_SIZE: u64 = 16 * 1024;
+
+/// Get identifier of the packages resources sharing service which is used
+/// instead of a torrent's info hash in the LSD announcements.
+fn service_id() -> String {
+    blake3::hash(b"agl-packages/lan-cache").to_hex()[..40].to_string()
+}
+
+/// Percent-encode relative path for use in a request URL.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~' | b'/') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    encoded
+}
+
+/// Decode percent-encoded request URL path.
+fn decode_path(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/// Resolve relative path within the resource folder. Return `None` if the path
+/// is pointing outside of the resource.
+fn resolve_resource_path(root: &Path, relative: &str) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+
+    // Joining an empty path appends a trailing slash which fails to resolve
+    // for file resources.
+    if relative.is_empty() {
+        return Some(root);
+    }
+
+    let path = root.join(relative).canonicalize().ok()?;
+
+    path.starts_with(&root).then_some(path)
+}
+
+/// List files and folders of a stored resource folder. Entries pointing
+/// outside of the resource are skipped.
+fn list_resource(root: &Path) -> std::io::Result<Vec<Json>> {
+    let root = root.canonicalize()?;
+
+    let mut entries = Vec::new();
+    let mut queue = vec![root.clone()];
+
+    while let Some(folder) = queue.pop() {
+        for entry in folder.read_dir()? {
+            let Ok(path) = entry?.path().canonicalize() else {
+                continue;
+            };
+
+            if !path.starts_with(&root) {
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(&root) else {
+                continue;
+            };
+
+            let relative = relative.to_string_lossy()
+                .replace('\\', "/");
+
+            if path.is_dir() {
+                entries.push(json!({
+                    "path": relative,
+                    "folder": true
+                }));
+
+                queue.push(path);
+            } else {
+                entries.push(json!({
+                    "path": relative,
+                    "size": path.metadata()?.len()
+                }));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &[(&str, String)],
+    length: u64
+) -> std::io::Result<()> {
+    let mut response = format!("HTTP/1.0 {status}\r\nContent-Length: {length}\r\n");
+
+    for (name, value) in headers {
+        response.push_str(&format!("{name}: {value}\r\n"));
+    }
+
+    response.push_str("\r\n");
+
+    stream.write_all(response.as_bytes())
+}
+
+/// Handle request to the resources server.
+///
+/// - `GET /resources/<hash>` returns file resource content or folder
+///   resource's entries list.
+/// - `GET /resources/<hash>/<path>` returns content of a folder resource's file.
+fn handle_connection(storage: &Storage, mut stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?.take(MAX_HEADERS_SIZE));
+    let mut request = String::new();
+
+    reader.read_line(&mut request)?;
+
+    // Skip request headers.
+    loop {
+        let mut line = String::new();
+
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut request = request.split_whitespace();
+
+    let (Some("GET"), Some(path)) = (request.next(), request.next()) else {
+        return write_response(&mut stream, "405 Method Not Allowed", &[], 0);
+    };
+
+    let Some(path) = path.strip_prefix("/resources/").and_then(decode_path) else {
+        return write_response(&mut stream, "404 Not Found", &[], 0);
+    };
+
+    let (hash, relative) = path.split_once('/')
+        .unwrap_or((path.as_str(), ""));
+
+    let Some(hash) = Hash::from_base32(hash) else {
+        return write_response(&mut stream, "400 Bad Request", &[], 0);
+    };
+
+    let root = storage.resource_path(&hash);
+
+    let Some(path) = resolve_resource_path(&root, relative) else {
+        return write_response(&mut stream, "404 Not Found", &[], 0);
+    };
+
+    if path.is_file() {
+        let mut file = std::fs::File::open(&path)?;
+        let length = file.metadata()?.len();
+
+        write_response(&mut stream, "200 OK", &[
+            ("X-Resource-Kind", String::from("file"))
+        ], length)?;
+
+        std::io::copy(&mut file, &mut stream)?;
+    }
+
+    else if relative.is_empty() {
+        let entries = serde_json::to_vec(&list_resource(&root)?)?;
+
+        write_response(&mut stream, "200 OK", &[
+            ("X-Resource-Kind", String::from("folder")),
+            ("Content-Type", String::from("application/json"))
+        ], entries.len() as u64)?;
+
+        stream.write_all(&entries)?;
+    }
+
+    else {
+        write_response(&mut stream, "404 Not Found", &[], 0)?;
+    }
+
+    Ok(())
+}
+
+/// Accept connections to the resources server, handling at most
+/// `MAX_CONNECTIONS` of them at the same time.
+fn serve(storage: Storage, listener: TcpListener) {
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+
+        if connections.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::AcqRel);
+
+            let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+            let _ = write_response(&mut stream, "503 Service Unavailable", &[], 0);
+
+            continue;
+        }
+
+        let storage = storage.clone();
+        let connections = connections.clone();
+
+        std::thread::spawn(move || {
+            #[allow(unused)]
+            if let Err(err) = handle_connection(&storage, stream) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(?err, "failed to handle LAN cache request");
+            }
+
+            connections.fetch_sub(1, Ordering::AcqRel);
+        });
+    }
+}
+
+/// Perform GET request to a peer and return the response headers, length
+/// and a reader of its body. At most `MAX_HEADERS_SIZE` bytes plus the body
+/// length announced by the peer are read from the connection.
+fn request(
+    peer: SocketAddr,
+    path: &str
+) -> std::io::Result<(HashMap<String, String>, u64, impl Read + use<>)> {
+    let mut stream = TcpStream::connect_timeout(&peer, CONNECT_TIMEOUT)?;
+
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    stream.write_all(format!("GET {path} HTTP/1.0\r\nHost: {peer}\r\n\r\n").as_bytes())?;
+
+    let mut reader = BufReader::new(stream.take(MAX_HEADERS_SIZE));
+    let mut status = String::new();
+
+    reader.read_line(&mut status)?;
+
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(std::io::Error::other(format!(
+            "peer returned error: {}",
+            status.trim()
+        )));
+    }
+
+    let mut headers = HashMap::new();
+
+    loop {
+        let mut line = String::new();
+
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers.get("content-length")
+        .and_then(|length| length.parse::<u64>().ok())
+        .ok_or_else(|| std::io::Error::other("missing content length"))?;
+
+    // Part of the body can already be buffered.
+    let buffered = reader.buffer().len() as u64;
+
+    reader.get_mut().set_limit(length.saturating_sub(buffered));
+
+    Ok((headers, length, reader.take(length)))
+}
+
+/// Copy response body to a new file, failing if the body is shorter than
+/// announced by the peer.
+fn write_body(mut body: impl Read, length: u64, path: &Path) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+
+    if std::io::copy(&mut body, &mut file)? != length {
+        return Err(std::io::Error::other("peer sent truncated response body"));
+    }
+
+    Ok(())
+}
+
+/// Download resource from a peer to the given path.
+///
+/// Peers are not trusted: resource's hash must be verified by the caller
+/// before using the downloaded files.
+fn fetch_from(peer: SocketAddr, hash: &Hash, path: &Path) -> std::io::Result<()> {
+    let base = format!("/resources/{}", hash.to_base32());
+
+    let (headers, length, mut body) = request(peer, &base)?;
+
+    match headers.get("x-resource-kind").map(String::as_str) {
+        Some("file") => write_body(body, length, path)?,
+
+        Some("folder") => {
+            if length > MAX_LISTING_SIZE {
+                return Err(std::io::Error::other("resource entries list is too large"));
+            }
+
+            let mut entries = Vec::new();
+
+            body.read_to_end(&mut entries)?;
+
+            let entries = serde_json::from_slice::<Vec<Json>>(&entries)?;
+
+            std::fs::create_dir_all(path)?;
+
+            for entry in entries {
+                let Some(relative) = entry.get("path").and_then(Json::as_str) else {
+                    continue;
+                };
+
+                // Don't let peers write files outside of the target folder.
+                if relative.split('/').any(|part| part.is_empty() || part == "..") {
+                    return Err(std::io::Error::other(format!("invalid resource entry path: {relative}")));
+                }
+
+                let entry_path = path.join(relative);
+
+                if entry.get("folder").and_then(Json::as_bool) == Some(true) {
+                    std::fs::create_dir_all(&entry_path)?;
+
+                    continue;
+                }
+
+                if let Some(parent) = entry_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+
+                let Some(size) = entry.get("size").and_then(Json::as_u64) else {
+                    return Err(std::io::Error::other(format!("missing resource entry size: {relative}")));
+                };
+
+                let url = format!("{base}/{}", encode_path(relative));
+
+                let (_, length, body) = request(peer, &url)?;
+
+                // Don't let peers send more data than they announced.
+                if length != size {
+                    return Err(std::io::Error::other(format!("resource entry size mismatch: {relative}")));
+                }
+
+                write_body(body, length, &entry_path)?;
+            }
+        }
+
+        _ => return Err(std::io::Error::other("unknown resource kind"))
+    }
+
+    Ok(())
+}
+
+/// Parse LSD announcement and return the announced port if it's sent by
+/// another launcher.
+fn parse_announce(message: &str, cookie: &str) -> Option<u16> {
+    let mut lines = message.lines();
+
+    if lines.next()?.trim() != "BT-SEARCH * HTTP/1.1" {
+        return None;
+    }
+
+    let mut port = None;
+    let mut info_hash = None;
+    let mut peer_cookie = None;
+
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        match name.trim().to_ascii_lowercase().as_str() {
+            "port" => port = value.trim().parse::<u16>().ok(),
+            "infohash" => info_hash = Some(value.trim().to_ascii_lowercase()),
+            "cookie" => peer_cookie = Some(value.trim().to_string()),
+
+            _ => ()
+        }
+    }
+
+    if info_hash? != service_id() || peer_cookie.as_deref() == Some(cookie) {
+        return None;
+    }
+
+    port
+}
+
+#[derive(Debug)]
+struct LanCacheInner {
+    port: u16,
+    cookie: String,
+    socket: UdpSocket,
+    peers: RwLock<HashMap<SocketAddr, Instant>>
+}
+
+impl LanCacheInner {
+    fn announce(&self) -> std::io::Result<()> {
+        let message = format!(
+            "BT-SEARCH * HTTP/1.1\r\nHost: {LSD_MULTICAST_ADDR}:{LSD_PORT}\r\nPort: {}\r\nInfohash: {}\r\ncookie: {}\r\n\r\n\r\n",
+            self.port,
+            service_id(),
+            self.cookie
+        );
+
+        self.socket.send_to(
+            message.as_bytes(),
+            SocketAddrV4::new(LSD_MULTICAST_ADDR, LSD_PORT)
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Packages resources cache shared with other launchers in the local network.
+#[derive(Debug, Clone)]
+pub struct LanCache(Arc<LanCacheInner>);
+
+impl LanCache {
+    /// Start serving resources of the given storage on the given TCP port
+    /// (random one if `0`) and discovering other launchers in the local
+    /// network.
+    pub fn start(storage: Storage, port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        let port = listener.local_addr()?.port();
+
+        // LSD port can be used by torrent clients so it must be shared.
+        let socket = socket2::Socket::new(
+            socket2::Domain::IPV4,
+            socket2::Type::DGRAM,
+            Some(socket2::Protocol::UDP)
+        )?;
+
+        socket.set_reuse_address(true)?;
+        socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, LSD_PORT)).into())?;
+        socket.join_multicast_v4(&LSD_MULTICAST_ADDR, &Ipv4Addr::UNSPECIFIED)?;
+
+        let cache = Self(Arc::new(LanCacheInner {
+            port,
+            cookie: Hash::rand().to_base32(),
+            socket: socket.into(),
+            peers: RwLock::new(HashMap::new())
+        }));
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(?port, "started packages LAN cache");
+
+        // Serve stored resources.
+        std::thread::spawn(move || serve(storage, listener));
+
+        // Periodically announce the launcher.
+        {
+            let cache = cache.0.clone();
+
+            std::thread::spawn(move || {
+                loop {
+                    #[allow(unused)]
+                    if let Err(err) = cache.announce() {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(?err, "failed to announce LAN cache");
+                    }
+
+                    std::thread::sleep(ANNOUNCE_INTERVAL);
+                }
+            });
+        }
+
+        // Listen to other launchers' announcements.
+        {
+            let cache = cache.0.clone();
+
+            std::thread::spawn(move || {
+                let mut buf = [0; 1024];
+
+                while let Ok((n, addr)) = cache.socket.recv_from(&mut buf) {
+                    let message = String::from_utf8_lossy(&buf[..n]);
+
+                    let Some(port) = parse_announce(&message, &cache.cookie) else {
+                        continue;
+                    };
+
+                    let peer = SocketAddr::new(addr.ip(), port);
+
+                    let is_new = match cache.peers.write() {
+                        Ok(mut peers) => peers.insert(peer, Instant::now()).is_none(),
+                        Err(_) => false
+                    };
+
+                    // Announce ourselves to the new peer right away instead
+                    // of waiting for the next announcement.
+                    if is_new {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(?peer, "discovered LAN cache peer");
+
+                        let _ = cache.announce();
+                    }
+                }
+            });
+        }
+
+        Ok(cache)
+    }
+
+    /// TCP port of the resources server.
+    #[inline]
+    pub fn port(&self) -> u16 {
+        self.0.port
+    }
+
+    /// List of recently announced peers.
+    pub fn peers(&self) -> Vec<SocketAddr> {
+        let Ok(peers) = self.0.peers.read() else {
+            return vec![];
+        };
+
+        peers.iter()
+            .filter(|(_, announced_at)| announced_at.elapsed() < PEER_TIMEOUT)
+            .map(|(peer, _)| *peer)
+            .collect()
+    }
+
+    /// Try to download resource with the given hash from the local network
+    /// peers to the given path. Return `Ok(true)` if the resource was
+    /// downloaded and its hash is verified. Blocks the current thread.
+    pub fn fetch(&self, hash: &Hash, path: &Path) -> std::io::Result<bool> {
+        fn remove(path: &Path) {
+            if path.is_dir() {
+                let _ = std::fs::remove_dir_all(path);
+            } else {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+
+        for peer in self.peers() {
+            #[allow(unused)]
+            if let Err(err) = fetch_from(peer, hash, path) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(?err, ?peer, ?hash, "failed to fetch resource from LAN peer");
+
+                remove(path);
+
+                continue;
+            }
+
+            if &Hash::digitize_path(path)? == hash {
+                #[cfg(feature = "tracing")]
+                tracing::info!(?peer, ?hash, "fetched resource from LAN peer");
+
+                return Ok(true);
+            }
+
+            remove(path);
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        assert_eq!(encode_path("folder/file name.txt"), "folder/file%20name.txt");
+        assert_eq!(decode_path("folder/file%20name.txt").as_deref(), Some("folder/file name.txt"));
+        assert_eq!(decode_path("bad%2"), None);
+    }
+
+    #[test]
+    fn announce() {
+        let message = format!("BT-SEARCH * HTTP/1.1\r\nHost: 239.192.152.143:6771\r\nPort: 1234\r\nInfohash: {}\r\ncookie: abc\r\n\r\n\r\n", service_id());
+
+        assert_eq!(parse_announce(&message, "def"), Some(1234));
+        assert_eq!(parse_announce(&message, "abc"), None);
+
+        let message = message.replace(&service_id(), &"0".repeat(40));
+
+        assert_eq!(parse_announce(&message, "def"), None);
+    }
+
+    #[test]
+    fn request_limits() -> std::io::Result<()> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let peer = listener.local_addr()?;
+
+        std::thread::spawn(move || {
+            for response in [
+                // Endless headers.
+                format!("HTTP/1.0 200 OK\r\nX-Header: {}", "a".repeat(MAX_HEADERS_SIZE as usize * 2)),
+
+                // Body longer than announced.
+                format!("HTTP/1.0 200 OK\r\nContent-Length: 5\r\n\r\n{}", "b".repeat(1024))
+            ] {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    break;
+                };
+
+                // Keep connections open so reading is stopped by the limits
+                // rather than by the end of stream.
+                std::thread::spawn(move || {
+                    let _ = stream.write_all(response.as_bytes());
+
+                    std::thread::sleep(IO_TIMEOUT);
+                });
+            }
+        });
+
+        assert!(request(peer, "/").is_err());
+
+        let (_, length, mut body) = request(peer, "/")?;
+        let mut content = Vec::new();
+
+        body.read_to_end(&mut content)?;
+
+        assert_eq!(length, 5);
+        assert_eq!(content, b"bbbbb");
+
+        Ok(())
+    }
+
+    #[test]
+    fn serve_fetch() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join(".agl-packages-lan-test");
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+
+        let storage = Storage::open(path.join("storage"))?;
+
+        // Store a folder resource.
+        let folder = path.join("folder");
+
+        std::fs::create_dir_all(folder.join("nested folder"))?;
+        std::fs::write(folder.join("file.txt"), b"Hello, World!")?;
+        std::fs::write(folder.join("nested folder/file 2.txt"), vec![42; 100_000])?;
+
+        let folder_hash = Hash::digitize_path(&folder)?;
+
+        std::fs::rename(&folder, storage.resource_path(&folder_hash))?;
+
+        // Store a file resource.
+        let file = path.join("file");
+
+        std::fs::write(&file, b"Example file")?;
+
+        let file_hash = Hash::digitize_path(&file)?;
+
+        std::fs::rename(&file, storage.resource_path(&file_hash))?;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let peer = listener.local_addr()?;
+
+        std::thread::spawn(move || serve(storage, listener));
+
+        fetch_from(peer, &folder_hash, &path.join("fetched_folder"))?;
+        fetch_from(peer, &file_hash, &path.join("fetched_file"))?;
+
+        assert_eq!(Hash::digitize_path(path.join("fetched_folder"))?, folder_hash);
+        assert_eq!(Hash::digitize_path(path.join("fetched_file"))?, file_hash);
+
+        assert!(fetch_from(peer, &Hash::rand(), &path.join("missing")).is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+}
@@ -24,6 +24,8 @@ pub mod format;
 pub mod package;
 pub mod storage;
 pub mod lock;
+pub mod torrent;
+pub mod lan;
 
 #[cfg(test)]
 mod tests;
@@ -97,30 +97,49 @@ pub struct ResourceInfoManifest {
     pub format: Option<ResourceFormat>,
 
     /// (optional) base32 seahash of the resource.
-    pub hash: Option<Hash>
+    pub hash: Option<Hash>,
+
+    /// (optional) magnet link, info hash or URI of a torrent file which can be
+    /// used to download the resource over BitTorrent protocol. The torrent
+    /// must contain a single file.
+    pub torrent: Option<String>,
+
+    /// List of HTTP mirrors of the resource. They are used as BEP 19 webseeds
+    /// of the resource's torrent and as fallback download sources.
+    pub webseeds: Vec<String>
 }
 
 impl ResourceInfoManifest {
     pub fn to_json(&self) -> Json {
-        match (self.format.as_ref(), self.hash.as_ref()) {
-            (Some(format), Some(hash)) => json!({
-                "uri": self.uri,
-                "format": format.to_string(),
-                "hash": hash.to_base32()
-            }),
-
-            (Some(format), None) => json!({
-                "uri": self.uri,
-                "format": format.to_string()
-            }),
-
-            (None, Some(hash)) => json!({
-                "uri": self.uri,
-                "hash": hash.to_base32()
-            }),
-
-            (None, None) => json!(self.uri)
+        if self.format.is_none()
+            && self.hash.is_none()
+            && self.torrent.is_none()
+            && self.webseeds.is_empty()
+        {
+            return json!(self.uri);
+        }
+
+        let mut value = json!({
+            "uri": self.uri
+        });
+
+        if let Some(format) = &self.format {
+            value["format"] = json!(format.to_string());
+        }
+
+        if let Some(hash) = &self.hash {
+            value["hash"] = json!(hash.to_base32());
+        }
+
+        if let Some(torrent) = &self.torrent {
+            value["torrent"] = json!(torrent);
         }
+
+        if !self.webseeds.is_empty() {
+            value["webseeds"] = json!(self.webseeds);
+        }
+
+        value
     }
 
     pub fn from_json(value: &Json) -> Option<Self> {
@@ -128,7 +147,9 @@ impl ResourceInfoManifest {
             return Some(Self {
                 uri: uri.to_string(),
                 format: None,
-                hash: None
+                hash: None,
+                torrent: None,
+                webseeds: vec![]
             });
         }
 
@@ -143,7 +164,51 @@ impl ResourceInfoManifest {
 
             hash: value.get("hash")
                 .and_then(Json::as_str)
-                .and_then(Hash::from_base32)
+                .and_then(Hash::from_base32),
+
+            torrent: value.get("torrent")
+                .and_then(Json::as_str)
+                .map(String::from),
+
+            webseeds: value.get("webseeds")
+                .and_then(Json::as_array)
+                .map(|webseeds| {
+                    webseeds.iter()
+                        .flat_map(Json::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_info_json() {
+        let resource = ResourceInfoManifest::from_json(&json!("example.zip"))
+            .unwrap();
+
+        assert_eq!(resource.to_json(), json!("example.zip"));
+
+        let value = json!({
+            "uri": "https://example.com/example.zip",
+            "format": "archive",
+            "torrent": "magnet:?xt=urn:btih:cdf37bb22c748fa8cb1594bdc39efed1bcd5cc31",
+            "webseeds": [
+                "https://mirror.example.com/example.zip"
+            ]
+        });
+
+        let resource = ResourceInfoManifest::from_json(&value)
+            .unwrap();
+
+        assert_eq!(resource.format, Some(ResourceFormat::Archive));
+        assert_eq!(resource.hash, None);
+        assert_eq!(resource.webseeds.len(), 1);
+        assert_eq!(resource.to_json(), value);
+    }
+}
@@ -18,11 +18,12 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use serde_json::Value as Json;
 
 use agl_core::network::downloader::{
-    Downloader, DownloadOptions, DownloaderError
+    Downloader, DownloaderTask, DownloadOptions, DownloaderError
 };
 
 use agl_core::tasks;
@@ -30,8 +31,10 @@ use agl_core::archives::{Archive, ArchiveFormat, ArchiveError};
 
 use crate::hash::Hash;
 use crate::format::ResourceFormat;
-use crate::package::PackageManifest;
+use crate::package::{PackageManifest, ResourceInfoManifest};
 use crate::lock::{Lock, LockedPackageInfo, LockedResourceInfo};
+use crate::torrent::{TorrentSource, TorrentSourceError};
+use crate::lan::LanCache;
 
 #[derive(Debug, thiserror::Error)]
 pub enum InstallPackagesError {
@@ -83,6 +86,28 @@ pub enum InstallPackagesError {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct InstallOptions {
+    /// Source of resources which have torrent sources. If unset, all the
+    /// resources are downloaded over HTTP.
+    pub torrent_source: Option<Arc<dyn TorrentSource>>,
+
+    /// Local network cache. If set, resources with known hashes are fetched
+    /// from other launchers in the local network before downloading them from
+    /// the internet.
+    pub lan_cache: Option<LanCache>
+}
+
+/// Resource downloading task.
+enum ResourceTask {
+    Http(DownloaderTask),
+
+    Torrent {
+        task: tasks::JoinHandle<Result<PathBuf, TorrentSourceError>>,
+        folder: PathBuf
+    }
+}
+
 /// Anime Games Launcher packages storage.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Storage {
@@ -221,10 +246,27 @@ impl Storage {
     }
 
     /// Install packages to the current storage and provide a lock for them.
+    #[inline]
     pub async fn install_packages<T: ToString>(
         &self,
         downloader: &Downloader,
         urls: impl IntoIterator<Item = T>
+    ) -> Result<Lock, InstallPackagesError> {
+        self.install_packages_with_options(downloader, urls, InstallOptions::default()).await
+    }
+
+    /// Install packages to the current storage using provided options and
+    /// provide a lock for them.
+    ///
+    /// Resources with torrent sources are downloaded using the provided
+    /// torrent source if it's available. If the torrent downloading fails then
+    /// the resource is downloaded over HTTP. Hashes of the resources are
+    /// verified in both cases.
+    pub async fn install_packages_with_options<T: ToString>(
+        &self,
+        downloader: &Downloader,
+        urls: impl IntoIterator<Item = T>,
+        options: InstallOptions
     ) -> Result<Lock, InstallPackagesError> {
         /// Normalize given URL.
         #[inline]
@@ -270,6 +312,118 @@ impl Storage {
             }
         }
 
+        /// Resolve URI relative to the package's parent URL.
+        #[inline]
+        fn resolve_url(parent_url: &str, uri: &str) -> String {
+            if uri.starts_with("http") {
+                normalize_url(uri)
+            } else {
+                normalize_url(format!("{parent_url}/{uri}"))
+            }
+        }
+
+        /// Resolve torrent source and webseeds URIs of the resource.
+        fn resolve_resource_info(
+            parent_url: &str,
+            resource_info: &ResourceInfoManifest
+        ) -> ResourceInfoManifest {
+            let mut resource_info = resource_info.clone();
+
+            resource_info.torrent = resource_info.torrent.map(|torrent| {
+                let is_info_hash = matches!(torrent.len(), 40 | 64)
+                    && torrent.chars().all(|char| char.is_ascii_hexdigit());
+
+                if torrent.starts_with("magnet:") || is_info_hash {
+                    torrent
+                } else {
+                    resolve_url(parent_url, &torrent)
+                }
+            });
+
+            resource_info.webseeds = resource_info.webseeds.iter()
+                .map(|webseed| resolve_url(parent_url, webseed))
+                .collect();
+
+            resource_info
+        }
+
+        /// Try to download resource from its mirrors.
+        async fn download_mirrors(
+            downloader: &Downloader,
+            mirrors: &[String],
+            path: &Path,
+            mut error: DownloaderError
+        ) -> Result<(), DownloaderError> {
+            for mirror in mirrors {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(?error, ?mirror, "failed to download resource, trying its mirror");
+
+                let task = downloader.download_with_options(
+                    mirror,
+                    path,
+                    DownloadOptions {
+                        continue_download: false,
+                        on_update: None,
+                        on_finish: None
+                    }
+                );
+
+                match task.wait().await {
+                    Ok(_) => return Ok(()),
+                    Err(err) => error = err
+                }
+            }
+
+            Err(error)
+        }
+
+        /// Start downloading the resource over BitTorrent protocol if it has
+        /// a torrent source, or over HTTP otherwise.
+        fn start_download(
+            storage: &Storage,
+            downloader: &Downloader,
+            options: &InstallOptions,
+            resource_url: &str,
+            resource_info: &ResourceInfoManifest,
+            temp_path: &Path
+        ) -> ResourceTask {
+            match (&resource_info.torrent, &options.torrent_source) {
+                (Some(torrent), Some(torrent_source)) => {
+                    let torrent = torrent.clone();
+                    let torrent_source = torrent_source.clone();
+
+                    let webseeds = std::iter::once(resource_url.to_string())
+                        .chain(resource_info.webseeds.iter().cloned())
+                        .collect::<Vec<_>>();
+
+                    let folder = storage.resource_path(&Hash::rand());
+
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(?resource_url, ?torrent, "downloading resource over BitTorrent protocol");
+
+                    ResourceTask::Torrent {
+                        task: tasks::spawn_blocking({
+                            let folder = folder.clone();
+
+                            move || torrent_source.download(&torrent, &webseeds, &folder)
+                        }),
+
+                        folder
+                    }
+                }
+
+                _ => ResourceTask::Http(downloader.download_with_options(
+                    resource_url,
+                    temp_path,
+                    DownloadOptions {
+                        continue_download: false,
+                        on_update: None,
+                        on_finish: None
+                    }
+                ))
+            }
+        }
+
         // Create [url] => [hash] table.
         let mut resource_hashes = HashMap::new();
 
@@ -390,14 +544,7 @@ impl Storage {
                     }
 
                     // Prepare resource URL.
-                    let mut resource_url = if resource_info.uri.starts_with("http") {
-                        resource_info.uri.clone()
-                    } else {
-                        format!("{parent_url}/{}", resource_info.uri)
-                    };
-
-                    // Normalize it.
-                    resource_url = normalize_url(resource_url);
+                    let resource_url = resolve_url(parent_url, &resource_info.uri);
 
                     // Obtain the resource format.
                     let resource_format = resource_info.format
@@ -415,7 +562,7 @@ impl Storage {
                     resources_queue.push((
                         resource_url.clone(),
                         resource_format,
-                        resource_info.clone()
+                        resolve_resource_info(parent_url, resource_info)
                     ));
                 }
 
@@ -430,14 +577,7 @@ impl Storage {
                     }
 
                     // Prepare resource URL.
-                    let mut resource_url = if resource_info.uri.starts_with("http") {
-                        resource_info.uri.clone()
-                    } else {
-                        format!("{parent_url}/{}", resource_info.uri)
-                    };
-
-                    // Normalize it.
-                    resource_url = normalize_url(resource_url);
+                    let resource_url = resolve_url(parent_url, &resource_info.uri);
 
                     // Obtain the resource format.
                     let resource_format = resource_info.format
@@ -455,7 +595,7 @@ impl Storage {
                     resources_queue.push((
                         resource_url.clone(),
                         resource_format,
-                        resource_info.clone()
+                        resolve_resource_info(parent_url, resource_info)
                     ));
                 }
 
@@ -469,6 +609,7 @@ impl Storage {
 
             // Iterate over the ordered resources.
             let mut tasks = Vec::new();
+            let mut lan_tasks = Vec::new();
 
             for (resource_url, resource_format, resource_info) in resources_queue.drain(..) {
                 // Skip already downloaded resources.
@@ -490,19 +631,71 @@ impl Storage {
                 // Prepare a temp path for the resource.
                 let temp_path = self.resource_path(&Hash::rand());
 
-                // Start downloading the resource.
-                let task = downloader.download_with_options(
-                    &resource_url,
-                    &temp_path,
-                    DownloadOptions {
-                        continue_download: false,
-                        on_update: None,
-                        on_finish: None
+                // Try to fetch the resource from the local network peers. Peers
+                // are requested in background while the other resources are
+                // being downloaded.
+                if let Some(lan_cache) = &options.lan_cache
+                    && let Some(expected_hash) = resource_info.hash
+                {
+                    let task = tasks::spawn_blocking({
+                        let lan_cache = lan_cache.clone();
+                        let temp_path = temp_path.clone();
+
+                        move || lan_cache.fetch(&expected_hash, &temp_path)
+                    });
+
+                    lan_tasks.push((
+                        task,
+                        expected_hash,
+                        resource_url,
+                        resource_format,
+                        resource_info,
+                        temp_path
+                    ));
+
+                    continue;
+                }
+
+                tasks.push((
+                    start_download(self, downloader, &options, &resource_url, &resource_info, &temp_path),
+                    resource_url,
+                    resource_format,
+                    resource_info,
+                    temp_path
+                ));
+            }
+
+            // Await the local network fetches and download the resources which
+            // weren't found there.
+            for (task, expected_hash, resource_url, resource_format, resource_info, temp_path) in lan_tasks.drain(..) {
+                if let Ok(Ok(true)) = task.await {
+                    let resource_path = self.resource_path(&expected_hash);
+
+                    // Resource could be stored by another package.
+                    if resource_path.exists() {
+                        if temp_path.is_dir() {
+                            tasks::fs::remove_dir_all(&temp_path).await?;
+                        } else {
+                            tasks::fs::remove_file(&temp_path).await?;
+                        }
+                    } else {
+                        tasks::fs::rename(&temp_path, resource_path).await?;
                     }
-                );
+
+                    // List this resource as processed.
+                    processed_resources.insert((resource_url.clone(), resource_format));
+
+                    // Link resource URL with its hash.
+                    resource_hashes.insert(resource_url.clone(), expected_hash);
+
+                    // Reference this resource in the lock info table.
+                    resources_lock_info.insert(expected_hash, resource_url);
+
+                    continue;
+                }
 
                 tasks.push((
-                    task,
+                    start_download(self, downloader, &options, &resource_url, &resource_info, &temp_path),
                     resource_url,
                     resource_format,
                     resource_info,
@@ -513,7 +706,49 @@ impl Storage {
             // Await the resources downloading tasks.
             for (task, resource_url, resource_format, resource_info, temp_path) in tasks.drain(..) {
                 // Wait until downloading is done.
-                task.wait().await?;
+                match task {
+                    ResourceTask::Http(task) => {
+                        if let Err(err) = task.wait().await {
+                            download_mirrors(downloader, &resource_info.webseeds, &temp_path, err).await?;
+                        }
+                    }
+
+                    ResourceTask::Torrent { task, folder } => {
+                        let result = task.await
+                            .map_err(TorrentSourceError::from)
+                            .and_then(|result| result);
+
+                        let result = match result {
+                            Ok(path) => tasks::fs::rename(path, &temp_path).await
+                                .map_err(TorrentSourceError::from),
+
+                            Err(err) => Err(err)
+                        };
+
+                        // Delete temporary torrent folder.
+                        let _ = tasks::fs::remove_dir_all(&folder).await;
+
+                        // Fallback to HTTP downloading.
+                        if let Err(err) = result {
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!(?err, ?resource_url, "failed to download resource over BitTorrent protocol");
+
+                            let task = downloader.download_with_options(
+                                &resource_url,
+                                &temp_path,
+                                DownloadOptions {
+                                    continue_download: false,
+                                    on_update: None,
+                                    on_finish: None
+                                }
+                            );
+
+                            if let Err(err) = task.wait().await {
+                                download_mirrors(downloader, &resource_info.webseeds, &temp_path, err).await?;
+                            }
+                        }
+                    }
+                }
 
                 // Process the downloaded resource file according to its format.
                 match resource_format {
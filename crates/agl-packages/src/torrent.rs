@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-packages
+// Copyright (C) 2025  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Downloading packages resources over BitTorrent protocol.
+
+use std::path::{Path, PathBuf};
+
+pub type TorrentSourceError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Source of the packages resources shared over BitTorrent protocol.
+///
+/// This crate doesn't implement BitTorrent protocol itself, so the torrent
+/// client is provided by the packages storage user.
+pub trait TorrentSource: Send + Sync {
+    /// Download single file torrent to the given folder and return path to the
+    /// downloaded file. Blocks the current thread until the downloading is
+    /// finished.
+    ///
+    /// `torrent` is either a magnet link, an info hash or URL of a torrent
+    /// file. `webseeds` are HTTP URLs of the same file (BEP 19).
+    fn download(
+        &self,
+        torrent: &str,
+        webseeds: &[String],
+        output_folder: &Path
+    ) -> Result<PathBuf, TorrentSourceError>;
+}
@@ -22,14 +22,104 @@ use std::time::Duration;
 
 use agl_core::export::network::reqwest::Client;
 use agl_core::tasks;
-use agl_core::network::downloader::{Downloader, DownloadOptions};
+use agl_core::network::downloader::{Downloader, DownloaderTask, DownloadOptions};
 
 use mlua::prelude::*;
 
+use super::task_api::CancellationToken;
 use super::*;
 
 pub const DOWNLOADER_WAIT_UPDATE_INTERVAL: Duration = Duration::from_millis(50);
 
+/// Information about a file downloaded with the downloader API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloaderTaskInfo {
+    /// Handle of the download task.
+    pub handle: i32,
+
+    /// URL of the downloaded file.
+    pub url: String,
+
+    /// Path to the output file.
+    pub output_file: PathBuf,
+
+    /// Amount of downloaded bytes.
+    pub current: u64,
+
+    /// Expected total amount of bytes.
+    pub total: u64,
+
+    /// Whether the download has finished.
+    pub finished: bool
+}
+
+#[derive(Debug)]
+struct DownloaderTaskEntry {
+    task: DownloaderTask,
+    url: String,
+    output_file: PathBuf,
+    on_update: Option<LuaFunction>,
+    on_finish: Option<LuaFunction>,
+
+    /// Child of the module's cancellation token, if it was provided. Cancelled
+    /// by the module or by the application with `DownloaderTasks::abort`.
+    cancel: CancellationToken
+}
+
+/// List of files downloaded by modules with the downloader API. Clone it
+/// before building the runtime to display or abort these downloads from the
+/// application.
+#[derive(Default, Debug, Clone)]
+pub struct DownloaderTasks(Arc<Mutex<HashMap<i32, DownloaderTaskEntry>>>);
+
+impl DownloaderTasks {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get list of the download tasks which were not waited or aborted by
+    /// the modules yet.
+    pub fn list(&self) -> Box<[DownloaderTaskInfo]> {
+        let Ok(tasks) = self.0.lock() else {
+            return Box::new([]);
+        };
+
+        let mut list = tasks.iter()
+            .map(|(handle, entry)| DownloaderTaskInfo {
+                handle: *handle,
+                url: entry.url.clone(),
+                output_file: entry.output_file.clone(),
+                current: entry.task.current(),
+                total: entry.task.total(),
+                finished: entry.task.is_finished()
+            })
+            .collect::<Box<[_]>>();
+
+        list.sort_by_key(|info| info.handle);
+
+        list
+    }
+
+    /// Abort the download task. The module receives an error from the
+    /// `downloader.progress` and `downloader.wait` functions.
+    ///
+    /// Return `false` if there's no such task.
+    pub fn abort(&self, handle: i32) -> bool {
+        let Ok(tasks) = self.0.lock() else {
+            return false;
+        };
+
+        let Some(entry) = tasks.get(&handle) else {
+            return false;
+        };
+
+        entry.cancel.cancel();
+
+        true
+    }
+}
+
 pub struct DownloaderApi {
     lua: Lua,
 
@@ -45,10 +135,10 @@ impl DownloaderApi {
     pub fn new(
         lua: Lua,
         api_context: ApiContext,
-        client: Client
+        client: Client,
+        tasks_handles: DownloaderTasks
     ) -> Result<Self, LuaError> {
         let downloader_handles = Arc::new(Mutex::new(HashMap::new()));
-        let tasks_handles = Arc::new(Mutex::new(HashMap::new()));
 
         Ok(Self {
             downloader_create: {
@@ -127,6 +217,10 @@ impl DownloaderApi {
                         let on_update = options.get::<LuaFunction>("on_update").ok();
                         let on_finish = options.get::<LuaFunction>("on_finish").ok();
 
+                        let cancel = options.get::<Option<CancellationToken>>("cancel")?
+                            .map(|cancel| cancel.child())
+                            .unwrap_or_default();
+
                         let downloader_handles = downloader_handles.lock()
                             .map_err(|err| {
                                 LuaError::external("failed to register downloader handle")
@@ -137,15 +231,17 @@ impl DownloaderApi {
                             return Err(LuaError::external("invalid downloader handle"));
                         };
 
-                        let mut tasks_handles = tasks_handles.lock()
+                        let mut tasks_handles = tasks_handles.0.lock()
                             .map_err(|err| {
                                 LuaError::external("failed to register downloader task handle")
                                     .context(err)
                             })?;
 
+                        let url = url.to_string_lossy();
+
                         let task = downloader.download_with_options(
-                            url.to_string_lossy(),
-                            output_file,
+                            &url,
+                            &output_file,
                             download_options
                         );
 
@@ -155,7 +251,14 @@ impl DownloaderApi {
                             handle = rand::random::<i32>();
                         }
 
-                        tasks_handles.insert(handle, (task, on_update, on_finish));
+                        tasks_handles.insert(handle, DownloaderTaskEntry {
+                            task,
+                            url,
+                            output_file,
+                            on_update,
+                            on_finish,
+                            cancel
+                        });
 
                         Ok(handle)
                     })
@@ -166,25 +269,38 @@ impl DownloaderApi {
                 let tasks_handles = tasks_handles.clone();
 
                 lua.create_function(move |lua: &Lua, handle: i32| {
-                    let handles = tasks_handles.lock()
+                    let mut handles = tasks_handles.0.lock()
                         .map_err(|err| {
                             LuaError::external("failed to read downloader handle")
                                 .context(err)
                         })?;
 
-                    let Some((task, on_update, _)) = handles.get(&handle) else {
+                    let Some(entry) = handles.get(&handle) else {
                         return Err(LuaError::external("invalid download task handle"));
                     };
 
+                    if entry.cancel.is_cancelled() {
+                        if let Some(entry) = handles.remove(&handle) {
+                            entry.task.abort();
+                        }
+
+                        return Err(LuaError::external("download was cancelled"));
+                    }
+
                     let progress = lua.create_table_with_capacity(0, 4)?;
 
-                    let current = task.current();
-                    let total = task.total();
+                    let current = entry.task.current();
+                    let total = entry.task.total();
 
                     progress.raw_set("current", current)?;
                     progress.raw_set("total", total)?;
-                    progress.raw_set("fraction", task.fraction())?;
-                    progress.raw_set("finished", task.is_finished())?;
+                    progress.raw_set("fraction", entry.task.fraction())?;
+                    progress.raw_set("finished", entry.task.is_finished())?;
+
+                    let on_update = entry.on_update.clone();
+
+                    // Callback can call the downloader API itself.
+                    drop(handles);
 
                     if let Some(on_update) = on_update {
                         on_update.call::<()>((current, total))?;
@@ -198,31 +314,54 @@ impl DownloaderApi {
                 let tasks_handles = tasks_handles.clone();
 
                 lua.create_function(move |_lua: &Lua, handle: i32| {
-                    let mut handles = tasks_handles.lock()
-                        .map_err(|err| {
-                            LuaError::external("failed to read downloader handle")
-                                .context(err)
-                        })?;
+                    // Keep the task in the list while it's downloaded so the
+                    // application could display and abort it.
+                    loop {
+                        let handles = tasks_handles.0.lock()
+                            .map_err(|err| {
+                                LuaError::external("failed to read downloader handle")
+                                    .context(err)
+                            })?;
 
-                    let Some((task, on_update, on_finish)) = handles.remove(&handle) else {
-                        return Err(LuaError::external("invalid download task handle"));
-                    };
+                        let Some(entry) = handles.get(&handle) else {
+                            return Err(LuaError::external("invalid download task handle"));
+                        };
+
+                        if entry.task.is_finished() || entry.cancel.is_cancelled() {
+                            break;
+                        }
 
-                    while !task.is_finished() {
-                        if let Some(on_update) = &on_update {
-                            let current = task.current();
-                            let total = task.total();
+                        let current = entry.task.current();
+                        let total = entry.task.total();
+                        let on_update = entry.on_update.clone();
 
+                        drop(handles);
+
+                        if let Some(on_update) = on_update {
                             on_update.call::<()>((current, total))?;
                         }
 
                         std::thread::sleep(DOWNLOADER_WAIT_UPDATE_INTERVAL);
                     }
 
-                    let result = tasks::block_on(task.wait())
+                    let entry = tasks_handles.0.lock()
+                        .map_err(|err| {
+                            LuaError::external("failed to read downloader handle")
+                                .context(err)
+                        })?
+                        .remove(&handle)
+                        .ok_or_else(|| LuaError::external("invalid download task handle"))?;
+
+                    if entry.cancel.is_cancelled() {
+                        entry.task.abort();
+
+                        return Err(LuaError::external("download was cancelled"));
+                    }
+
+                    let result = tasks::block_on(entry.task.wait())
                         .map_err(LuaError::external)?;
 
-                    if let Some(on_finish) = on_finish {
+                    if let Some(on_finish) = entry.on_finish {
                         on_finish.call::<()>(result)?;
                     }
 
@@ -234,14 +373,14 @@ impl DownloaderApi {
                 let tasks_handles = tasks_handles.clone();
 
                 lua.create_function(move |_lua: &Lua, handle: i32| {
-                    let mut handles = tasks_handles.lock()
+                    let mut handles = tasks_handles.0.lock()
                         .map_err(|err| {
                             LuaError::external("failed to read downloader handle")
                                 .context(err)
                         })?;
 
-                    if let Some((task, _, _)) = handles.remove(&handle) {
-                        task.abort();
+                    if let Some(entry) = handles.remove(&handle) {
+                        entry.task.abort();
                     }
 
                     Ok(())
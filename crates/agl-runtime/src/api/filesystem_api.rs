@@ -16,24 +16,419 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::fs::File;
 use std::io::{Read, Write, Seek, SeekFrom};
+use std::str::FromStr;
 use std::time::{Duration, UNIX_EPOCH};
 
 use mlua::prelude::*;
 
 use bufrw::BufReaderWriter;
 
+use notify::{Watcher, RecommendedWatcher, RecursiveMode, EventKind};
+use notify::event::{ModifyKind, RenameMode};
+
 use agl_core::tasks::fs;
+use agl_core::hashes::{Hasher, HashAlgorithm};
 
 use super::bytes::Bytes;
-use super::task_api::{Promise, PromiseValue, TaskOutput, task_output};
+use super::task_api::{Promise, PromiseValue, TaskOutput, CancellationToken, Progress, task_output};
 use super::*;
 
 pub const IO_READ_CHUNK_LEN: usize = 4096; // 4 KiB file reads
 pub const IO_BUFFER_SIZE: usize = 64 * 1024; // 64 KiB read/write in-RAM cache
+pub const IO_COPY_CHUNK_LEN: usize = 1024 * 1024; // 1 MiB tree copy chunks
+pub const WATCH_MAX_EVENTS: usize = 4096; // older events are dropped
+
+/// Table of files opened by the `fs.open` function.
+pub type FileHandles = Arc<Mutex<HashMap<i32, BufReaderWriter<File>>>>;
+
+/// Table of watchers created by the `fs.watch` function.
+type WatchHandles = Arc<Mutex<HashMap<i32, (RecommendedWatcher, Arc<WatchQueue>, Option<LuaFunction>)>>>;
+
+/// Filesystem change reported by the `fs.watch` function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WatchEvent {
+    Create(PathBuf),
+    Modify(PathBuf),
+    Remove(PathBuf),
+    Rename {
+        from: PathBuf,
+        to: PathBuf
+    }
+}
+
+impl WatchEvent {
+    /// Convert notify event into the list of watch events.
+    fn from_event(event: notify::Event) -> Vec<Self> {
+        let mut paths = event.paths;
+
+        match event.kind {
+            EventKind::Create(_) => paths.into_iter().map(Self::Create).collect(),
+            EventKind::Remove(_) => paths.into_iter().map(Self::Remove).collect(),
+
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                let to = paths.remove(1);
+                let from = paths.remove(0);
+
+                vec![Self::Rename { from, to }]
+            }
+
+            // Files moved outside or inside of the watched directory.
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths.into_iter().map(Self::Remove).collect(),
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths.into_iter().map(Self::Create).collect(),
+
+            EventKind::Modify(_) => paths.into_iter().map(Self::Modify).collect(),
+
+            EventKind::Access(_) | EventKind::Any | EventKind::Other => vec![]
+        }
+    }
+
+    /// Get list of paths affected by the event.
+    fn paths(&self) -> Vec<&Path> {
+        match self {
+            Self::Create(path) |
+            Self::Modify(path) |
+            Self::Remove(path) => vec![path],
+
+            Self::Rename { from, to } => vec![from, to]
+        }
+    }
+
+    fn to_lua(&self, lua: &Lua) -> Result<LuaTable, LuaError> {
+        let event = lua.create_table_with_capacity(0, 3)?;
+
+        let (kind, path) = match self {
+            Self::Create(path) => ("create", path),
+            Self::Modify(path) => ("modify", path),
+            Self::Remove(path) => ("remove", path),
+            Self::Rename { to, .. } => ("rename", to)
+        };
+
+        event.raw_set("type", kind)?;
+        event.raw_set("path", lua.create_string(path.as_os_str().as_encoded_bytes())?)?;
+
+        if let Self::Rename { from, .. } = self {
+            event.raw_set("from", lua.create_string(from.as_os_str().as_encoded_bytes())?)?;
+        }
+
+        Ok(event)
+    }
+}
+
+/// Queue of events received by a filesystem watcher.
+#[derive(Debug, Default)]
+struct WatchQueue {
+    events: Mutex<VecDeque<WatchEvent>>,
+    notify: tasks::sync::Notify,
+    closed: AtomicBool
+}
+
+impl WatchQueue {
+    fn push(&self, event: WatchEvent) {
+        if let Ok(mut events) = self.events.lock() {
+            if events.len() >= WATCH_MAX_EVENTS {
+                events.pop_front();
+            }
+
+            events.push_back(event);
+        }
+
+        self.notify.notify_one();
+    }
+
+    fn pop(&self) -> Option<WatchEvent> {
+        self.events.lock().ok()?.pop_front()
+    }
+
+    fn drain(&self) -> Vec<WatchEvent> {
+        self.events.lock()
+            .map(|mut events| events.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+}
+
+/// Method used to copy files by the tree operations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum CopyMethod {
+    Copy,
+
+    /// Share file content using copy-on-write filesystems features if
+    /// possible, otherwise copy the file.
+    #[default]
+    Reflink,
+
+    /// Create a hard link to the source file if possible, otherwise copy it.
+    Hardlink
+}
+
+impl FromStr for CopyMethod {
+    type Err = LuaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "copy"     => Ok(Self::Copy),
+            "reflink"  => Ok(Self::Reflink),
+            "hardlink" => Ok(Self::Hardlink),
+
+            _ => Err(LuaError::external(format!("unsupported copy method: {s}")))
+        }
+    }
+}
+
+#[inline]
+fn cancelled_error() -> std::io::Error {
+    std::io::Error::other("operation was cancelled")
+}
+
+#[inline]
+fn check_token(token: Option<&CancellationToken>) -> std::io::Result<()> {
+    if token.is_some_and(CancellationToken::is_cancelled) {
+        return Err(cancelled_error());
+    }
+
+    Ok(())
+}
+
+/// Recursively list entries of the given path without following symlinks.
+/// Returned paths are relative to the root; the root itself is returned with
+/// an empty path. Paths which cannot be accessed are skipped.
+fn walk_tree(
+    root: &Path,
+    api_context: &ApiContext,
+    token: Option<&CancellationToken>,
+    progress: Option<&Progress>
+) -> std::io::Result<Vec<(PathBuf, std::fs::Metadata)>> {
+    let mut entries = vec![(PathBuf::new(), root.symlink_metadata()?)];
+    let mut i = 0;
+
+    while i < entries.len() {
+        check_token(token)?;
+
+        let (path, metadata) = &entries[i];
+
+        if let Some(progress) = progress && metadata.is_file() {
+            progress.add(metadata.len());
+        }
+
+        if metadata.is_dir() {
+            let path = path.clone();
+
+            for entry in root.join(&path).read_dir()? {
+                let entry = entry?;
+
+                if !api_context.can_access_path(&entry.path()) {
+                    continue;
+                }
+
+                entries.push((path.join(entry.file_name()), entry.metadata()?));
+            }
+        }
+
+        i += 1;
+    }
+
+    Ok(entries)
+}
+
+/// Join relative tree path to the root path.
+#[inline]
+fn tree_path(root: &Path, path: &Path) -> PathBuf {
+    if path.as_os_str().is_empty() {
+        root.to_path_buf()
+    } else {
+        root.join(path)
+    }
+}
+
+/// Copy file from source to target path using the given method, reporting
+/// copied bytes to the progress. Target file must not exist if reflink or
+/// hardlink method is used.
+fn copy_file(
+    source: &Path,
+    target: &Path,
+    method: CopyMethod,
+    token: Option<&CancellationToken>,
+    progress: &Progress
+) -> std::io::Result<()> {
+    let metadata = source.metadata()?;
+
+    let linked = match method {
+        CopyMethod::Copy => false,
+        CopyMethod::Reflink => reflink_copy::reflink(source, target).is_ok(),
+        CopyMethod::Hardlink => std::fs::hard_link(source, target).is_ok()
+    };
+
+    // Hard links share metadata with the source file.
+    if linked && method == CopyMethod::Hardlink {
+        progress.add(metadata.len());
+
+        return Ok(());
+    }
+
+    let target_file = if linked {
+        progress.add(metadata.len());
+
+        File::options().write(true).open(target)?
+    }
+
+    else {
+        let mut source_file = File::open(source)?;
+        let mut target_file = File::create(target)?;
+
+        let mut buf = vec![0; IO_COPY_CHUNK_LEN];
+
+        loop {
+            check_token(token)?;
+
+            let n = source_file.read(&mut buf)?;
+
+            if n == 0 {
+                break;
+            }
+
+            target_file.write_all(&buf[..n])?;
+
+            progress.add(n as u64);
+        }
+
+        target_file
+    };
+
+    // Keep modification time so the file is not copied again by `fs.sync`.
+    target_file.set_modified(metadata.modified()?)?;
+    target_file.set_permissions(metadata.permissions())?;
+
+    Ok(())
+}
+
+/// Copy symlink without following it.
+fn copy_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        std::os::unix::fs::symlink(source.read_link()?, target)
+    }
+
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (source, target);
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "symlinks are not supported on this platform"
+        ))
+    }
+}
+
+/// Calculate hash of the file's content.
+fn hash_file(path: &Path, algorithm: HashAlgorithm) -> std::io::Result<Box<[u8]>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::new(algorithm);
+
+    std::io::copy(&mut file, &mut hasher)?;
+
+    Ok(hasher.finalize().0)
+}
+
+/// Check if target file has to be replaced by the source file.
+fn file_changed(
+    source: &Path,
+    source_metadata: &std::fs::Metadata,
+    target: &Path,
+    checksum: Option<HashAlgorithm>
+) -> std::io::Result<bool> {
+    let target_metadata = target.symlink_metadata()?;
+
+    if !target_metadata.is_file() || source_metadata.len() != target_metadata.len() {
+        return Ok(true);
+    }
+
+    match checksum {
+        Some(algorithm) => Ok(hash_file(source, algorithm)? != hash_file(target, algorithm)?),
+        None => Ok(source_metadata.modified()? != target_metadata.modified()?)
+    }
+}
+
+/// Remove file, symlink or directory on the given path.
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Parse unix file mode from a number or an octal string like `"755"`.
+fn parse_file_mode(mode: LuaValue) -> Result<u32, LuaError> {
+    match mode {
+        LuaValue::Integer(mode) => u32::try_from(mode)
+            .map_err(|_| LuaError::external("invalid file mode")),
+
+        LuaValue::Number(mode) if mode >= 0.0 && mode.fract() == 0.0 => Ok(mode as u32),
+
+        LuaValue::String(mode) => u32::from_str_radix(&mode.to_str()?, 8)
+            .map_err(|err| LuaError::external(format!("invalid file mode: {err}"))),
+
+        _ => Err(LuaError::external("invalid file mode"))
+    }
+}
+
+/// Common options of the tree operations.
+struct TreeOptions {
+    method: CopyMethod,
+    overwrite: bool,
+    checksum: Option<HashAlgorithm>,
+    delete: bool,
+    on_progress: Option<LuaFunction>,
+    cancel: Option<CancellationToken>
+}
+
+impl TreeOptions {
+    fn from_lua(options: Option<LuaTable>) -> Result<Self, LuaError> {
+        let mut result = Self {
+            method: CopyMethod::default(),
+            overwrite: false,
+            checksum: None,
+            delete: false,
+            on_progress: None,
+            cancel: None
+        };
+
+        let Some(options) = options else {
+            return Ok(result);
+        };
+
+        if let Some(method) = options.get::<Option<LuaString>>("method")? {
+            result.method = CopyMethod::from_str(&method.to_string_lossy())?;
+        }
+
+        if let Some(algorithm) = options.get::<Option<LuaString>>("checksum")? {
+            result.checksum = Some({
+                HashAlgorithm::from_str(&algorithm.to_string_lossy())
+                    .map_err(LuaError::external)?
+            });
+        }
+
+        result.overwrite = options.get::<bool>("overwrite").unwrap_or_default();
+        result.delete = options.get::<bool>("delete").unwrap_or_default();
+        result.on_progress = options.get::<LuaFunction>("on_progress").ok();
+        result.cancel = options.get::<Option<CancellationToken>>("cancel")?;
+
+        Ok(result)
+    }
+}
 
 pub struct FilesystemApi {
     lua: Lua,
@@ -58,12 +453,32 @@ pub struct FilesystemApi {
     fs_remove_file: LuaFunctionBuilder,
     fs_create_dir: LuaFunctionBuilder,
     fs_read_dir: LuaFunctionBuilder,
-    fs_remove_dir: LuaFunctionBuilder
+    fs_remove_dir: LuaFunctionBuilder,
+
+    fs_copy_tree: LuaFunctionBuilder,
+    fs_sync: LuaFunctionBuilder,
+    fs_size: LuaFunctionBuilder,
+    fs_hardlink: LuaFunctionBuilder,
+    fs_symlink: LuaFunctionBuilder,
+    fs_read_link: LuaFunctionBuilder,
+    fs_chmod: LuaFunctionBuilder,
+    fs_get_xattr: LuaFunctionBuilder,
+    fs_set_xattr: LuaFunctionBuilder,
+    fs_remove_xattr: LuaFunctionBuilder,
+    fs_list_xattrs: LuaFunctionBuilder,
+
+    fs_watch: LuaFunctionBuilder,
+    fs_poll_events: LuaFunction,
+    fs_next_event: LuaFunction,
+    fs_unwatch: LuaFunction,
+
+    file_handles: FileHandles
 }
 
 impl FilesystemApi {
     pub fn new(lua: Lua, api_context: ApiContext) -> Result<Self, LuaError> {
-        let file_handles = Arc::new(Mutex::new(HashMap::new()));
+        let file_handles: FileHandles = Arc::new(Mutex::new(HashMap::new()));
+        let watch_handles: WatchHandles = Arc::new(Mutex::new(HashMap::new()));
 
         Ok(Self {
             fs_exists: {
@@ -117,9 +532,9 @@ impl FilesystemApi {
                             return Err(LuaError::external("this path cannot be accessed"));
                         }
 
-                        let metadata = path.metadata()?;
+                        let metadata = path.symlink_metadata()?;
 
-                        let result = lua.create_table_with_capacity(0, 6)?;
+                        let result = lua.create_table_with_capacity(0, 7)?;
 
                         result.raw_set("created_at", {
                             metadata.created().ok()
@@ -153,6 +568,13 @@ impl FilesystemApi {
 
                         result.raw_set("length", metadata.len())?;
 
+                        #[cfg(target_family = "unix")]
+                        result.raw_set("mode", {
+                            use std::os::unix::fs::PermissionsExt;
+
+                            metadata.permissions().mode() & 0o7777
+                        })?;
+
                         let permissions = lua.create_table_with_capacity(0, 2)?;
 
                         permissions.raw_set("read", module_context.can_read_path(&path))?;
@@ -996,40 +1418,1287 @@ impl FilesystemApi {
                 })
             },
 
-            lua
-        })
-    }
+            fs_copy_tree: {
+                let api_context = api_context.clone();
 
-    /// Create new lua table with API functions.
-    pub fn create_env(
-        &self,
-        context: &ModuleContext
-    ) -> Result<LuaTable, LuaError> {
-        let env = self.lua.create_table_with_capacity(0, 20)?;
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
 
-        env.raw_set("exists", (self.fs_exists)(&self.lua, context)?)?;
-        env.raw_set("metadata", (self.fs_metadata)(&self.lua, context)?)?;
-        env.raw_set("copy", (self.fs_copy)(&self.lua, context)?)?;
-        env.raw_set("move", (self.fs_move)(&self.lua, context)?)?;
-        env.raw_set("remove", (self.fs_remove)(&self.lua, context)?)?;
-        env.raw_set("open", (self.fs_open)(&self.lua, context)?)?;
-        env.raw_set("seek", &self.fs_seek)?;
-        env.raw_set("seek_rel", &self.fs_seek_rel)?;
-        env.raw_set("truncate", &self.fs_truncate)?;
-        env.raw_set("read", &self.fs_read)?;
-        env.raw_set("write", &self.fs_write)?;
-        env.raw_set("flush", &self.fs_flush)?;
-        env.raw_set("close", &self.fs_close)?;
+                    lua.create_function(move |lua: &Lua, (mut source, mut target, options): (PathBuf, PathBuf, Option<LuaTable>)| {
+                        if source.is_relative() {
+                            source = module_context.module_dir.join(source);
+                        }
 
-        env.raw_set("create_file", (self.fs_create_file)(&self.lua, context)?)?;
-        env.raw_set("read_file", (self.fs_read_file)(&self.lua, context)?)?;
-        env.raw_set("write_file", (self.fs_write_file)(&self.lua, context)?)?;
-        env.raw_set("remove_file", (self.fs_remove_file)(&self.lua, context)?)?;
-        env.raw_set("create_dir", (self.fs_create_dir)(&self.lua, context)?)?;
-        env.raw_set("read_dir", (self.fs_read_dir)(&self.lua, context)?)?;
-        env.raw_set("remove_dir", (self.fs_remove_dir)(&self.lua, context)?)?;
+                        if target.is_relative() {
+                            target = module_context.module_dir.join(target);
+                        }
 
-        Ok(env)
+                        source = normalize_path(source, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize source path: {err}"))
+                            })?;
+
+                        target = normalize_path(target, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize target path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&source) {
+                            return Err(LuaError::external("source path cannot be accessed"));
+                        }
+
+                        if !api_context.can_access_path(&target) {
+                            return Err(LuaError::external("target path cannot be accessed"));
+                        }
+
+                        if !source.exists() {
+                            return Err(LuaError::external("source path doesn't exists"));
+                        }
+
+                        if !module_context.can_read_path(&source) {
+                            return Err(LuaError::external("no source path read permissions"));
+                        }
+
+                        if !module_context.can_write_path(&target) {
+                            return Err(LuaError::external("no target path write permissions"));
+                        }
+
+                        let options = TreeOptions::from_lua(options)?;
+                        let progress = Progress::new();
+
+                        let value = {
+                            let api_context = api_context.clone();
+                            let progress = progress.clone();
+                            let token = options.cancel.clone();
+
+                            PromiseValue::from_blocking(move || {
+                                let token = token.as_ref();
+                                let entries = walk_tree(&source, &api_context, token, None)?;
+
+                                progress.set_total({
+                                    entries.iter()
+                                        .filter(|(_, metadata)| metadata.is_file())
+                                        .map(|(_, metadata)| metadata.len())
+                                        .sum()
+                                });
+
+                                for (path, metadata) in entries {
+                                    check_token(token)?;
+
+                                    let source_path = tree_path(&source, &path);
+                                    let target_path = tree_path(&target, &path);
+
+                                    if metadata.is_dir() {
+                                        std::fs::create_dir_all(&target_path)?;
+
+                                        continue;
+                                    }
+
+                                    if !metadata.is_file() && !metadata.is_symlink() {
+                                        continue;
+                                    }
+
+                                    if target_path.symlink_metadata().is_ok() {
+                                        if !options.overwrite {
+                                            return Err(LuaError::external(format!(
+                                                "target path already exists: {}",
+                                                target_path.display()
+                                            )));
+                                        }
+
+                                        remove_path(&target_path)?;
+                                    }
+
+                                    if metadata.is_symlink() {
+                                        copy_symlink(&source_path, &target_path)?;
+                                    } else {
+                                        copy_file(&source_path, &target_path, options.method, token, &progress)?;
+                                    }
+                                }
+
+                                Ok(task_output(Ok(LuaValue::Nil)))
+                            })
+                        };
+
+                        Promise::new(value)
+                            .with_progress(progress, options.on_progress)
+                            .with_token(options.cancel)
+                            .into_lua(lua)
+                    })
+                })
+            },
+
+            fs_sync: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |lua: &Lua, (mut source, mut target, options): (PathBuf, PathBuf, Option<LuaTable>)| {
+                        if source.is_relative() {
+                            source = module_context.module_dir.join(source);
+                        }
+
+                        if target.is_relative() {
+                            target = module_context.module_dir.join(target);
+                        }
+
+                        source = normalize_path(source, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize source path: {err}"))
+                            })?;
+
+                        target = normalize_path(target, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize target path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&source) {
+                            return Err(LuaError::external("source path cannot be accessed"));
+                        }
+
+                        if !api_context.can_access_path(&target) {
+                            return Err(LuaError::external("target path cannot be accessed"));
+                        }
+
+                        if !source.is_dir() {
+                            return Err(LuaError::external("source path is not a directory"));
+                        }
+
+                        if !module_context.can_read_path(&source) {
+                            return Err(LuaError::external("no source path read permissions"));
+                        }
+
+                        if !module_context.can_write_path(&target) {
+                            return Err(LuaError::external("no target path write permissions"));
+                        }
+
+                        let options = TreeOptions::from_lua(options)?;
+                        let progress = Progress::new();
+
+                        let value = {
+                            let api_context = api_context.clone();
+                            let progress = progress.clone();
+                            let token = options.cancel.clone();
+
+                            PromiseValue::from_blocking(move || {
+                                let token = token.as_ref();
+                                let entries = walk_tree(&source, &api_context, token, None)?;
+
+                                progress.set_total({
+                                    entries.iter()
+                                        .filter(|(_, metadata)| metadata.is_file())
+                                        .map(|(_, metadata)| metadata.len())
+                                        .sum()
+                                });
+
+                                let mut copied = 0;
+                                let mut skipped = 0;
+                                let mut removed = 0;
+
+                                for (path, metadata) in &entries {
+                                    check_token(token)?;
+
+                                    let source_path = tree_path(&source, path);
+                                    let target_path = tree_path(&target, path);
+
+                                    let target_exists = target_path.symlink_metadata().is_ok();
+
+                                    if metadata.is_dir() {
+                                        if target_exists && !target_path.is_dir() {
+                                            remove_path(&target_path)?;
+                                        }
+
+                                        std::fs::create_dir_all(&target_path)?;
+                                    }
+
+                                    else if metadata.is_symlink() {
+                                        if target_exists {
+                                            if target_path.read_link().ok() == source_path.read_link().ok() {
+                                                skipped += 1;
+
+                                                continue;
+                                            }
+
+                                            remove_path(&target_path)?;
+                                        }
+
+                                        copy_symlink(&source_path, &target_path)?;
+
+                                        copied += 1;
+                                    }
+
+                                    // Skip special files like sockets or pipes.
+                                    else if !metadata.is_file() {
+                                        continue;
+                                    }
+
+                                    else if !target_exists || file_changed(&source_path, metadata, &target_path, options.checksum)? {
+                                        if target_exists {
+                                            remove_path(&target_path)?;
+                                        }
+
+                                        copy_file(&source_path, &target_path, options.method, token, &progress)?;
+
+                                        copied += 1;
+                                    }
+
+                                    else {
+                                        progress.add(metadata.len());
+
+                                        skipped += 1;
+                                    }
+                                }
+
+                                // Remove target entries which don't exist in
+                                // the source directory.
+                                if options.delete {
+                                    let source_paths = entries.into_iter()
+                                        .map(|(path, _)| path)
+                                        .collect::<HashSet<_>>();
+
+                                    for (path, _) in walk_tree(&target, &api_context, token, None)? {
+                                        check_token(token)?;
+
+                                        let Some(parent) = path.parent() else {
+                                            continue;
+                                        };
+
+                                        // Skip entries of already removed directories.
+                                        if !source_paths.contains(parent) {
+                                            continue;
+                                        }
+
+                                        if !source_paths.contains(&path) {
+                                            remove_path(&tree_path(&target, &path))?;
+
+                                            removed += 1;
+                                        }
+                                    }
+                                }
+
+                                Ok(Box::new(move |lua: &Lua| {
+                                    let result = lua.create_table_with_capacity(0, 3)?;
+
+                                    result.raw_set("copied", copied)?;
+                                    result.raw_set("skipped", skipped)?;
+                                    result.raw_set("removed", removed)?;
+
+                                    Ok(LuaValue::Table(result))
+                                }) as TaskOutput)
+                            })
+                        };
+
+                        Promise::new(value)
+                            .with_progress(progress, options.on_progress)
+                            .with_token(options.cancel)
+                            .into_lua(lua)
+                    })
+                })
+            },
+
+            fs_size: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |lua: &Lua, (mut path, options): (PathBuf, Option<LuaTable>)| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_read_path(&path) {
+                            return Err(LuaError::external("no path read permissions"));
+                        }
+
+                        let options = TreeOptions::from_lua(options)?;
+                        let progress = Progress::new();
+
+                        let value = {
+                            let api_context = api_context.clone();
+                            let progress = progress.clone();
+                            let token = options.cancel.clone();
+
+                            PromiseValue::from_blocking(move || {
+                                let size = walk_tree(&path, &api_context, token.as_ref(), Some(&progress))?
+                                    .into_iter()
+                                    .filter(|(_, metadata)| metadata.is_file())
+                                    .map(|(_, metadata)| metadata.len())
+                                    .sum::<u64>();
+
+                                Ok(Box::new(move |lua: &Lua| {
+                                    size.into_lua(lua)
+                                }) as TaskOutput)
+                            })
+                        };
+
+                        Promise::new(value)
+                            .with_progress(progress, options.on_progress)
+                            .with_token(options.cancel)
+                            .into_lua(lua)
+                    })
+                })
+            },
+
+            fs_hardlink: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |_lua: &Lua, (mut source, mut target): (PathBuf, PathBuf)| {
+                        if source.is_relative() {
+                            source = module_context.module_dir.join(source);
+                        }
+
+                        if target.is_relative() {
+                            target = module_context.module_dir.join(target);
+                        }
+
+                        source = normalize_path(source, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize source path: {err}"))
+                            })?;
+
+                        target = normalize_path(target, false)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize target path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&source) {
+                            return Err(LuaError::external("source path cannot be accessed"));
+                        }
+
+                        if !api_context.can_access_path(&target) {
+                            return Err(LuaError::external("target path cannot be accessed"));
+                        }
+
+                        if !source.is_file() {
+                            return Err(LuaError::external("source path is not a file"));
+                        }
+
+                        // Hard links share the content so modifying one of
+                        // them will modify the source file too.
+                        if !module_context.can_write_path(&source) {
+                            return Err(LuaError::external("no source path write permissions"));
+                        }
+
+                        if !module_context.can_write_path(&target) {
+                            return Err(LuaError::external("no target path write permissions"));
+                        }
+
+                        std::fs::hard_link(source, target)?;
+
+                        Ok(())
+                    })
+                })
+            },
+
+            fs_symlink: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |_lua: &Lua, (target, mut link): (PathBuf, PathBuf)| {
+                        if link.is_relative() {
+                            link = module_context.module_dir.join(link);
+                        }
+
+                        link = normalize_path(link, false)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize link path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&link) {
+                            return Err(LuaError::external("link path cannot be accessed"));
+                        }
+
+                        if !module_context.can_write_path(&link) {
+                            return Err(LuaError::external("no link path write permissions"));
+                        }
+
+                        // Relative symlinks are resolved from the link's
+                        // parent folder.
+                        let mut resolved_target = target.clone();
+
+                        if resolved_target.is_relative() && let Some(parent) = link.parent() {
+                            resolved_target = parent.join(resolved_target);
+                        }
+
+                        resolved_target = normalize_path(resolved_target, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize target path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&resolved_target) {
+                            return Err(LuaError::external("target path cannot be accessed"));
+                        }
+
+                        if !module_context.can_read_path(&resolved_target) {
+                            return Err(LuaError::external("no target path read permissions"));
+                        }
+
+                        #[cfg(target_family = "unix")]
+                        {
+                            std::os::unix::fs::symlink(target, link)?;
+
+                            Ok(())
+                        }
+
+                        #[cfg(not(target_family = "unix"))]
+                        {
+                            let _ = (target, link);
+
+                            Err(LuaError::runtime("symlinks are not supported on this platform"))
+                        }
+                    })
+                })
+            },
+
+            fs_read_link: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |lua: &Lua, mut path: PathBuf| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, false)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_read_path(&path) {
+                            return Err(LuaError::external("no path read permissions"));
+                        }
+
+                        let target = path.read_link()?;
+
+                        lua.create_string(target.as_os_str().as_encoded_bytes())
+                    })
+                })
+            },
+
+            fs_chmod: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |_lua: &Lua, (mut path, mode): (PathBuf, LuaValue)| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_write_path(&path) {
+                            return Err(LuaError::external("no path write permissions"));
+                        }
+
+                        let mode = parse_file_mode(mode)?;
+
+                        #[cfg(target_family = "unix")]
+                        {
+                            use std::os::unix::fs::PermissionsExt;
+
+                            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+
+                            Ok(())
+                        }
+
+                        #[cfg(not(target_family = "unix"))]
+                        {
+                            let _ = (path, mode);
+
+                            Err(LuaError::runtime("file modes are not supported on this platform"))
+                        }
+                    })
+                })
+            },
+
+            fs_get_xattr: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |_lua: &Lua, (mut path, name): (PathBuf, String)| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_read_path(&path) {
+                            return Err(LuaError::external("no path read permissions"));
+                        }
+
+                        let value = xattr::get(path, name)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to read extended attribute: {err}"))
+                            })?;
+
+                        Ok(value.map(Bytes::from))
+                    })
+                })
+            },
+
+            fs_set_xattr: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |_lua: &Lua, (mut path, name, value): (PathBuf, String, Bytes)| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_write_path(&path) {
+                            return Err(LuaError::external("no path write permissions"));
+                        }
+
+                        xattr::set(path, name, value.as_slice())
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to write extended attribute: {err}"))
+                            })?;
+
+                        Ok(())
+                    })
+                })
+            },
+
+            fs_remove_xattr: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |_lua: &Lua, (mut path, name): (PathBuf, String)| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_write_path(&path) {
+                            return Err(LuaError::external("no path write permissions"));
+                        }
+
+                        xattr::remove(path, name)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to remove extended attribute: {err}"))
+                            })?;
+
+                        Ok(())
+                    })
+                })
+            },
+
+            fs_list_xattrs: {
+                let api_context = api_context.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+
+                    lua.create_function(move |lua: &Lua, mut path: PathBuf| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_read_path(&path) {
+                            return Err(LuaError::external("no path read permissions"));
+                        }
+
+                        let names = xattr::list(path)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to list extended attributes: {err}"))
+                            })?;
+
+                        let result = lua.create_table()?;
+
+                        for name in names {
+                            result.raw_push(lua.create_string(name.as_encoded_bytes())?)?;
+                        }
+
+                        Ok(result)
+                    })
+                })
+            },
+
+            fs_watch: {
+                let api_context = api_context.clone();
+                let watch_handles = watch_handles.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+                    let watch_handles = watch_handles.clone();
+
+                    lua.create_function(move |_lua: &Lua, (mut path, options): (PathBuf, Option<LuaTable>)| {
+                        if path.is_relative() {
+                            path = module_context.module_dir.join(path);
+                        }
+
+                        path = normalize_path(path, true)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to normalize path: {err}"))
+                            })?;
+
+                        if !api_context.can_access_path(&path) {
+                            return Err(LuaError::external("this path cannot be accessed"));
+                        }
+
+                        if !module_context.can_read_path(&path) {
+                            return Err(LuaError::external("no path read permissions"));
+                        }
+
+                        let mut recursive = false;
+                        let mut on_event = None;
+
+                        if let Some(options) = options {
+                            recursive = options.get::<bool>("recursive").unwrap_or_default();
+                            on_event = options.get::<LuaFunction>("on_event").ok();
+                        }
+
+                        let queue = Arc::new(WatchQueue::default());
+
+                        let mut watcher = {
+                            let api_context = api_context.clone();
+                            let module_context = module_context.clone();
+                            let queue = queue.clone();
+
+                            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                                let event = match event {
+                                    Ok(event) => event,
+
+                                    #[allow(unused)]
+                                    Err(err) => {
+                                        #[cfg(feature = "tracing")]
+                                        tracing::warn!(?err, "filesystem watcher error");
+
+                                        return;
+                                    }
+                                };
+
+                                for event in WatchEvent::from_event(event) {
+                                    // Do not report changes of paths which
+                                    // module can't read.
+                                    let allowed = event.paths()
+                                        .into_iter()
+                                        .all(|path| {
+                                            api_context.can_access_path(path) &&
+                                                module_context.can_read_path(path)
+                                        });
+
+                                    if allowed {
+                                        queue.push(event);
+                                    }
+                                }
+                            })
+                        }.map_err(|err| {
+                            LuaError::external(format!("failed to create filesystem watcher: {err}"))
+                        })?;
+
+                        let mode = if recursive {
+                            RecursiveMode::Recursive
+                        } else {
+                            RecursiveMode::NonRecursive
+                        };
+
+                        watcher.watch(&path, mode)
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to watch path: {err}"))
+                            })?;
+
+                        let mut handles = watch_handles.lock()
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to register handle: {err}"))
+                            })?;
+
+                        let mut handle = rand::random::<i32>();
+
+                        while handles.contains_key(&handle) {
+                            handle = rand::random::<i32>();
+                        }
+
+                        handles.insert(handle, (watcher, queue, on_event));
+
+                        Ok(handle)
+                    })
+                })
+            },
+
+            fs_poll_events: {
+                let watch_handles = watch_handles.clone();
+
+                lua.create_function(move |lua: &Lua, handle: i32| {
+                    let (queue, on_event) = {
+                        let handles = watch_handles.lock()
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to read handle: {err}"))
+                            })?;
+
+                        let Some((_, queue, on_event)) = handles.get(&handle) else {
+                            return Err(LuaError::external("invalid watcher handle"));
+                        };
+
+                        (queue.clone(), on_event.clone())
+                    };
+
+                    let events = queue.drain();
+                    let result = lua.create_table_with_capacity(events.len(), 0)?;
+
+                    for event in events {
+                        let event = event.to_lua(lua)?;
+
+                        if let Some(on_event) = &on_event {
+                            on_event.call::<()>(event.clone())?;
+                        }
+
+                        result.raw_push(event)?;
+                    }
+
+                    Ok(result)
+                })?
+            },
+
+            fs_next_event: {
+                let watch_handles = watch_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (handle, token): (i32, Option<CancellationToken>)| {
+                    let queue = {
+                        let handles = watch_handles.lock()
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to read handle: {err}"))
+                            })?;
+
+                        let Some((_, queue, _)) = handles.get(&handle) else {
+                            return Err(LuaError::external("invalid watcher handle"));
+                        };
+
+                        queue.clone()
+                    };
+
+                    let value = PromiseValue::from_future(async move {
+                        loop {
+                            if let Some(event) = queue.pop() {
+                                return Ok(Box::new(move |lua: &Lua| {
+                                    event.to_lua(lua).map(LuaValue::Table)
+                                }) as TaskOutput);
+                            }
+
+                            if queue.is_closed() {
+                                return Ok(task_output(Ok(LuaValue::Nil)));
+                            }
+
+                            queue.notify.notified().await;
+                        }
+                    });
+
+                    Promise::new(value)
+                        .with_token(token)
+                        .into_lua(lua)
+                })?
+            },
+
+            fs_unwatch: {
+                let watch_handles = watch_handles.clone();
+
+                lua.create_function(move |_lua: &Lua, handle: i32| {
+                    let mut handles = watch_handles.lock()
+                        .map_err(|err| {
+                            LuaError::external(format!("failed to read handle: {err}"))
+                        })?;
+
+                    // Dropping the watcher stops it.
+                    if let Some((_, queue, _)) = handles.remove(&handle) {
+                        queue.close();
+                    }
+
+                    Ok(())
+                })?
+            },
+
+            file_handles,
+
+            lua
+        })
+    }
+
+    /// Table of files opened by the `fs.open` function. Used by other APIs to
+    /// read or write files by their handles.
+    #[inline]
+    pub fn file_handles(&self) -> FileHandles {
+        self.file_handles.clone()
+    }
+
+    /// Create new lua table with API functions.
+    pub fn create_env(
+        &self,
+        context: &ModuleContext
+    ) -> Result<LuaTable, LuaError> {
+        let env = self.lua.create_table_with_capacity(0, 35)?;
+
+        env.raw_set("exists", (self.fs_exists)(&self.lua, context)?)?;
+        env.raw_set("metadata", (self.fs_metadata)(&self.lua, context)?)?;
+        env.raw_set("copy", (self.fs_copy)(&self.lua, context)?)?;
+        env.raw_set("move", (self.fs_move)(&self.lua, context)?)?;
+        env.raw_set("remove", (self.fs_remove)(&self.lua, context)?)?;
+        env.raw_set("open", (self.fs_open)(&self.lua, context)?)?;
+        env.raw_set("seek", &self.fs_seek)?;
+        env.raw_set("seek_rel", &self.fs_seek_rel)?;
+        env.raw_set("truncate", &self.fs_truncate)?;
+        env.raw_set("read", &self.fs_read)?;
+        env.raw_set("write", &self.fs_write)?;
+        env.raw_set("flush", &self.fs_flush)?;
+        env.raw_set("close", &self.fs_close)?;
+
+        env.raw_set("create_file", (self.fs_create_file)(&self.lua, context)?)?;
+        env.raw_set("read_file", (self.fs_read_file)(&self.lua, context)?)?;
+        env.raw_set("write_file", (self.fs_write_file)(&self.lua, context)?)?;
+        env.raw_set("remove_file", (self.fs_remove_file)(&self.lua, context)?)?;
+        env.raw_set("create_dir", (self.fs_create_dir)(&self.lua, context)?)?;
+        env.raw_set("read_dir", (self.fs_read_dir)(&self.lua, context)?)?;
+        env.raw_set("remove_dir", (self.fs_remove_dir)(&self.lua, context)?)?;
+
+        env.raw_set("copy_tree", (self.fs_copy_tree)(&self.lua, context)?)?;
+        env.raw_set("sync", (self.fs_sync)(&self.lua, context)?)?;
+        env.raw_set("size", (self.fs_size)(&self.lua, context)?)?;
+        env.raw_set("hardlink", (self.fs_hardlink)(&self.lua, context)?)?;
+        env.raw_set("symlink", (self.fs_symlink)(&self.lua, context)?)?;
+        env.raw_set("read_link", (self.fs_read_link)(&self.lua, context)?)?;
+        env.raw_set("chmod", (self.fs_chmod)(&self.lua, context)?)?;
+        env.raw_set("get_xattr", (self.fs_get_xattr)(&self.lua, context)?)?;
+        env.raw_set("set_xattr", (self.fs_set_xattr)(&self.lua, context)?)?;
+        env.raw_set("remove_xattr", (self.fs_remove_xattr)(&self.lua, context)?)?;
+        env.raw_set("list_xattrs", (self.fs_list_xattrs)(&self.lua, context)?)?;
+
+        env.raw_set("watch", (self.fs_watch)(&self.lua, context)?)?;
+        env.raw_set("poll_events", &self.fs_poll_events)?;
+        env.raw_set("next_event", &self.fs_next_event)?;
+        env.raw_set("unwatch", &self.fs_unwatch)?;
+
+        Ok(env)
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    /// Prepare test folder with a module folder which is accessible by the
+    /// module, a folder outside of the module's sandbox and a private folder
+    /// within the module folder.
+    fn test_env(name: &str) -> Result<(FilesystemApi, LuaTable, PathBuf), LuaError> {
+        let path = std::env::temp_dir().join(format!(".agl-fs-{name}-test"));
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+
+        let module_dir = path.join("module");
+
+        std::fs::create_dir_all(&module_dir)?;
+        std::fs::create_dir_all(path.join("outside"))?;
+        std::fs::create_dir_all(module_dir.join("private"))?;
+
+        let api_context = ApiContext {
+            private_paths: Arc::new(RwLock::new(vec![module_dir.join("private")]))
+        };
+
+        let context = ModuleContext {
+            temp_dir: Arc::new(module_dir.clone()),
+            module_dir: Arc::new(module_dir.clone()),
+            persistent_dir: Arc::new(module_dir),
+            ..ModuleContext::default()
+        };
+
+        let api = FilesystemApi::new(Lua::new(), api_context)?;
+        let env = api.create_env(&context)?;
+
+        Ok((api, env, path))
+    }
+
+    fn call_await<T: FromLuaMulti>(env: &LuaTable, name: &str, args: impl IntoLuaMulti) -> Result<T, LuaError> {
+        env.call_function::<LuaAnyUserData>(name, args)?
+            .call_method::<T>("await", ())
+    }
+
+    #[test]
+    fn fs_watch() -> Result<(), LuaError> {
+        let (api, env, path) = test_env("watch")?;
+
+        std::fs::create_dir_all(path.join("module/folder"))?;
+
+        let handle = env.call_function::<i32>("watch", "folder")?;
+
+        std::fs::write(path.join("module/folder/file"), b"Hello, World!")?;
+
+        let event = call_await::<LuaTable>(&env, "next_event", handle)?;
+
+        assert_eq!(event.get::<String>("type")?, "create");
+        assert_eq!(event.get::<PathBuf>("path")?, path.join("module/folder/file"));
+
+        std::fs::rename(path.join("module/folder/file"), path.join("module/folder/renamed"))?;
+
+        // Wait for the rename event.
+        let mut renamed = false;
+
+        for _ in 0..50 {
+            let events = env.call_function::<Vec<LuaTable>>("poll_events", handle)?;
+
+            renamed = events.iter().any(|event| {
+                event.get::<String>("type").is_ok_and(|kind| kind == "rename") &&
+                    event.get::<PathBuf>("from").is_ok_and(|from| from == path.join("module/folder/file"))
+            });
+
+            if renamed {
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        assert!(renamed);
+
+        env.call_function::<()>("unwatch", handle)?;
+
+        assert!(env.call_function::<LuaTable>("poll_events", handle).is_err());
+
+        // Recursive watchers report changes of nested folders.
+        std::fs::create_dir_all(path.join("module/folder/nested"))?;
+
+        let options = api.lua.create_table()?;
+
+        options.raw_set("recursive", true)?;
+
+        let handle = env.call_function::<i32>("watch", ("folder", options))?;
+
+        std::fs::write(path.join("module/folder/nested/file"), b"Hello, World!")?;
+
+        let event = call_await::<LuaTable>(&env, "next_event", handle)?;
+
+        assert_eq!(event.get::<String>("type")?, "create");
+        assert_eq!(event.get::<PathBuf>("path")?, path.join("module/folder/nested/file"));
+
+        env.call_function::<()>("unwatch", handle)?;
+
+        // Sandbox.
+        assert!(env.call_function::<i32>("watch", path.join("outside")).is_err());
+        assert!(env.call_function::<i32>("watch", "private").is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_copy_tree() -> Result<(), LuaError> {
+        let (api, env, path) = test_env("copy-tree")?;
+
+        let source = path.join("module/source");
+
+        std::fs::create_dir_all(source.join("folder"))?;
+        std::fs::write(source.join("file"), b"Hello, World!")?;
+        std::fs::write(source.join("folder/file"), vec![42; 100_000])?;
+        std::os::unix::fs::symlink("folder/file", source.join("link"))?;
+
+        call_await::<()>(&env, "copy_tree", ("source", "target"))?;
+
+        let target = path.join("module/target");
+
+        assert_eq!(std::fs::read(target.join("file"))?, b"Hello, World!");
+        assert_eq!(std::fs::read(target.join("folder/file"))?, vec![42; 100_000]);
+        assert_eq!(target.join("link").read_link()?, PathBuf::from("folder/file"));
+
+        // Existing files are not overwritten by default.
+        assert!(call_await::<()>(&env, "copy_tree", ("source", "target")).is_err());
+
+        let options = api.lua.create_table()?;
+
+        options.raw_set("overwrite", true)?;
+
+        call_await::<()>(&env, "copy_tree", ("source", "target", options))?;
+
+        // Sandbox.
+        assert!(env.call_function::<LuaAnyUserData>("copy_tree", ("source", path.join("outside"))).is_err());
+        assert!(env.call_function::<LuaAnyUserData>("copy_tree", (path.join("outside"), "target2")).is_err());
+        assert!(env.call_function::<LuaAnyUserData>("copy_tree", ("private", "target2")).is_err());
+        assert!(env.call_function::<LuaAnyUserData>("copy_tree", ("source", "private/target")).is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_sync() -> Result<(), LuaError> {
+        let (api, env, path) = test_env("sync")?;
+
+        let source = path.join("module/source");
+        let target = path.join("module/target");
+
+        std::fs::create_dir_all(source.join("folder"))?;
+        std::fs::create_dir_all(target.join("removed_folder"))?;
+
+        std::fs::write(source.join("same"), b"same")?;
+        std::fs::write(source.join("changed"), b"new content")?;
+        std::fs::write(source.join("folder/new"), b"new")?;
+
+        std::fs::write(target.join("changed"), b"old")?;
+        std::fs::write(target.join("removed"), b"removed")?;
+        std::fs::write(target.join("removed_folder/file"), b"removed")?;
+
+        // Copy the unchanged file with its modification time.
+        call_await::<()>(&env, "copy_tree", ("source/same", "target/same"))?;
+
+        let options = api.lua.create_table()?;
+
+        options.raw_set("delete", true)?;
+
+        let result = call_await::<LuaTable>(&env, "sync", ("source", "target", options.clone()))?;
+
+        assert_eq!(result.get::<u32>("copied")?, 2);
+        assert_eq!(result.get::<u32>("skipped")?, 1);
+        assert_eq!(result.get::<u32>("removed")?, 2);
+
+        assert_eq!(std::fs::read(target.join("changed"))?, b"new content");
+        assert_eq!(std::fs::read(target.join("folder/new"))?, b"new");
+        assert!(!target.join("removed").exists());
+        assert!(!target.join("removed_folder").exists());
+
+        options.raw_set("checksum", "blake3")?;
+
+        let result = call_await::<LuaTable>(&env, "sync", ("source", "target", options))?;
+
+        assert_eq!(result.get::<u32>("copied")?, 0);
+        assert_eq!(result.get::<u32>("skipped")?, 3);
+        assert_eq!(result.get::<u32>("removed")?, 0);
+
+        // Sandbox.
+        assert!(env.call_function::<LuaAnyUserData>("sync", ("source", path.join("outside"))).is_err());
+        assert!(env.call_function::<LuaAnyUserData>("sync", (path.join("outside"), "target")).is_err());
+        assert!(env.call_function::<LuaAnyUserData>("sync", ("source", "private")).is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_size() -> Result<(), LuaError> {
+        let (_api, env, path) = test_env("size")?;
+
+        let folder = path.join("module/folder");
+
+        std::fs::create_dir_all(folder.join("nested"))?;
+        std::fs::write(folder.join("file"), vec![0; 1000])?;
+        std::fs::write(folder.join("nested/file"), vec![0; 234])?;
+
+        assert_eq!(call_await::<u64>(&env, "size", "folder")?, 1234);
+        assert_eq!(call_await::<u64>(&env, "size", "folder/file")?, 1000);
+
+        // Sandbox.
+        assert!(env.call_function::<LuaAnyUserData>("size", path.join("outside")).is_err());
+        assert!(env.call_function::<LuaAnyUserData>("size", "private").is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_hardlink() -> Result<(), LuaError> {
+        let (_api, env, path) = test_env("hardlink")?;
+
+        std::fs::write(path.join("module/file"), b"Hello")?;
+
+        env.call_function::<()>("hardlink", ("file", "link"))?;
+
+        std::fs::write(path.join("module/link"), b"Hello, World!")?;
+
+        assert_eq!(std::fs::read(path.join("module/file"))?, b"Hello, World!");
+
+        // Sandbox.
+        std::fs::write(path.join("outside/file"), b"outside")?;
+
+        assert!(env.call_function::<()>("hardlink", (path.join("outside/file"), "outside_link")).is_err());
+        assert!(env.call_function::<()>("hardlink", ("file", path.join("outside/link"))).is_err());
+        assert!(env.call_function::<()>("hardlink", ("file", "private/link")).is_err());
+        assert!(env.call_function::<()>("hardlink", ("missing", "link2")).is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_symlink() -> Result<(), LuaError> {
+        let (_api, env, path) = test_env("symlink")?;
+
+        std::fs::write(path.join("module/file"), b"Hello, World!")?;
+
+        env.call_function::<()>("symlink", ("file", "link"))?;
+
+        assert_eq!(env.call_function::<String>("read_link", "link")?, "file");
+        assert_eq!(std::fs::read(path.join("module/link"))?, b"Hello, World!");
+
+        // Sandbox.
+        assert!(env.call_function::<()>("symlink", ("../outside", "outside_link")).is_err());
+        assert!(env.call_function::<()>("symlink", ("private", "private_link")).is_err());
+        assert!(env.call_function::<()>("symlink", ("file", path.join("outside/link"))).is_err());
+
+        std::os::unix::fs::symlink("file", path.join("outside/link"))?;
+
+        assert!(env.call_function::<String>("read_link", path.join("outside/link")).is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_chmod() -> Result<(), LuaError> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_api, env, path) = test_env("chmod")?;
+
+        let file = path.join("module/file");
+
+        std::fs::write(&file, b"Hello, World!")?;
+
+        env.call_function::<()>("chmod", ("file", "600"))?;
+
+        assert_eq!(file.metadata()?.permissions().mode() & 0o777, 0o600);
+
+        env.call_function::<()>("chmod", ("file", 0o755))?;
+
+        assert_eq!(file.metadata()?.permissions().mode() & 0o777, 0o755);
+
+        assert!(env.call_function::<()>("chmod", ("file", "rwx")).is_err());
+        assert!(env.call_function::<()>("chmod", ("file", -1)).is_err());
+
+        // Sandbox.
+        std::fs::write(path.join("outside/file"), b"outside")?;
+
+        assert!(env.call_function::<()>("chmod", (path.join("outside/file"), "777")).is_err());
+        assert!(env.call_function::<()>("chmod", ("private", "777")).is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_xattr() -> Result<(), LuaError> {
+        let (_api, env, path) = test_env("xattr")?;
+
+        let file = path.join("module/file");
+
+        std::fs::write(&file, b"Hello, World!")?;
+
+        // Extended attributes are not supported by all filesystems.
+        if xattr::set(&file, "user.agl-test", b"").is_err() {
+            std::fs::remove_dir_all(&path)?;
+
+            return Ok(());
+        }
+
+        env.call_function::<()>("set_xattr", ("file", "user.example", "value"))?;
+
+        let value = env.call_function::<Bytes>("get_xattr", ("file", "user.example"))?;
+
+        assert_eq!(&value[..], b"value");
+
+        let names = env.call_function::<Vec<String>>("list_xattrs", "file")?;
+
+        assert!(names.contains(&String::from("user.example")));
+
+        env.call_function::<()>("remove_xattr", ("file", "user.example"))?;
+
+        assert!(env.call_function::<Option<Bytes>>("get_xattr", ("file", "user.example"))?.is_none());
+
+        // Sandbox.
+        std::fs::write(path.join("outside/file"), b"outside")?;
+
+        assert!(env.call_function::<()>("set_xattr", (path.join("outside/file"), "user.example", "value")).is_err());
+        assert!(env.call_function::<Option<Bytes>>("get_xattr", (path.join("outside/file"), "user.example")).is_err());
+        assert!(env.call_function::<Vec<String>>("list_xattrs", path.join("outside/file")).is_err());
+        assert!(env.call_function::<()>("remove_xattr", ("private", "user.example")).is_err());
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
     }
 }
 
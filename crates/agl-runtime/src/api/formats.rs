@@ -0,0 +1,649 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-runtime
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Structured data formats which don't have serde compatible value types.
+
+use mlua::prelude::*;
+
+use quick_xml::events::{Event, BytesStart, BytesEnd, BytesText};
+
+use agl_core::vdf::{self, VdfValue};
+
+/// Maximal nesting depth of the encoded and decoded tables.
+const MAX_DEPTH: usize = 128;
+
+/// Path of nested tables from the encoded root table to the current one. Used
+/// to reject cyclic and too deeply nested tables instead of overflowing the
+/// stack.
+#[derive(Default)]
+struct TablesPath(Vec<*const std::ffi::c_void>);
+
+impl TablesPath {
+    fn enter(&mut self, table: &LuaTable) -> Result<(), LuaError> {
+        let pointer = table.to_pointer();
+
+        if self.0.contains(&pointer) {
+            return Err(LuaError::external("can't encode table which contains itself"));
+        }
+
+        if self.0.len() >= MAX_DEPTH {
+            return Err(LuaError::external(format!("can't encode tables nested deeper than {MAX_DEPTH} levels")));
+        }
+
+        self.0.push(pointer);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn leave(&mut self) {
+        self.0.pop();
+    }
+}
+
+/// Convert basic lua value into a string.
+fn value_to_string(value: &LuaValue) -> Option<String> {
+    match value {
+        LuaValue::String(value) => Some(value.to_string_lossy()),
+        LuaValue::Integer(value) => Some(value.to_string()),
+        LuaValue::Boolean(value) => Some(value.to_string()),
+
+        LuaValue::Number(value) if value.fract() == 0.0 && value.is_finite() => {
+            Some((*value as i64).to_string())
+        }
+
+        LuaValue::Number(value) => Some(value.to_string()),
+
+        _ => None
+    }
+}
+
+/// Get table pairs with stringified keys sorted alphabetically.
+fn sorted_pairs(table: &LuaTable) -> Result<Vec<(String, LuaValue)>, LuaError> {
+    let mut pairs = Vec::new();
+
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+
+        let Some(key) = value_to_string(&key) else {
+            return Err(LuaError::external("unsupported table key type"));
+        };
+
+        pairs.push((key, value));
+    }
+
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(pairs)
+}
+
+/// Parsed line of an INI file.
+enum IniLine<'a> {
+    Section(&'a str),
+    Pair(&'a str, &'a str),
+    Other
+}
+
+fn parse_ini_line(line: &str) -> IniLine<'_> {
+    let line = line.trim();
+
+    if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+        return IniLine::Other;
+    }
+
+    if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+        return IniLine::Section(section.trim());
+    }
+
+    match line.split_once('=') {
+        Some((key, value)) => IniLine::Pair(key.trim(), value.trim()),
+        None => IniLine::Other
+    }
+}
+
+/// Decode INI file into a table. Keys without section are stored in the table
+/// itself, sections are stored as nested tables.
+pub fn decode_ini(lua: &Lua, text: &str) -> Result<LuaTable, LuaError> {
+    let result = lua.create_table()?;
+    let mut section = result.clone();
+
+    for line in text.lines() {
+        match parse_ini_line(line) {
+            IniLine::Section(name) => {
+                section = match result.raw_get::<Option<LuaTable>>(name)? {
+                    Some(section) => section,
+                    None => {
+                        let table = lua.create_table()?;
+
+                        result.raw_set(name, &table)?;
+
+                        table
+                    }
+                };
+            }
+
+            IniLine::Pair(key, value) => section.raw_set(key, value)?,
+            IniLine::Other => ()
+        }
+    }
+
+    Ok(result)
+}
+
+/// Encode table into INI file. Nested tables are written as sections.
+pub fn encode_ini(table: &LuaTable) -> Result<String, LuaError> {
+    let mut global = String::new();
+    let mut sections = String::new();
+
+    for (key, value) in sorted_pairs(table)? {
+        if let LuaValue::Table(section) = value {
+            if !sections.is_empty() {
+                sections.push('\n');
+            }
+
+            sections.push_str(&format!("[{key}]\n"));
+
+            for (key, value) in sorted_pairs(&section)? {
+                if let Some(value) = value_to_string(&value) {
+                    sections.push_str(&format!("{key}={value}\n"));
+                }
+            }
+        }
+
+        else if let Some(value) = value_to_string(&value) {
+            global.push_str(&format!("{key}={value}\n"));
+        }
+    }
+
+    if !global.is_empty() && !sections.is_empty() {
+        global.push('\n');
+    }
+
+    Ok(global + &sections)
+}
+
+/// Change value of the INI file's key keeping the rest of the file as is. Key
+/// is removed if no value is given. Missing keys and sections are added.
+pub fn ini_set(
+    text: &str,
+    section: Option<&str>,
+    key: &str,
+    value: Option<&str>
+) -> String {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let mut lines = text.lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    let mut current_section = None;
+    let mut section_found = section.is_none();
+
+    // Index of the line after which a new key should be inserted.
+    let mut insert_after = None;
+
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].clone();
+
+        match parse_ini_line(&line) {
+            IniLine::Section(name) => {
+                current_section = Some(name.to_string());
+
+                if section.is_some_and(|section| section == name) {
+                    section_found = true;
+                    insert_after = Some(i);
+                }
+            }
+
+            IniLine::Pair(name, _) if current_section.as_deref() == section => {
+                if name == key {
+                    match value {
+                        // Keep original formatting of the line.
+                        Some(value) => {
+                            let separator = line.find('=')
+                                .unwrap_or(line.len());
+
+                            let spaces = line[separator + 1..].len() - line[separator + 1..].trim_start().len();
+
+                            lines[i] = format!("{}{}{value}", &line[..separator + 1], &line[separator + 1..separator + 1 + spaces]);
+                        }
+
+                        None => {
+                            lines.remove(i);
+                        }
+                    }
+
+                    return lines.join(newline) + newline;
+                }
+
+                insert_after = Some(i);
+            }
+
+            _ => ()
+        }
+
+        i += 1;
+    }
+
+    let Some(value) = value else {
+        return text.to_string();
+    };
+
+    let line = format!("{key}={value}");
+
+    match (section_found, insert_after) {
+        (true, Some(i)) => lines.insert(i + 1, line),
+        (true, None) => lines.insert(0, line),
+
+        (false, _) => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+
+            lines.push(format!("[{}]", section.unwrap_or_default()));
+            lines.push(line);
+        }
+    }
+
+    lines.join(newline) + newline
+}
+
+/// Decode XML document into a table of its root element:
+/// `{ name, attributes, children }`, where children are either elements or
+/// text strings.
+pub fn decode_xml(lua: &Lua, text: &str) -> Result<LuaValue, LuaError> {
+    fn create_element(lua: &Lua, start: &BytesStart) -> Result<LuaTable, LuaError> {
+        let element = lua.create_table_with_capacity(0, 3)?;
+        let attributes = lua.create_table()?;
+
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(LuaError::external)?;
+
+            let value = attribute.unescape_value()
+                .map_err(LuaError::external)?;
+
+            attributes.raw_set(lua.create_string(attribute.key.as_ref())?, value.as_ref())?;
+        }
+
+        element.raw_set("name", lua.create_string(start.name().as_ref())?)?;
+        element.raw_set("attributes", attributes)?;
+        element.raw_set("children", lua.create_table()?)?;
+
+        Ok(element)
+    }
+
+    fn push_child(stack: &[LuaTable], child: impl IntoLua) -> Result<(), LuaError> {
+        if let Some(parent) = stack.last() {
+            parent.raw_get::<LuaTable>("children")?
+                .raw_push(child)?;
+        }
+
+        Ok(())
+    }
+
+    let mut reader = quick_xml::Reader::from_str(text);
+
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<LuaTable> = Vec::new();
+    let mut root = None;
+
+    loop {
+        let event = reader.read_event()
+            .map_err(|err| LuaError::external(format!("failed to parse xml: {err}")))?;
+
+        match event {
+            Event::Start(start) => {
+                stack.push(create_element(lua, &start)?);
+            }
+
+            Event::Empty(start) => {
+                let element = create_element(lua, &start)?;
+
+                if stack.is_empty() {
+                    root.get_or_insert(element);
+                } else {
+                    push_child(&stack, element)?;
+                }
+            }
+
+            Event::End(_) => {
+                let Some(element) = stack.pop() else {
+                    return Err(LuaError::external("failed to parse xml: unexpected closing tag"));
+                };
+
+                if stack.is_empty() {
+                    root.get_or_insert(element);
+                } else {
+                    push_child(&stack, element)?;
+                }
+            }
+
+            Event::Text(text) => {
+                let text = text.unescape()
+                    .map_err(LuaError::external)?;
+
+                push_child(&stack, text.as_ref())?;
+            }
+
+            Event::CData(text) => {
+                push_child(&stack, lua.create_string(&*text)?)?;
+            }
+
+            Event::Eof => break,
+
+            _ => ()
+        }
+    }
+
+    match root {
+        Some(root) => Ok(LuaValue::Table(root)),
+        None => Ok(LuaValue::Nil)
+    }
+}
+
+/// Encode table of the root element into XML document.
+pub fn encode_xml(element: &LuaTable, pretty: bool) -> Result<Vec<u8>, LuaError> {
+    fn write_element<W: std::io::Write>(
+        writer: &mut quick_xml::Writer<W>,
+        element: &LuaTable,
+        path: &mut TablesPath
+    ) -> Result<(), LuaError> {
+        path.enter(element)?;
+
+        let name = element.get::<String>("name")?;
+
+        let mut start = BytesStart::new(name.as_str());
+
+        if let Some(attributes) = element.get::<Option<LuaTable>>("attributes")? {
+            for (key, value) in sorted_pairs(&attributes)? {
+                let value = value_to_string(&value).unwrap_or_default();
+
+                start.push_attribute((key.as_str(), value.as_str()));
+            }
+        }
+
+        let children = element.get::<Option<LuaTable>>("children")?
+            .filter(|children| children.raw_len() > 0);
+
+        let Some(children) = children else {
+            path.leave();
+
+            return writer.write_event(Event::Empty(start))
+                .map_err(LuaError::external);
+        };
+
+        writer.write_event(Event::Start(start))
+            .map_err(LuaError::external)?;
+
+        for child in children.sequence_values::<LuaValue>() {
+            match child? {
+                LuaValue::Table(child) => write_element(writer, &child, path)?,
+
+                child => {
+                    if let Some(text) = value_to_string(&child) {
+                        writer.write_event(Event::Text(BytesText::new(&text)))
+                            .map_err(LuaError::external)?;
+                    }
+                }
+            }
+        }
+
+        path.leave();
+
+        writer.write_event(Event::End(BytesEnd::new(name.as_str())))
+            .map_err(LuaError::external)
+    }
+
+    let mut writer = if pretty {
+        quick_xml::Writer::new_with_indent(Vec::new(), b' ', 4)
+    } else {
+        quick_xml::Writer::new(Vec::new())
+    };
+
+    write_element(&mut writer, element, &mut TablesPath::default())?;
+
+    Ok(writer.into_inner())
+}
+
+/// Decode Valve's text KeyValues (VDF) document into a table.
+pub fn decode_vdf(lua: &Lua, text: &str) -> Result<LuaTable, LuaError> {
+    #[derive(Debug, PartialEq, Eq)]
+    enum Token {
+        String(String),
+        Open,
+        Close
+    }
+
+    fn tokenize(text: &str) -> Result<Vec<Token>, LuaError> {
+        let mut tokens = Vec::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '{' => tokens.push(Token::Open),
+                '}' => tokens.push(Token::Close),
+
+                '"' => {
+                    let mut value = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+
+                            Some('\\') => match chars.next() {
+                                Some('n') => value.push('\n'),
+                                Some('t') => value.push('\t'),
+                                Some(char) => value.push(char),
+                                None => break
+                            },
+
+                            Some(char) => value.push(char),
+
+                            None => return Err(LuaError::external("failed to parse vdf: unterminated string"))
+                        }
+                    }
+
+                    tokens.push(Token::String(value));
+                }
+
+                // Comments.
+                '/' if chars.peek() == Some(&'/') => {
+                    for char in chars.by_ref() {
+                        if char == '\n' {
+                            break;
+                        }
+                    }
+                }
+
+                // Conditionals like `[$WIN32]` are ignored.
+                '[' => {
+                    for char in chars.by_ref() {
+                        if char == ']' {
+                            break;
+                        }
+                    }
+                }
+
+                char if char.is_whitespace() => (),
+
+                // Unquoted tokens.
+                char => {
+                    let mut value = String::from(char);
+
+                    while let Some(char) = chars.peek() {
+                        if char.is_whitespace() || ['{', '}', '"'].contains(char) {
+                            break;
+                        }
+
+                        value.push(*char);
+
+                        chars.next();
+                    }
+
+                    tokens.push(Token::String(value));
+                }
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    fn read_map(
+        lua: &Lua,
+        tokens: &mut std::vec::IntoIter<Token>,
+        depth: usize
+    ) -> Result<LuaTable, LuaError> {
+        if depth > MAX_DEPTH {
+            return Err(LuaError::external(format!("failed to parse vdf: maps nesting is deeper than {MAX_DEPTH} levels")));
+        }
+
+        let nested = depth > 0;
+        let table = lua.create_table()?;
+
+        loop {
+            let key = match tokens.next() {
+                Some(Token::String(key)) => key,
+                Some(Token::Close) if nested => return Ok(table),
+                None if !nested => return Ok(table),
+
+                _ => return Err(LuaError::external("failed to parse vdf: unexpected token"))
+            };
+
+            match tokens.next() {
+                Some(Token::String(value)) => table.raw_set(key, value)?,
+                Some(Token::Open) => table.raw_set(key, read_map(lua, tokens, depth + 1)?)?,
+
+                _ => return Err(LuaError::external("failed to parse vdf: missing value"))
+            }
+        }
+    }
+
+    read_map(lua, &mut tokenize(text)?.into_iter(), 0)
+}
+
+/// Encode table into Valve's text KeyValues (VDF) document.
+pub fn encode_vdf(table: &LuaTable) -> Result<String, LuaError> {
+    fn escape(value: &str) -> String {
+        value.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t")
+    }
+
+    fn write_map(buf: &mut String, table: &LuaTable, path: &mut TablesPath) -> Result<(), LuaError> {
+        let indent = "\t".repeat(path.0.len());
+
+        path.enter(table)?;
+
+        for (key, value) in sorted_pairs(table)? {
+            let key = escape(&key);
+
+            if let LuaValue::Table(table) = value {
+                buf.push_str(&format!("{indent}\"{key}\"\n{indent}{{\n"));
+
+                write_map(buf, &table, path)?;
+
+                buf.push_str(&format!("{indent}}}\n"));
+            }
+
+            else if let Some(value) = value_to_string(&value) {
+                buf.push_str(&format!("{indent}\"{key}\"\t\t\"{}\"\n", escape(&value)));
+            }
+        }
+
+        path.leave();
+
+        Ok(())
+    }
+
+    let mut buf = String::new();
+
+    write_map(&mut buf, table, &mut TablesPath::default())?;
+
+    Ok(buf)
+}
+
+/// Decode Steam's binary VDF file (like `shortcuts.vdf`) into a table.
+pub fn decode_binary_vdf(lua: &Lua, bytes: &[u8]) -> Result<LuaTable, LuaError> {
+    fn create_map(lua: &Lua, entries: Vec<(String, VdfValue)>) -> Result<LuaTable, LuaError> {
+        let table = lua.create_table_with_capacity(0, entries.len())?;
+
+        for (key, value) in entries {
+            match value {
+                VdfValue::Map(entries) => table.raw_set(key, create_map(lua, entries)?)?,
+                VdfValue::String(value) => table.raw_set(key, value)?,
+                VdfValue::Int(value) => table.raw_set(key, value)?
+            }
+        }
+
+        Ok(table)
+    }
+
+    let entries = vdf::decode(bytes)
+        .map_err(|err| LuaError::external(format!("failed to parse vdf: {err}")))?;
+
+    create_map(lua, entries)
+}
+
+/// Encode table into Steam's binary VDF file. Integer numbers are stored as
+/// 32 bit unsigned integers.
+pub fn encode_binary_vdf(table: &LuaTable) -> Result<Vec<u8>, LuaError> {
+    fn to_int(value: impl TryInto<u32>) -> Result<VdfValue, LuaError> {
+        value.try_into()
+            .map(VdfValue::Int)
+            .map_err(|_| LuaError::external("binary vdf integer value must be in 0..=4294967295 range"))
+    }
+
+    fn read_map(table: &LuaTable, path: &mut TablesPath) -> Result<Vec<(String, VdfValue)>, LuaError> {
+        path.enter(table)?;
+
+        let mut entries = Vec::new();
+
+        for (key, value) in sorted_pairs(table)? {
+            let value = match value {
+                LuaValue::Table(table) => VdfValue::Map(read_map(&table, path)?),
+                LuaValue::Integer(value) => to_int(value)?,
+
+                LuaValue::Number(value) if value.fract() == 0.0 => {
+                    if !(0.0..=u32::MAX as f64).contains(&value) {
+                        return Err(LuaError::external("binary vdf integer value must be in 0..=4294967295 range"));
+                    }
+
+                    VdfValue::Int(value as u32)
+                }
+
+                value => match value_to_string(&value) {
+                    Some(value) => VdfValue::String(value),
+                    None => continue
+                }
+            };
+
+            entries.push((key, value));
+        }
+
+        path.leave();
+
+        Ok(entries)
+    }
+
+    vdf::encode(&read_map(table, &mut TablesPath::default())?)
+        .map_err(|err| LuaError::external(format!("failed to encode vdf: {err}")))
+}
@@ -17,25 +17,524 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-
-use agl_core::export::network::reqwest::{Client, RequestBuilder, Method};
+use std::io::Read;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use agl_core::export::network::reqwest::{
+    Client,
+    RequestBuilder,
+    Request,
+    Response,
+    Method,
+    Body,
+    StatusCode,
+    Url
+};
+
+use agl_core::export::network::reqwest::header::{self, HeaderMap, HeaderValue};
+use agl_core::export::network::reqwest::multipart::{Form, Part};
 use agl_core::tasks;
 
 use mlua::prelude::*;
 
+use serde_json::{json, Value as Json};
+
 use super::bytes::Bytes;
-use super::task_api::{Promise, PromiseValue, TaskOutput, task_output};
+use super::filesystem_api::FileHandles;
+use super::task_api::{Promise, PromiseValue, TaskOutput, CancellationToken, task_output};
+use super::*;
+
+pub const HTTP_UPLOAD_CHUNK_SIZE: usize = 64 * 1024; // 64 KiB file uploads
+pub const HTTP_DEFAULT_MAX_REDIRECTS: usize = 10;
+pub const HTTP_DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(1000);
+
+/// Name of the cookie jar file in the module directory.
+pub const HTTP_COOKIES_FILE: &str = ".http-cookies.json";
+
+type CookieJars = Arc<Mutex<HashMap<PathBuf, Arc<Mutex<CookieJar>>>>>;
+
+#[inline]
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub host_only: bool,
+    pub path: String,
+    pub expires: Option<u64>,
+    pub secure: bool
+}
+
+impl Cookie {
+    /// Parse `Set-Cookie` header value received from the given URL.
+    pub fn parse(header: &str, url: &Url) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        let mut attributes = header.split(';');
+
+        let (name, value) = attributes.next()?.split_once('=')?;
+
+        let name = name.trim();
+
+        if name.is_empty() {
+            return None;
+        }
+
+        // Default path is the URL path up to its last segment.
+        let path = match url.path().rsplit_once('/') {
+            Some((path, _)) if !path.is_empty() => path.to_string(),
+            _ => String::from("/")
+        };
+
+        let mut cookie = Self {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path,
+            expires: None,
+            secure: false
+        };
+
+        let mut has_max_age = false;
+
+        for attribute in attributes {
+            let (key, value) = attribute.split_once('=')
+                .unwrap_or((attribute, ""));
+
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" => {
+                    let domain = value.trim_start_matches('.')
+                        .to_ascii_lowercase();
+
+                    if domain.is_empty() {
+                        continue;
+                    }
+
+                    // Reject cookies for foreign domains.
+                    if host != domain && !host.ends_with(&format!(".{domain}")) {
+                        return None;
+                    }
+
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+
+                "path" if value.starts_with('/') => {
+                    cookie.path = value.to_string();
+                }
+
+                "max-age" => {
+                    if let Ok(max_age) = value.parse::<i64>() {
+                        cookie.expires = Some(unix_timestamp().saturating_add_signed(max_age.max(0)));
+
+                        has_max_age = true;
+                    }
+                }
+
+                "expires" if !has_max_age => {
+                    let expires = time::OffsetDateTime::parse(
+                        value,
+                        &time::format_description::well_known::Rfc2822
+                    );
+
+                    if let Ok(expires) = expires {
+                        cookie.expires = Some(expires.unix_timestamp().max(0) as u64);
+                    }
+                }
+
+                "secure" => cookie.secure = true,
+
+                _ => ()
+            }
+        }
+
+        Some(cookie)
+    }
+
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires <= unix_timestamp())
+    }
+
+    /// Check if the cookie should be sent to the given URL.
+    pub fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+
+        let host = host.to_ascii_lowercase();
+
+        let domain_matches = if self.host_only {
+            host == self.domain
+        } else {
+            host == self.domain || host.ends_with(&format!(".{}", self.domain))
+        };
+
+        let path = url.path();
+
+        let path_matches = path == self.path || (path.starts_with(&self.path) && (
+            self.path.ends_with('/') || path[self.path.len()..].starts_with('/')
+        ));
+
+        domain_matches && path_matches
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired()
+    }
+
+    pub fn to_json(&self) -> Json {
+        json!({
+            "name": self.name,
+            "value": self.value,
+            "domain": self.domain,
+            "host_only": self.host_only,
+            "path": self.path,
+            "expires": self.expires,
+            "secure": self.secure
+        })
+    }
+
+    pub fn from_json(value: &Json) -> Option<Self> {
+        Some(Self {
+            name: value.get("name")?.as_str()?.to_string(),
+            value: value.get("value")?.as_str()?.to_string(),
+            domain: value.get("domain")?.as_str()?.to_string(),
+            host_only: value.get("host_only")?.as_bool()?,
+            path: value.get("path")?.as_str()?.to_string(),
+            expires: value.get("expires").and_then(Json::as_u64),
+            secure: value.get("secure")?.as_bool()?
+        })
+    }
+
+    pub fn to_lua(&self, lua: &Lua) -> Result<LuaTable, LuaError> {
+        let cookie = lua.create_table_with_capacity(0, 6)?;
+
+        cookie.raw_set("name", self.name.as_str())?;
+        cookie.raw_set("value", self.value.as_str())?;
+        cookie.raw_set("domain", self.domain.as_str())?;
+        cookie.raw_set("path", self.path.as_str())?;
+        cookie.raw_set("expires", self.expires)?;
+        cookie.raw_set("secure", self.secure)?;
+
+        Ok(cookie)
+    }
+}
+
+/// Cookies storage persisted in a JSON file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieJar {
+    path: PathBuf,
+    cookies: Vec<Cookie>
+}
+
+impl CookieJar {
+    /// Open cookie jar file. Empty jar is returned if the file doesn't exist
+    /// or can't be read.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path: PathBuf = path.into();
+
+        let cookies = std::fs::read(&path).ok()
+            .and_then(|jar| serde_json::from_slice::<Json>(&jar).ok())
+            .and_then(|jar| {
+                jar.as_array().map(|cookies| {
+                    cookies.iter()
+                        .filter_map(Cookie::from_json)
+                        .filter(|cookie| !cookie.is_expired())
+                        .collect()
+                })
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            cookies
+        }
+    }
+
+    /// Save cookie jar to its file.
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() && !parent.is_dir() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let cookies = self.cookies.iter()
+            .filter(|cookie| !cookie.is_expired())
+            .map(Cookie::to_json)
+            .collect::<Vec<_>>();
+
+        std::fs::write(&self.path, serde_json::to_vec(&cookies)?)
+    }
+
+    /// Store cookies from the response headers. Return `true` if the jar was
+    /// updated.
+    pub fn store(&mut self, headers: &HeaderMap, url: &Url) -> bool {
+        let mut updated = false;
+
+        for header in headers.get_all(header::SET_COOKIE) {
+            let Some(cookie) = header.to_str().ok()
+                .and_then(|header| Cookie::parse(header, url))
+            else {
+                continue;
+            };
+
+            self.cookies.retain(|stored| {
+                stored.name != cookie.name
+                    || stored.domain != cookie.domain
+                    || stored.path != cookie.path
+            });
+
+            if !cookie.is_expired() {
+                self.cookies.push(cookie);
+            }
+
+            updated = true;
+        }
+
+        updated
+    }
+
+    /// Get `Cookie` header value for the given URL.
+    pub fn header(&self, url: &Url) -> Option<HeaderValue> {
+        let cookies = self.cookies(Some(url))
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>();
+
+        if cookies.is_empty() {
+            return None;
+        }
+
+        HeaderValue::from_str(&cookies.join("; ")).ok()
+    }
+
+    /// Iterate over stored cookies, optionally filtering them for the given
+    /// URL.
+    pub fn cookies(&self, url: Option<&Url>) -> impl Iterator<Item = &Cookie> {
+        self.cookies.iter()
+            .filter(move |cookie| {
+                match url {
+                    Some(url) => cookie.matches(url),
+                    None => !cookie.is_expired()
+                }
+            })
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+}
+
+fn get_cookie_jar(jars: &CookieJars, module_dir: &Path) -> Result<Arc<Mutex<CookieJar>>, LuaError> {
+    let mut jars = jars.lock()
+        .map_err(|err| {
+            LuaError::external(format!("failed to open cookie jar: {err}"))
+        })?;
+
+    let jar = jars.entry(module_dir.to_path_buf())
+        .or_insert_with(|| {
+            Arc::new(Mutex::new(CookieJar::open(module_dir.join(HTTP_COOKIES_FILE))))
+        });
+
+    Ok(jar.clone())
+}
+
+/// Requests sending policy.
+#[derive(Debug, Clone)]
+struct RequestPolicy {
+    /// Maximal amount of redirects to follow.
+    max_redirects: usize,
+
+    /// Amount of times to retry failed requests.
+    retries: u32,
+
+    /// Delay between retries.
+    retry_delay: Duration,
+
+    /// Cookie jar used to store cookies between requests.
+    cookies: Option<Arc<Mutex<CookieJar>>>
+}
+
+impl RequestPolicy {
+    fn from_options(
+        options: Option<&LuaTable>,
+        module_dir: &Path,
+        cookie_jars: &CookieJars
+    ) -> Result<Self, LuaError> {
+        let mut policy = Self {
+            max_redirects: HTTP_DEFAULT_MAX_REDIRECTS,
+            retries: 0,
+            retry_delay: HTTP_DEFAULT_RETRY_DELAY,
+            cookies: None
+        };
+
+        let Some(options) = options else {
+            return Ok(policy);
+        };
+
+        match options.get::<LuaValue>("redirect")? {
+            LuaValue::Boolean(false) => policy.max_redirects = 0,
+            LuaValue::Nil | LuaValue::Boolean(true) => (),
+
+            value => {
+                policy.max_redirects = options.get::<usize>("redirect")
+                    .map_err(|err| {
+                        LuaError::external(format!("invalid redirect policy {value:?}"))
+                            .context(err)
+                    })?;
+            }
+        }
+
+        match options.get::<LuaValue>("retry")? {
+            LuaValue::Nil => (),
+
+            LuaValue::Table(retry) => {
+                policy.retries = retry.get::<Option<u32>>("attempts")?.unwrap_or(1);
+
+                if let Some(delay) = retry.get::<Option<u64>>("delay")? {
+                    policy.retry_delay = Duration::from_millis(delay);
+                }
+            }
+
+            value => {
+                policy.retries = options.get::<u32>("retry")
+                    .map_err(|err| {
+                        LuaError::external(format!("invalid retry policy {value:?}"))
+                            .context(err)
+                    })?;
+            }
+        }
+
+        if options.get::<Option<bool>>("cookies")?.unwrap_or_default() {
+            policy.cookies = Some(get_cookie_jar(cookie_jars, module_dir)?);
+        }
+
+        Ok(policy)
+    }
+}
+
+/// Create request body streaming a file opened by `fs.open`.
+fn file_body(file_handles: &FileHandles, handle: i32) -> Result<Body, LuaError> {
+    let handles = file_handles.lock()
+        .map_err(|err| {
+            LuaError::external(format!("failed to read file handle: {err}"))
+        })?;
+
+    if !handles.contains_key(&handle) {
+        return Err(LuaError::external("invalid file handle"));
+    }
+
+    drop(handles);
+
+    let stream = futures_util::stream::unfold(Some(file_handles.clone()), move |file_handles| async move {
+        let file_handles = file_handles?;
+
+        let result = tasks::spawn_blocking(move || -> std::io::Result<(Vec<u8>, FileHandles)> {
+            let mut buf = vec![0; HTTP_UPLOAD_CHUNK_SIZE];
+
+            let len = {
+                let mut handles = file_handles.lock()
+                    .map_err(|err| std::io::Error::other(err.to_string()))?;
+
+                let Some(file) = handles.get_mut(&handle) else {
+                    return Err(std::io::Error::other("invalid file handle"));
+                };
+
+                file.read(&mut buf)?
+            };
+
+            buf.truncate(len);
+
+            Ok((buf, file_handles))
+        }).await;
+
+        match result {
+            Ok(Ok((buf, _))) if buf.is_empty() => None,
+            Ok(Ok((buf, file_handles))) => Some((Ok(buf), Some(file_handles))),
+            Ok(Err(err)) => Some((Err(err), None)),
+            Err(err) => Some((Err(std::io::Error::other(err)), None))
+        }
+    });
+
+    Ok(Body::wrap_stream(stream))
+}
+
+/// Create request body from a lua value. Numbers are treated as `fs.open`
+/// file handles and streamed, other values are converted into bytes.
+fn create_body(
+    lua: &Lua,
+    value: LuaValue,
+    file_handles: &FileHandles
+) -> Result<Body, LuaError> {
+    match value {
+        LuaValue::Integer(_) | LuaValue::Number(_) => {
+            file_body(file_handles, i32::from_lua(value, lua)?)
+        }
+
+        value => Ok(Body::from(Bytes::from_lua(value, lua)?.to_vec()))
+    }
+}
+
+fn create_multipart(
+    lua: &Lua,
+    fields: LuaTable,
+    file_handles: &FileHandles
+) -> Result<Form, LuaError> {
+    let mut form = Form::new();
+
+    for field in fields.sequence_values::<LuaTable>() {
+        let field = field?;
+
+        let name = field.get::<String>("name")?;
+
+        let mut part = match field.get::<LuaValue>("body")? {
+            LuaValue::Integer(_) | LuaValue::Number(_) => {
+                let handle = field.get::<i32>("body")?;
+
+                Part::stream(file_body(file_handles, handle)?)
+            }
+
+            value => Part::bytes(Bytes::from_lua(value, lua)?.to_vec())
+        };
+
+        if let Some(file_name) = field.get::<Option<String>>("file_name")? {
+            part = part.file_name(file_name);
+        }
+
+        if let Some(mime) = field.get::<Option<String>>("mime")? {
+            part = part.mime_str(&mime)
+                .map_err(|err| {
+                    LuaError::external(format!("invalid multipart field mime type: {err}"))
+                })?;
+        }
+
+        form = form.part(name, part);
+    }
+
+    Ok(form)
+}
 
 fn create_request(
+    lua: &Lua,
     client: &Client,
     url: impl AsRef<str>,
-    options: Option<LuaTable>
-) -> Result<RequestBuilder, LuaError> {
+    options: Option<&LuaTable>,
+    file_handles: &FileHandles
+) -> Result<Request, LuaError> {
     let mut method = String::from("get");
 
     // Change the request method if provided.
-    if let Some(options) = &options {
+    if let Some(options) = options {
         method = options.get::<Option<String>>("method")?
             .unwrap_or(String::from("get"));
     }
@@ -53,10 +552,10 @@ fn create_request(
         _ => return Err(LuaError::external("invalid request method"))
     };
 
-    let mut request = client.request(method, url.as_ref());
+    let mut request: RequestBuilder = client.request(method, url.as_ref());
 
     // Set request header and body if provided.
-    if let Some(options) = &options {
+    if let Some(options) = options {
         if let Some(headers) = options.get::<Option<LuaTable>>("headers")? {
             for pair in headers.pairs::<String, String>() {
                 let (key, value) = pair?;
@@ -65,175 +564,351 @@ fn create_request(
             }
         }
 
-        if let Some(body) = options.get::<Option<Bytes>>("body")? {
-            request = request.body(body.to_vec());
+        if let Some(fields) = options.get::<Option<LuaTable>>("multipart")? {
+            request = request.multipart(create_multipart(lua, fields, file_handles)?);
+        }
+
+        else {
+            let body = options.get::<LuaValue>("body")?;
+
+            if !body.is_nil() {
+                request = request.body(create_body(lua, body, file_handles)?);
+            }
+        }
+    }
+
+    request.build()
+        .map_err(|err| {
+            LuaError::external(format!("failed to build request: {err}"))
+        })
+}
+
+/// Get optional cancellation token from the request options.
+fn get_cancel_token(options: Option<&LuaTable>) -> Result<Option<CancellationToken>, LuaError> {
+    match options {
+        Some(options) => options.get::<Option<CancellationToken>>("cancel"),
+        None => Ok(None)
+    }
+}
+
+/// Send the request, retrying it on network errors and server-side failures.
+/// Requests with streamed bodies can't be retried.
+async fn send_with_retries(
+    client: &Client,
+    mut request: Request,
+    policy: &RequestPolicy
+) -> Result<Response, LuaError> {
+    let mut attempt = 0;
+
+    loop {
+        let retry_request = if attempt < policy.retries {
+            request.try_clone()
+        } else {
+            None
+        };
+
+        let result = client.execute(request).await;
+
+        let Some(retry_request) = retry_request else {
+            return result.map_err(|err| {
+                LuaError::external(format!("failed to perform request: {err}"))
+            });
+        };
+
+        match result {
+            Ok(response) if !response.status().is_server_error()
+                && response.status() != StatusCode::TOO_MANY_REQUESTS =>
+            {
+                return Ok(response);
+            }
+
+            Err(err) if err.is_builder() => {
+                return Err(LuaError::external(format!("failed to perform request: {err}")));
+            }
+
+            _ => ()
+        }
+
+        attempt += 1;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(url = retry_request.url().as_str(), attempt, "retrying failed request");
+
+        tasks::sleep(policy.retry_delay).await;
+
+        request = retry_request;
+    }
+}
+
+/// Send the request following the redirects and storing the cookies according
+/// to the given policy.
+async fn send_request(
+    client: &Client,
+    mut request: Request,
+    policy: &RequestPolicy
+) -> Result<Response, LuaError> {
+    let mut redirects = 0;
+
+    loop {
+        if let Some(jar) = &policy.cookies {
+            let jar = jar.lock()
+                .map_err(|err| {
+                    LuaError::external(format!("failed to read cookie jar: {err}"))
+                })?;
+
+            request.headers_mut().remove(header::COOKIE);
+
+            if let Some(cookies) = jar.header(request.url()) {
+                request.headers_mut().insert(header::COOKIE, cookies);
+            }
+        }
+
+        let url = request.url().clone();
+        let method = request.method().clone();
+        let headers = request.headers().clone();
+
+        // Needed to repeat the request body on 307 and 308 redirects.
+        let redirect_request = request.try_clone();
+
+        let response = send_with_retries(client, request, policy).await?;
+
+        if let Some(jar) = &policy.cookies {
+            let mut jar = jar.lock()
+                .map_err(|err| {
+                    LuaError::external(format!("failed to update cookie jar: {err}"))
+                })?;
+
+            if jar.store(response.headers(), &url) {
+                jar.save()?;
+            }
+        }
+
+        if redirects >= policy.max_redirects {
+            return Ok(response);
+        }
+
+        let location = response.headers()
+            .get(header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| url.join(location).ok());
+
+        let Some(location) = location else {
+            return Ok(response);
+        };
+
+        let mut next_request = match response.status() {
+            StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => {
+                let Some(mut next_request) = redirect_request else {
+                    return Ok(response);
+                };
+
+                *next_request.url_mut() = location;
+
+                next_request
+            }
+
+            StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER => {
+                let method = if method == Method::HEAD {
+                    Method::HEAD
+                } else {
+                    Method::GET
+                };
+
+                let mut next_request = Request::new(method, location);
+
+                *next_request.headers_mut() = headers;
+
+                next_request.headers_mut().remove(header::CONTENT_TYPE);
+                next_request.headers_mut().remove(header::CONTENT_LENGTH);
+                next_request.headers_mut().remove(header::CONTENT_ENCODING);
+                next_request.headers_mut().remove(header::TRANSFER_ENCODING);
+
+                next_request
+            }
+
+            _ => return Ok(response)
+        };
+
+        // Do not leak credentials to other hosts.
+        if next_request.url().host_str() != url.host_str()
+            || next_request.url().port_or_known_default() != url.port_or_known_default()
+        {
+            next_request.headers_mut().remove(header::AUTHORIZATION);
+            next_request.headers_mut().remove(header::PROXY_AUTHORIZATION);
+            next_request.headers_mut().remove(header::COOKIE);
         }
+
+        request = next_request;
+        redirects += 1;
+    }
+}
+
+fn create_headers_table(lua: &Lua, headers: HeaderMap) -> Result<LuaTable, LuaError> {
+    let headers_table = lua.create_table_with_capacity(0, headers.len())?;
+
+    for (key, value) in headers.iter() {
+        headers_table.raw_set(
+            key.to_string(),
+            lua.create_string(value.as_bytes())?
+        )?;
     }
 
-    Ok(request)
+    let headers_metatable = lua.create_table_with_capacity(0, 1)?;
+
+    headers_metatable.raw_set(
+        "__index",
+        lua.create_function::<_, (LuaTable, String), LuaValue>(
+            move |lua: &Lua, (_, key): (LuaTable, String)| {
+                headers.get(&key)
+                    .map(|value| {
+                        lua.create_string(value.as_bytes())
+                            .map(LuaValue::String)
+                    })
+                    .unwrap_or(Ok(LuaValue::Nil))
+            }
+        )?
+    )?;
+
+    headers_table.set_metatable(Some(headers_metatable))?;
+
+    Ok(headers_table)
 }
 
-#[derive(Debug)]
 pub struct HttpApi {
     lua: Lua,
 
-    http_fetch: LuaFunction,
-    http_open: LuaFunction,
+    http_fetch: LuaFunctionBuilder,
+    http_open: LuaFunctionBuilder,
     http_read: LuaFunction,
-    http_close: LuaFunction
+    http_close: LuaFunction,
+    http_cookies: LuaFunctionBuilder,
+    http_clear_cookies: LuaFunctionBuilder
 }
 
 impl HttpApi {
-    pub fn new(lua: Lua, client: Client) -> Result<Self, LuaError> {
+    pub fn new(
+        lua: Lua,
+        client: Client,
+        file_handles: FileHandles
+    ) -> Result<Self, LuaError> {
         let net_handles = Arc::new(Mutex::new(HashMap::new()));
+        let cookie_jars: CookieJars = Arc::new(Mutex::new(HashMap::new()));
 
         Ok(Self {
             http_fetch: {
                 let client = client.clone();
+                let file_handles = file_handles.clone();
+                let cookie_jars = cookie_jars.clone();
 
-                lua.create_function(move |lua: &Lua, (url, options): (String, Option<LuaTable>)| {
-                    let request = create_request(&client, url, options)?;
-
-                    let value = PromiseValue::from_future(async move {
-                        let response = request.send().await
-                            .map_err(|err| {
-                                LuaError::external(format!("failed to perform request: {err}"))
-                            })?;
-
-                        let status = response.status();
-                        let headers = response.headers().clone();
-
-                        let body = response.bytes().await
-                            .map_err(|err| {
-                                LuaError::external(format!("failed to fetch body: {err}"))
-                            })?
-                            .to_vec();
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let client = client.clone();
+                    let file_handles = file_handles.clone();
+                    let cookie_jars = cookie_jars.clone();
+                    let module_dir = module_context.module_dir.clone();
 
-                        Ok(Box::new(move |lua: &Lua| {
-                            let headers_table = lua.create_table_with_capacity(0, headers.len())?;
+                    lua.create_function(move |lua: &Lua, (url, options): (String, Option<LuaTable>)| {
+                        let token = get_cancel_token(options.as_ref())?;
+                        let policy = RequestPolicy::from_options(options.as_ref(), &module_dir, &cookie_jars)?;
+                        let request = create_request(lua, &client, url, options.as_ref(), &file_handles)?;
 
-                            for (key, value) in headers.iter() {
-                                headers_table.raw_set(
-                                    key.to_string(),
-                                    lua.create_string(value.as_bytes())?
-                                )?;
-                            }
+                        let client = client.clone();
 
-                            let headers_metatable = lua.create_table_with_capacity(0, 1)?;
+                        let value = PromiseValue::from_future(async move {
+                            let response = send_request(&client, request, &policy).await?;
 
-                            headers_metatable.raw_set(
-                                "__index",
-                                lua.create_function::<_, (LuaTable, String), LuaValue>(
-                                    move |lua: &Lua, (_, key): (LuaTable, String)| {
-                                        headers.get(&key)
-                                            .map(|value| {
-                                                lua.create_string(value.as_bytes())
-                                                    .map(LuaValue::String)
-                                            })
-                                            .unwrap_or(Ok(LuaValue::Nil))
-                                    }
-                                )?
-                            )?;
+                            let status = response.status();
+                            let headers = response.headers().clone();
 
-                            headers_table.set_metatable(Some(headers_metatable))?;
+                            let body = response.bytes().await
+                                .map_err(|err| {
+                                    LuaError::external(format!("failed to fetch body: {err}"))
+                                })?
+                                .to_vec();
 
-                            let body = Bytes::new(body.into_boxed_slice());
+                            Ok(Box::new(move |lua: &Lua| {
+                                let body = Bytes::new(body.into_boxed_slice());
 
-                            let result = lua.create_table_with_capacity(0, 4)?;
+                                let result = lua.create_table_with_capacity(0, 4)?;
 
-                            result.raw_set("status", status.as_u16())?;
-                            result.raw_set("is_ok", status.is_success())?;
-                            result.raw_set("headers", headers_table)?;
-                            result.raw_set("body", body)?;
+                                result.raw_set("status", status.as_u16())?;
+                                result.raw_set("is_ok", status.is_success())?;
+                                result.raw_set("headers", create_headers_table(lua, headers)?)?;
+                                result.raw_set("body", body)?;
 
-                            Ok(LuaValue::Table(result))
-                        }) as TaskOutput)
-                    });
+                                Ok(LuaValue::Table(result))
+                            }) as TaskOutput)
+                        });
 
-                    Promise::new(value)
-                        .into_lua(lua)
-                })?
+                        Promise::new(value)
+                            .with_token(token)
+                            .into_lua(lua)
+                    })
+                })
             },
 
             http_open: {
                 let client = client.clone();
+                let file_handles = file_handles.clone();
+                let cookie_jars = cookie_jars.clone();
                 let net_handles = net_handles.clone();
 
-                lua.create_function(move |lua: &Lua, (url, options): (String, Option<LuaTable>)| {
-                    let request = create_request(&client, url, options)?;
-
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let client = client.clone();
+                    let file_handles = file_handles.clone();
+                    let cookie_jars = cookie_jars.clone();
                     let net_handles = net_handles.clone();
+                    let module_dir = module_context.module_dir.clone();
 
-                    let value = PromiseValue::from_future(async move {
-                        let response = request.send().await
-                            .map_err(|err| {
-                                LuaError::external(format!("failed to perform request: {err}"))
-                            })?;
+                    lua.create_function(move |lua: &Lua, (url, options): (String, Option<LuaTable>)| {
+                        let token = get_cancel_token(options.as_ref())?;
+                        let policy = RequestPolicy::from_options(options.as_ref(), &module_dir, &cookie_jars)?;
+                        let request = create_request(lua, &client, url, options.as_ref(), &file_handles)?;
 
-                        let status = response.status();
-                        let headers = response.headers().clone();
-
-                        let mut handles = net_handles.lock()
-                            .map_err(|err| {
-                                LuaError::external(format!("failed to register handle: {err}"))
-                            })?;
+                        let client = client.clone();
+                        let net_handles = net_handles.clone();
 
-                        let mut handle = rand::random::<i32>();
+                        let value = PromiseValue::from_future(async move {
+                            let response = send_request(&client, request, &policy).await?;
 
-                        while handles.contains_key(&handle) {
-                            handle = rand::random::<i32>();
-                        }
+                            let status = response.status();
+                            let headers = response.headers().clone();
 
-                        handles.insert(handle, response);
+                            let mut handles = net_handles.lock()
+                                .map_err(|err| {
+                                    LuaError::external(format!("failed to register handle: {err}"))
+                                })?;
 
-                        Ok(Box::new(move |lua: &Lua| {
-                            let headers_table = lua.create_table_with_capacity(0, headers.len())?;
+                            let mut handle = rand::random::<i32>();
 
-                            for (key, value) in headers.iter() {
-                                headers_table.raw_set(
-                                    key.to_string(),
-                                    lua.create_string(value.as_bytes())?
-                                )?;
+                            while handles.contains_key(&handle) {
+                                handle = rand::random::<i32>();
                             }
 
-                            let headers_metatable = lua.create_table_with_capacity(0, 1)?;
+                            handles.insert(handle, response);
 
-                            headers_metatable.raw_set(
-                                "__index",
-                                lua.create_function::<_, (LuaTable, String), LuaValue>(
-                                    move |lua: &Lua, (_, key): (LuaTable, String)| {
-                                        headers.get(&key)
-                                            .map(|value| {
-                                                lua.create_string(value.as_bytes())
-                                                    .map(LuaValue::String)
-                                            })
-                                            .unwrap_or(Ok(LuaValue::Nil))
-                                    }
-                                )?
-                            )?;
+                            Ok(Box::new(move |lua: &Lua| {
+                                let result = lua.create_table_with_capacity(0, 4)?;
 
-                            headers_table.set_metatable(Some(headers_metatable))?;
+                                result.raw_set("status", status.as_u16())?;
+                                result.raw_set("is_ok", status.is_success())?;
+                                result.raw_set("headers", create_headers_table(lua, headers)?)?;
+                                result.raw_set("handle", handle)?;
 
-                            let result = lua.create_table_with_capacity(0, 4)?;
-
-                            result.raw_set("status", status.as_u16())?;
-                            result.raw_set("is_ok", status.is_success())?;
-                            result.raw_set("headers", headers_table)?;
-                            result.raw_set("handle", handle)?;
-
-                            Ok(LuaValue::Table(result))
-                        }) as TaskOutput)
-                    });
+                                Ok(LuaValue::Table(result))
+                            }) as TaskOutput)
+                        });
 
-                    Promise::new(value)
-                        .into_lua(lua)
-                })?
+                        Promise::new(value)
+                            .with_token(token)
+                            .into_lua(lua)
+                    })
+                })
             },
 
             http_read: {
                 let net_handles = net_handles.clone();
 
-                lua.create_function(move |lua: &Lua, handle: i32| {
+                lua.create_function(move |lua: &Lua, (handle, token): (i32, Option<CancellationToken>)| {
                     let net_handles = net_handles.clone();
 
                     let value = PromiseValue::from_blocking(move || {
@@ -265,6 +940,7 @@ impl HttpApi {
                     });
 
                     Promise::new(value)
+                        .with_token(token)
                         .into_lua(lua)
                 })?
             },
@@ -283,18 +959,75 @@ impl HttpApi {
                 })?
             },
 
+            http_cookies: {
+                let cookie_jars = cookie_jars.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let cookie_jars = cookie_jars.clone();
+                    let module_dir = module_context.module_dir.clone();
+
+                    lua.create_function(move |lua: &Lua, url: Option<String>| {
+                        let url = url.map(|url| Url::parse(&url))
+                            .transpose()
+                            .map_err(|err| {
+                                LuaError::external(format!("invalid url: {err}"))
+                            })?;
+
+                        let jar = get_cookie_jar(&cookie_jars, &module_dir)?;
+
+                        let jar = jar.lock()
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to read cookie jar: {err}"))
+                            })?;
+
+                        let cookies = lua.create_table()?;
+
+                        for cookie in jar.cookies(url.as_ref()) {
+                            cookies.raw_push(cookie.to_lua(lua)?)?;
+                        }
+
+                        Ok(cookies)
+                    })
+                })
+            },
+
+            http_clear_cookies: {
+                let cookie_jars = cookie_jars.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let cookie_jars = cookie_jars.clone();
+                    let module_dir = module_context.module_dir.clone();
+
+                    lua.create_function(move |_lua: &Lua, ()| {
+                        let jar = get_cookie_jar(&cookie_jars, &module_dir)?;
+
+                        let mut jar = jar.lock()
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to update cookie jar: {err}"))
+                            })?;
+
+                        jar.clear();
+                        jar.save()?;
+
+                        Ok(())
+                    })
+                })
+            },
+
             lua
         })
     }
 
     /// Create new lua table with API functions.
-    pub fn create_env(&self) -> Result<LuaTable, LuaError> {
-        let env = self.lua.create_table_with_capacity(0, 4)?;
+    pub fn create_env(&self, context: &ModuleContext) -> Result<LuaTable, LuaError> {
+        let env = self.lua.create_table_with_capacity(0, 6)?;
 
-        env.raw_set("fetch", &self.http_fetch)?;
-        env.raw_set("open", &self.http_open)?;
+        env.raw_set("fetch", (self.http_fetch)(&self.lua, context)?)?;
+        env.raw_set("open", (self.http_open)(&self.lua, context)?)?;
         env.raw_set("read", &self.http_read)?;
         env.raw_set("close", &self.http_close)?;
+        env.raw_set("cookies", (self.http_cookies)(&self.lua, context)?)?;
+        env.raw_set("clear_cookies", (self.http_clear_cookies)(&self.lua, context)?)?;
 
         Ok(env)
     }
@@ -304,11 +1037,26 @@ impl HttpApi {
 mod tests {
     use super::*;
 
+    fn get_api() -> Result<(HttpApi, LuaTable), LuaError> {
+        let lua = Lua::new();
+
+        let client = Client::builder()
+            .redirect(agl_core::export::network::reqwest::redirect::Policy::none())
+            .build()
+            .map_err(LuaError::external)?;
+
+        let api = HttpApi::new(lua, client, FileHandles::default())?;
+        let env = api.create_env(&ModuleContext::default())?;
+
+        Ok((api, env))
+    }
+
     #[test]
     fn fetch() -> Result<(), LuaError> {
-        let api = HttpApi::new(Lua::new(), Client::new())?;
+        let (_api, env) = get_api()?;
 
-        let promise = api.http_fetch.call::<LuaAnyUserData>(
+        let promise = env.call_function::<LuaAnyUserData>(
+            "fetch",
             "https://raw.githubusercontent.com/an-anime-team/anime-games-launcher/refs/heads/next/crates/agl-runtime/tests/simple_package/package.json"
         )?;
 
@@ -323,9 +1071,11 @@ mod tests {
 
     #[test]
     fn read() -> Result<(), LuaError> {
-        let api = HttpApi::new(Lua::new(), Client::new())?;
+        let (api, env) = get_api()?;
 
-        let promise = api.http_open.call::<LuaAnyUserData>(
+        // Redirects to the objects.githubusercontent.com.
+        let promise = env.call_function::<LuaAnyUserData>(
+            "open",
             "https://github.com/doitsujin/dxvk/releases/download/v2.4/dxvk-2.4.tar.gz"
         )?;
 
@@ -352,4 +1102,33 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn cookies() {
+        let url = Url::parse("https://sub.example.com/api/login").unwrap();
+
+        let cookie = Cookie::parse("session=abc; Domain=.example.com; Path=/; Max-Age=3600; Secure", &url).unwrap();
+
+        assert_eq!(cookie.name, "session");
+        assert_eq!(cookie.value, "abc");
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert!(cookie.secure);
+
+        assert!(cookie.matches(&Url::parse("https://example.com/").unwrap()));
+        assert!(cookie.matches(&Url::parse("https://other.example.com/path").unwrap()));
+        assert!(!cookie.matches(&Url::parse("http://example.com/").unwrap()));
+        assert!(!cookie.matches(&Url::parse("https://example.org/").unwrap()));
+
+        let cookie = Cookie::parse("token=123", &url).unwrap();
+
+        assert_eq!(cookie.domain, "sub.example.com");
+        assert_eq!(cookie.path, "/api");
+        assert!(cookie.matches(&Url::parse("https://sub.example.com/api/user").unwrap()));
+        assert!(!cookie.matches(&Url::parse("https://sub.example.com/apiv2").unwrap()));
+        assert!(!cookie.matches(&Url::parse("https://example.com/api").unwrap()));
+
+        assert!(Cookie::parse("evil=1; Domain=example.org", &url).is_none());
+        assert!(Cookie::parse("old=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT", &url).unwrap().is_expired());
+    }
 }
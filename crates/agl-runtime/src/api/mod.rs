@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::path::{Path, PathBuf};
+use std::path::{Path, PathBuf, Component};
 use std::sync::{Arc, RwLock};
 
 use mlua::prelude::*;
@@ -25,13 +25,17 @@ use agl_core::export::network::reqwest;
 use agl_core::tasks;
 
 pub mod bytes;
+pub mod formats;
 
 pub mod string_api;
+pub mod regex_api;
+pub mod version_api;
 pub mod path_api;
 pub mod task_api;
 pub mod system_api;
 pub mod filesystem_api;
 pub mod http_api;
+pub mod ws_api;
 pub mod downloader_api;
 pub mod archive_api;
 pub mod hash_api;
@@ -46,6 +50,9 @@ pub mod protobuf_api;
 #[cfg(feature = "torrent-api")]
 pub mod torrent_api;
 
+#[cfg(feature = "torrent-api")]
+pub mod torrent_file;
+
 #[cfg(feature = "portal-api")]
 pub mod portal_api;
 
@@ -56,22 +63,82 @@ pub mod process_api;
 
 use crate::module::ModuleScope;
 
-/// Normalize path by resolving symbolic links.
+/// Maximal amount of symbolic links resolved by the `normalize_path` function.
+const MAX_SYMLINKS_DEPTH: usize = 40;
+
+/// Normalize path by lexically resolving `.` and `..` components and symbolic
+/// links in all its parent components. If `resolve_symlinks` is set then the
+/// last path component is resolved as well.
 pub fn normalize_path(
-    mut path: PathBuf,
+    path: PathBuf,
     resolve_symlinks: bool
 ) -> std::io::Result<PathBuf> {
-    while resolve_symlinks && path.is_symlink() {
-        path = path.read_link()?;
+    let mut result = PathBuf::new();
+    let mut symlinks = 0;
+
+    // Remaining path components in reversed order.
+    let mut components = path.components()
+        .rev()
+        .map(|component| PathBuf::from(component.as_os_str()))
+        .collect::<Vec<_>>();
+
+    while let Some(component) = components.pop() {
+        match component.components().next() {
+            Some(Component::Prefix(_)) | Some(Component::RootDir) => {
+                result.push(component);
+            }
+
+            Some(Component::ParentDir) => {
+                result.pop();
+            }
+
+            Some(Component::Normal(_)) => {
+                result.push(component);
+
+                let is_last = components.is_empty();
+
+                if (resolve_symlinks || !is_last) && result.is_symlink() {
+                    symlinks += 1;
+
+                    if symlinks > MAX_SYMLINKS_DEPTH {
+                        return Err(std::io::Error::other("too many levels of symbolic links"));
+                    }
+
+                    let target = result.read_link()?;
+
+                    // Relative symlinks are resolved from their parent folder,
+                    // absolute ones replace the whole path.
+                    result.pop();
+
+                    components.extend({
+                        target.components()
+                            .rev()
+                            .map(|component| PathBuf::from(component.as_os_str()))
+                    });
+                }
+            }
+
+            Some(Component::CurDir) | None => ()
+        }
     }
 
-    Ok(path.components().collect())
+    Ok(result)
 }
 
+#[inline]
 fn path_is_parent_of(parent: &Path, child: &Path) -> bool {
-    parent.components()
-        .zip(child.components())
-        .all(|(p, c)| p == c)
+    child.starts_with(parent)
+}
+
+/// Check if path is stored in the allowed folder, resolving symlinks in the
+/// allowed folder's path.
+fn path_is_allowed(allowed: &Path, path: &Path) -> bool {
+    if path_is_parent_of(allowed, path) {
+        return true;
+    }
+
+    normalize_path(allowed.to_path_buf(), true)
+        .is_ok_and(|allowed| path_is_parent_of(&allowed, path))
 }
 
 /// Luau runtime API building context.
@@ -87,7 +154,9 @@ impl ApiContext {
     pub fn can_access_path(&self, path: &Path) -> bool {
         if let Ok(private_paths) = self.private_paths.read() {
             for private_path in private_paths.iter() {
-                if path_is_parent_of(private_path, path) {
+                // Parent folders of private paths are not accessible either
+                // so they can't be moved or removed as a whole.
+                if path_is_parent_of(private_path, path) || path_is_parent_of(path, private_path) {
                     return false;
                 }
             }
@@ -126,14 +195,20 @@ impl Default for ModuleContext {
 }
 
 impl ModuleContext {
-    /// Check if a path is allowed to be read by the current module.
+    /// Check if a path is allowed to be read by the current module. Symlinks
+    /// in the path's parent components are resolved so they can't be used to
+    /// escape the allowed folders.
     pub fn can_read_path(
         &self,
         path: &Path
     ) -> bool {
-        if path_is_parent_of(&self.temp_dir, path)
-            || path_is_parent_of(&self.module_dir, path)
-            || path_is_parent_of(&self.persistent_dir, path)
+        let Ok(path) = normalize_path(path.to_path_buf(), false) else {
+            return false;
+        };
+
+        if path_is_allowed(&self.temp_dir, &path)
+            || path_is_allowed(&self.module_dir, &path)
+            || path_is_allowed(&self.persistent_dir, &path)
         {
             return true;
         }
@@ -143,7 +218,7 @@ impl ModuleContext {
                 .chain(scope.sandbox_write_paths.iter());
 
             for allowed_path in rw_paths {
-                if path_is_parent_of(allowed_path, path) {
+                if path_is_allowed(allowed_path, &path) {
                     return true;
                 }
             }
@@ -152,21 +227,27 @@ impl ModuleContext {
         false
     }
 
-    /// Check if a path is allowed to be written by the current module.
+    /// Check if a path is allowed to be written by the current module. Symlinks
+    /// in the path's parent components are resolved so they can't be used to
+    /// escape the allowed folders.
     pub fn can_write_path(
         &self,
         path: &Path
     ) -> bool {
-        if path_is_parent_of(&self.temp_dir, path)
-            || path_is_parent_of(&self.module_dir, path)
-            || path_is_parent_of(&self.persistent_dir, path)
+        let Ok(path) = normalize_path(path.to_path_buf(), false) else {
+            return false;
+        };
+
+        if path_is_allowed(&self.temp_dir, &path)
+            || path_is_allowed(&self.module_dir, &path)
+            || path_is_allowed(&self.persistent_dir, &path)
         {
             return true;
         }
 
         if let Ok(scope) = self.scope.read() {
             for allowed_path in &scope.sandbox_write_paths {
-                if path_is_parent_of(allowed_path, path) {
+                if path_is_allowed(allowed_path, &path) {
                     return true;
                 }
             }
@@ -205,6 +286,15 @@ pub struct ApiOptions {
     /// Reqwest client used by the network API.
     pub reqwest_client: reqwest::Client,
 
+    /// Reqwest client used by the HTTP API. It must be built with disabled
+    /// redirects (`reqwest::redirect::Policy::none()`) because the API follows
+    /// them itself to apply per-request redirect policies and cookie jars.
+    pub http_client: reqwest::Client,
+
+    /// List of files downloaded by modules with the downloader API. Keep its
+    /// clone to display and abort these downloads.
+    pub downloader_tasks: downloader_api::DownloaderTasks,
+
     /// BitTorrent server instance. If `None` is provided then the torrent API
     /// will be disabled for all the modules.
     #[cfg(feature = "torrent-api")]
@@ -240,11 +330,14 @@ pub struct Api {
     r#await: LuaFunction,
 
     string_api: string_api::StringApi,
+    regex_api: regex_api::RegexApi,
+    version_api: version_api::VersionApi,
     path_api: path_api::PathApi,
     task_api: task_api::TaskApi,
     system_api: system_api::SystemApi,
     filesystem_api: filesystem_api::FilesystemApi,
     http_api: http_api::HttpApi,
+    ws_api: ws_api::WsApi,
     downloader_api: downloader_api::DownloaderApi,
     archive_api: archive_api::ArchiveApi,
     hash_api: hash_api::HashApi,
@@ -279,6 +372,13 @@ impl Api {
             private_paths.push(options.secrets_file.clone());
         }
 
+        let filesystem_api = filesystem_api::FilesystemApi::new(
+            options.lua.clone(),
+            api_context.clone()
+        )?;
+
+        let file_handles = filesystem_api.file_handles();
+
         Ok(Self {
             clone: options.lua.create_function(|lua, value: LuaValue| {
                 fn clone_value(lua: &Lua, value: LuaValue) -> Result<LuaValue, LuaError> {
@@ -376,24 +476,30 @@ impl Api {
             })?,
 
             string_api: string_api::StringApi::new(options.lua.clone())?,
+            regex_api: regex_api::RegexApi::new(options.lua.clone())?,
+            version_api: version_api::VersionApi::new(options.lua.clone())?,
             path_api: path_api::PathApi::new(options.lua.clone())?,
             task_api: task_api::TaskApi::new(options.lua.clone())?,
             system_api: system_api::SystemApi::new(options.lua.clone())?,
 
-            filesystem_api: filesystem_api::FilesystemApi::new(
+            filesystem_api,
+
+            ws_api: ws_api::WsApi::new(
                 options.lua.clone(),
-                api_context.clone()
+                options.http_client.clone()
             )?,
 
             http_api: http_api::HttpApi::new(
                 options.lua.clone(),
-                options.reqwest_client.clone()
+                options.http_client,
+                file_handles
             )?,
 
             downloader_api: downloader_api::DownloaderApi::new(
                 options.lua.clone(),
                 api_context.clone(),
-                options.reqwest_client.clone()
+                options.reqwest_client.clone(),
+                options.downloader_tasks
             )?,
 
             archive_api: archive_api::ArchiveApi::new(
@@ -517,6 +623,8 @@ impl Api {
         // String API.
         if scope.allow_string_api {
             env.raw_set("str", self.string_api.create_env()?)?;
+            env.raw_set("regex", self.regex_api.create_env()?)?;
+            env.raw_set("version", self.version_api.create_env()?)?;
         }
 
         // Path API.
@@ -541,7 +649,8 @@ impl Api {
 
         // HTTP API.
         if scope.allow_http_api {
-            env.raw_set("http", self.http_api.create_env()?)?;
+            env.raw_set("http", self.http_api.create_env(context)?)?;
+            env.raw_set("ws", self.ws_api.create_env()?)?;
         }
 
         // Downloader API.
@@ -601,3 +710,56 @@ impl Api {
         Ok(env)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_paths() -> std::io::Result<()> {
+        assert_eq!(normalize_path(PathBuf::from("/a/./b/../c"), true)?, PathBuf::from("/a/c"));
+        assert_eq!(normalize_path(PathBuf::from("/a/../../b"), true)?, PathBuf::from("/b"));
+
+        Ok(())
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn symlinks_escape() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join(".agl-runtime-symlinks-escape-test");
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+
+        let module_dir = path.join("module");
+        let outside_dir = path.join("outside");
+
+        std::fs::create_dir_all(&module_dir)?;
+        std::fs::create_dir_all(&outside_dir)?;
+
+        std::os::unix::fs::symlink("../outside", module_dir.join("link"))?;
+
+        let context = ModuleContext {
+            temp_dir: Arc::new(module_dir.clone()),
+            module_dir: Arc::new(module_dir.clone()),
+            persistent_dir: Arc::new(module_dir.clone()),
+            ..ModuleContext::default()
+        };
+
+        assert_eq!(normalize_path(module_dir.join("link/file"), false)?, outside_dir.join("file"));
+        assert_eq!(normalize_path(module_dir.join("link"), false)?, module_dir.join("link"));
+        assert_eq!(normalize_path(module_dir.join("link"), true)?, outside_dir);
+
+        assert!(context.can_write_path(&module_dir.join("file")));
+        assert!(context.can_write_path(&module_dir.join("link")));
+
+        assert!(!context.can_read_path(&module_dir.join("link/file")));
+        assert!(!context.can_write_path(&module_dir.join("../outside/file")));
+        assert!(!context.can_write_path(&path));
+
+        std::fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+}
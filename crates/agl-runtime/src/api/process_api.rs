@@ -18,17 +18,232 @@
 
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::fs::File;
 use std::io::{Read, Write};
-use std::process::{Command, Stdio};
+use std::process::{Command, Stdio, Child, Output};
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
 
 use mlua::prelude::*;
 
+#[cfg(unix)]
+use nix::pty::Winsize;
+
+#[cfg(unix)]
+use nix::sys::signal::{self, Signal};
+
+#[cfg(unix)]
+use nix::unistd::Pid;
+
 use super::bytes::Bytes;
-use super::task_api::{Promise, PromiseValue, TaskOutput};
+use super::task_api::{Promise, PromiseValue, TaskOutput, CancellationToken};
 use super::*;
 
 const PROCESS_READ_CHUNK_SIZE: usize = 4096; // 4 KiB stdout/stderr reads
 
+const PROCESS_CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+#[cfg(unix)]
+const PROCESS_PTY_DEFAULT_COLS: u16 = 80;
+
+#[cfg(unix)]
+const PROCESS_PTY_DEFAULT_ROWS: u16 = 24;
+
+/// Process spawned by the `process.open` function.
+struct ProcessHandle {
+    child: Child,
+
+    /// Master side of the process's pseudo-terminal. Both stdout and stderr
+    /// of the process are read from it.
+    pty: Option<File>,
+
+    /// Whether the process is a leader of its own process group.
+    group: bool
+}
+
+impl ProcessHandle {
+    /// Send signal to the process or to its whole process group.
+    #[cfg(unix)]
+    fn signal(&self, signal: Signal, group: bool) -> Result<(), LuaError> {
+        let pid = Pid::from_raw(self.child.id() as i32);
+
+        let result = if group {
+            if !self.group {
+                return Err(LuaError::external("process doesn't have its own process group"));
+            }
+
+            signal::killpg(pid, signal)
+        } else {
+            signal::kill(pid, signal)
+        };
+
+        result.map_err(|err| {
+            LuaError::external(format!("failed to send signal to the process: {err}"))
+        })
+    }
+
+    /// Kill the process with its process group.
+    fn kill(&mut self) -> Result<(), LuaError> {
+        #[cfg(unix)]
+        if self.group {
+            return self.signal(Signal::SIGKILL, true);
+        }
+
+        Ok(self.child.kill()?)
+    }
+}
+
+/// Parse signal name like `"term"` or `"SIGTERM"`.
+#[cfg(unix)]
+fn parse_signal(name: &str) -> Result<Signal, LuaError> {
+    let name = name.to_ascii_lowercase();
+
+    match name.strip_prefix("sig").unwrap_or(&name) {
+        "term" => Ok(Signal::SIGTERM),
+        "int"  => Ok(Signal::SIGINT),
+        "kill" => Ok(Signal::SIGKILL),
+        "hup"  => Ok(Signal::SIGHUP),
+        "quit" => Ok(Signal::SIGQUIT),
+        "stop" => Ok(Signal::SIGSTOP),
+        "cont" => Ok(Signal::SIGCONT),
+        "tstp" => Ok(Signal::SIGTSTP),
+        "usr1" => Ok(Signal::SIGUSR1),
+        "usr2" => Ok(Signal::SIGUSR2),
+
+        _ => Err(LuaError::external(format!("unsupported signal: {name}")))
+    }
+}
+
+/// Read pseudo-terminal size from the `process.open` options.
+#[cfg(unix)]
+fn parse_pty_size(value: LuaValue) -> Result<Option<Winsize>, LuaError> {
+    let mut size = Winsize {
+        ws_row: PROCESS_PTY_DEFAULT_ROWS,
+        ws_col: PROCESS_PTY_DEFAULT_COLS,
+        ws_xpixel: 0,
+        ws_ypixel: 0
+    };
+
+    match value {
+        LuaValue::Nil | LuaValue::Boolean(false) => return Ok(None),
+        LuaValue::Boolean(true) => (),
+
+        LuaValue::Table(options) => {
+            if let Some(cols) = options.get::<Option<u16>>("cols")? {
+                size.ws_col = cols;
+            }
+
+            if let Some(rows) = options.get::<Option<u16>>("rows")? {
+                size.ws_row = rows;
+            }
+        }
+
+        _ => return Err(LuaError::external("invalid pty option format"))
+    }
+
+    Ok(Some(size))
+}
+
+/// Open pseudo-terminal of the given size and make it the controlling
+/// terminal of the command's process spawned in a new session. Return
+/// master side of the pseudo-terminal.
+#[cfg(unix)]
+fn attach_pty(command: &mut Command, size: Winsize) -> Result<File, LuaError> {
+    let pty = nix::pty::openpty(&size, None)
+        .map_err(|err| {
+            LuaError::external(format!("failed to open pseudo-terminal: {err}"))
+        })?;
+
+    command
+        .stdin(Stdio::from(pty.slave.try_clone()?))
+        .stdout(Stdio::from(pty.slave.try_clone()?))
+        .stderr(Stdio::from(pty.slave));
+
+    // SAFETY: only async-signal-safe functions are called after the fork.
+    unsafe {
+        command.pre_exec(|| {
+            nix::unistd::setsid()?;
+
+            if nix::libc::ioctl(0, nix::libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+
+            Ok(())
+        });
+    }
+
+    Ok(File::from(pty.master))
+}
+
+/// Check whether reading the pseudo-terminal failed because the process
+/// has closed it.
+#[cfg(unix)]
+fn is_pty_closed(err: &std::io::Error) -> bool {
+    err.raw_os_error() == Some(nix::libc::EIO)
+}
+
+#[cfg(not(unix))]
+fn is_pty_closed(_err: &std::io::Error) -> bool {
+    false
+}
+
+/// Wait for the process output, killing it if the token is cancelled.
+fn wait_with_token(
+    mut child: Child,
+    token: &CancellationToken
+) -> Result<Output, LuaError> {
+    fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<std::io::Result<Vec<u8>>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut buf)?;
+            }
+
+            Ok(buf)
+        })
+    }
+
+    drop(child.stdin.take());
+
+    // Read outputs in separate threads so the process is not blocked when
+    // its pipes are full.
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if token.is_cancelled() {
+            child.kill()?;
+            child.wait()?;
+
+            return Err(LuaError::external("process was cancelled"));
+        }
+
+        std::thread::sleep(PROCESS_CANCEL_CHECK_INTERVAL);
+    };
+
+    let stdout = stdout.join()
+        .map_err(|_| LuaError::external("failed to read process stdout"))??;
+
+    let stderr = stderr.join()
+        .map_err(|_| LuaError::external("failed to read process stderr"))??;
+
+    Ok(Output {
+        status,
+        stdout,
+        stderr
+    })
+}
+
 pub struct ProcessApi {
     lua: Lua,
 
@@ -39,7 +254,9 @@ pub struct ProcessApi {
     process_stderr: LuaFunction,
     process_kill: LuaFunction,
     process_wait: LuaFunction,
-    process_finished: LuaFunction
+    process_finished: LuaFunction,
+    process_signal: LuaFunction,
+    process_resize: LuaFunction
 }
 
 impl ProcessApi {
@@ -50,7 +267,7 @@ impl ProcessApi {
             process_exec: Box::new(|lua: &Lua, module_context: &ModuleContext| {
                 let module_dir = module_context.module_dir.to_path_buf();
 
-                lua.create_function(move |lua: &Lua, (binary, args, env): (String, Option<LuaTable>, Option<LuaTable>)| {
+                lua.create_function(move |lua: &Lua, (binary, args, env, token): (String, Option<LuaTable>, Option<LuaTable>, Option<CancellationToken>)| {
                     let module_dir = module_dir.clone();
 
                     let args = args
@@ -84,6 +301,8 @@ impl ProcessApi {
                                 .context(err)
                         })?;
 
+                    let promise_token = token.clone();
+
                     let value = PromiseValue::from_blocking(move || {
                         let mut command = Command::new(binary);
 
@@ -112,7 +331,10 @@ impl ProcessApi {
                         tracing::debug!(?command, "running command");
 
                         // Execute the command.
-                        let output = command.output()?;
+                        let output = match &token {
+                            Some(token) => wait_with_token(command.spawn()?, token)?,
+                            None => command.output()?
+                        };
 
                         Ok(Box::new(move |lua: &Lua| {
                             // Prepare the output.
@@ -131,6 +353,7 @@ impl ProcessApi {
                     });
 
                     Promise::new(value)
+                        .with_token(promise_token)
                         .into_lua(lua)
                 })
             }),
@@ -142,7 +365,7 @@ impl ProcessApi {
                     let module_dir = module_context.module_dir.to_path_buf();
                     let process_handles = process_handles.clone();
 
-                    lua.create_function(move |_lua: &Lua, (binary, args, env): (String, Option<LuaTable>, Option<LuaTable>)| {
+                    lua.create_function(move |_lua: &Lua, (binary, args, env, options): (String, Option<LuaTable>, Option<LuaTable>, Option<LuaTable>)| {
                         let mut command = Command::new(binary);
 
                         let mut command = command
@@ -175,6 +398,46 @@ impl ProcessApi {
                             }
                         }
 
+                        #[cfg(unix)]
+                        let (pty, group) = {
+                            let mut pty_size = None;
+                            let mut group = false;
+
+                            if let Some(options) = &options {
+                                pty_size = parse_pty_size(options.get::<LuaValue>("pty")?)?;
+                                group = options.get::<bool>("process_group").unwrap_or_default();
+                            }
+
+                            // Spawn the process in a new session with the
+                            // pseudo-terminal as its controlling terminal.
+                            match pty_size {
+                                Some(size) => (Some(attach_pty(command, size)?), true),
+
+                                None => {
+                                    if group {
+                                        command = command.process_group(0);
+                                    }
+
+                                    (None, group)
+                                }
+                            }
+                        };
+
+                        #[cfg(not(unix))]
+                        let (pty, group) = {
+                            if let Some(options) = &options {
+                                if options.get::<Option<LuaValue>>("pty")?.is_some_and(|pty| pty.as_boolean() != Some(false)) {
+                                    return Err(LuaError::runtime("pseudo-terminals are not supported on this platform"));
+                                }
+
+                                if options.get::<bool>("process_group").unwrap_or_default() {
+                                    return Err(LuaError::runtime("process groups are not supported on this platform"));
+                                }
+                            }
+
+                            (None, false)
+                        };
+
                         // Start the process and store it.
                         let mut handles = process_handles.lock()
                             .map_err(|err| LuaError::external(format!("failed to register handle: {err}")))?;
@@ -188,7 +451,11 @@ impl ProcessApi {
                         #[cfg(feature = "tracing")]
                         tracing::debug!(?command, "spawned process");
 
-                        handles.insert(handle, command.spawn()?);
+                        handles.insert(handle, ProcessHandle {
+                            child: command.spawn()?,
+                            pty,
+                            group
+                        });
 
                         Ok(handle)
                     })
@@ -207,7 +474,11 @@ impl ProcessApi {
                     };
 
                     // Try to write data to the process's stdin.
-                    if let Some(stdin) = &mut process.stdin {
+                    if let Some(pty) = &mut process.pty {
+                        pty.write_all(&data)?;
+                    }
+
+                    else if let Some(stdin) = &mut process.child.stdin {
                         stdin.write_all(&data)?;
                     }
 
@@ -226,8 +497,28 @@ impl ProcessApi {
                         return Err(LuaError::external("invalid process handle"));
                     };
 
+                    // Read the process's pseudo-terminal chunk.
+                    if let Some(pty) = &mut process.pty {
+                        let mut buf = [0; PROCESS_READ_CHUNK_SIZE];
+
+                        // Reading the pseudo-terminal fails when the process
+                        // is closed.
+                        let len = match pty.read(&mut buf) {
+                            Ok(len) => len,
+                            Err(err) if is_pty_closed(&err) => 0,
+                            Err(err) => return Err(err.into())
+                        };
+
+                        if len == 0 {
+                            return Ok(LuaValue::Nil);
+                        }
+
+                        return Bytes::from(buf[..len].to_vec())
+                            .into_lua(lua);
+                    }
+
                     // Read the process's stdout chunk.
-                    if let Some(stdout) = &mut process.stdout {
+                    if let Some(stdout) = &mut process.child.stdout {
                         let mut buf = [0; PROCESS_READ_CHUNK_SIZE];
 
                         let len = stdout.read(&mut buf)?;
@@ -256,7 +547,7 @@ impl ProcessApi {
                     };
 
                     // Read the process's stderr chunk.
-                    if let Some(stderr) = &mut process.stderr {
+                    if let Some(stderr) = &mut process.child.stderr {
                         let mut buf = [0; PROCESS_READ_CHUNK_SIZE];
 
                         let len = stderr.read(&mut buf)?;
@@ -284,8 +575,10 @@ impl ProcessApi {
                         return Err(LuaError::external("invalid process handle"));
                     };
 
-                    // Kill the process and remove its handle.
+                    // Kill the process with its process group and remove
+                    // its handle.
                     process.kill()?;
+
                     handles.remove(&handle);
 
                     Ok(())
@@ -304,7 +597,7 @@ impl ProcessApi {
                     };
 
                     // Wait until the process has finished.
-                    let output = process.wait_with_output()?;
+                    let output = process.child.wait_with_output()?;
 
                     // Prepare lua result.
                     let result = lua.create_table_with_capacity(0, 4)?;
@@ -332,7 +625,80 @@ impl ProcessApi {
                         return Err(LuaError::external("invalid process handle"));
                     };
 
-                    Ok(process.try_wait()?.is_some())
+                    Ok(process.child.try_wait()?.is_some())
+                })?
+            },
+
+            process_signal: {
+                let process_handles = process_handles.clone();
+
+                lua.create_function(move |_lua: &Lua, (handle, signal, group): (i32, LuaString, Option<bool>)| {
+                    let handles = process_handles.lock()
+                        .map_err(|err| LuaError::external(format!("failed to read handle: {err}")))?;
+
+                    let Some(process) = handles.get(&handle) else {
+                        return Err(LuaError::external("invalid process handle"));
+                    };
+
+                    #[cfg(unix)]
+                    {
+                        let signal = parse_signal(&signal.to_string_lossy())?;
+
+                        process.signal(signal, group.unwrap_or_default())
+                    }
+
+                    #[cfg(not(unix))]
+                    {
+                        let _ = (process, signal, group);
+
+                        Err(LuaError::runtime("signals are not supported on this platform"))
+                    }
+                })?
+            },
+
+            process_resize: {
+                let process_handles = process_handles.clone();
+
+                lua.create_function(move |_lua: &Lua, (handle, cols, rows): (i32, u16, u16)| {
+                    let handles = process_handles.lock()
+                        .map_err(|err| LuaError::external(format!("failed to read handle: {err}")))?;
+
+                    let Some(process) = handles.get(&handle) else {
+                        return Err(LuaError::external("invalid process handle"));
+                    };
+
+                    let Some(pty) = &process.pty else {
+                        return Err(LuaError::external("process doesn't have a pseudo-terminal"));
+                    };
+
+                    #[cfg(not(unix))]
+                    {
+                        let _ = (pty, cols, rows);
+
+                        Err(LuaError::runtime("pseudo-terminals are not supported on this platform"))
+                    }
+
+                    #[cfg(unix)]
+                    {
+                        let size = Winsize {
+                            ws_row: rows,
+                            ws_col: cols,
+                            ws_xpixel: 0,
+                            ws_ypixel: 0
+                        };
+
+                        // SAFETY: the file descriptor is valid while the handle
+                        // is stored and the size struct outlives the call.
+                        let result = unsafe {
+                            nix::libc::ioctl(pty.as_raw_fd(), nix::libc::TIOCSWINSZ as _, &size as *const Winsize)
+                        };
+
+                        if result == -1 {
+                            return Err(std::io::Error::last_os_error().into());
+                        }
+
+                        Ok(())
+                    }
                 })?
             },
 
@@ -345,7 +711,7 @@ impl ProcessApi {
         &self,
         context: &ModuleContext
     ) -> Result<LuaTable, LuaError> {
-        let env = self.lua.create_table_with_capacity(0, 8)?;
+        let env = self.lua.create_table_with_capacity(0, 10)?;
 
         env.raw_set("exec", (self.process_exec)(&self.lua, context)?)?;
         env.raw_set("open", (self.process_open)(&self.lua, context)?)?;
@@ -355,78 +721,151 @@ impl ProcessApi {
         env.raw_set("wait", &self.process_wait)?;
         env.raw_set("kill", &self.process_kill)?;
         env.raw_set("finished", &self.process_finished)?;
+        env.raw_set("signal", &self.process_signal)?;
+        env.raw_set("resize", &self.process_resize)?;
 
         Ok(env)
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-
-//     #[test]
-//     fn process_exec() -> anyhow::Result<()> {
-//         let api = ProcessApi::new(Lua::new())?;
-
-//         let env = api.create_env(&Context {
-//             resource_hash: Hash::rand(),
-//             temp_folder: std::env::temp_dir(),
-//             module_folder: std::env::temp_dir(),
-//             persistent_folder: std::env::temp_dir(),
-//             input_resources: vec![],
-//             ext_process_api: false,
-//             ext_allowed_paths: vec![],
-//             local_validator: LocalValidator::open(std::env::temp_dir().join("local_validator.json"))?
-//         })?;
-
-//         let output = env.call_function::<LuaTable>("exec", (
-//             "bash", ["-c", "echo $TEST"],
-//             HashMap::from([
-//                 ("TEST", "Hello, World!")
-//             ])
-//         ))?;
-
-//         assert_eq!(output.get::<i32>("status")?, 0);
-//         assert!(output.get::<bool>("is_ok")?);
-//         assert_eq!(output.get::<Vec<u8>>("stdout")?, b"Hello, World!\n");
-
-//         Ok(())
-//     }
-
-//     #[test]
-//     fn process_open() -> anyhow::Result<()> {
-//         let api = ProcessAPI::new(Lua::new())?;
-
-//         let env = api.create_env(&Context {
-//             resource_hash: Hash::rand(),
-//             temp_folder: std::env::temp_dir(),
-//             module_folder: std::env::temp_dir(),
-//             persistent_folder: std::env::temp_dir(),
-//             input_resources: vec![],
-//             ext_process_api: false,
-//             ext_allowed_paths: vec![],
-//             local_validator: LocalValidator::open(std::env::temp_dir().join("local_validator.json"))?
-//         })?;
-
-//         let handle = env.call_function::<i32>("open", (
-//             "bash", ["-c", "echo $TEST"],
-//             HashMap::from([
-//                 ("TEST", "Hello, World!")
-//             ])
-//         ))?;
-
-//         while !api.process_finished.call::<bool>(handle)? {
-//             std::thread::sleep(std::time::Duration::from_millis(100));
-//         }
-
-//         assert_eq!(api.process_stdout.call::<Vec<u8>>(handle)?, b"Hello, World!\n");
-
-//         let output = api.process_wait.call::<LuaTable>(handle)?;
-
-//         assert_eq!(output.get::<i32>("status")?, 0);
-//         assert!(output.get::<bool>("is_ok")?);
-//         assert!(output.get::<Vec<u8>>("stdout")?.is_empty());
-
-//         Ok(())
-//     }
-// }
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Read the process's output until it's closed.
+    fn read_output(env: &LuaTable, handle: i32) -> Result<String, LuaError> {
+        let mut output = Vec::new();
+
+        while let Some(chunk) = env.call_function::<Option<Bytes>>("stdout", handle)? {
+            output.extend_from_slice(&chunk);
+        }
+
+        Ok(String::from_utf8_lossy(&output).to_string())
+    }
+
+    #[test]
+    fn process_pty() -> Result<(), LuaError> {
+        let api = ProcessApi::new(Lua::new())?;
+        let env = api.create_env(&ModuleContext::default())?;
+
+        let options = api.lua.create_table()?;
+        let pty = api.lua.create_table()?;
+
+        pty.raw_set("cols", 100)?;
+        pty.raw_set("rows", 30)?;
+
+        options.raw_set("pty", pty)?;
+
+        let handle = env.call_function::<i32>("open", (
+            "sh", ["-c", "echo $TEST; stty size; test -t 0 && echo tty"],
+            HashMap::from([("TEST", "Hello, World!")]),
+            options
+        ))?;
+
+        let output = read_output(&env, handle)?;
+
+        assert!(output.contains("Hello, World!"));
+        assert!(output.contains("30 100"));
+        assert!(output.contains("tty"));
+
+        let output = env.call_function::<LuaTable>("wait", handle)?;
+
+        assert!(output.get::<bool>("is_ok")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn process_pty_resize() -> Result<(), LuaError> {
+        let api = ProcessApi::new(Lua::new())?;
+        let env = api.create_env(&ModuleContext::default())?;
+
+        let options = api.lua.create_table()?;
+
+        options.raw_set("pty", true)?;
+
+        let handle = env.call_function::<i32>("open", (
+            "sh", ["-c", "stty size; read _; stty size"],
+            LuaValue::Nil,
+            options
+        ))?;
+
+        let output = env.call_function::<Bytes>("stdout", handle)?;
+
+        assert!(output.as_string_lossy().contains("24 80"));
+
+        env.call_function::<()>("resize", (handle, 120, 40))?;
+        env.call_function::<i32>("stdin", (handle, "\n"))?;
+
+        let output = read_output(&env, handle)?;
+
+        assert!(output.contains("40 120"));
+
+        // Processes without pseudo-terminal can't be resized.
+        let handle = env.call_function::<i32>("open", ("true", LuaValue::Nil))?;
+
+        assert!(env.call_function::<()>("resize", (handle, 120, 40)).is_err());
+
+        env.call_function::<LuaTable>("wait", handle)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn process_group_kill() -> Result<(), LuaError> {
+        let api = ProcessApi::new(Lua::new())?;
+        let env = api.create_env(&ModuleContext::default())?;
+
+        let options = api.lua.create_table()?;
+
+        options.raw_set("process_group", true)?;
+
+        let handle = env.call_function::<i32>("open", (
+            "sh", ["-c", "sleep 30 & echo $!; wait"],
+            LuaValue::Nil,
+            options
+        ))?;
+
+        let child = env.call_function::<Bytes>("stdout", handle)?;
+
+        let child = child.as_string_lossy()
+            .trim()
+            .parse::<i32>()
+            .map_err(LuaError::external)?;
+
+        let child = Pid::from_raw(child);
+
+        assert!(signal::kill(child, None).is_ok());
+
+        env.call_function::<()>("kill", handle)?;
+
+        // Wait until the killed child process is reaped.
+        let mut killed = false;
+
+        for _ in 0..50 {
+            if signal::kill(child, None).is_err() {
+                killed = true;
+
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        assert!(killed);
+
+        // Process group signals can't be sent to processes without
+        // their own process group.
+        let handle = env.call_function::<i32>("open", ("sleep", ["30"]))?;
+
+        assert!(env.call_function::<()>("signal", (handle, "term", true)).is_err());
+
+        env.call_function::<()>("signal", (handle, "SIGTERM"))?;
+
+        let output = env.call_function::<LuaTable>("wait", handle)?;
+
+        assert!(!output.get::<bool>("is_ok")?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,539 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-runtime
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use mlua::prelude::*;
+
+use regex::bytes::{Regex, RegexBuilder, Captures};
+use globset::{GlobBuilder, GlobSetBuilder};
+
+/// Maximal length of a regex or glob pattern.
+pub const MAX_PATTERN_LEN: usize = 4096;
+
+/// Maximal size of a compiled regex. Complex patterns (like `\w{1000}`) can
+/// expand into huge automatons so their compilation is rejected.
+pub const MAX_REGEX_SIZE: usize = 1024 * 1024; // 1 MiB
+
+/// Maximal length of a text processed by the regex functions. Matching time is
+/// linear to the text length so it's limited by this value as well.
+pub const MAX_TEXT_LEN: usize = 16 * 1024 * 1024; // 16 MiB
+
+type RegexHandles = Arc<Mutex<HashMap<i32, Regex>>>;
+
+/// Build regex from the lua pattern string and options table.
+fn build_regex(pattern: &[u8], options: Option<&LuaTable>) -> Result<Regex, LuaError> {
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(LuaError::external(format!("regex pattern is longer than {MAX_PATTERN_LEN} bytes")));
+    }
+
+    let pattern = std::str::from_utf8(pattern)
+        .map_err(|err| LuaError::external(format!("regex pattern must be a utf-8 string: {err}")))?;
+
+    let mut builder = RegexBuilder::new(pattern);
+
+    builder.size_limit(MAX_REGEX_SIZE)
+        .dfa_size_limit(MAX_REGEX_SIZE);
+
+    if let Some(options) = options {
+        if let Some(value) = options.get::<Option<bool>>("case_insensitive")? {
+            builder.case_insensitive(value);
+        }
+
+        if let Some(value) = options.get::<Option<bool>>("multi_line")? {
+            builder.multi_line(value);
+        }
+
+        if let Some(value) = options.get::<Option<bool>>("dot_matches_new_line")? {
+            builder.dot_matches_new_line(value);
+        }
+
+        if let Some(value) = options.get::<Option<bool>>("ignore_whitespace")? {
+            builder.ignore_whitespace(value);
+        }
+
+        if let Some(value) = options.get::<Option<bool>>("unicode")? {
+            builder.unicode(value);
+        }
+    }
+
+    builder.build()
+        .map_err(|err| LuaError::external(format!("invalid regex pattern: {err}")))
+}
+
+/// Get regex from its handle or compile a new one from the pattern string.
+fn get_regex(handles: &RegexHandles, regex: LuaValue) -> Result<Regex, LuaError> {
+    match regex {
+        LuaValue::String(pattern) => build_regex(&pattern.as_bytes(), None),
+
+        LuaValue::Integer(handle) => {
+            let handles = handles.lock()
+                .map_err(|err| LuaError::external(format!("failed to read handle: {err}")))?;
+
+            handles.get(&(handle as i32))
+                .cloned()
+                .ok_or_else(|| LuaError::external("invalid regex handle"))
+        }
+
+        LuaValue::Number(handle) => get_regex(handles, LuaValue::Integer(handle as i64)),
+
+        _ => Err(LuaError::external("regex handle or pattern string expected"))
+    }
+}
+
+fn check_text(text: &LuaString) -> Result<(), LuaError> {
+    if text.as_bytes().len() > MAX_TEXT_LEN {
+        return Err(LuaError::external(format!("text is longer than {MAX_TEXT_LEN} bytes")));
+    }
+
+    Ok(())
+}
+
+/// Convert 1-based lua string index into a byte offset.
+fn text_offset(text: &[u8], init: Option<i64>) -> usize {
+    match init {
+        Some(init) if init > 0 => (init as usize - 1).min(text.len()),
+        Some(init) if init < 0 => text.len().saturating_sub(init.unsigned_abs() as usize),
+
+        _ => 0
+    }
+}
+
+fn match_to_lua(lua: &Lua, found: regex::bytes::Match) -> Result<LuaTable, LuaError> {
+    let table = lua.create_table_with_capacity(0, 3)?;
+
+    table.raw_set("start", found.start() + 1)?;
+    table.raw_set("finish", found.end())?;
+    table.raw_set("text", lua.create_string(found.as_bytes())?)?;
+
+    Ok(table)
+}
+
+fn captures_to_lua(lua: &Lua, regex: &Regex, captures: &Captures) -> Result<LuaTable, LuaError> {
+    let table = lua.create_table_with_capacity(captures.len(), 0)?;
+
+    for (i, group) in captures.iter().enumerate() {
+        if let Some(group) = group {
+            table.raw_set(i, lua.create_string(group.as_bytes())?)?;
+        }
+    }
+
+    for name in regex.capture_names().flatten() {
+        if let Some(group) = captures.name(name) {
+            table.raw_set(name, lua.create_string(group.as_bytes())?)?;
+        }
+    }
+
+    Ok(table)
+}
+
+pub struct RegexApi {
+    lua: Lua,
+
+    regex_new: LuaFunction,
+    regex_free: LuaFunction,
+    regex_escape: LuaFunction,
+    regex_is_match: LuaFunction,
+    regex_find: LuaFunction,
+    regex_find_all: LuaFunction,
+    regex_captures: LuaFunction,
+    regex_captures_all: LuaFunction,
+    regex_replace: LuaFunction,
+    regex_split: LuaFunction,
+    regex_glob: LuaFunction
+}
+
+impl RegexApi {
+    pub fn new(lua: Lua) -> Result<Self, LuaError> {
+        let regex_handles: RegexHandles = Arc::new(Mutex::new(HashMap::new()));
+
+        Ok(Self {
+            regex_new: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |_lua: &Lua, (pattern, options): (LuaString, Option<LuaTable>)| {
+                    let regex = build_regex(&pattern.as_bytes(), options.as_ref())?;
+
+                    let mut handles = regex_handles.lock()
+                        .map_err(|err| {
+                            LuaError::external("failed to register regex handle")
+                                .context(err)
+                        })?;
+
+                    let mut handle = rand::random::<i32>();
+
+                    while handles.contains_key(&handle) {
+                        handle = rand::random::<i32>();
+                    }
+
+                    handles.insert(handle, regex);
+
+                    Ok(handle)
+                })?
+            },
+
+            regex_free: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |_lua: &Lua, handle: i32| {
+                    let mut handles = regex_handles.lock()
+                        .map_err(|err| LuaError::external(format!("failed to read handle: {err}")))?;
+
+                    handles.remove(&handle);
+
+                    Ok(())
+                })?
+            },
+
+            regex_escape: lua.create_function(|_lua: &Lua, text: String| {
+                Ok(regex::escape(&text))
+            })?,
+
+            regex_is_match: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |_lua: &Lua, (regex, text): (LuaValue, LuaString)| {
+                    check_text(&text)?;
+
+                    Ok(get_regex(&regex_handles, regex)?.is_match(&text.as_bytes()))
+                })?
+            },
+
+            regex_find: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (regex, text, init): (LuaValue, LuaString, Option<i64>)| {
+                    check_text(&text)?;
+
+                    let regex = get_regex(&regex_handles, regex)?;
+
+                    let text = text.as_bytes();
+                    let offset = text_offset(&text, init);
+
+                    regex.find_at(&text, offset)
+                        .map(|found| match_to_lua(lua, found))
+                        .transpose()
+                })?
+            },
+
+            regex_find_all: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (regex, text): (LuaValue, LuaString)| {
+                    check_text(&text)?;
+
+                    let regex = get_regex(&regex_handles, regex)?;
+                    let result = lua.create_table()?;
+
+                    for found in regex.find_iter(&text.as_bytes()) {
+                        result.raw_push(match_to_lua(lua, found)?)?;
+                    }
+
+                    Ok(result)
+                })?
+            },
+
+            regex_captures: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (regex, text, init): (LuaValue, LuaString, Option<i64>)| {
+                    check_text(&text)?;
+
+                    let regex = get_regex(&regex_handles, regex)?;
+
+                    let text = text.as_bytes();
+                    let offset = text_offset(&text, init);
+
+                    let mut locations = regex.capture_locations();
+
+                    if regex.captures_read_at(&mut locations, &text, offset).is_none() {
+                        return Ok(None);
+                    }
+
+                    let table = lua.create_table_with_capacity(locations.len(), 0)?;
+
+                    for i in 0..locations.len() {
+                        if let Some((start, end)) = locations.get(i) {
+                            table.raw_set(i, lua.create_string(&text[start..end])?)?;
+                        }
+                    }
+
+                    for (i, name) in regex.capture_names().enumerate() {
+                        if let Some(name) = name && let Some((start, end)) = locations.get(i) {
+                            table.raw_set(name, lua.create_string(&text[start..end])?)?;
+                        }
+                    }
+
+                    Ok(Some(table))
+                })?
+            },
+
+            regex_captures_all: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (regex, text): (LuaValue, LuaString)| {
+                    check_text(&text)?;
+
+                    let regex = get_regex(&regex_handles, regex)?;
+                    let result = lua.create_table()?;
+
+                    for captures in regex.captures_iter(&text.as_bytes()) {
+                        result.raw_push(captures_to_lua(lua, &regex, &captures)?)?;
+                    }
+
+                    Ok(result)
+                })?
+            },
+
+            regex_replace: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (regex, text, replacement, limit): (LuaValue, LuaString, LuaValue, Option<usize>)| {
+                    check_text(&text)?;
+
+                    let regex = get_regex(&regex_handles, regex)?;
+                    let text = text.as_bytes();
+
+                    let result = match replacement {
+                        LuaValue::Function(callback) => {
+                            let mut error = None;
+
+                            let result = regex.replacen(&text, limit.unwrap_or_default(), |captures: &Captures| {
+                                if error.is_some() {
+                                    return Vec::new();
+                                }
+
+                                let value = captures_to_lua(lua, &regex, captures)
+                                    .and_then(|captures| callback.call::<LuaString>(captures));
+
+                                match value {
+                                    Ok(value) => value.as_bytes().to_vec(),
+
+                                    Err(err) => {
+                                        error = Some(err);
+
+                                        Vec::new()
+                                    }
+                                }
+                            });
+
+                            if let Some(err) = error {
+                                return Err(err);
+                            }
+
+                            result.to_vec()
+                        }
+
+                        LuaValue::String(replacement) => {
+                            regex.replacen(&text, limit.unwrap_or_default(), replacement.as_bytes().as_ref())
+                                .to_vec()
+                        }
+
+                        _ => return Err(LuaError::external("replacement string or function expected"))
+                    };
+
+                    lua.create_string(result)
+                })?
+            },
+
+            regex_split: {
+                let regex_handles = regex_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (regex, text, limit): (LuaValue, LuaString, Option<usize>)| {
+                    check_text(&text)?;
+
+                    let regex = get_regex(&regex_handles, regex)?;
+                    let text = text.as_bytes();
+
+                    let result = lua.create_table()?;
+
+                    match limit {
+                        Some(limit) if limit > 0 => {
+                            for part in regex.splitn(&text, limit) {
+                                result.raw_push(lua.create_string(part)?)?;
+                            }
+                        }
+
+                        _ => {
+                            for part in regex.split(&text) {
+                                result.raw_push(lua.create_string(part)?)?;
+                            }
+                        }
+                    }
+
+                    Ok(result)
+                })?
+            },
+
+            regex_glob: lua.create_function(|_lua: &Lua, (patterns, path, options): (LuaValue, String, Option<LuaTable>)| {
+                let patterns = match patterns {
+                    LuaValue::String(pattern) => vec![pattern.to_string_lossy()],
+                    LuaValue::Table(patterns) => patterns.sequence_values::<String>()
+                        .collect::<Result<Vec<_>, _>>()?,
+
+                    _ => return Err(LuaError::external("glob pattern string or table expected"))
+                };
+
+                let mut case_insensitive = false;
+                let mut literal_separator = true;
+
+                if let Some(options) = options {
+                    case_insensitive = options.get::<Option<bool>>("case_insensitive")?
+                        .unwrap_or(case_insensitive);
+
+                    literal_separator = options.get::<Option<bool>>("literal_separator")?
+                        .unwrap_or(literal_separator);
+                }
+
+                let mut builder = GlobSetBuilder::new();
+
+                for pattern in patterns {
+                    if pattern.len() > MAX_PATTERN_LEN {
+                        return Err(LuaError::external(format!("glob pattern is longer than {MAX_PATTERN_LEN} bytes")));
+                    }
+
+                    let glob = GlobBuilder::new(&pattern)
+                        .case_insensitive(case_insensitive)
+                        .literal_separator(literal_separator)
+                        .backslash_escape(true)
+                        .build()
+                        .map_err(|err| LuaError::external(format!("invalid glob pattern: {err}")))?;
+
+                    builder.add(glob);
+                }
+
+                let globs = builder.build()
+                    .map_err(|err| LuaError::external(format!("invalid glob pattern: {err}")))?;
+
+                Ok(globs.is_match(path))
+            })?,
+
+            lua
+        })
+    }
+
+    /// Create new lua table with API functions.
+    pub fn create_env(&self) -> Result<LuaTable, LuaError> {
+        let env = self.lua.create_table_with_capacity(0, 11)?;
+
+        env.raw_set("new", &self.regex_new)?;
+        env.raw_set("free", &self.regex_free)?;
+        env.raw_set("escape", &self.regex_escape)?;
+        env.raw_set("is_match", &self.regex_is_match)?;
+        env.raw_set("find", &self.regex_find)?;
+        env.raw_set("find_all", &self.regex_find_all)?;
+        env.raw_set("captures", &self.regex_captures)?;
+        env.raw_set("captures_all", &self.regex_captures_all)?;
+        env.raw_set("replace", &self.regex_replace)?;
+        env.raw_set("split", &self.regex_split)?;
+        env.raw_set("glob", &self.regex_glob)?;
+
+        Ok(env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching() -> Result<(), LuaError> {
+        let api = RegexApi::new(Lua::new())?;
+
+        let regex = api.regex_new.call::<i32>(r"(?<major>\d+)\.(?<minor>\d+)(?:\.(\d+))?")?;
+
+        assert!(api.regex_is_match.call::<bool>((regex, "version 1.2.3"))?);
+        assert!(!api.regex_is_match.call::<bool>((regex, "version one"))?);
+        assert!(api.regex_is_match.call::<bool>((r"^\w+$", "hello"))?);
+
+        let found = api.regex_find.call::<LuaTable>((regex, "version 1.2.3"))?;
+
+        assert_eq!(found.get::<usize>("start")?, 9);
+        assert_eq!(found.get::<usize>("finish")?, 13);
+        assert_eq!(found.get::<String>("text")?, "1.2.3");
+
+        assert_eq!(api.regex_find.call::<Option<LuaTable>>((regex, "1.2 and 3.4", 2))?.unwrap().get::<String>("text")?, "3.4");
+        assert_eq!(api.regex_find_all.call::<LuaTable>((regex, "1.2 and 3.4"))?.raw_len(), 2);
+
+        let captures = api.regex_captures.call::<LuaTable>((regex, "version 1.2.3"))?;
+
+        assert_eq!(captures.get::<String>(0)?, "1.2.3");
+        assert_eq!(captures.get::<String>(1)?, "1");
+        assert_eq!(captures.get::<String>("minor")?, "2");
+        assert_eq!(captures.get::<String>(3)?, "3");
+
+        let captures = api.regex_captures_all.call::<LuaTable>((regex, "1.2 and 3.4"))?;
+
+        assert_eq!(captures.get::<LuaTable>(2)?.get::<String>("major")?, "3");
+        assert_eq!(captures.get::<LuaTable>(2)?.get::<Option<String>>(3)?, None);
+
+        api.regex_free.call::<()>(regex)?;
+
+        assert!(api.regex_is_match.call::<bool>((regex, "1.2")).is_err());
+        assert!(api.regex_new.call::<i32>(r"(\w{100}){100}").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_split() -> Result<(), LuaError> {
+        let lua = Lua::new();
+        let api = RegexApi::new(lua.clone())?;
+
+        assert_eq!(api.regex_replace.call::<String>((r"(\w+)=(\w+)", "a=1 b=2", "$2=$1"))?, "1=a 2=b");
+        assert_eq!(api.regex_replace.call::<String>((r"\d", "1 2 3", "x", 2))?, "x x 3");
+
+        let callback = lua.create_function(|_, captures: LuaTable| {
+            Ok(captures.get::<String>(0)?.to_uppercase())
+        })?;
+
+        assert_eq!(api.regex_replace.call::<String>((r"[a-z]+", "hello, world", callback))?, "HELLO, WORLD");
+
+        let parts = api.regex_split.call::<Vec<String>>((r"\s*,\s*", "a , b,c"))?;
+
+        assert_eq!(parts, ["a", "b", "c"]);
+
+        let parts = api.regex_split.call::<Vec<String>>((r",", "a,b,c", 2))?;
+
+        assert_eq!(parts, ["a", "b,c"]);
+
+        assert_eq!(api.regex_escape.call::<String>("1.2+")?, r"1\.2\+");
+
+        Ok(())
+    }
+
+    #[test]
+    fn glob() -> Result<(), LuaError> {
+        let lua = Lua::new();
+        let api = RegexApi::new(lua.clone())?;
+
+        assert!(api.regex_glob.call::<bool>(("*.dll", "d3d11.dll"))?);
+        assert!(!api.regex_glob.call::<bool>(("*.dll", "system32/d3d11.dll"))?);
+        assert!(api.regex_glob.call::<bool>(("**/*.dll", "system32/d3d11.dll"))?);
+        assert!(api.regex_glob.call::<bool>((vec!["*.exe", "*.dll"], "game.exe"))?);
+
+        let options = lua.create_table()?;
+
+        options.set("case_insensitive", true)?;
+
+        assert!(api.regex_glob.call::<bool>(("*.DLL", "d3d11.dll", options))?);
+
+        Ok(())
+    }
+}
@@ -23,6 +23,7 @@ use mlua::prelude::*;
 use encoding_rs::Encoding;
 
 use super::bytes::Bytes;
+use super::formats;
 
 /// Filter provided lua value to keep only basic types like numbers, booleans,
 /// strings and tables.
@@ -62,7 +63,12 @@ enum StringEncoding {
     Json { pretty: bool },
     Bson,
     Toml { pretty: bool },
-    Yaml
+    Yaml,
+    MessagePack,
+    Ini,
+    Xml { pretty: bool },
+    Vdf,
+    VdfBinary
 }
 
 impl StringEncoding {
@@ -127,6 +133,37 @@ impl StringEncoding {
 
                 lua.create_string(value)
             }
+
+            Self::MessagePack => {
+                let value = rmp_serde::to_vec_named(&value)
+                    .map_err(LuaError::external)?;
+
+                lua.create_string(value)
+            }
+
+            Self::Ini => {
+                let value = LuaTable::from_lua(value, lua)?;
+
+                lua.create_string(formats::encode_ini(&value)?)
+            }
+
+            Self::Xml { pretty } => {
+                let value = LuaTable::from_lua(value, lua)?;
+
+                lua.create_string(formats::encode_xml(&value, *pretty)?)
+            }
+
+            Self::Vdf => {
+                let value = LuaTable::from_lua(value, lua)?;
+
+                lua.create_string(formats::encode_vdf(&value)?)
+            }
+
+            Self::VdfBinary => {
+                let value = LuaTable::from_lua(value, lua)?;
+
+                lua.create_string(formats::encode_binary_vdf(&value)?)
+            }
         }
     }
 
@@ -202,6 +239,41 @@ impl StringEncoding {
 
                 Ok(filter_lua_value(lua.to_value(&value)?)?)
             }
+
+            Self::MessagePack => {
+                let value = rmp_serde::from_slice::<rmpv::Value>(&string.as_bytes())
+                    .map_err(LuaError::external)?;
+
+                Ok(filter_lua_value(lua.to_value(&value)?)?)
+            }
+
+            Self::Ini => {
+                let string = string.to_string_lossy()
+                    .to_string();
+
+                formats::decode_ini(lua, &string)
+                    .map(LuaValue::Table)
+            }
+
+            Self::Xml { .. } => {
+                let string = string.to_string_lossy()
+                    .to_string();
+
+                formats::decode_xml(lua, &string)
+            }
+
+            Self::Vdf => {
+                let string = string.to_string_lossy()
+                    .to_string();
+
+                formats::decode_vdf(lua, &string)
+                    .map(LuaValue::Table)
+            }
+
+            Self::VdfBinary => {
+                formats::decode_binary_vdf(lua, &string.as_bytes())
+                    .map(LuaValue::Table)
+            }
         }
     }
 }
@@ -286,6 +358,16 @@ impl FromStr for StringEncoding {
 
             "yaml" => Ok(Self::Yaml),
 
+            "msgpack" | "messagepack" => Ok(Self::MessagePack),
+
+            "ini" => Ok(Self::Ini),
+
+            "xml" | "xml/compact" => Ok(Self::Xml { pretty: false }),
+            "xml/pretty" => Ok(Self::Xml { pretty: true }),
+
+            "vdf" => Ok(Self::Vdf),
+            "vdf/binary" => Ok(Self::VdfBinary),
+
             _ => Err(())
         }
     }
@@ -299,6 +381,7 @@ pub struct StringApi {
     str_from_bytes: LuaFunction,
     str_encode: LuaFunction,
     str_decode: LuaFunction,
+    str_ini_set: LuaFunction,
 
     str_lowercase: LuaFunction,
     str_uppercase: LuaFunction,
@@ -356,6 +439,10 @@ impl StringApi {
                 encoding.decode(lua, value)
             })?,
 
+            str_ini_set: lua.create_function(|_lua: &Lua, (ini, section, key, value): (String, Option<String>, String, Option<String>)| {
+                Ok(formats::ini_set(&ini, section.as_deref(), &key, value.as_deref()))
+            })?,
+
             str_lowercase: lua.create_function(|_lua: &Lua, value: Bytes| {
                 Ok(value.as_string_lossy().to_lowercase())
             })?,
@@ -414,12 +501,13 @@ impl StringApi {
 
     /// Create new lua table with API functions.
     pub fn create_env(&self) -> Result<LuaTable, LuaError> {
-        let env = self.lua.create_table_with_capacity(0, 7)?;
+        let env = self.lua.create_table_with_capacity(0, 8)?;
 
         env.raw_set("to_bytes", &self.str_to_bytes)?;
         env.raw_set("from_bytes", &self.str_from_bytes)?;
         env.raw_set("encode", &self.str_encode)?;
         env.raw_set("decode", &self.str_decode)?;
+        env.raw_set("ini_set", &self.str_ini_set)?;
 
         env.raw_set("lowercase", &self.str_lowercase)?;
         env.raw_set("uppercase", &self.str_uppercase)?;
@@ -489,7 +577,8 @@ mod tests {
             ("json", b"{ \"test_string\": \"str\", \"test_bool\": true, \"test_null\": null }".as_slice()),
             ("bson", [0x31, 0x00, 0x00, 0x00, 0x02, 0x74, 0x65, 0x73, 0x74, 0x5f, 0x73, 0x74, 0x72, 0x69, 0x6e, 0x67, 0x00, 0x04, 0x00, 0x00, 0x00, 0x73, 0x74, 0x72, 0x00, 0x0a, 0x74, 0x65, 0x73, 0x74, 0x5f, 0x6e, 0x75, 0x6c, 0x6c, 0x00, 0x08, 0x74, 0x65, 0x73, 0x74, 0x5f, 0x62, 0x6f, 0x6f, 0x6c, 0x00, 0x01, 0x00].as_slice()),
             ("toml", b"test_string = \"str\"\ntest_bool = true".as_slice()),
-            ("yaml", b"test_string: \"str\"\ntest_bool: true\ntest_null: null".as_slice())
+            ("yaml", b"test_string: \"str\"\ntest_bool: true\ntest_null: null".as_slice()),
+            ("msgpack", [0x82, 0xab, 0x74, 0x65, 0x73, 0x74, 0x5f, 0x73, 0x74, 0x72, 0x69, 0x6e, 0x67, 0xa3, 0x73, 0x74, 0x72, 0xa9, 0x74, 0x65, 0x73, 0x74, 0x5f, 0x62, 0x6f, 0x6f, 0x6c, 0xc3].as_slice())
         ];
 
         for (name, value) in encodings {
@@ -515,6 +604,174 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn structured_formats() -> Result<(), LuaError> {
+        let lua = Lua::new();
+        let api = StringApi::new(lua.clone())?;
+
+        // INI
+        let ini = api.str_decode.call::<LuaTable>(("ini", "; comment\nname = test\n\n[Section]\nkey=value\n"))?;
+
+        assert_eq!(ini.get::<String>("name")?, "test");
+        assert_eq!(ini.get::<LuaTable>("Section")?.get::<String>("key")?, "value");
+
+        let encoded = api.str_encode.call::<String>(("ini", ini))?;
+
+        assert_eq!(encoded, "name=test\n\n[Section]\nkey=value\n");
+
+        // XML
+        let xml = api.str_decode.call::<LuaTable>(("xml", "<?xml version=\"1.0\"?>\n<root id=\"1\">\n    <item>a &amp; b</item>\n    <empty/>\n</root>"))?;
+
+        assert_eq!(xml.get::<String>("name")?, "root");
+        assert_eq!(xml.get::<LuaTable>("attributes")?.get::<String>("id")?, "1");
+
+        let children = xml.get::<LuaTable>("children")?;
+
+        assert_eq!(children.raw_len(), 2);
+        assert_eq!(children.get::<LuaTable>(1)?.get::<LuaTable>("children")?.get::<String>(1)?, "a & b");
+        assert_eq!(children.get::<LuaTable>(2)?.get::<String>("name")?, "empty");
+
+        let encoded = api.str_encode.call::<String>(("xml", xml))?;
+
+        assert_eq!(encoded, "<root id=\"1\"><item>a &amp; b</item><empty/></root>");
+
+        // VDF
+        let vdf = api.str_decode.call::<LuaTable>(("vdf", "\"AppState\"\n{\n\t\"appid\"\t\t\"123\" // comment\n\tname Test\n\t\"UserConfig\" { \"language\" \"english\" }\n}\n"))?;
+
+        let app_state = vdf.get::<LuaTable>("AppState")?;
+
+        assert_eq!(app_state.get::<String>("appid")?, "123");
+        assert_eq!(app_state.get::<String>("name")?, "Test");
+        assert_eq!(app_state.get::<LuaTable>("UserConfig")?.get::<String>("language")?, "english");
+
+        let encoded = api.str_encode.call::<LuaString>(("vdf", vdf))?;
+        let decoded = api.str_decode.call::<LuaTable>(("vdf", encoded))?;
+
+        assert_eq!(decoded.get::<LuaTable>("AppState")?.get::<String>("name")?, "Test");
+
+        // Binary VDF
+        let table = lua.create_table()?;
+        let shortcut = lua.create_table()?;
+
+        shortcut.set("AppName", "Game")?;
+        shortcut.set("appid", 12345)?;
+
+        table.set("shortcuts", lua.create_sequence_from([shortcut])?)?;
+
+        let encoded = api.str_encode.call::<LuaString>(("vdf/binary", table))?;
+        let decoded = api.str_decode.call::<LuaTable>(("vdf/binary", encoded))?;
+
+        let shortcut = decoded.get::<LuaTable>("shortcuts")?.get::<LuaTable>("1")?;
+
+        assert_eq!(shortcut.get::<String>("AppName")?, "Game");
+        assert_eq!(shortcut.get::<u32>("appid")?, 12345);
+
+        Ok(())
+    }
+
+    #[test]
+    fn structured_formats_limits() -> Result<(), LuaError> {
+        let lua = Lua::new();
+        let api = StringApi::new(lua.clone())?;
+
+        // Cyclic tables.
+        let table = lua.create_table()?;
+
+        table.set("self", &table)?;
+
+        assert!(api.str_encode.call::<LuaString>(("vdf", &table)).is_err());
+        assert!(api.str_encode.call::<LuaString>(("vdf/binary", &table)).is_err());
+
+        let element = lua.create_table()?;
+
+        element.set("name", "root")?;
+        element.set("children", lua.create_sequence_from([&element])?)?;
+
+        assert!(api.str_encode.call::<LuaString>(("xml", element)).is_err());
+
+        // Too deeply nested tables.
+        let mut element = lua.create_table()?;
+
+        element.set("name", "child")?;
+
+        for _ in 0..200 {
+            let parent = lua.create_table()?;
+
+            parent.set("name", "parent")?;
+            parent.set("children", lua.create_sequence_from([element])?)?;
+
+            element = parent;
+        }
+
+        assert!(api.str_encode.call::<LuaString>(("xml", element)).is_err());
+
+        let mut deep = lua.create_table()?;
+
+        for _ in 0..200 {
+            let parent = lua.create_table()?;
+
+            parent.set("child", deep)?;
+
+            deep = parent;
+        }
+
+        assert!(api.str_encode.call::<LuaString>(("vdf", &deep)).is_err());
+        assert!(api.str_encode.call::<LuaString>(("vdf/binary", &deep)).is_err());
+
+        let text = format!("{}{}", "\"k\" {".repeat(200), "}".repeat(200));
+
+        assert!(api.str_decode.call::<LuaTable>(("vdf", text)).is_err());
+
+        // Binary VDF integers out of the u32 range.
+        for value in [LuaValue::Integer(-1), LuaValue::Integer(u32::MAX as i64 + 1), LuaValue::Number(-1.0)] {
+            let table = lua.create_table()?;
+
+            table.set("value", value)?;
+
+            assert!(api.str_encode.call::<LuaString>(("vdf/binary", table)).is_err());
+        }
+
+        let table = lua.create_table()?;
+
+        table.set("value", u32::MAX)?;
+
+        let encoded = api.str_encode.call::<LuaString>(("vdf/binary", table))?;
+        let decoded = api.str_decode.call::<LuaTable>(("vdf/binary", encoded))?;
+
+        assert_eq!(decoded.get::<u32>("value")?, u32::MAX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ini_set() -> Result<(), LuaError> {
+        let api = StringApi::new(Lua::new())?;
+
+        let ini = "; comment\r\nname = test\r\n\r\n[Section]\r\n# another comment\r\nkey = value\r\n";
+
+        assert_eq!(
+            api.str_ini_set.call::<String>((ini, "Section", "key", "new"))?,
+            "; comment\r\nname = test\r\n\r\n[Section]\r\n# another comment\r\nkey = new\r\n"
+        );
+
+        assert_eq!(
+            api.str_ini_set.call::<String>((ini, "Section", "other", 1))?,
+            "; comment\r\nname = test\r\n\r\n[Section]\r\n# another comment\r\nkey = value\r\nother=1\r\n"
+        );
+
+        assert_eq!(
+            api.str_ini_set.call::<String>((ini, LuaValue::Nil, "name", LuaValue::Nil))?,
+            "; comment\r\n\r\n[Section]\r\n# another comment\r\nkey = value\r\n"
+        );
+
+        assert_eq!(
+            api.str_ini_set.call::<String>((ini, "New", "key", "value"))?,
+            "; comment\r\nname = test\r\n\r\n[Section]\r\n# another comment\r\nkey = value\r\n\r\n[New]\r\nkey=value\r\n"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn text_lowercase() -> Result<(), LuaError> {
         let lua = Lua::new();
@@ -16,7 +16,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::{Mutex, MutexGuard};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 use mlua::prelude::*;
 
@@ -37,6 +40,294 @@ pub fn task_output(result: Result<LuaValue, LuaError>) -> TaskOutput {
     Box::new(move |_: &Lua| result) as TaskOutput
 }
 
+/// Interval between background promises polls when they're awaited in a loop.
+pub const PROMISE_AWAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Default, Debug)]
+struct CancellationTokenInner {
+    cancelled: AtomicBool,
+    notify: tasks::sync::Notify,
+    children: Mutex<Vec<CancellationToken>>
+}
+
+/// A lua usertype which can be used to cancel promises and standard API
+/// operations. Cancelling a token cancels all of its child tokens as well.
+#[derive(Default, Debug, Clone)]
+pub struct CancellationToken(Arc<CancellationTokenInner>);
+
+impl CancellationToken {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create new token which will be cancelled together with the current
+    /// one, but can also be cancelled independently.
+    pub fn child(&self) -> Self {
+        let child = Self::new();
+
+        let mut children = self.0.children.lock()
+            .expect("failed to lock cancellation token children");
+
+        if self.is_cancelled() {
+            child.cancel();
+        } else {
+            children.push(child.clone());
+        }
+
+        child
+    }
+
+    /// Cancel the token and all of its child tokens.
+    pub fn cancel(&self) {
+        if self.0.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        self.0.notify.notify_waiters();
+
+        let children = std::mem::take(&mut *self.0.children.lock()
+            .expect("failed to lock cancellation token children"));
+
+        for child in children {
+            child.cancel();
+        }
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.0.notify.notified();
+
+            if self.is_cancelled() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
+
+impl FromLua for CancellationToken {
+    fn from_lua(value: LuaValue, _: &Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::UserData(object) => Ok(object.borrow::<Self>()?.clone()),
+
+            _ => Err(LuaError::external("can't convert value into CancellationToken type"))
+        }
+    }
+}
+
+impl LuaUserData for CancellationToken {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("cancelled", |_, token: &Self| -> Result<bool, LuaError> {
+            Ok(token.is_cancelled())
+        });
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("cancel", |_, token: &Self, ()| -> Result<(), LuaError> {
+            token.cancel();
+
+            Ok(())
+        });
+
+        methods.add_method("child", |_, token: &Self, ()| -> Result<Self, LuaError> {
+            Ok(token.child())
+        });
+    }
+}
+
+#[derive(Default, Debug)]
+struct ProgressInner {
+    current: AtomicU64,
+    total: AtomicU64,
+    updated: AtomicBool
+}
+
+/// Progress of a background task. Updated from the task and reported to the
+/// lua side by the promise created with `Promise::with_progress`.
+#[derive(Default, Debug, Clone)]
+pub struct Progress(Arc<ProgressInner>);
+
+impl Progress {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn current(&self) -> u64 {
+        self.0.current.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.0.total.load(Ordering::Acquire)
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.0.total.store(total, Ordering::Release);
+        self.0.updated.store(true, Ordering::Release);
+    }
+
+    pub fn add(&self, diff: u64) {
+        self.0.current.fetch_add(diff, Ordering::AcqRel);
+        self.0.updated.store(true, Ordering::Release);
+    }
+
+    /// Get current and total progress values if they were updated since the
+    /// last call.
+    fn take_update(&self) -> Option<(u64, u64)> {
+        if self.0.updated.swap(false, Ordering::AcqRel) {
+            Some((self.current(), self.total()))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ChannelInner {
+    queue: Mutex<VecDeque<LuaValue>>,
+    capacity: usize,
+    closed: AtomicBool
+}
+
+/// A lua usertype for bounded queue of values which can be used to pass
+/// messages between coroutines and promises.
+#[derive(Debug, Clone)]
+pub struct Channel(Arc<ChannelInner>);
+
+impl Channel {
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(ChannelInner {
+            queue: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            closed: AtomicBool::new(false)
+        }))
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, VecDeque<LuaValue>> {
+        self.0.queue.lock().expect("failed to lock channel queue")
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.0.closed.load(Ordering::SeqCst)
+    }
+
+    /// Close the channel. Already sent values can still be received.
+    #[inline]
+    pub fn close(&self) {
+        self.0.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Try to push a value to the channel. Return it back if the channel is
+    /// full or closed.
+    pub fn try_send(&self, value: LuaValue) -> Result<(), LuaValue> {
+        let mut queue = self.lock();
+
+        if self.is_closed() || queue.len() >= self.0.capacity {
+            return Err(value);
+        }
+
+        queue.push_back(value);
+
+        Ok(())
+    }
+
+    /// Try to take a value from the channel.
+    #[inline]
+    pub fn try_recv(&self) -> Option<LuaValue> {
+        self.lock().pop_front()
+    }
+}
+
+impl LuaUserData for Channel {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("len", |_, channel: &Self| -> Result<usize, LuaError> {
+            Ok(channel.lock().len())
+        });
+
+        fields.add_field_method_get("capacity", |_, channel: &Self| -> Result<usize, LuaError> {
+            Ok(channel.0.capacity)
+        });
+
+        fields.add_field_method_get("closed", |_, channel: &Self| -> Result<bool, LuaError> {
+            Ok(channel.is_closed())
+        });
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("try_send", |_, channel: &Self, value: LuaValue| -> Result<bool, LuaError> {
+            Ok(channel.try_send(value).is_ok())
+        });
+
+        methods.add_method("try_recv", |_, channel: &Self, ()| -> Result<(bool, LuaValue), LuaError> {
+            match channel.try_recv() {
+                Some(value) => Ok((true, value)),
+                None => Ok((false, LuaValue::Nil))
+            }
+        });
+
+        methods.add_method("send", |lua: &Lua, channel: &Self, value: LuaValue| -> Result<Promise, LuaError> {
+            let channel = channel.clone();
+            let value = Mutex::new(Some(value));
+
+            let callback = lua.create_function(move |_, ()| -> Result<(bool, bool), LuaError> {
+                let mut value = value.lock()
+                    .map_err(|_| LuaError::external("failed to lock sent value"))?;
+
+                let Some(inner) = value.take() else {
+                    return Ok((true, true));
+                };
+
+                if channel.is_closed() {
+                    return Ok((true, false));
+                }
+
+                match channel.try_send(inner) {
+                    Ok(()) => Ok((true, true)),
+
+                    Err(inner) => {
+                        *value = Some(inner);
+
+                        Ok((false, false))
+                    }
+                }
+            })?;
+
+            Ok(Promise::new(PromiseValue::Callback(callback)))
+        });
+
+        methods.add_method("recv", |lua: &Lua, channel: &Self, ()| -> Result<Promise, LuaError> {
+            let channel = channel.clone();
+
+            let callback = lua.create_function(move |_, ()| -> Result<(bool, LuaValue), LuaError> {
+                match channel.try_recv() {
+                    Some(value) => Ok((true, value)),
+                    None => Ok((channel.is_closed(), LuaValue::Nil))
+                }
+            })?;
+
+            Ok(Promise::new(PromiseValue::Callback(callback)))
+        });
+
+        methods.add_method("close", |_, channel: &Self, ()| -> Result<(), LuaError> {
+            channel.close();
+
+            Ok(())
+        });
+    }
+}
+
 /// Inner value of a promise. Exists because promise can mutate its stored value
 /// on the fly.
 pub enum PromiseValue {
@@ -45,7 +336,26 @@ pub enum PromiseValue {
     Coroutine(LuaThread),
     LuaPromise(LuaAnyUserData),
     Task(JoinHandle<Result<TaskOutput, LuaError>>),
-    AnyTask(Box<[Promise]>)
+    AnyTask(Box<[Promise]>),
+
+    /// List of promises and already obtained outputs of them.
+    AllTasks(Box<[Promise]>, Box<[Option<LuaValue>]>),
+
+    Timeout {
+        promise: Box<Promise>,
+        deadline: Instant
+    },
+
+    Cancellable {
+        promise: Box<Promise>,
+        token: CancellationToken
+    },
+
+    Progress {
+        promise: Box<Promise>,
+        progress: Progress,
+        callback: LuaFunction
+    }
 }
 
 impl std::fmt::Debug for PromiseValue {
@@ -73,6 +383,27 @@ impl std::fmt::Debug for PromiseValue {
 
             Self::AnyTask(tasks) => f.debug_struct("PromiseValue")
                 .field("tasks", &tasks)
+                .finish(),
+
+            Self::AllTasks(tasks, outputs) => f.debug_struct("PromiseValue")
+                .field("tasks", &tasks)
+                .field("outputs", &outputs)
+                .finish(),
+
+            Self::Timeout { promise, deadline } => f.debug_struct("PromiseValue")
+                .field("promise", &promise)
+                .field("deadline", &deadline)
+                .finish(),
+
+            Self::Cancellable { promise, token } => f.debug_struct("PromiseValue")
+                .field("promise", &promise)
+                .field("token", &token)
+                .finish(),
+
+            Self::Progress { promise, progress, callback } => f.debug_struct("PromiseValue")
+                .field("promise", &promise)
+                .field("progress", &progress)
+                .field("callback", &callback)
                 .finish()
         }
     }
@@ -107,6 +438,91 @@ impl PromiseValue {
     ) -> Self {
         Self::Task(tasks::spawn_blocking(callback))
     }
+
+    /// Abort background tasks of the promise value and all the nested
+    /// promises as soon as provided token is cancelled, without waiting for
+    /// the promise to be polled.
+    fn watch_token(&mut self, token: &CancellationToken) {
+        match self {
+            Self::Task(_) => {
+                let Self::Task(handle) = std::mem::replace(self, Self::Value(LuaValue::Nil)) else {
+                    unreachable!();
+                };
+
+                let token = token.clone();
+
+                // Keep the original task abortable when the watcher task
+                // itself is aborted.
+                let inner = AbortOnDrop(handle.abort_handle());
+
+                *self = Self::from_future(async move {
+                    let _inner = inner;
+
+                    tasks::select! {
+                        output = handle => output.map_err(|err| {
+                            LuaError::external(format!("failed to execute task: {err}"))
+                        })?,
+
+                        _ = token.cancelled() => Err(LuaError::external("task cancelled"))
+                    }
+                });
+            }
+
+            Self::AnyTask(tasks) | Self::AllTasks(tasks, _) => {
+                for task in tasks {
+                    task.watch_token(token);
+                }
+            }
+
+            Self::Timeout { promise, .. } |
+            Self::Cancellable { promise, .. } |
+            Self::Progress { promise, .. } => promise.watch_token(token),
+
+            Self::Value(_) |
+            Self::Callback(_) |
+            Self::Coroutine(_) |
+            Self::LuaPromise(_) => ()
+        }
+    }
+
+    /// Abort execution of the promise value and all the nested promises.
+    pub fn abort(self) {
+        match self {
+            Self::Task(handle) => handle.abort(),
+
+            Self::LuaPromise(promise) => {
+                #[allow(unused)]
+                if let Err(err) = promise.call_method::<()>("abort", ()) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?err, "failed to abort lua promise");
+                }
+            }
+
+            Self::AnyTask(tasks) | Self::AllTasks(tasks, _) => {
+                for task in tasks {
+                    task.abort();
+                }
+            }
+
+            Self::Timeout { promise, .. } |
+            Self::Cancellable { promise, .. } |
+            Self::Progress { promise, .. } => promise.abort(),
+
+            Self::Value(_) |
+            Self::Callback(_) |
+            Self::Coroutine(_) => ()
+        }
+    }
+}
+
+/// Aborts the task when dropped.
+struct AbortOnDrop(tasks::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.abort();
+    }
 }
 
 /// A lua usertype wrapper over a promise value. Implements `poll` method to
@@ -123,6 +539,70 @@ impl Promise {
         Self::new(PromiseValue::from_lua_value(value))
     }
 
+    /// Wrap the promise so it's aborted when provided token is cancelled.
+    /// Background tasks are aborted right away, other promises are aborted
+    /// on the next poll.
+    pub fn with_token(self, token: Option<CancellationToken>) -> Self {
+        match token {
+            Some(token) => {
+                self.watch_token(&token);
+
+                Self::new(PromiseValue::Cancellable {
+                    promise: Box::new(self),
+                    token
+                })
+            }
+
+            None => self
+        }
+    }
+
+    fn watch_token(&self, token: &CancellationToken) {
+        if let Some(value) = self.lock().as_mut() {
+            value.watch_token(token);
+        }
+    }
+
+    /// Wrap the promise so the callback is called with current and total
+    /// progress values every time they're updated while the promise is polled.
+    pub fn with_progress(self, progress: Progress, callback: Option<LuaFunction>) -> Self {
+        match callback {
+            Some(callback) => Self::new(PromiseValue::Progress {
+                promise: Box::new(self),
+                progress,
+                callback
+            }),
+
+            None => self
+        }
+    }
+
+    /// Check if the promise is executed in background. Wrappers over multiple
+    /// promises are background only if all of them are background.
+    pub fn is_background(&self) -> bool {
+        match &*self.lock() {
+            Some(PromiseValue::Task(_)) => true,
+
+            Some(PromiseValue::AnyTask(tasks)) |
+            Some(PromiseValue::AllTasks(tasks, _)) => {
+                tasks.iter().all(Promise::is_background)
+            }
+
+            Some(PromiseValue::Timeout { promise, .. }) |
+            Some(PromiseValue::Cancellable { promise, .. }) |
+            Some(PromiseValue::Progress { promise, .. }) => promise.is_background(),
+
+            _ => false
+        }
+    }
+
+    /// Abort promise execution.
+    pub fn abort(&self) {
+        if let Some(value) = self.lock().take() {
+            value.abort();
+        }
+    }
+
     #[inline]
     fn lock(&self) -> MutexGuard<'_, Option<PromiseValue>> {
         self.0.lock().expect("failed to lock promise value")
@@ -142,7 +622,7 @@ impl LuaUserData for Promise {
         });
 
         fields.add_field_method_get("background", |_, promise: &Self| -> Result<bool, LuaError> {
-            Ok(matches!(&*promise.lock(), Some(PromiseValue::Task(_))))
+            Ok(promise.is_background())
         });
     }
 
@@ -254,6 +734,95 @@ impl LuaUserData for Promise {
 
                     Ok((Some(false), LuaValue::Nil))
                 }
+
+                PromiseValue::AllTasks(tasks, mut outputs) => {
+                    let mut finished = true;
+                    let mut aborted = None;
+
+                    for (task, output) in tasks.iter().zip(outputs.iter_mut()) {
+                        if output.is_some() {
+                            continue;
+                        }
+
+                        match poll(lua, task)? {
+                            (Some(true), value) => *output = Some(value),
+                            (Some(false), _) => finished = false,
+
+                            (None, value) => {
+                                aborted = Some(value);
+
+                                break;
+                            }
+                        }
+                    }
+
+                    // Abort all the other tasks if any of them was aborted.
+                    if let Some(value) = aborted {
+                        PromiseValue::AllTasks(tasks, outputs).abort();
+
+                        return Ok((None, value));
+                    }
+
+                    if !finished {
+                        *lock = Some(PromiseValue::AllTasks(tasks, outputs));
+
+                        return Ok((Some(false), LuaValue::Nil));
+                    }
+
+                    let result = lua.create_table_with_capacity(outputs.len(), 0)?;
+
+                    for (i, output) in outputs.into_iter().enumerate() {
+                        result.raw_set(i + 1, output.unwrap_or(LuaValue::Nil))?;
+                    }
+
+                    Ok((Some(true), LuaValue::Table(result)))
+                }
+
+                PromiseValue::Timeout { promise, deadline } => {
+                    if Instant::now() >= deadline {
+                        promise.abort();
+
+                        return Ok((None, LuaValue::Nil));
+                    }
+
+                    let (status, value) = poll(lua, &promise)?;
+
+                    if status == Some(false) {
+                        *lock = Some(PromiseValue::Timeout { promise, deadline });
+                    }
+
+                    Ok((status, value))
+                }
+
+                PromiseValue::Cancellable { promise, token } => {
+                    if token.is_cancelled() {
+                        promise.abort();
+
+                        return Ok((None, LuaValue::Nil));
+                    }
+
+                    let (status, value) = poll(lua, &promise)?;
+
+                    if status == Some(false) {
+                        *lock = Some(PromiseValue::Cancellable { promise, token });
+                    }
+
+                    Ok((status, value))
+                }
+
+                PromiseValue::Progress { promise, progress, callback } => {
+                    let (status, value) = poll(lua, &promise)?;
+
+                    if let Some((current, total)) = progress.take_update() {
+                        callback.call::<()>((current, total))?;
+                    }
+
+                    if status == Some(false) {
+                        *lock = Some(PromiseValue::Progress { promise, progress, callback });
+                    }
+
+                    Ok((status, value))
+                }
             }
         }
 
@@ -371,19 +940,42 @@ impl LuaUserData for Promise {
                         }
                     }
                 }
-            }
-        });
 
-        methods.add_method("abort", |_lua: &Lua, promise: &Self, ()| -> Result<(), LuaError> {
-            let mut lock = promise.lock();
+                value @ (PromiseValue::AllTasks(..) | PromiseValue::Timeout { .. } | PromiseValue::Cancellable { .. } | PromiseValue::Progress { .. }) => {
+                    let abort_error = match &value {
+                        PromiseValue::Timeout { .. } => Some("promise timed out"),
+                        PromiseValue::Cancellable { .. } => Some("promise was cancelled"),
+                        _ => None
+                    };
 
-            let Some(value) = lock.take() else {
-                return Ok(());
-            };
+                    drop(lock);
+
+                    let promise = Promise::new(value);
+
+                    loop {
+                        match poll(lua, &promise)? {
+                            (Some(true), value) => return Ok(value),
 
-            if let PromiseValue::Task(handle) = value {
-                handle.abort();
+                            (Some(false), _) => {
+                                if promise.is_background() {
+                                    std::thread::sleep(PROMISE_AWAIT_INTERVAL);
+                                }
+                            }
+
+                            (None, value) => {
+                                return match abort_error {
+                                    Some(err) => Err(LuaError::external(err)),
+                                    None => Ok(value)
+                                };
+                            }
+                        }
+                    }
+                }
             }
+        });
+
+        methods.add_method("abort", |_lua: &Lua, promise: &Self, ()| -> Result<(), LuaError> {
+            promise.abort();
 
             Ok(())
         });
@@ -410,14 +1002,19 @@ pub struct TaskApi {
 
     task_create: LuaFunction,
     task_sleep: LuaFunction,
-    task_any: LuaFunction
+    task_any: LuaFunction,
+    task_all: LuaFunction,
+    task_race_with_timeout: LuaFunction,
+    task_cancel_token: LuaFunction,
+    task_channel: LuaFunction
 }
 
 impl TaskApi {
     pub fn new(lua: Lua) -> Result<Self, LuaError> {
         Ok(Self {
-            task_create: lua.create_function(|lua: &Lua, task: LuaValue| {
+            task_create: lua.create_function(|lua: &Lua, (task, token): (LuaValue, Option<CancellationToken>)| {
                 Promise::from_lua_value(task)
+                    .with_token(token)
                     .into_lua(lua)
             })?,
 
@@ -463,18 +1060,173 @@ impl TaskApi {
                     .into_lua(lua)
             })?,
 
+            task_all: lua.create_function(|lua: &Lua, lua_tasks: LuaVariadic<LuaValue>| {
+                let promises = lua_tasks.into_iter()
+                    .map(Promise::from_lua_value)
+                    .collect::<Box<[Promise]>>();
+
+                let outputs = vec![None; promises.len()]
+                    .into_boxed_slice();
+
+                Promise::new(PromiseValue::AllTasks(promises, outputs))
+                    .into_lua(lua)
+            })?,
+
+            task_race_with_timeout: lua.create_function(|lua: &Lua, (duration, lua_tasks): (u32, LuaVariadic<LuaValue>)| {
+                if lua_tasks.is_empty() {
+                    return Promise::from_lua_value(LuaValue::Nil)
+                        .into_lua(lua);
+                }
+
+                let promises = lua_tasks.into_iter()
+                    .map(Promise::from_lua_value)
+                    .collect::<Box<[Promise]>>();
+
+                let deadline = Instant::now() + Duration::from_millis(duration as u64);
+
+                Promise::new(PromiseValue::Timeout {
+                    promise: Box::new(Promise::new(PromiseValue::AnyTask(promises))),
+                    deadline
+                }).into_lua(lua)
+            })?,
+
+            task_cancel_token: lua.create_function(|_: &Lua, ()| {
+                Ok(CancellationToken::new())
+            })?,
+
+            task_channel: lua.create_function(|_: &Lua, capacity: Option<usize>| {
+                Ok(Channel::new(capacity.unwrap_or(1)))
+            })?,
+
             lua
         })
     }
 
     /// Create new lua table with API functions.
     pub fn create_env(&self) -> Result<LuaTable, LuaError> {
-        let env = self.lua.create_table_with_capacity(0, 3)?;
+        let env = self.lua.create_table_with_capacity(0, 7)?;
 
         env.raw_set("create", &self.task_create)?;
         env.raw_set("sleep", &self.task_sleep)?;
         env.raw_set("any", &self.task_any)?;
+        env.raw_set("all", &self.task_all)?;
+        env.raw_set("race_with_timeout", &self.task_race_with_timeout)?;
+        env.raw_set("cancel_token", &self.task_cancel_token)?;
+        env.raw_set("channel", &self.task_channel)?;
 
         Ok(env)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_lua() -> Result<Lua, LuaError> {
+        let lua = Lua::new();
+        let api = TaskApi::new(lua.clone())?;
+
+        lua.globals().raw_set("task", api.create_env()?)?;
+
+        Ok(lua)
+    }
+
+    #[test]
+    fn all() -> Result<(), LuaError> {
+        get_lua()?.load(r#"
+            local promise = task.all(1, task.sleep(50, function() return 2 end), task.create(function()
+                return true, 3
+            end))
+
+            local outputs = promise:await()
+
+            assert(outputs[1] == 1)
+            assert(outputs[2] == 2)
+            assert(outputs[3] == 3)
+        "#).exec()
+    }
+
+    #[test]
+    fn race_with_timeout() -> Result<(), LuaError> {
+        get_lua()?.load(r#"
+            local function never()
+                return false, nil
+            end
+
+            assert(task.race_with_timeout(1000, never, task.sleep(50, function() return 1 end)):await() == 1)
+
+            local timed_out = not pcall(function()
+                task.race_with_timeout(50, never):await()
+            end)
+
+            assert(timed_out)
+        "#).exec()
+    }
+
+    #[test]
+    fn cancel_token() -> Result<(), LuaError> {
+        get_lua()?.load(r#"
+            local token = task.cancel_token()
+            local child = token:child()
+
+            local promise = task.create(function()
+                return false, nil
+            end, child)
+
+            assert(promise:poll() == false)
+
+            token:cancel()
+
+            assert(child.cancelled)
+            assert(promise:poll() == nil)
+        "#).exec()
+    }
+
+    #[test]
+    fn cancel_token_without_poll() {
+        let token = CancellationToken::new();
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let promise = Promise::new(PromiseValue::from_future({
+            let finished = finished.clone();
+
+            async move {
+                tasks::sleep(Duration::from_millis(300)).await;
+
+                finished.store(true, Ordering::SeqCst);
+
+                Ok(Box::new(|_: &Lua| Ok(LuaValue::Nil)) as TaskOutput)
+            }
+        })).with_token(Some(token.clone()));
+
+        token.cancel();
+
+        std::thread::sleep(Duration::from_millis(600));
+
+        assert!(!finished.load(Ordering::SeqCst));
+
+        drop(promise);
+    }
+
+    #[test]
+    fn channel() -> Result<(), LuaError> {
+        get_lua()?.load(r#"
+            local channel = task.channel(1)
+
+            assert(channel:try_send("a"))
+            assert(not channel:try_send("b"))
+
+            local send = channel:send("b")
+
+            assert(send:poll() == false)
+            assert(channel:recv():await() == "a")
+            assert(send:await())
+
+            channel:close()
+
+            assert(channel:recv():await() == "b")
+            assert(channel:recv():await() == nil)
+            assert(not channel:try_send("c"))
+        "#).exec()
+    }
+}
@@ -16,10 +16,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::collections::{HashSet, HashMap};
 use std::path::PathBuf;
 use std::borrow::Cow;
 use std::sync::mpsc::Sender;
+use std::num::NonZeroU32;
+use std::time::{Duration, Instant};
 
 use mlua::prelude::*;
 
@@ -28,17 +30,20 @@ use librqbit::{
     SessionOptions as TorrentSessionOptions,
     AddTorrent as AddTorrentInfo,
     AddTorrentOptions,
-    AddTorrentResponse,
-    CreateTorrentOptions,
-    Magnet
+    AddTorrentResponse
 };
 
 use librqbit::api::TorrentIdOrHash;
+use librqbit::limits::LimitsConfig;
+use librqbit::SessionPersistenceConfig;
+
+use serde_json::{json, Value as Json};
 
 use agl_core::tasks;
 
 use super::bytes::Bytes;
-use super::task_api::{Promise, PromiseValue, TaskOutput, task_output};
+use super::torrent_file::{TorrentFileOptions, TorrentVersion};
+use super::task_api::{Promise, PromiseValue, TaskOutput, CancellationToken, task_output};
 use super::*;
 
 #[derive(Debug, thiserror::Error)]
@@ -62,10 +67,392 @@ pub enum TorrentServerError {
     ReadMetadata(#[source] Box<dyn std::error::Error + Send + 'static>),
 
     #[error("failed to pause or resume a torrent: {0}")]
-    PauseOrResume(#[source] Box<dyn std::error::Error + Send + 'static>)
+    PauseOrResume(#[source] Box<dyn std::error::Error + Send + 'static>),
+
+    #[error("failed to update torrent files: {0}")]
+    UpdateFiles(#[source] Box<dyn std::error::Error + Send + 'static>),
+
+    #[error("invalid torrent files filter: {0}")]
+    InvalidFilesFilter(String),
+
+    #[error("torrent is owned by another module")]
+    NotOwner
+}
+
+/// Interval between torrents seeding limits checks.
+const LIMITS_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Name of the file within the session persistence folder which stores torrents
+/// output folders and limits.
+const SESSION_STATE_FILE: &str = "agl-torrents.json";
+
+/// Interval between packages resources torrents progress checks.
+#[cfg(feature = "packages-support")]
+const PACKAGES_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Packages resources torrents are removed and downloaded over HTTP if they
+/// didn't make any progress during this time.
+#[cfg(feature = "packages-support")]
+const PACKAGES_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Speed and seeding limits of torrents.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TorrentLimits {
+    /// Maximal download speed in bytes per second.
+    pub download_speed: Option<u32>,
+
+    /// Maximal upload speed in bytes per second.
+    pub upload_speed: Option<u32>,
+
+    /// Stop seeding when ratio of uploaded bytes to the torrent size reaches
+    /// this value.
+    pub seed_ratio: Option<f64>,
+
+    /// Stop seeding after this amount of seconds.
+    pub seed_time: Option<u64>
+}
+
+impl TorrentLimits {
+    /// Use values of the other limits for unset values.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            download_speed: self.download_speed.or(other.download_speed),
+            upload_speed: self.upload_speed.or(other.upload_speed),
+            seed_ratio: self.seed_ratio.or(other.seed_ratio),
+            seed_time: self.seed_time.or(other.seed_time)
+        }
+    }
+
+    fn to_json(self) -> Json {
+        json!({
+            "download_speed": self.download_speed,
+            "upload_speed": self.upload_speed,
+            "seed_ratio": self.seed_ratio,
+            "seed_time": self.seed_time
+        })
+    }
+
+    fn from_json(value: &Json) -> Self {
+        Self {
+            download_speed: value.get("download_speed")
+                .and_then(Json::as_u64)
+                .and_then(|value| u32::try_from(value).ok()),
+
+            upload_speed: value.get("upload_speed")
+                .and_then(Json::as_u64)
+                .and_then(|value| u32::try_from(value).ok()),
+
+            seed_ratio: value.get("seed_ratio")
+                .and_then(Json::as_f64),
+
+            seed_time: value.get("seed_time")
+                .and_then(Json::as_u64)
+        }
+    }
+
+    fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        // Speeds which don't fit into u32 are not limited anyway.
+        Ok(Self {
+            download_speed: value.get::<Option<u64>>("download_speed")?
+                .and_then(|value| u32::try_from(value).ok()),
+
+            upload_speed: value.get::<Option<u64>>("upload_speed")?
+                .and_then(|value| u32::try_from(value).ok()),
+
+            seed_ratio: value.get("seed_ratio")?,
+            seed_time: value.get("seed_time")?
+        })
+    }
+
+    fn to_limits_config(self) -> LimitsConfig {
+        LimitsConfig {
+            download_bps: self.download_speed.and_then(NonZeroU32::new),
+            upload_bps: self.upload_speed.and_then(NonZeroU32::new)
+        }
+    }
+}
+
+/// Options of adding a torrent to the torrent server.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TorrentAddOptions {
+    /// Additional trackers of the torrent.
+    pub trackers: Option<Vec<String>>,
+
+    /// Add torrent in paused state.
+    pub paused: bool,
+
+    /// Restart torrent if it's already added.
+    pub restart: bool,
+
+    /// Files of the torrent which should be downloaded. All files are
+    /// downloaded if unset.
+    pub files: Option<TorrentFilesFilter>,
+
+    /// Speed and seeding limits of the torrent.
+    pub limits: TorrentLimits,
+
+    /// Identifier of the module which adds the torrent. Torrents added by
+    /// modules can be managed only by the same modules from the runtime API,
+    /// and cannot be re-added by other modules.
+    pub owner: Option<String>
+}
+
+/// Files of a torrent which should be downloaded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TorrentFilesFilter {
+    /// Indices of the files within the torrent.
+    Indices(Vec<usize>),
+
+    /// Glob patterns of the files' relative paths.
+    Globs(Vec<String>)
+}
+
+impl TorrentFilesFilter {
+    fn from_lua(value: &LuaTable) -> Result<Self, LuaError> {
+        let values = value.sequence_values::<LuaValue>()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if values.iter().all(|value| value.is_integer() || value.is_number()) {
+            let indices = values.iter()
+                .map(|value| {
+                    let index = match value {
+                        LuaValue::Integer(index) => *index,
+                        LuaValue::Number(index) => *index as i64,
+
+                        _ => 0
+                    };
+
+                    // Lua indices start from 1.
+                    if index < 1 {
+                        return Err(LuaError::external("invalid torrent file index"));
+                    }
+
+                    Ok(index as usize - 1)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            return Ok(Self::Indices(indices));
+        }
+
+        if values.iter().all(LuaValue::is_string) {
+            let globs = values.iter()
+                .flat_map(LuaValue::as_string)
+                .map(|glob| glob.to_string_lossy())
+                .collect::<Vec<_>>();
+
+            return Ok(Self::Globs(globs));
+        }
+
+        Err(LuaError::external("torrent files filter must contain either file indices or glob patterns"))
+    }
+
+    /// Convert glob patterns into a single regex.
+    fn globs_regex(globs: &[String]) -> Result<String, TorrentServerError> {
+        let mut patterns = Vec::with_capacity(globs.len());
+
+        for glob in globs {
+            let glob = globset::GlobBuilder::new(glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|err| TorrentServerError::InvalidFilesFilter(err.to_string()))?;
+
+            // librqbit only accepts regexes matching valid UTF-8 strings,
+            // while globset disables unicode mode.
+            let regex = glob.regex()
+                .trim_start_matches("(?-u)");
+
+            patterns.push(format!("(?:{regex})"));
+        }
+
+        Ok(patterns.join("|"))
+    }
+
+    /// Get indices of the torrent files matching the filter.
+    fn select(&self, files: &[PathBuf]) -> Result<HashSet<usize>, TorrentServerError> {
+        match self {
+            Self::Indices(indices) => Ok(indices.iter().copied().collect()),
+
+            Self::Globs(globs) => {
+                let mut builder = globset::GlobSetBuilder::new();
+
+                for glob in globs {
+                    let glob = globset::GlobBuilder::new(glob)
+                        .literal_separator(true)
+                        .build()
+                        .map_err(|err| TorrentServerError::InvalidFilesFilter(err.to_string()))?;
+
+                    builder.add(glob);
+                }
+
+                let globs = builder.build()
+                    .map_err(|err| TorrentServerError::InvalidFilesFilter(err.to_string()))?;
+
+                Ok(files.iter()
+                    .enumerate()
+                    .filter(|(_, path)| globs.is_match(path))
+                    .map(|(i, _)| i)
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Torrent info which is not stored by the torrent session itself.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct TorrentState {
+    /// Folder where the torrent is downloaded.
+    output_folder: Option<PathBuf>,
+
+    /// Identifier of the module which added the torrent.
+    owner: Option<String>,
+
+    /// Torrent-specific limits.
+    limits: TorrentLimits,
+
+    /// Indices of the files selected for downloading. All the files are
+    /// downloaded if unset.
+    selected_files: Option<HashSet<usize>>,
+
+    /// Time when the torrent started seeding in the current session.
+    seeding_since: Option<Instant>,
+
+    /// Seeding limits were reached and the torrent was paused. Reset when
+    /// limits are changed.
+    limits_reached: bool
+}
+
+/// Torrents state stored alongside the torrent session.
+#[derive(Debug, Default, Clone)]
+struct TorrentServerState {
+    /// Path to the state file. If unset, state is not persistent.
+    path: Option<PathBuf>,
+
+    /// Limits applied to all the torrents.
+    global_limits: TorrentLimits,
+
+    /// States of torrents by their info hashes.
+    torrents: HashMap<String, TorrentState>
+}
+
+impl TorrentServerState {
+    fn load(path: Option<PathBuf>, global_limits: TorrentLimits) -> Self {
+        let mut state = Self {
+            path,
+            global_limits,
+            torrents: HashMap::new()
+        };
+
+        let Some(path) = &state.path else {
+            return state;
+        };
+
+        let Ok(content) = std::fs::read(path) else {
+            return state;
+        };
+
+        let Ok(Json::Object(torrents)) = serde_json::from_slice::<Json>(&content) else {
+            return state;
+        };
+
+        for (info_hash, torrent) in torrents {
+            state.torrents.insert(info_hash, TorrentState {
+                output_folder: torrent.get("output_folder")
+                    .and_then(Json::as_str)
+                    .map(PathBuf::from),
+
+                owner: torrent.get("owner")
+                    .and_then(Json::as_str)
+                    .map(String::from),
+
+                limits: torrent.get("limits")
+                    .map(TorrentLimits::from_json)
+                    .unwrap_or_default(),
+
+                selected_files: torrent.get("selected_files")
+                    .and_then(Json::as_array)
+                    .map(|files| {
+                        files.iter()
+                            .flat_map(Json::as_u64)
+                            .flat_map(|index| usize::try_from(index).ok())
+                            .collect()
+                    }),
+
+                ..TorrentState::default()
+            });
+        }
+
+        state
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let torrents = self.torrents.iter()
+            .map(|(info_hash, torrent)| {
+                (info_hash.clone(), json!({
+                    "output_folder": torrent.output_folder,
+                    "owner": torrent.owner,
+                    "limits": torrent.limits.to_json(),
+                    "selected_files": torrent.selected_files
+                }))
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        #[allow(unused)]
+        if let Err(err) = std::fs::write(path, Json::Object(torrents).to_string()) {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, ?path, "failed to save torrent server state");
+        }
+    }
+}
+
+/// Get relative paths of the torrent files.
+fn get_files(
+    handle: &librqbit::ManagedTorrent
+) -> Result<Vec<PathBuf>, TorrentServerError> {
+    handle.with_metadata(|metadata| {
+        metadata.file_infos.iter()
+            .map(|file| file.relative_filename.clone())
+            .collect::<Vec<_>>()
+    }).map_err(|err| TorrentServerError::ReadMetadata(err.into()))
+}
+
+/// Calculate estimated time of torrent downloading in seconds.
+fn get_eta(current: u64, total: u64, speed: u64) -> Option<u64> {
+    if current >= total {
+        return Some(0);
+    }
+
+    if speed == 0 {
+        return None;
+    }
+
+    Some((total - current).div_ceil(speed))
+}
+
+fn get_stats(stats: &librqbit::TorrentStats) -> TorrentStats {
+    let (download_speed, upload_speed) = stats.live.as_ref()
+        .map(|live| {
+            (
+                (live.download_speed.mbps * 1024.0 * 1024.0) as u64,
+                (live.upload_speed.mbps * 1024.0 * 1024.0) as u64
+            )
+        })
+        .unwrap_or_default();
+
+    TorrentStats {
+        current: stats.progress_bytes,
+        total: stats.total_bytes,
+        uploaded: stats.uploaded_bytes,
+        download_speed,
+        upload_speed,
+        eta: get_eta(stats.progress_bytes, stats.total_bytes, download_speed)
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TorrentServerOptions {
     /// Default torrents downloading folder.
     pub default_folder: PathBuf,
@@ -83,7 +470,14 @@ pub struct TorrentServerOptions {
     pub enable_dht: bool,
 
     /// Enable UPnP.
-    pub enable_upnp: bool
+    pub enable_upnp: bool,
+
+    /// Optional path to the folder where the torrents session is stored. If
+    /// set, then added torrents will be restored after the server restart.
+    pub persistence_folder: Option<PathBuf>,
+
+    /// Default speed and seeding limits of all the torrents.
+    pub limits: TorrentLimits
 }
 
 impl Default for TorrentServerOptions {
@@ -94,7 +488,9 @@ impl Default for TorrentServerOptions {
             trackers: HashSet::new(),
             blocklist_url: None,
             enable_dht: true,
-            enable_upnp: true
+            enable_upnp: true,
+            persistence_folder: None,
+            limits: TorrentLimits::default()
         }
     }
 }
@@ -132,7 +528,10 @@ pub struct TorrentFileInfo {
     pub path: PathBuf,
 
     /// Total size of the file.
-    pub size: u64
+    pub size: u64,
+
+    /// Whether the file is selected for downloading.
+    pub selected: bool
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -144,7 +543,17 @@ pub struct TorrentStats {
     pub total: u64,
 
     /// Amount of bytes uploaded in the current session.
-    pub uploaded: u64
+    pub uploaded: u64,
+
+    /// Current download speed in bytes per second.
+    pub download_speed: u64,
+
+    /// Current upload speed in bytes per second.
+    pub upload_speed: u64,
+
+    /// Estimated downloading time in seconds. `None` if the torrent is not
+    /// being downloaded.
+    pub eta: Option<u64>
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -164,6 +573,13 @@ pub struct TorrentInfo {
     /// Stats of the torrent.
     pub stats: TorrentStats,
 
+    /// Folder where the torrent is downloaded. It's unknown for torrents
+    /// added before the session persistence was enabled.
+    pub output_folder: Option<PathBuf>,
+
+    /// Identifier of the module which added the torrent.
+    pub owner: Option<String>,
+
     /// Whether the torrent is paused.
     pub paused: bool,
 
@@ -176,9 +592,7 @@ enum TorrentServerMsg {
     Add {
         torrent: Box<[u8]>,
         output_folder: PathBuf,
-        trackers: Option<Vec<String>>,
-        paused: bool,
-        restart: bool,
+        options: TorrentAddOptions,
         sender: Sender<Result<String, TorrentServerError>>
     },
 
@@ -195,7 +609,27 @@ enum TorrentServerMsg {
         info_hash: String,
         pause: bool,
         sender: Sender<Result<(), TorrentServerError>>
-    }
+    },
+
+    Delete {
+        info_hash: String,
+        delete_files: bool,
+        sender: Sender<Result<(), TorrentServerError>>
+    },
+
+    SelectFiles {
+        info_hash: String,
+        files: TorrentFilesFilter,
+        sender: Sender<Result<(), TorrentServerError>>
+    },
+
+    SetLimits {
+        info_hash: Option<String>,
+        limits: TorrentLimits,
+        sender: Sender<Result<(), TorrentServerError>>
+    },
+
+    CheckLimits
 }
 
 #[derive(Debug, Clone)]
@@ -206,11 +640,49 @@ impl TorrentServer {
     pub fn start(options: TorrentServerOptions) -> Self {
         let (sender, receiver) = std::sync::mpsc::channel();
 
-        tasks::spawn(async move {
+        // Periodically check torrents seeding limits.
+        {
+            let sender = sender.clone();
+
+            tasks::spawn(async move {
+                loop {
+                    tasks::sleep(LIMITS_CHECK_INTERVAL).await;
+
+                    if sender.send(TorrentServerMsg::CheckLimits).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        // Messages are received synchronously, so the server runs in its own
+        // thread to not block runtime workers used by the torrent session.
+        std::thread::spawn(move || tasks::block_on(async move {
+            #[allow(unused)]
+            if let Some(folder) = &options.persistence_folder
+                && let Err(err) = std::fs::create_dir_all(folder)
+            {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err, ?folder, "failed to create torrent session folder");
+            }
+
+            let mut state = TorrentServerState::load(
+                options.persistence_folder.as_ref()
+                    .map(|folder| folder.join(SESSION_STATE_FILE)),
+                options.limits
+            );
+
             let session = TorrentSession::new_with_opts(
                 options.default_folder,
                 TorrentSessionOptions {
-                    persistence: None,
+                    persistence: options.persistence_folder.as_ref()
+                        .map(|folder| SessionPersistenceConfig::Json {
+                            folder: Some(folder.clone())
+                        }),
+
+                    fastresume: options.persistence_folder.is_some(),
+
+                    ratelimits: options.limits.to_limits_config(),
 
                     socks_proxy_url: options.socks_proxy,
 
@@ -242,9 +714,14 @@ impl TorrentServer {
                     TorrentServerMsg::Add {
                         torrent,
                         output_folder,
-                        trackers,
-                        paused,
-                        restart,
+                        options: TorrentAddOptions {
+                            trackers,
+                            paused,
+                            restart,
+                            files,
+                            limits,
+                            owner
+                        },
                         sender
                     } => {
                         fn get_torrent_info<'a>(
@@ -294,17 +771,37 @@ impl TorrentServer {
                                     .collect()
                             });
 
-                        let options = AddTorrentOptions {
+                        let (only_files, only_files_regex) = match &files {
+                            Some(TorrentFilesFilter::Indices(indices)) => (Some(indices.clone()), None),
+
+                            Some(TorrentFilesFilter::Globs(globs)) => {
+                                match TorrentFilesFilter::globs_regex(globs) {
+                                    Ok(regex) => (None, Some(regex)),
+                                    Err(err) => {
+                                        let _ = sender.send(Err(err));
+
+                                        continue;
+                                    }
+                                }
+                            }
+
+                            None => (None, None)
+                        };
+
+                        let get_options = || AddTorrentOptions {
                             output_folder: Some(output_folder.to_string_lossy().to_string()),
                             overwrite: true,
                             defer_writes: Some(false),
                             trackers: Some(trackers.clone()),
                             paused,
+                            only_files: only_files.clone(),
+                            only_files_regex: only_files_regex.clone(),
+                            ratelimits: limits.to_limits_config(),
 
                             ..AddTorrentOptions::default()
                         };
 
-                        let mut torrent_handle = match session.add_torrent(info, Some(options)).await {
+                        let mut torrent_handle = match session.add_torrent(info, Some(get_options())).await {
                             Ok(torrent) => torrent,
                             Err(err) => {
                                 #[cfg(feature = "tracing")]
@@ -316,6 +813,17 @@ impl TorrentServer {
                             }
                         };
 
+                        // Modules can't take over torrents of other modules.
+                        if let AddTorrentResponse::AlreadyManaged(_, handle) = &torrent_handle
+                            && owner.is_some()
+                            && state.torrents.get(&handle.info_hash().as_string())
+                                .is_some_and(|torrent| torrent.owner != owner)
+                        {
+                            let _ = sender.send(Err(TorrentServerError::NotOwner));
+
+                            continue;
+                        }
+
                         if let AddTorrentResponse::AlreadyManaged(id, _) = torrent_handle && restart {
                             if let Err(err) = session.delete(id.into(), false).await {
                                 #[cfg(feature = "tracing")]
@@ -338,17 +846,7 @@ impl TorrentServer {
                                 }
                             };
 
-                            let options = AddTorrentOptions {
-                                output_folder: Some(output_folder.to_string_lossy().to_string()),
-                                overwrite: true,
-                                defer_writes: Some(false),
-                                trackers: Some(trackers),
-                                paused,
-
-                                ..AddTorrentOptions::default()
-                            };
-
-                            torrent_handle = match session.add_torrent(info, Some(options)).await {
+                            torrent_handle = match session.add_torrent(info, Some(get_options())).await {
                                 Ok(torrent_handle) => torrent_handle,
                                 Err(err) => {
                                     #[cfg(feature = "tracing")]
@@ -361,13 +859,45 @@ impl TorrentServer {
                             };
                         }
 
-                        let info_hash = match torrent_handle {
+                        let (info_hash, handle) = match torrent_handle {
                             AddTorrentResponse::Added(_, handle) |
-                            AddTorrentResponse::AlreadyManaged(_, handle) => handle.info_hash(),
-                            AddTorrentResponse::ListOnly(info) => info.info_hash
+                            AddTorrentResponse::AlreadyManaged(_, handle) => (handle.info_hash(), Some(handle)),
+                            AddTorrentResponse::ListOnly(info) => (info.info_hash, None)
+                        };
+
+                        let info_hash = info_hash.as_string();
+
+                        // Remember selected files to report them in the
+                        // torrent info.
+                        let selected_files = match (&files, &handle) {
+                            (Some(files), Some(handle)) => {
+                                get_files(handle)
+                                    .and_then(|paths| files.select(&paths))
+                                    .ok()
+                            }
+
+                            _ => None
                         };
 
-                        let _ = sender.send(Ok(info_hash.as_string()));
+                        // Keep the owner if the torrent is re-added by the
+                        // runtime host.
+                        let owner = owner.or_else(|| {
+                            state.torrents.get(&info_hash)
+                                .and_then(|torrent| torrent.owner.clone())
+                        });
+
+                        state.torrents.insert(info_hash.clone(), TorrentState {
+                            output_folder: Some(output_folder),
+                            owner,
+                            limits,
+                            selected_files,
+
+                            ..TorrentState::default()
+                        });
+
+                        state.save();
+
+                        let _ = sender.send(Ok(info_hash));
                     }
 
                     TorrentServerMsg::List { sender } => {
@@ -379,11 +909,7 @@ impl TorrentServer {
                                     TorrentListInfo {
                                         name: info.name(),
                                         info_hash: info.info_hash().as_string(),
-                                        stats: TorrentStats {
-                                            current: stats.progress_bytes,
-                                            total: stats.total_bytes,
-                                            uploaded: stats.uploaded_bytes
-                                        },
+                                        stats: get_stats(&stats),
                                         paused: info.is_paused(),
                                         finished: stats.finished
                                     }
@@ -427,13 +953,19 @@ impl TorrentServer {
                             }
                         }
 
+                        let torrent_state = state.torrents.get(&info.info_hash().as_string());
+
+                        let selected_files = torrent_state
+                            .and_then(|torrent| torrent.selected_files.as_ref());
+
                         let mut files = Vec::new();
 
                         let result = info.with_metadata(|metadata| {
-                            for file in &metadata.file_infos {
+                            for (i, file) in metadata.file_infos.iter().enumerate() {
                                 files.push(TorrentFileInfo {
                                     path: file.relative_filename.clone(),
-                                    size: file.len
+                                    size: file.len,
+                                    selected: selected_files.is_none_or(|files| files.contains(&i))
                                 });
                             }
                         });
@@ -456,11 +988,11 @@ impl TorrentServer {
                                 .collect(),
                             peers: peers.into_boxed_slice(),
                             files: files.into_boxed_slice(),
-                            stats: TorrentStats {
-                                current: stats.progress_bytes,
-                                total: stats.total_bytes,
-                                uploaded: stats.uploaded_bytes
-                            },
+                            stats: get_stats(&stats),
+                            output_folder: torrent_state
+                                .and_then(|torrent| torrent.output_folder.clone()),
+                            owner: torrent_state
+                                .and_then(|torrent| torrent.owner.clone()),
                             paused: info.is_paused(),
                             finished: stats.finished
                         })));
@@ -507,78 +1039,243 @@ impl TorrentServer {
 
                         let _ = sender.send(Ok(()));
                     }
-                }
-            }
-        });
 
-        Self(sender)
-    }
+                    TorrentServerMsg::Delete {
+                        info_hash,
+                        delete_files,
+                        sender
+                    } => {
+                        let info_hash = match TorrentIdOrHash::parse(&info_hash) {
+                            Ok(info_hash) => info_hash,
 
-    /// Try to add torrent file, magnet link or info hash to the downloading
-    /// queue. If succeeded - return info hash string of the added torrent.
-    pub fn add_torrent(
-        &self,
-        torrent: Box<[u8]>,
-        output_folder: PathBuf,
-        trackers: Option<Vec<String>>,
-        paused: bool,
-        restart: bool
-    ) -> Result<String, TorrentServerError> {
-        let (sender, receiver) = std::sync::mpsc::channel();
+                            Err(err) => {
+                                #[cfg(feature = "tracing")]
+                                tracing::error!(?err, "failed to parse torrent info hash");
 
-        let result = self.0.send(TorrentServerMsg::Add {
-            torrent,
-            output_folder,
-            trackers,
-            paused,
-            restart,
-            sender
-        });
+                                let _ = sender.send(Err(TorrentServerError::InvalidInfoHash(err.into())));
 
-        if result.is_err() {
-            return Err(TorrentServerError::ServerIsOffline);
-        }
+                                continue;
+                            }
+                        };
 
-        receiver.recv()
-            .map_err(|_| TorrentServerError::ServerIsOffline)?
-    }
+                        let Some(info) = session.get(info_hash) else {
+                            let _ = sender.send(Ok(()));
 
-    /// Try to get list of info hashes of all the added torrents.
-    pub fn list(&self) -> Result<Box<[TorrentListInfo]>, TorrentServerError> {
-        let (sender, receiver) = std::sync::mpsc::channel();
+                            continue;
+                        };
 
-        let result = self.0.send(TorrentServerMsg::List {
-            sender
-        });
+                        if let Err(err) = session.delete(info_hash, delete_files).await {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!(?err, ?info_hash, ?delete_files, "failed to delete torrent");
 
-        if result.is_err() {
-            return Err(TorrentServerError::ServerIsOffline);
-        }
+                            let _ = sender.send(Err(TorrentServerError::DeleteTorrent(err.into())));
 
-        receiver.recv()
-            .map_err(|_| TorrentServerError::ServerIsOffline)
-    }
+                            continue;
+                        }
 
-    /// Try to get information about added torrent file with provided info hash.
-    /// Return `Ok(None)` if there's no torrent with provided info hash.
-    pub fn get_info(
-        &self,
-        info_hash: impl ToString
-    ) -> Result<Option<TorrentInfo>, TorrentServerError> {
-        let (sender, receiver) = std::sync::mpsc::channel();
+                        state.torrents.remove(&info.info_hash().as_string());
+                        state.save();
 
-        let result = self.0.send(TorrentServerMsg::GetInfo {
-            info_hash: info_hash.to_string(),
-            sender
-        });
+                        let _ = sender.send(Ok(()));
+                    }
 
-        if result.is_err() {
-            return Err(TorrentServerError::ServerIsOffline);
-        }
+                    TorrentServerMsg::SelectFiles {
+                        info_hash,
+                        files,
+                        sender
+                    } => {
+                        let info_hash = match TorrentIdOrHash::parse(&info_hash) {
+                            Ok(info_hash) => info_hash,
 
-        receiver.recv()
-            .map_err(|_| TorrentServerError::ServerIsOffline)?
-    }
+                            Err(err) => {
+                                #[cfg(feature = "tracing")]
+                                tracing::error!(?err, "failed to parse torrent info hash");
+
+                                let _ = sender.send(Err(TorrentServerError::InvalidInfoHash(err.into())));
+
+                                continue;
+                            }
+                        };
+
+                        let Some(info) = session.get(info_hash) else {
+                            let _ = sender.send(Ok(()));
+
+                            continue;
+                        };
+
+                        let selected_files = match get_files(&info).and_then(|paths| files.select(&paths)) {
+                            Ok(selected_files) => selected_files,
+                            Err(err) => {
+                                let _ = sender.send(Err(err));
+
+                                continue;
+                            }
+                        };
+
+                        if let Err(err) = session.update_only_files(&info, &selected_files).await {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!(?err, ?info_hash, "failed to update torrent files");
+
+                            let _ = sender.send(Err(TorrentServerError::UpdateFiles(err.into())));
+
+                            continue;
+                        }
+
+                        state.torrents.entry(info.info_hash().as_string())
+                            .or_default()
+                            .selected_files = Some(selected_files);
+
+                        state.save();
+
+                        let _ = sender.send(Ok(()));
+                    }
+
+                    TorrentServerMsg::SetLimits {
+                        info_hash,
+                        limits,
+                        sender
+                    } => {
+                        match info_hash {
+                            Some(info_hash) => {
+                                let torrent = state.torrents.entry(info_hash)
+                                    .or_default();
+
+                                torrent.limits = limits;
+                                torrent.limits_reached = false;
+                            }
+
+                            None => {
+                                state.global_limits = limits;
+
+                                session.ratelimits.set_download_bps(limits.download_speed.and_then(NonZeroU32::new));
+                                session.ratelimits.set_upload_bps(limits.upload_speed.and_then(NonZeroU32::new));
+
+                                for torrent in state.torrents.values_mut() {
+                                    torrent.limits_reached = false;
+                                }
+                            }
+                        }
+
+                        state.save();
+
+                        let _ = sender.send(Ok(()));
+                    }
+
+                    TorrentServerMsg::CheckLimits => {
+                        let torrents = session.with_torrents(|torrents| {
+                            torrents.into_iter()
+                                .map(|(_, handle)| handle.clone())
+                                .collect::<Vec<_>>()
+                        });
+
+                        for handle in torrents {
+                            let stats = handle.stats();
+
+                            let torrent = state.torrents.entry(handle.info_hash().as_string())
+                                .or_default();
+
+                            if !stats.finished || handle.is_paused() {
+                                torrent.seeding_since = None;
+
+                                continue;
+                            }
+
+                            if torrent.limits_reached {
+                                continue;
+                            }
+
+                            let limits = torrent.limits.or(state.global_limits);
+
+                            let seeding_since = *torrent.seeding_since
+                                .get_or_insert_with(Instant::now);
+
+                            let ratio_reached = limits.seed_ratio.is_some_and(|ratio| {
+                                stats.total_bytes > 0 && stats.uploaded_bytes as f64 / stats.total_bytes as f64 >= ratio
+                            });
+
+                            let time_reached = limits.seed_time.is_some_and(|time| {
+                                seeding_since.elapsed().as_secs() >= time
+                            });
+
+                            if ratio_reached || time_reached {
+                                torrent.limits_reached = true;
+
+                                #[allow(unused)]
+                                if let Err(err) = session.pause(&handle).await {
+                                    #[cfg(feature = "tracing")]
+                                    tracing::error!(?err, "failed to pause torrent after reaching seeding limits");
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }));
+
+        Self(sender)
+    }
+
+    /// Try to add torrent file, magnet link or info hash to the downloading
+    /// queue. If succeeded - return info hash string of the added torrent.
+    pub fn add_torrent(
+        &self,
+        torrent: Box<[u8]>,
+        output_folder: PathBuf,
+        options: TorrentAddOptions
+    ) -> Result<String, TorrentServerError> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let result = self.0.send(TorrentServerMsg::Add {
+            torrent,
+            output_folder,
+            options,
+            sender
+        });
+
+        if result.is_err() {
+            return Err(TorrentServerError::ServerIsOffline);
+        }
+
+        receiver.recv()
+            .map_err(|_| TorrentServerError::ServerIsOffline)?
+    }
+
+    /// Try to get list of info hashes of all the added torrents.
+    pub fn list(&self) -> Result<Box<[TorrentListInfo]>, TorrentServerError> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let result = self.0.send(TorrentServerMsg::List {
+            sender
+        });
+
+        if result.is_err() {
+            return Err(TorrentServerError::ServerIsOffline);
+        }
+
+        receiver.recv()
+            .map_err(|_| TorrentServerError::ServerIsOffline)
+    }
+
+    /// Try to get information about added torrent file with provided info hash.
+    /// Return `Ok(None)` if there's no torrent with provided info hash.
+    pub fn get_info(
+        &self,
+        info_hash: impl ToString
+    ) -> Result<Option<TorrentInfo>, TorrentServerError> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let result = self.0.send(TorrentServerMsg::GetInfo {
+            info_hash: info_hash.to_string(),
+            sender
+        });
+
+        if result.is_err() {
+            return Err(TorrentServerError::ServerIsOffline);
+        }
+
+        receiver.recv()
+            .map_err(|_| TorrentServerError::ServerIsOffline)?
+    }
 
     /// Try to pause or resume torrent downloading and seeding.
     pub fn pause_or_resume(
@@ -601,6 +1298,191 @@ impl TorrentServer {
         receiver.recv()
             .map_err(|_| TorrentServerError::ServerIsOffline)?
     }
+
+    /// Try to delete torrent from the session, optionally with its downloaded
+    /// files.
+    pub fn delete(
+        &self,
+        info_hash: impl ToString,
+        delete_files: bool
+    ) -> Result<(), TorrentServerError> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let result = self.0.send(TorrentServerMsg::Delete {
+            info_hash: info_hash.to_string(),
+            delete_files,
+            sender
+        });
+
+        if result.is_err() {
+            return Err(TorrentServerError::ServerIsOffline);
+        }
+
+        receiver.recv()
+            .map_err(|_| TorrentServerError::ServerIsOffline)?
+    }
+
+    /// Try to change list of torrent files which should be downloaded.
+    pub fn select_files(
+        &self,
+        info_hash: impl ToString,
+        files: TorrentFilesFilter
+    ) -> Result<(), TorrentServerError> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let result = self.0.send(TorrentServerMsg::SelectFiles {
+            info_hash: info_hash.to_string(),
+            files,
+            sender
+        });
+
+        if result.is_err() {
+            return Err(TorrentServerError::ServerIsOffline);
+        }
+
+        receiver.recv()
+            .map_err(|_| TorrentServerError::ServerIsOffline)?
+    }
+
+    /// Try to change seeding limits of the torrent with provided info hash, or
+    /// global speed and seeding limits if no info hash is given.
+    pub fn set_limits(
+        &self,
+        info_hash: Option<String>,
+        limits: TorrentLimits
+    ) -> Result<(), TorrentServerError> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let result = self.0.send(TorrentServerMsg::SetLimits {
+            info_hash,
+            limits,
+            sender
+        });
+
+        if result.is_err() {
+            return Err(TorrentServerError::ServerIsOffline);
+        }
+
+        receiver.recv()
+            .map_err(|_| TorrentServerError::ServerIsOffline)?
+    }
+}
+
+#[cfg(feature = "packages-support")]
+impl agl_packages::torrent::TorrentSource for TorrentServer {
+    fn download(
+        &self,
+        torrent: &str,
+        webseeds: &[String],
+        output_folder: &std::path::Path
+    ) -> Result<PathBuf, agl_packages::torrent::TorrentSourceError> {
+        let mut torrent = torrent.to_string();
+
+        // Clients which support BEP 19 will use webseeds from the magnet link.
+        if torrent.starts_with("magnet:") {
+            for webseed in webseeds {
+                torrent.push_str("&ws=");
+                torrent.push_str(&super::torrent_file::percent_encode(webseed));
+            }
+        }
+
+        std::fs::create_dir_all(output_folder)?;
+
+        // Magnet links may never resolve their metadata if there are no
+        // peers, so adding is limited by the stall timeout as well.
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        {
+            let server = self.clone();
+            let output_folder = output_folder.to_path_buf();
+
+            std::thread::spawn(move || {
+                let _ = sender.send(server.add_torrent(
+                    torrent.into_bytes().into_boxed_slice(),
+                    output_folder,
+                    TorrentAddOptions {
+                        restart: true,
+                        ..TorrentAddOptions::default()
+                    }
+                ));
+            });
+        }
+
+        let info_hash = receiver.recv_timeout(PACKAGES_STALL_TIMEOUT)
+            .map_err(|_| "failed to resolve torrent metadata")?
+            .map_err(|err| err.to_string())?;
+
+        let mut current = 0;
+        let mut last_progress = Instant::now();
+
+        loop {
+            std::thread::sleep(PACKAGES_POLL_INTERVAL);
+
+            let info = self.get_info(&info_hash)
+                .map_err(|err| err.to_string())?
+                .ok_or("torrent was removed")?;
+
+            if info.finished {
+                self.delete(&info_hash, false)
+                    .map_err(|err| err.to_string())?;
+
+                let [file] = info.files.as_ref() else {
+                    return Err("torrent must contain a single file".into());
+                };
+
+                return Ok(output_folder.join(&file.path));
+            }
+
+            if info.stats.current > current {
+                current = info.stats.current;
+                last_progress = Instant::now();
+            }
+
+            else if last_progress.elapsed() > PACKAGES_STALL_TIMEOUT {
+                let _ = self.delete(&info_hash, true);
+
+                return Err("torrent downloading has stalled".into());
+            }
+        }
+    }
+}
+
+/// Get identifier of the module used as the owner of its torrents.
+#[inline]
+fn module_owner(module_context: &ModuleContext) -> String {
+    module_context.module_dir.to_string_lossy().to_string()
+}
+
+/// Check that the torrent with provided info hash was added by the module.
+/// Unknown torrents are allowed.
+fn check_owner(
+    torrent_server: &TorrentServer,
+    info_hash: &str,
+    owner: &str
+) -> Result<Option<TorrentInfo>, LuaError> {
+    let info = torrent_server.get_info(info_hash)
+        .map_err(|err| LuaError::external(err.to_string()))?;
+
+    if let Some(info) = &info
+        && info.owner.as_deref() != Some(owner)
+    {
+        return Err(LuaError::external(TorrentServerError::NotOwner.to_string()));
+    }
+
+    Ok(info)
+}
+
+fn stats_to_lua(lua: &Lua, stats: &TorrentStats) -> Result<LuaTable, LuaError> {
+    let table = lua.create_table_with_capacity(0, 6)?;
+
+    table.raw_set("current", stats.current)?;
+    table.raw_set("total", stats.total)?;
+    table.raw_set("uploaded", stats.uploaded)?;
+    table.raw_set("download_speed", stats.download_speed)?;
+    table.raw_set("upload_speed", stats.upload_speed)?;
+    table.raw_set("eta", stats.eta)?;
+
+    Ok(table)
 }
 
 pub struct TorrentApi {
@@ -610,8 +1492,11 @@ pub struct TorrentApi {
     torrent_add: LuaFunctionBuilder,
     torrent_list: LuaFunction,
     torrent_info: LuaFunction,
-    torrent_pause: LuaFunction,
-    torrent_resume: LuaFunction
+    torrent_pause: LuaFunctionBuilder,
+    torrent_resume: LuaFunctionBuilder,
+    torrent_delete: LuaFunctionBuilder,
+    torrent_select_files: LuaFunctionBuilder,
+    torrent_set_limits: LuaFunctionBuilder
 }
 
 impl TorrentApi {
@@ -623,25 +1508,45 @@ impl TorrentApi {
         Ok(Self {
             torrent_create: {
                 let api_context = api_context.clone();
+                let torrent_server = server.clone();
 
                 Box::new(move |lua: &Lua, module_context: &ModuleContext| {
                     let api_context = api_context.clone();
                     let module_context = module_context.clone();
+                    let torrent_server = torrent_server.clone();
 
                     lua.create_function(move |lua: &Lua, (mut path, options): (PathBuf, Option<LuaTable>)| {
-                        let mut name = None;
-                        let mut piece_size = None;
-                        let mut trackers = vec![];
+                        let mut torrent_options = TorrentFileOptions::default();
+                        let mut seed = false;
 
                         if let Some(options) = options {
-                            name = options.get::<Option<String>>("name")?;
-                            piece_size = options.get::<Option<u32>>("piece_size")?;
+                            torrent_options.name = options.get::<Option<String>>("name")?;
+                            torrent_options.piece_size = options.get::<Option<u32>>("piece_size")?;
 
                             if let Some(opt_trackers) = options.get::<Option<Vec<String>>>("trackers")? {
-                                trackers = opt_trackers;
+                                torrent_options.trackers = opt_trackers;
+                            }
+
+                            if let Some(opt_private) = options.get::<Option<bool>>("private")? {
+                                torrent_options.private = opt_private;
+                            }
+
+                            if let Some(opt_version) = options.get::<Option<LuaString>>("version")? {
+                                torrent_options.version = opt_version.to_string_lossy()
+                                    .parse::<TorrentVersion>()
+                                    .map_err(|err| LuaError::external(err.to_string()))?;
+                            }
+
+                            if let Some(opt_seed) = options.get::<Option<bool>>("seed")? {
+                                seed = opt_seed;
                             }
                         }
 
+                        // librqbit can only work with v1 metadata.
+                        if seed && !torrent_options.version.has_v1() {
+                            return Err(LuaError::external("v2 torrents cannot be seeded, use hybrid torrents instead"));
+                        }
+
                         if path.is_relative() {
                             path = module_context.module_dir.join(path);
                         }
@@ -659,38 +1564,49 @@ impl TorrentApi {
                             return Err(LuaError::external("no path read permissions"));
                         }
 
-                        let value = PromiseValue::from_future(async move {
-                            let result = librqbit::create_torrent(
-                                &path,
-                                CreateTorrentOptions {
-                                    name: name.as_deref(),
-                                    piece_length: piece_size
-                                }
-                            ).await;
-
-                            let torrent = result.map_err(|err| {
-                                LuaError::external(format!("failed to create torrent: {err}"))
-                            })?;
-
-                            let mut magnet = Magnet::from_id20(
-                                torrent.info_hash(),
-                                trackers,
-                                None
-                            );
-
-                            magnet.name = name.clone();
+                        let torrent_server = torrent_server.clone();
+                        let owner = module_owner(&module_context);
 
-                            let content = torrent.as_bytes()
+                        let value = PromiseValue::from_blocking(move || {
+                            let torrent = super::torrent_file::create_torrent(&path, &torrent_options)
                                 .map_err(|err| {
-                                    LuaError::external(format!("failed to get torrent file content: {err}"))
+                                    LuaError::external(format!("failed to create torrent: {err}"))
                                 })?;
 
+                            if seed {
+                                // Torrent files are stored directly in the
+                                // output folder, and single file torrents are
+                                // stored under their name.
+                                let output_folder = if path.is_file() {
+                                    path.parent()
+                                        .map(PathBuf::from)
+                                        .unwrap_or_default()
+                                } else {
+                                    path.clone()
+                                };
+
+                                torrent_server.add_torrent(
+                                    torrent.content.clone().into_boxed_slice(),
+                                    output_folder,
+                                    TorrentAddOptions {
+                                        owner: Some(owner),
+                                        ..TorrentAddOptions::default()
+                                    }
+                                ).map_err(|err| {
+                                    LuaError::external(format!("failed to seed torrent: {err}"))
+                                })?;
+                            }
+
                             Ok(Box::new(move |lua: &Lua| {
-                                let result = lua.create_table_with_capacity(0, 3)?;
+                                let result = lua.create_table_with_capacity(0, 4)?;
 
-                                result.raw_set("info_hash", torrent.info_hash().as_string())?;
-                                result.raw_set("magnet", magnet.to_string())?;
-                                result.raw_set("content", content.to_vec())?;
+                                result.raw_set("info_hash", torrent.info_hash())?;
+                                result.raw_set("magnet", torrent.magnet())?;
+                                result.raw_set("content", torrent.content)?;
+
+                                if let Some(info_hash) = torrent.info_hash_v2 {
+                                    result.raw_set("info_hash_v2", hex::encode(info_hash))?;
+                                }
 
                                 Ok(LuaValue::Table(result))
                             }) as TaskOutput)
@@ -716,6 +1632,9 @@ impl TorrentApi {
                         let mut trackers = None;
                         let mut paused = false;
                         let mut restart = true;
+                        let mut files = None;
+                        let mut limits = TorrentLimits::default();
+                        let mut cancel = None;
 
                         if let Some(options) = options {
                             // New option name.
@@ -745,6 +1664,16 @@ impl TorrentApi {
                             if let Some(opt_restart) = options.get::<Option<bool>>("restart")? {
                                 restart = opt_restart;
                             }
+
+                            if let Some(opt_files) = options.get::<Option<LuaTable>>("files")? {
+                                files = Some(TorrentFilesFilter::from_lua(&opt_files)?);
+                            }
+
+                            if let Some(opt_limits) = options.get::<Option<LuaTable>>("limits")? {
+                                limits = TorrentLimits::from_lua(&opt_limits)?;
+                            }
+
+                            cancel = options.get::<Option<CancellationToken>>("cancel")?;
                         }
 
                         if output_dir.is_relative() {
@@ -765,20 +1694,46 @@ impl TorrentApi {
                         }
 
                         let torrent_server = torrent_server.clone();
+                        let owner = module_owner(&module_context);
+                        let promise_cancel = cancel.clone();
 
                         let value = PromiseValue::from_blocking(move || {
                             let result = torrent_server.add_torrent(
                                 (*torrent).clone(),
                                 output_dir,
-                                trackers,
-                                paused,
-                                restart
+                                TorrentAddOptions {
+                                    trackers,
+                                    paused,
+                                    restart,
+                                    files,
+                                    limits,
+                                    owner: Some(owner)
+                                }
                             );
 
                             let result = result.map_err(|err| {
                                 LuaError::external(err.to_string())
                             })?;
 
+                            // Pause the torrent when the token is cancelled.
+                            if let Some(cancel) = cancel {
+                                let info_hash = result.clone();
+
+                                tasks::spawn(async move {
+                                    cancel.cancelled().await;
+
+                                    let result = tasks::spawn_blocking(move || {
+                                        torrent_server.pause_or_resume(info_hash, true)
+                                    }).await;
+
+                                    #[allow(unused)]
+                                    if let Ok(Err(err)) = result {
+                                        #[cfg(feature = "tracing")]
+                                        tracing::warn!(?err, "failed to pause cancelled torrent");
+                                    }
+                                });
+                            }
+
                             Ok(Box::new(move |lua: &Lua| {
                                 lua.create_string(result.as_bytes())
                                     .map(LuaValue::String)
@@ -786,6 +1741,7 @@ impl TorrentApi {
                         });
 
                         Promise::new(value)
+                            .with_token(promise_cancel)
                             .into_lua(lua)
                     })
                 })
@@ -805,11 +1761,7 @@ impl TorrentApi {
                             let result = lua.create_table_with_capacity(torrents.len(), 0)?;
 
                             for torrent in torrents {
-                                let stats = lua.create_table_with_capacity(0, 3)?;
-
-                                stats.raw_set("current", torrent.stats.current)?;
-                                stats.raw_set("total", torrent.stats.total)?;
-                                stats.raw_set("uploaded", torrent.stats.uploaded)?;
+                                let stats = stats_to_lua(lua, &torrent.stats)?;
 
                                 let torrent_info = lua.create_table_with_capacity(0, 5)?;
 
@@ -860,27 +1812,25 @@ impl TorrentApi {
                             let files = lua.create_table_with_capacity(info.files.len(), 0)?;
 
                             for file in info.files {
-                                let file_info = lua.create_table_with_capacity(0, 2)?;
+                                let file_info = lua.create_table_with_capacity(0, 3)?;
 
                                 file_info.raw_set("path", file.path)?;
                                 file_info.raw_set("size", file.size)?;
+                                file_info.raw_set("selected", file.selected)?;
 
                                 files.raw_push(file_info)?;
                             }
 
-                            let stats = lua.create_table_with_capacity(0, 3)?;
-
-                            stats.raw_set("current", info.stats.current)?;
-                            stats.raw_set("total", info.stats.total)?;
-                            stats.raw_set("uploaded", info.stats.uploaded)?;
+                            let stats = stats_to_lua(lua, &info.stats)?;
 
-                            let result = lua.create_table_with_capacity(0, 7)?;
+                            let result = lua.create_table_with_capacity(0, 8)?;
 
                             result.raw_set("name", info.name)?;
                             result.raw_set("trackers", info.trackers)?;
                             result.raw_set("peers", peers)?;
                             result.raw_set("files", files)?;
                             result.raw_set("stats", stats)?;
+                            result.raw_set("output_directory", info.output_folder)?;
                             result.raw_set("paused", info.paused)?;
                             result.raw_set("finished", info.finished)?;
 
@@ -896,37 +1846,165 @@ impl TorrentApi {
             torrent_pause: {
                 let torrent_server = server.clone();
 
-                lua.create_function(move |lua: &Lua, info_hash: String| {
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
                     let torrent_server = torrent_server.clone();
+                    let owner = module_owner(module_context);
 
-                    let value = PromiseValue::from_blocking(move || {
-                        torrent_server.pause_or_resume(info_hash, true)
-                            .map_err(|err| LuaError::external(err.to_string()))?;
+                    lua.create_function(move |lua: &Lua, info_hash: String| {
+                        let torrent_server = torrent_server.clone();
+                        let owner = owner.clone();
 
-                        Ok(task_output(Ok(LuaValue::Nil)))
-                    });
+                        let value = PromiseValue::from_blocking(move || {
+                            check_owner(&torrent_server, &info_hash, &owner)?;
 
-                    Promise::new(value)
-                        .into_lua(lua)
-                })?
+                            torrent_server.pause_or_resume(info_hash, true)
+                                .map_err(|err| LuaError::external(err.to_string()))?;
+
+                            Ok(task_output(Ok(LuaValue::Nil)))
+                        });
+
+                        Promise::new(value)
+                            .into_lua(lua)
+                    })
+                })
             },
 
             torrent_resume: {
                 let torrent_server = server.clone();
 
-                lua.create_function(move |lua: &Lua, info_hash: String| {
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
                     let torrent_server = torrent_server.clone();
+                    let owner = module_owner(module_context);
 
-                    let value = PromiseValue::from_blocking(move || {
-                        torrent_server.pause_or_resume(info_hash, false)
-                            .map_err(|err| LuaError::external(err.to_string()))?;
+                    lua.create_function(move |lua: &Lua, info_hash: String| {
+                        let torrent_server = torrent_server.clone();
+                        let owner = owner.clone();
 
-                        Ok(task_output(Ok(LuaValue::Nil)))
-                    });
+                        let value = PromiseValue::from_blocking(move || {
+                            check_owner(&torrent_server, &info_hash, &owner)?;
 
-                    Promise::new(value)
-                        .into_lua(lua)
-                })?
+                            torrent_server.pause_or_resume(info_hash, false)
+                                .map_err(|err| LuaError::external(err.to_string()))?;
+
+                            Ok(task_output(Ok(LuaValue::Nil)))
+                        });
+
+                        Promise::new(value)
+                            .into_lua(lua)
+                    })
+                })
+            },
+
+            torrent_delete: {
+                let api_context = api_context.clone();
+                let torrent_server = server.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let api_context = api_context.clone();
+                    let module_context = module_context.clone();
+                    let torrent_server = torrent_server.clone();
+
+                    lua.create_function(move |lua: &Lua, (info_hash, delete_files): (String, Option<bool>)| {
+                        let api_context = api_context.clone();
+                        let module_context = module_context.clone();
+                        let torrent_server = torrent_server.clone();
+
+                        let delete_files = delete_files.unwrap_or(false);
+
+                        let value = PromiseValue::from_blocking(move || {
+                            let info = check_owner(&torrent_server, &info_hash, &module_owner(&module_context))?;
+
+                            // Torrent files can be deleted only if the module
+                            // can write to the torrent's output directory.
+                            if delete_files && let Some(info) = info {
+                                let Some(output_folder) = info.output_folder else {
+                                    return Err(LuaError::external("torrent output directory is unknown"));
+                                };
+
+                                let output_folder = normalize_path(output_folder, true)
+                                    .map_err(|err| {
+                                        LuaError::external(format!("failed to normalize output directory path: {err}"))
+                                    })?;
+
+                                if !api_context.can_access_path(&output_folder) {
+                                    return Err(LuaError::external("output directory path cannot be accessed"));
+                                }
+
+                                if !module_context.can_write_path(&output_folder) {
+                                    return Err(LuaError::external("no output directory write permissions"));
+                                }
+                            }
+
+                            torrent_server.delete(info_hash, delete_files)
+                                .map_err(|err| LuaError::external(err.to_string()))?;
+
+                            Ok(task_output(Ok(LuaValue::Nil)))
+                        });
+
+                        Promise::new(value)
+                            .into_lua(lua)
+                    })
+                })
+            },
+
+            torrent_select_files: {
+                let torrent_server = server.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let torrent_server = torrent_server.clone();
+                    let owner = module_owner(module_context);
+
+                    lua.create_function(move |lua: &Lua, (info_hash, files): (String, LuaTable)| {
+                        let torrent_server = torrent_server.clone();
+                        let owner = owner.clone();
+                        let files = TorrentFilesFilter::from_lua(&files)?;
+
+                        let value = PromiseValue::from_blocking(move || {
+                            check_owner(&torrent_server, &info_hash, &owner)?;
+
+                            torrent_server.select_files(info_hash, files)
+                                .map_err(|err| LuaError::external(err.to_string()))?;
+
+                            Ok(task_output(Ok(LuaValue::Nil)))
+                        });
+
+                        Promise::new(value)
+                            .into_lua(lua)
+                    })
+                })
+            },
+
+            torrent_set_limits: {
+                let torrent_server = server.clone();
+
+                Box::new(move |lua: &Lua, module_context: &ModuleContext| {
+                    let torrent_server = torrent_server.clone();
+                    let owner = module_owner(module_context);
+
+                    lua.create_function(move |lua: &Lua, (info_hash, limits): (Option<String>, LuaTable)| {
+                        // Global limits are the user's preference so they can
+                        // be changed only by the launcher.
+                        let Some(info_hash) = info_hash else {
+                            return Err(LuaError::external("global torrent limits cannot be changed by modules"));
+                        };
+
+                        let torrent_server = torrent_server.clone();
+                        let owner = owner.clone();
+                        let limits = TorrentLimits::from_lua(&limits)?;
+
+                        let value = PromiseValue::from_blocking(move || {
+                            check_owner(&torrent_server, &info_hash, &owner)?;
+
+                            torrent_server.set_limits(Some(info_hash), limits)
+                                .map_err(|err| LuaError::external(err.to_string()))?;
+
+                            Ok(task_output(Ok(LuaValue::Nil)))
+                        });
+
+                        Promise::new(value)
+                            .into_lua(lua)
+                    })
+                })
             },
 
             lua
@@ -938,15 +2016,285 @@ impl TorrentApi {
         &self,
         context: &ModuleContext
     ) -> Result<LuaTable, LuaError> {
-        let env = self.lua.create_table_with_capacity(0, 6)?;
+        let env = self.lua.create_table_with_capacity(0, 9)?;
 
         env.raw_set("create", (self.torrent_create)(&self.lua, context)?)?;
         env.raw_set("add", (self.torrent_add)(&self.lua, context)?)?;
         env.raw_set("list", &self.torrent_list)?;
         env.raw_set("info", &self.torrent_info)?;
-        env.raw_set("pause", &self.torrent_pause)?;
-        env.raw_set("resume", &self.torrent_resume)?;
+        env.raw_set("pause", (self.torrent_pause)(&self.lua, context)?)?;
+        env.raw_set("resume", (self.torrent_resume)(&self.lua, context)?)?;
+        env.raw_set("delete", (self.torrent_delete)(&self.lua, context)?)?;
+        env.raw_set("select_files", (self.torrent_select_files)(&self.lua, context)?)?;
+        env.raw_set("set_limits", (self.torrent_set_limits)(&self.lua, context)?)?;
 
         Ok(env)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::{Arc, RwLock};
+
+    use super::super::torrent_file::create_torrent;
+    use super::*;
+
+    fn prepare_dir(name: &str) -> std::io::Result<PathBuf> {
+        let path = std::env::temp_dir()
+            .join(".agl-runtime-torrent-api-test")
+            .join(name);
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+
+        std::fs::create_dir_all(&path)?;
+
+        Ok(path)
+    }
+
+    /// Create torrent of a folder with two files and return its content.
+    fn prepare_torrent(folder: &Path) -> std::io::Result<Box<[u8]>> {
+        std::fs::create_dir_all(folder)?;
+
+        std::fs::write(folder.join("a.txt"), "Hello, World!")?;
+        std::fs::write(folder.join("b.bin"), [1, 2, 3, 4, 5])?;
+
+        let torrent = create_torrent(folder, &TorrentFileOptions {
+            piece_size: Some(16384),
+            ..TorrentFileOptions::default()
+        })?;
+
+        Ok(torrent.content.into_boxed_slice())
+    }
+
+    fn start_server(path: &Path) -> TorrentServer {
+        TorrentServer::start(TorrentServerOptions {
+            default_folder: path.to_path_buf(),
+            enable_dht: false,
+            enable_upnp: false,
+            persistence_folder: Some(path.join("session")),
+            ..TorrentServerOptions::default()
+        })
+    }
+
+    fn paused() -> TorrentAddOptions {
+        TorrentAddOptions {
+            paused: true,
+            ..TorrentAddOptions::default()
+        }
+    }
+
+    /// Wait until files of the torrent are checked. Test torrents are always
+    /// fully downloaded.
+    fn wait_checked(server: &TorrentServer, info_hash: &str) -> Result<(), TorrentServerError> {
+        for _ in 0..100 {
+            if server.get_info(info_hash)?.is_some_and(|info| info.finished) {
+                return Ok(());
+            }
+
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        panic!("torrent files were not checked");
+    }
+
+    fn selected_files(
+        server: &TorrentServer,
+        info_hash: &str
+    ) -> Result<Vec<(PathBuf, bool)>, TorrentServerError> {
+        let mut files = server.get_info(info_hash)?
+            .map(|info| info.files.into_iter())
+            .into_iter()
+            .flatten()
+            .map(|file| (file.path, file.selected))
+            .collect::<Vec<_>>();
+
+        files.sort();
+
+        Ok(files)
+    }
+
+    #[test]
+    fn delete() -> Result<(), TorrentServerError> {
+        let path = prepare_dir("delete")?;
+        let folder = path.join("data");
+        let torrent = prepare_torrent(&folder)?;
+
+        let server = start_server(&path);
+
+        let info_hash = server.add_torrent(torrent.clone(), folder.clone(), paused())?;
+
+        assert!(server.get_info(&info_hash)?.is_some());
+
+        server.delete(&info_hash, false)?;
+
+        assert!(server.get_info(&info_hash)?.is_none());
+        assert!(server.list()?.is_empty());
+        assert!(folder.join("a.txt").exists());
+
+        // Deleting unknown torrents is a no-op.
+        server.delete(&info_hash, false)?;
+
+        let info_hash = server.add_torrent(torrent, folder.clone(), paused())?;
+
+        server.delete(&info_hash, true)?;
+
+        assert!(server.get_info(&info_hash)?.is_none());
+        assert!(!folder.join("a.txt").exists());
+        assert!(!folder.join("b.bin").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn select_files() -> Result<(), TorrentServerError> {
+        let path = prepare_dir("select_files")?;
+        let folder = path.join("data");
+        let torrent = prepare_torrent(&folder)?;
+
+        let server = start_server(&path);
+
+        let info_hash = server.add_torrent(torrent, folder, TorrentAddOptions {
+            files: Some(TorrentFilesFilter::Globs(vec![String::from("*.txt")])),
+            ..paused()
+        })?;
+
+        wait_checked(&server, &info_hash)?;
+
+        assert_eq!(selected_files(&server, &info_hash)?, [
+            (PathBuf::from("a.txt"), true),
+            (PathBuf::from("b.bin"), false)
+        ]);
+
+        server.select_files(&info_hash, TorrentFilesFilter::Globs(vec![String::from("*.bin")]))?;
+
+        assert_eq!(selected_files(&server, &info_hash)?, [
+            (PathBuf::from("a.txt"), false),
+            (PathBuf::from("b.bin"), true)
+        ]);
+
+        assert!(matches!(
+            server.select_files(&info_hash, TorrentFilesFilter::Globs(vec![String::from("[")])),
+            Err(TorrentServerError::InvalidFilesFilter(_))
+        ));
+
+        server.delete(&info_hash, false)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn limits() -> Result<(), Box<dyn std::error::Error>> {
+        let path = prepare_dir("limits")?;
+        let folder = path.join("data");
+        let torrent = prepare_torrent(&folder)?;
+
+        let server = start_server(&path);
+
+        let info_hash = server.add_torrent(torrent, folder, paused())?;
+
+        let limits = TorrentLimits {
+            download_speed: Some(1024),
+            seed_ratio: Some(1.5),
+            ..TorrentLimits::default()
+        };
+
+        server.set_limits(Some(info_hash.clone()), limits)?;
+        server.set_limits(None, TorrentLimits {
+            upload_speed: Some(2048),
+            ..TorrentLimits::default()
+        })?;
+
+        // Torrent limits are stored in the session state.
+        let state = TorrentServerState::load(
+            Some(path.join("session").join(SESSION_STATE_FILE)),
+            TorrentLimits::default()
+        );
+
+        assert_eq!(state.torrents[&info_hash].limits, limits);
+
+        // Values which don't fit into u32 are dropped.
+        let limits = TorrentLimits::from_json(&json!({
+            "download_speed": u64::MAX,
+            "upload_speed": 4096,
+            "seed_time": u64::MAX
+        }));
+
+        assert_eq!(limits, TorrentLimits {
+            download_speed: None,
+            upload_speed: Some(4096),
+            seed_ratio: None,
+            seed_time: Some(u64::MAX)
+        });
+
+        let lua = Lua::new();
+
+        let limits = TorrentLimits::from_lua(&lua.load("{ download_speed = 2 ^ 40, upload_speed = 512 }").eval()?)?;
+
+        assert_eq!(limits.download_speed, None);
+        assert_eq!(limits.upload_speed, Some(512));
+
+        server.delete(&info_hash, false)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn owner() -> Result<(), LuaError> {
+        let path = prepare_dir("owner")?;
+
+        let module_context = |name: &str| ModuleContext {
+            temp_dir: Arc::new(path.join(name)),
+            module_dir: Arc::new(path.join(name)),
+            persistent_dir: Arc::new(path.join(name)),
+            scope: Arc::new(RwLock::new(ModuleScope::default()))
+        };
+
+        let folder = path.join("a/data");
+        let torrent = prepare_torrent(&folder)?;
+
+        let lua = Lua::new();
+        let server = start_server(&path);
+        let api = TorrentApi::new(lua.clone(), ApiContext::default(), server.clone())?;
+
+        let env_a = api.create_env(&module_context("a"))?;
+        let env_b = api.create_env(&module_context("b"))?;
+
+        let call_await = |env: &LuaTable, name: &str, args: LuaMultiValue| {
+            env.call_function::<LuaAnyUserData>(name, args)?
+                .call_method::<LuaValue>("await", ())
+        };
+
+        let options = lua.create_table()?;
+
+        options.raw_set("output_directory", folder.to_string_lossy().to_string())?;
+        options.raw_set("paused", true)?;
+
+        let info_hash = call_await(&env_a, "add", (lua.create_string(&torrent)?, options.clone()).into_lua_multi(&lua)?)?;
+        let limits = lua.create_table()?;
+
+        wait_checked(&server, &info_hash.to_string()?)
+            .map_err(|err| LuaError::external(err.to_string()))?;
+
+        assert!(call_await(&env_b, "add", (lua.create_string(&torrent)?, options).into_lua_multi(&lua)?).is_err());
+
+        // Other modules can't manage the torrent.
+        for (name, args) in [
+            ("select_files", (info_hash.clone(), lua.create_sequence_from([1])?).into_lua_multi(&lua)?),
+            ("set_limits", (info_hash.clone(), limits.clone()).into_lua_multi(&lua)?),
+            ("resume", (info_hash.clone(),).into_lua_multi(&lua)?),
+            ("pause", (info_hash.clone(),).into_lua_multi(&lua)?),
+            ("delete", (info_hash.clone(), false).into_lua_multi(&lua)?)
+        ] {
+            assert!(call_await(&env_b, name, args.clone()).is_err());
+            assert!(call_await(&env_a, name, args).is_ok());
+        }
+
+        // Global limits can be changed only by the launcher.
+        assert!(env_a.call_function::<LuaValue>("set_limits", (LuaNil, limits)).is_err());
+
+        Ok(())
+    }
+}
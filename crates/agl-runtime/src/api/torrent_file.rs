@@ -0,0 +1,770 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-runtime
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! BitTorrent v1 (BEP 3), v2 (BEP 52) and hybrid torrent files builder.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::io::Read;
+
+use agl_core::hashes::{Hasher, HashAlgorithm};
+
+/// Size of the v2 merkle tree leaf block.
+const BLOCK_SIZE: usize = 16 * 1024;
+
+/// Minimal allowed piece size.
+const MIN_PIECE_SIZE: u32 = BLOCK_SIZE as u32;
+
+/// Maximal piece size which will be chosen automatically.
+const MAX_DEFAULT_PIECE_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Approximate amount of pieces which the automatically chosen piece size
+/// should produce.
+const TARGET_PIECES_AMOUNT: u64 = 2000;
+
+/// Version of the BitTorrent protocol used by a torrent file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TorrentVersion {
+    /// Original torrent format, supported by all the clients.
+    #[default]
+    V1,
+
+    /// BitTorrent v2 format with per-file merkle trees.
+    V2,
+
+    /// Torrent file which contains both v1 and v2 metadata.
+    Hybrid
+}
+
+impl TorrentVersion {
+    #[inline]
+    pub const fn has_v1(&self) -> bool {
+        matches!(self, Self::V1 | Self::Hybrid)
+    }
+
+    #[inline]
+    pub const fn has_v2(&self) -> bool {
+        matches!(self, Self::V2 | Self::Hybrid)
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::V1     => "v1",
+            Self::V2     => "v2",
+            Self::Hybrid => "hybrid"
+        }
+    }
+}
+
+impl std::fmt::Display for TorrentVersion {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for TorrentVersion {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" | "1" => Ok(Self::V1),
+            "v2" | "2" => Ok(Self::V2),
+            "hybrid" | "v1+v2" => Ok(Self::Hybrid),
+
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unknown torrent version: {s}")
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TorrentFileOptions {
+    /// Name of the torrent. Name of the source file or folder is used if unset.
+    pub name: Option<String>,
+
+    /// Size of a torrent piece in bytes. Must be a power of two not lower than
+    /// 16 KiB. Chosen automatically from the total files size if unset.
+    pub piece_size: Option<u32>,
+
+    /// List of trackers announce URLs.
+    pub trackers: Vec<String>,
+
+    /// Whether the torrent is private (BEP 27). Clients will not use DHT and
+    /// other peers discovery methods for private torrents.
+    pub private: bool,
+
+    /// Version of the torrent file.
+    pub version: TorrentVersion
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentFile {
+    /// Name of the torrent.
+    pub name: String,
+
+    /// List of trackers announce URLs.
+    pub trackers: Vec<String>,
+
+    /// SHA1 info hash of v1 and hybrid torrents.
+    pub info_hash_v1: Option<[u8; 20]>,
+
+    /// SHA256 info hash of v2 and hybrid torrents.
+    pub info_hash_v2: Option<[u8; 32]>,
+
+    /// Bencoded torrent file content.
+    pub content: Vec<u8>
+}
+
+impl TorrentFile {
+    /// Get hex-encoded info hash of the torrent. Returns v1 info hash for
+    /// hybrid torrents.
+    pub fn info_hash(&self) -> String {
+        match (&self.info_hash_v1, &self.info_hash_v2) {
+            (Some(hash), _) => hex::encode(hash),
+            (None, Some(hash)) => hex::encode(hash),
+            (None, None) => String::new()
+        }
+    }
+
+    /// Build magnet link of the torrent.
+    pub fn magnet(&self) -> String {
+        let mut magnet = String::from("magnet:?");
+        let mut params = Vec::with_capacity(self.trackers.len() + 3);
+
+        if let Some(hash) = &self.info_hash_v1 {
+            params.push(format!("xt=urn:btih:{}", hex::encode(hash)));
+        }
+
+        if let Some(hash) = &self.info_hash_v2 {
+            // 0x12 is the sha2-256 multihash code and 0x20 is the hash length.
+            params.push(format!("xt=urn:btmh:1220{}", hex::encode(hash)));
+        }
+
+        params.push(format!("dn={}", percent_encode(&self.name)));
+
+        for tracker in &self.trackers {
+            params.push(format!("tr={}", percent_encode(tracker)));
+        }
+
+        magnet.push_str(&params.join("&"));
+
+        magnet
+    }
+}
+
+/// Bencoded value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Bencode {
+    Int(i64),
+    Bytes(Vec<u8>),
+    List(Vec<Bencode>),
+    Dict(BTreeMap<Vec<u8>, Bencode>)
+}
+
+impl Bencode {
+    #[inline]
+    fn str(value: impl AsRef<str>) -> Self {
+        Self::Bytes(value.as_ref().as_bytes().to_vec())
+    }
+
+    fn dict<const N: usize>(pairs: [(&str, Bencode); N]) -> Self {
+        Self::Dict(pairs.into_iter()
+            .map(|(key, value)| (key.as_bytes().to_vec(), value))
+            .collect())
+    }
+
+    fn insert(&mut self, key: impl AsRef<[u8]>, value: Bencode) {
+        if let Self::Dict(dict) = self {
+            dict.insert(key.as_ref().to_vec(), value);
+        }
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Int(value) => {
+                buf.push(b'i');
+                buf.extend_from_slice(value.to_string().as_bytes());
+                buf.push(b'e');
+            }
+
+            Self::Bytes(value) => {
+                buf.extend_from_slice(value.len().to_string().as_bytes());
+                buf.push(b':');
+                buf.extend_from_slice(value);
+            }
+
+            Self::List(values) => {
+                buf.push(b'l');
+
+                for value in values {
+                    value.encode(buf);
+                }
+
+                buf.push(b'e');
+            }
+
+            // BTreeMap keeps keys sorted as required by the spec.
+            Self::Dict(values) => {
+                buf.push(b'd');
+
+                for (key, value) in values {
+                    Self::Bytes(key.clone()).encode(buf);
+                    value.encode(buf);
+                }
+
+                buf.push(b'e');
+            }
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        self.encode(&mut buf);
+
+        buf
+    }
+}
+
+/// File which will be stored in the torrent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SourceFile {
+    path: PathBuf,
+    components: Vec<String>,
+    length: u64
+}
+
+/// Recursively list files of the given folder, sorted by their relative paths.
+fn collect_files(root: &Path) -> std::io::Result<Vec<SourceFile>> {
+    fn walk(
+        root: &Path,
+        components: &[String],
+        files: &mut Vec<SourceFile>
+    ) -> std::io::Result<()> {
+        for entry in root.read_dir()? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+
+            let mut entry_components = components.to_vec();
+
+            entry_components.push(entry.file_name().to_string_lossy().to_string());
+
+            // Symlinks can't be represented in torrents so they're skipped.
+            if metadata.is_dir() {
+                walk(&entry.path(), &entry_components, files)?;
+            }
+
+            else if metadata.is_file() {
+                files.push(SourceFile {
+                    path: entry.path(),
+                    components: entry_components,
+                    length: metadata.len()
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+
+    walk(root, &[], &mut files)?;
+
+    files.sort_by(|a, b| a.components.cmp(&b.components));
+
+    Ok(files)
+}
+
+/// Choose piece size so that the torrent would have around
+/// `TARGET_PIECES_AMOUNT` pieces.
+fn default_piece_size(total_size: u64) -> u32 {
+    let mut piece_size = MIN_PIECE_SIZE;
+
+    while piece_size < MAX_DEFAULT_PIECE_SIZE && total_size / piece_size as u64 > TARGET_PIECES_AMOUNT {
+        piece_size *= 2;
+    }
+
+    piece_size
+}
+
+#[inline]
+fn sha1(buf: &[u8]) -> std::io::Result<[u8; 20]> {
+    let hash = Hasher::new(HashAlgorithm::Sha1).hash(buf)?;
+
+    hash.as_ref().try_into()
+        .map_err(std::io::Error::other)
+}
+
+#[inline]
+fn sha256(buf: &[u8]) -> std::io::Result<[u8; 32]> {
+    let hash = Hasher::new(HashAlgorithm::Sha2_256).hash(buf)?;
+
+    hash.as_ref().try_into()
+        .map_err(std::io::Error::other)
+}
+
+/// Calculate root of a merkle tree of the given width. Missing hashes are
+/// filled by the `pad` value.
+fn merkle_root(
+    mut hashes: Vec<[u8; 32]>,
+    width: usize,
+    pad: [u8; 32]
+) -> std::io::Result<[u8; 32]> {
+    hashes.resize(width.max(1), pad);
+
+    while hashes.len() > 1 {
+        let mut parents = Vec::with_capacity(hashes.len() / 2);
+
+        for pair in hashes.chunks_exact(2) {
+            parents.push(sha256(&[pair[0], pair[1]].concat())?);
+        }
+
+        hashes = parents;
+    }
+
+    Ok(hashes[0])
+}
+
+/// Merkle tree state of a single file of a v2 torrent.
+struct FileMerkleTree {
+    blocks_per_piece: usize,
+    piece_blocks: Vec<[u8; 32]>,
+    piece_layer: Vec<[u8; 32]>
+}
+
+impl FileMerkleTree {
+    fn new(piece_size: u32) -> Self {
+        let blocks_per_piece = piece_size as usize / BLOCK_SIZE;
+
+        Self {
+            blocks_per_piece,
+            piece_blocks: Vec::with_capacity(blocks_per_piece),
+            piece_layer: Vec::new()
+        }
+    }
+
+    fn push_block(&mut self, block: &[u8]) -> std::io::Result<()> {
+        self.piece_blocks.push(sha256(block)?);
+
+        if self.piece_blocks.len() == self.blocks_per_piece {
+            let blocks = std::mem::take(&mut self.piece_blocks);
+
+            self.piece_layer.push(merkle_root(blocks, self.blocks_per_piece, [0; 32])?);
+        }
+
+        Ok(())
+    }
+
+    /// Return pieces root hash and the piece layer of the file. Piece layer
+    /// is empty for files not larger than a single piece.
+    fn finalize(mut self) -> std::io::Result<([u8; 32], Vec<[u8; 32]>)> {
+        // Small file - root is calculated over the blocks directly.
+        if self.piece_layer.is_empty() {
+            let width = self.piece_blocks.len().next_power_of_two();
+
+            return Ok((merkle_root(self.piece_blocks, width, [0; 32])?, vec![]));
+        }
+
+        if !self.piece_blocks.is_empty() {
+            let blocks = std::mem::take(&mut self.piece_blocks);
+
+            self.piece_layer.push(merkle_root(blocks, self.blocks_per_piece, [0; 32])?);
+        }
+
+        // Root of a piece-sized subtree of zero blocks.
+        let mut pad = [0; 32];
+        let mut width = 1;
+
+        while width < self.blocks_per_piece {
+            pad = sha256(&[pad, pad].concat())?;
+            width *= 2;
+        }
+
+        let width = self.piece_layer.len().next_power_of_two();
+        let root = merkle_root(self.piece_layer.clone(), width, pad)?;
+
+        Ok((root, self.piece_layer))
+    }
+}
+
+/// Read as many bytes as possible into the buffer, returning amount of read
+/// bytes. Less bytes than the buffer's length is returned only on EOF.
+fn read_block(file: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut read = 0;
+
+    while read < buf.len() {
+        match file.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err)
+        }
+    }
+
+    Ok(read)
+}
+
+/// Percent-encode string for use in a magnet link.
+pub(crate) fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    encoded
+}
+
+/// Create torrent file from the given file or folder. This function reads
+/// and hashes all the files so it can take a lot of time.
+pub fn create_torrent(
+    path: impl AsRef<Path>,
+    options: &TorrentFileOptions
+) -> std::io::Result<TorrentFile> {
+    let path = path.as_ref();
+    let metadata = path.metadata()?;
+
+    // Single file torrents don't have path components.
+    let files = if metadata.is_file() {
+        vec![SourceFile {
+            path: path.to_path_buf(),
+            components: vec![],
+            length: metadata.len()
+        }]
+    } else {
+        collect_files(path)?
+    };
+
+    if files.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "no files to create torrent from"
+        ));
+    }
+
+    let name = match &options.name {
+        Some(name) => name.clone(),
+        None => path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "can't get torrent name from its path"
+            ))?
+    };
+
+    let total_size = files.iter()
+        .map(|file| file.length)
+        .sum::<u64>();
+
+    let piece_size = match options.piece_size {
+        Some(piece_size) => {
+            if piece_size < MIN_PIECE_SIZE || !piece_size.is_power_of_two() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "piece size must be a power of two not lower than 16 KiB"
+                ));
+            }
+
+            piece_size
+        }
+
+        None => default_piece_size(total_size)
+    };
+
+    let version = options.version;
+    let single_file = metadata.is_file();
+
+    let mut v1_pieces = Vec::new();
+    let mut v1_piece = Vec::with_capacity(piece_size as usize);
+    let mut v1_files = Vec::with_capacity(files.len());
+
+    let mut v2_file_tree = Bencode::Dict(BTreeMap::new());
+    let mut v2_piece_layers = Bencode::Dict(BTreeMap::new());
+
+    let mut block = vec![0; BLOCK_SIZE];
+
+    for (i, file) in files.iter().enumerate() {
+        let mut reader = std::fs::File::open(&file.path)?;
+        let mut tree = FileMerkleTree::new(piece_size);
+
+        let mut length = 0;
+
+        loop {
+            let n = read_block(&mut reader, &mut block)?;
+
+            if n == 0 {
+                break;
+            }
+
+            length += n as u64;
+
+            if version.has_v1() {
+                let mut block = &block[..n];
+
+                while !block.is_empty() {
+                    let m = block.len().min(piece_size as usize - v1_piece.len());
+
+                    v1_piece.extend_from_slice(&block[..m]);
+
+                    if v1_piece.len() == piece_size as usize {
+                        v1_pieces.extend_from_slice(&sha1(&v1_piece)?);
+                        v1_piece.clear();
+                    }
+
+                    block = &block[m..];
+                }
+            }
+
+            if version.has_v2() {
+                tree.push_block(&block[..n])?;
+            }
+
+            if n < BLOCK_SIZE {
+                break;
+            }
+        }
+
+        if length != file.length {
+            return Err(std::io::Error::other(format!(
+                "file {:?} was modified while creating the torrent",
+                file.path
+            )));
+        }
+
+        if version.has_v1() {
+            v1_files.push(Bencode::dict([
+                ("length", Bencode::Int(length as i64)),
+                ("path", Bencode::List(file.components.iter().map(Bencode::str).collect()))
+            ]));
+        }
+
+        // Hybrid torrents must align v1 files to pieces boundaries using
+        // padding files (BEP 47) so that v1 and v2 pieces match.
+        if version == TorrentVersion::Hybrid && i + 1 < files.len() && !v1_piece.is_empty() {
+            let padding = piece_size as usize - v1_piece.len();
+
+            v1_piece.resize(piece_size as usize, 0);
+            v1_pieces.extend_from_slice(&sha1(&v1_piece)?);
+            v1_piece.clear();
+
+            v1_files.push(Bencode::dict([
+                ("attr", Bencode::str("p")),
+                ("length", Bencode::Int(padding as i64)),
+                ("path", Bencode::List(vec![
+                    Bencode::str(".pad"),
+                    Bencode::str(padding.to_string())
+                ]))
+            ]));
+        }
+
+        if version.has_v2() {
+            let mut entry = Bencode::dict([
+                ("length", Bencode::Int(length as i64))
+            ]);
+
+            if length > 0 {
+                let (root, layer) = tree.finalize()?;
+
+                entry.insert("pieces root", Bencode::Bytes(root.to_vec()));
+
+                if !layer.is_empty() {
+                    v2_piece_layers.insert(root, Bencode::Bytes(layer.concat()));
+                }
+            }
+
+            // Single file v2 torrents store the file under the torrent name.
+            let components = if single_file {
+                vec![name.clone()]
+            } else {
+                file.components.clone()
+            };
+
+            let mut node = &mut v2_file_tree;
+
+            for component in components {
+                let Bencode::Dict(dict) = node else {
+                    unreachable!();
+                };
+
+                node = dict.entry(component.into_bytes())
+                    .or_insert_with(|| Bencode::Dict(BTreeMap::new()));
+            }
+
+            node.insert("", entry);
+        }
+    }
+
+    if !v1_piece.is_empty() {
+        v1_pieces.extend_from_slice(&sha1(&v1_piece)?);
+    }
+
+    let mut info = Bencode::dict([
+        ("name", Bencode::str(&name)),
+        ("piece length", Bencode::Int(piece_size as i64))
+    ]);
+
+    if options.private {
+        info.insert("private", Bencode::Int(1));
+    }
+
+    if version.has_v1() {
+        info.insert("pieces", Bencode::Bytes(v1_pieces));
+
+        if single_file {
+            info.insert("length", Bencode::Int(total_size as i64));
+        } else {
+            info.insert("files", Bencode::List(v1_files));
+        }
+    }
+
+    if version.has_v2() {
+        info.insert("meta version", Bencode::Int(2));
+        info.insert("file tree", v2_file_tree);
+    }
+
+    let info_bytes = info.to_bytes();
+
+    let mut torrent = Bencode::dict([
+        ("info", info)
+    ]);
+
+    if let Some(tracker) = options.trackers.first() {
+        torrent.insert("announce", Bencode::str(tracker));
+    }
+
+    if options.trackers.len() > 1 {
+        torrent.insert("announce-list", Bencode::List({
+            options.trackers.iter()
+                .map(|tracker| Bencode::List(vec![Bencode::str(tracker)]))
+                .collect()
+        }));
+    }
+
+    if version.has_v2() {
+        torrent.insert("piece layers", v2_piece_layers);
+    }
+
+    Ok(TorrentFile {
+        name,
+        trackers: options.trackers.clone(),
+        info_hash_v1: version.has_v1().then(|| sha1(&info_bytes)).transpose()?,
+        info_hash_v2: version.has_v2().then(|| sha256(&info_bytes)).transpose()?,
+        content: torrent.to_bytes()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prepare_dir(name: &str) -> std::io::Result<PathBuf> {
+        let path = std::env::temp_dir()
+            .join(".agl-runtime-torrent-file-test")
+            .join(name);
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+
+        std::fs::create_dir_all(&path)?;
+
+        Ok(path)
+    }
+
+    #[test]
+    fn single_file() -> std::io::Result<()> {
+        let path = prepare_dir("single")?.join("test.txt");
+
+        std::fs::write(&path, "Hello, World!")?;
+
+        let options = TorrentFileOptions {
+            piece_size: Some(16384),
+            ..TorrentFileOptions::default()
+        };
+
+        let torrent = create_torrent(&path, &options)?;
+
+        assert_eq!(torrent.info_hash(), "400931a76098eee6baaab11608d5463f1eb305b0");
+        assert_eq!(torrent.info_hash_v2, None);
+        assert_eq!(torrent.magnet(), "magnet:?xt=urn:btih:400931a76098eee6baaab11608d5463f1eb305b0&dn=test.txt");
+
+        let torrent = create_torrent(&path, &TorrentFileOptions {
+            version: TorrentVersion::V2,
+            ..options.clone()
+        })?;
+
+        assert_eq!(torrent.info_hash(), "f61c5d97c9fee4a73c648c06e518b91ef3ffc4ec7086554ce793bbe35af2d39a");
+
+        let torrent = create_torrent(&path, &TorrentFileOptions {
+            version: TorrentVersion::Hybrid,
+            ..options
+        })?;
+
+        assert_eq!(torrent.info_hash(), "5aadd20a8d1e818785ee1040bb73db48ec4cc3a3");
+        assert_eq!(torrent.info_hash_v2.map(hex::encode).as_deref(), Some("2c6691e1c5d3301c8b436341f31b20ea56ec06a252c3ef53fb3998b25f613832"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn v2_folder() -> std::io::Result<()> {
+        let path = prepare_dir("dir")?;
+
+        let content = (0..40000)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<u8>>();
+
+        std::fs::write(path.join("a.bin"), content)?;
+        std::fs::write(path.join("b.txt"), "Hello")?;
+
+        let torrent = create_torrent(&path, &TorrentFileOptions {
+            piece_size: Some(16384),
+            private: true,
+            version: TorrentVersion::V2,
+            ..TorrentFileOptions::default()
+        })?;
+
+        assert_eq!(torrent.info_hash(), "b400355a58ec182b5e8afc2d963cc28bd5935ca4cdfdf329c453fde994212f4b");
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_piece_size() -> std::io::Result<()> {
+        let path = prepare_dir("piece-size")?.join("test.txt");
+
+        std::fs::write(&path, "Hello, World!")?;
+
+        let options = TorrentFileOptions {
+            piece_size: Some(20000),
+            ..TorrentFileOptions::default()
+        };
+
+        assert!(create_torrent(&path, &options).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-runtime
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use mlua::prelude::*;
+
+use agl_core::version::{Version, VersionReq};
+
+fn parse_version(value: &str) -> Result<Version, LuaError> {
+    Version::from_str(value)
+        .map_err(LuaError::external)
+}
+
+fn parse_range(value: &str) -> Result<VersionReq, LuaError> {
+    VersionReq::from_str(value)
+        .map_err(LuaError::external)
+}
+
+pub struct VersionApi {
+    lua: Lua,
+
+    version_parse: LuaFunction,
+    version_compare: LuaFunction,
+    version_matches: LuaFunction,
+    version_sort: LuaFunction,
+    version_max: LuaFunction
+}
+
+impl VersionApi {
+    pub fn new(lua: Lua) -> Result<Self, LuaError> {
+        Ok(Self {
+            version_parse: lua.create_function(|lua: &Lua, value: String| {
+                let Ok(version) = Version::from_str(&value) else {
+                    return Ok(None);
+                };
+
+                let result = lua.create_table_with_capacity(0, 6)?;
+
+                result.raw_set("major", version.major())?;
+                result.raw_set("minor", version.minor())?;
+                result.raw_set("patch", version.patch())?;
+                result.raw_set("numbers", version.numbers().to_vec())?;
+
+                if version.is_prerelease() {
+                    let pre = version.pre().iter()
+                        .map(|identifier| identifier.to_string())
+                        .collect::<Vec<_>>();
+
+                    result.raw_set("pre", pre.join("."))?;
+                }
+
+                result.raw_set("build", version.build())?;
+
+                Ok(Some(result))
+            })?,
+
+            version_compare: lua.create_function(|_lua: &Lua, (a, b): (String, String)| {
+                let ordering = parse_version(&a)?.cmp(&parse_version(&b)?);
+
+                Ok(match ordering {
+                    Ordering::Less => -1,
+                    Ordering::Equal => 0,
+                    Ordering::Greater => 1
+                })
+            })?,
+
+            version_matches: lua.create_function(|_lua: &Lua, (version, range): (String, String)| {
+                Ok(parse_range(&range)?.matches(&parse_version(&version)?))
+            })?,
+
+            version_sort: lua.create_function(|_lua: &Lua, (versions, descending): (Vec<String>, Option<bool>)| {
+                let mut versions = versions.into_iter()
+                    .map(|value| parse_version(&value).map(|version| (version, value)))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                versions.sort_by(|a, b| a.0.cmp(&b.0));
+
+                if descending.unwrap_or(false) {
+                    versions.reverse();
+                }
+
+                Ok(versions.into_iter()
+                    .map(|(_, value)| value)
+                    .collect::<Vec<_>>())
+            })?,
+
+            version_max: lua.create_function(|_lua: &Lua, (versions, range): (Vec<String>, Option<String>)| {
+                let range = range.as_deref()
+                    .map(parse_range)
+                    .transpose()?;
+
+                let mut max: Option<(Version, String)> = None;
+
+                for value in versions {
+                    // Invalid versions are skipped so this function can be
+                    // used on lists of arbitrary tags or file names.
+                    let Ok(version) = Version::from_str(&value) else {
+                        continue;
+                    };
+
+                    if let Some(range) = &range && !range.matches(&version) {
+                        continue;
+                    }
+
+                    if max.as_ref().is_none_or(|(max, _)| &version > max) {
+                        max = Some((version, value));
+                    }
+                }
+
+                Ok(max.map(|(_, value)| value))
+            })?,
+
+            lua
+        })
+    }
+
+    /// Create new lua table with API functions.
+    pub fn create_env(&self) -> Result<LuaTable, LuaError> {
+        let env = self.lua.create_table_with_capacity(0, 5)?;
+
+        env.raw_set("parse", &self.version_parse)?;
+        env.raw_set("compare", &self.version_compare)?;
+        env.raw_set("matches", &self.version_matches)?;
+        env.raw_set("sort", &self.version_sort)?;
+        env.raw_set("max", &self.version_max)?;
+
+        Ok(env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() -> Result<(), LuaError> {
+        let api = VersionApi::new(Lua::new())?;
+
+        let version = api.version_parse.call::<LuaTable>("v1.2.3.45-beta+abc")?;
+
+        assert_eq!(version.get::<u64>("major")?, 1);
+        assert_eq!(version.get::<u64>("patch")?, 3);
+        assert_eq!(version.get::<Vec<u64>>("numbers")?, [1, 2, 3, 45]);
+        assert_eq!(version.get::<String>("pre")?, "beta");
+        assert_eq!(version.get::<String>("build")?, "abc");
+
+        assert!(api.version_parse.call::<Option<LuaTable>>("latest")?.is_none());
+
+        assert_eq!(api.version_compare.call::<i32>(("1.2.10", "1.2.9"))?, 1);
+        assert_eq!(api.version_compare.call::<i32>(("1.2", "1.2.0"))?, 0);
+        assert_eq!(api.version_compare.call::<i32>(("1.2.0-rc1", "1.2.0"))?, -1);
+        assert!(api.version_compare.call::<i32>(("1.2", "latest")).is_err());
+
+        assert!(api.version_matches.call::<bool>(("1.4.2", "^1.2"))?);
+        assert!(!api.version_matches.call::<bool>(("2.0.0", ">=1.2, <2"))?);
+
+        assert_eq!(
+            api.version_sort.call::<Vec<String>>((["1.10", "1.9", "v1.2.3"], true))?,
+            ["1.10", "1.9", "v1.2.3"]
+        );
+
+        assert_eq!(
+            api.version_sort.call::<Vec<String>>(["1.10", "1.9", "v1.2.3"])?,
+            ["v1.2.3", "1.9", "1.10"]
+        );
+
+        assert_eq!(
+            api.version_max.call::<Option<String>>((["1.10", "latest", "2.0", "1.9"], "<2"))?.as_deref(),
+            Some("1.10")
+        );
+
+        Ok(())
+    }
+}
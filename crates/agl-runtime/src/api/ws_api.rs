@@ -0,0 +1,441 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// agl-runtime
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use agl_core::export::network::reqwest::{Client, StatusCode, Upgraded, Url, Version};
+use agl_core::export::network::reqwest::header;
+use agl_core::hashes::{Hasher, HashAlgorithm};
+use agl_core::tasks;
+
+use futures_util::{SinkExt, StreamExt};
+use futures_util::stream::{SplitSink, SplitStream};
+
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::protocol::{Role, CloseFrame};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+use base64::Engine;
+
+use mlua::prelude::*;
+
+use super::bytes::Bytes;
+use super::task_api::{Promise, PromiseValue, TaskOutput, CancellationToken, task_output};
+
+/// Magic string used to calculate the `Sec-WebSocket-Accept` header value.
+const WEBSOCKET_ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Open websocket connection.
+struct WsConnection {
+    sink: tasks::sync::Mutex<SplitSink<WebSocketStream<Upgraded>, Message>>,
+    stream: tasks::sync::Mutex<SplitStream<WebSocketStream<Upgraded>>>
+}
+
+type WsHandles = Arc<Mutex<HashMap<i32, Arc<WsConnection>>>>;
+
+fn get_connection(handles: &WsHandles, handle: i32) -> Result<Arc<WsConnection>, LuaError> {
+    let handles = handles.lock()
+        .map_err(|err| {
+            LuaError::external(format!("failed to read handle: {err}"))
+        })?;
+
+    handles.get(&handle)
+        .cloned()
+        .ok_or_else(|| LuaError::external("invalid websocket handle"))
+}
+
+/// Open websocket connection using the HTTP upgrade mechanism, so the client's
+/// proxy and user agent settings are applied.
+async fn connect(
+    client: Client,
+    mut url: Url,
+    headers: Vec<(String, String)>,
+    protocols: Vec<String>
+) -> Result<WebSocketStream<Upgraded>, LuaError> {
+    let scheme = match url.scheme() {
+        "ws" | "http" => "http",
+        "wss" | "https" => "https",
+
+        _ => return Err(LuaError::external("unsupported websocket url scheme"))
+    };
+
+    url.set_scheme(scheme)
+        .map_err(|_| LuaError::external("invalid websocket url"))?;
+
+    let key = base64::engine::general_purpose::STANDARD
+        .encode(rand::random::<[u8; 16]>());
+
+    let mut request = client.get(url)
+        .version(Version::HTTP_11)
+        .header(header::CONNECTION, "Upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_VERSION, "13")
+        .header(header::SEC_WEBSOCKET_KEY, &key);
+
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+
+    if !protocols.is_empty() {
+        request = request.header(header::SEC_WEBSOCKET_PROTOCOL, protocols.join(", "));
+    }
+
+    let response = request.send().await
+        .map_err(|err| {
+            LuaError::external(format!("failed to perform websocket handshake: {err}"))
+        })?;
+
+    if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+        return Err(LuaError::external(format!(
+            "server refused websocket connection: {}",
+            response.status()
+        )));
+    }
+
+    let accept = Hasher::new(HashAlgorithm::Sha1)
+        .hash(format!("{key}{WEBSOCKET_ACCEPT_GUID}"))
+        .map_err(|err| {
+            LuaError::external(format!("failed to hash websocket key: {err}"))
+        })?;
+
+    let accept = base64::engine::general_purpose::STANDARD.encode(accept);
+
+    let accept_matches = response.headers()
+        .get(header::SEC_WEBSOCKET_ACCEPT)
+        .is_some_and(|value| value.as_bytes() == accept.as_bytes());
+
+    if !accept_matches {
+        return Err(LuaError::external("invalid websocket handshake response"));
+    }
+
+    let upgraded = response.upgrade().await
+        .map_err(|err| {
+            LuaError::external(format!("failed to upgrade websocket connection: {err}"))
+        })?;
+
+    Ok(WebSocketStream::from_raw_socket(upgraded, Role::Client, None).await)
+}
+
+pub struct WsApi {
+    lua: Lua,
+
+    ws_connect: LuaFunction,
+    ws_send: LuaFunction,
+    ws_receive: LuaFunction,
+    ws_ping: LuaFunction,
+    ws_close: LuaFunction
+}
+
+impl WsApi {
+    pub fn new(lua: Lua, client: Client) -> Result<Self, LuaError> {
+        let ws_handles: WsHandles = Arc::new(Mutex::new(HashMap::new()));
+
+        Ok(Self {
+            ws_connect: {
+                let ws_handles = ws_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (url, options): (String, Option<LuaTable>)| {
+                    let url = Url::parse(&url)
+                        .map_err(|err| {
+                            LuaError::external(format!("invalid websocket url: {err}"))
+                        })?;
+
+                    let mut headers = Vec::new();
+                    let mut protocols = Vec::new();
+                    let mut token = None;
+
+                    if let Some(options) = options {
+                        if let Some(opt_headers) = options.get::<Option<LuaTable>>("headers")? {
+                            for pair in opt_headers.pairs::<String, String>() {
+                                headers.push(pair?);
+                            }
+                        }
+
+                        if let Some(opt_protocols) = options.get::<Option<Vec<String>>>("protocols")? {
+                            protocols = opt_protocols;
+                        }
+
+                        token = options.get::<Option<CancellationToken>>("cancel")?;
+                    }
+
+                    let client = client.clone();
+                    let ws_handles = ws_handles.clone();
+
+                    let value = PromiseValue::from_future(async move {
+                        let (sink, stream) = connect(client, url, headers, protocols).await?
+                            .split();
+
+                        let connection = Arc::new(WsConnection {
+                            sink: tasks::sync::Mutex::new(sink),
+                            stream: tasks::sync::Mutex::new(stream)
+                        });
+
+                        let mut handles = ws_handles.lock()
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to register handle: {err}"))
+                            })?;
+
+                        let mut handle = rand::random::<i32>();
+
+                        while handles.contains_key(&handle) {
+                            handle = rand::random::<i32>();
+                        }
+
+                        handles.insert(handle, connection);
+
+                        Ok(Box::new(move |lua: &Lua| {
+                            handle.into_lua(lua)
+                        }) as TaskOutput)
+                    });
+
+                    Promise::new(value)
+                        .with_token(token)
+                        .into_lua(lua)
+                })?
+            },
+
+            ws_send: {
+                let ws_handles = ws_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (handle, message): (i32, LuaValue)| {
+                    let connection = get_connection(&ws_handles, handle)?;
+
+                    // Send valid UTF-8 lua strings as text messages and
+                    // everything else as binary ones.
+                    let message = match message {
+                        LuaValue::String(message) => match message.to_str() {
+                            Ok(message) => Message::text(message.to_string()),
+                            Err(_) => Message::binary(message.as_bytes().to_vec())
+                        },
+
+                        message => Message::binary(Bytes::from_lua(message, lua)?.as_slice().to_vec())
+                    };
+
+                    let value = PromiseValue::from_future(async move {
+                        connection.sink.lock().await
+                            .send(message).await
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to send websocket message: {err}"))
+                            })?;
+
+                        Ok(task_output(Ok(LuaValue::Nil)))
+                    });
+
+                    Promise::new(value)
+                        .into_lua(lua)
+                })?
+            },
+
+            ws_receive: {
+                let ws_handles = ws_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (handle, token): (i32, Option<CancellationToken>)| {
+                    let connection = get_connection(&ws_handles, handle)?;
+
+                    let value = PromiseValue::from_future(async move {
+                        let mut stream = connection.stream.lock().await;
+
+                        loop {
+                            let Some(message) = stream.next().await else {
+                                return Ok(task_output(Ok(LuaValue::Nil)));
+                            };
+
+                            let message = message.map_err(|err| {
+                                LuaError::external(format!("failed to receive websocket message: {err}"))
+                            })?;
+
+                            match message {
+                                Message::Text(text) => {
+                                    let text = text.to_string();
+
+                                    return Ok(Box::new(move |lua: &Lua| {
+                                        let result = lua.create_table_with_capacity(0, 2)?;
+
+                                        result.raw_set("type", "text")?;
+                                        result.raw_set("data", text)?;
+
+                                        Ok(LuaValue::Table(result))
+                                    }) as TaskOutput);
+                                }
+
+                                Message::Binary(data) => {
+                                    let data = Bytes::new(data.to_vec().into_boxed_slice());
+
+                                    return Ok(Box::new(move |lua: &Lua| {
+                                        let result = lua.create_table_with_capacity(0, 2)?;
+
+                                        result.raw_set("type", "binary")?;
+                                        result.raw_set("data", data)?;
+
+                                        Ok(LuaValue::Table(result))
+                                    }) as TaskOutput);
+                                }
+
+                                Message::Close(_) => return Ok(task_output(Ok(LuaValue::Nil))),
+
+                                // Pings are answered automatically.
+                                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => ()
+                            }
+                        }
+                    });
+
+                    Promise::new(value)
+                        .with_token(token)
+                        .into_lua(lua)
+                })?
+            },
+
+            ws_ping: {
+                let ws_handles = ws_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (handle, payload): (i32, Option<Bytes>)| {
+                    let connection = get_connection(&ws_handles, handle)?;
+
+                    let payload = payload.map(|payload| payload.as_slice().to_vec())
+                        .unwrap_or_default();
+
+                    let value = PromiseValue::from_future(async move {
+                        connection.sink.lock().await
+                            .send(Message::Ping(payload.into())).await
+                            .map_err(|err| {
+                                LuaError::external(format!("failed to send websocket ping: {err}"))
+                            })?;
+
+                        Ok(task_output(Ok(LuaValue::Nil)))
+                    });
+
+                    Promise::new(value)
+                        .into_lua(lua)
+                })?
+            },
+
+            ws_close: {
+                let ws_handles = ws_handles.clone();
+
+                lua.create_function(move |lua: &Lua, (handle, code, reason): (i32, Option<u16>, Option<String>)| {
+                    let connection = ws_handles.lock()
+                        .map_err(|err| {
+                            LuaError::external(format!("failed to read handle: {err}"))
+                        })?
+                        .remove(&handle);
+
+                    let Some(connection) = connection else {
+                        return Promise::from_lua_value(LuaValue::Nil)
+                            .into_lua(lua);
+                    };
+
+                    let frame = CloseFrame {
+                        code: CloseCode::from(code.unwrap_or(1000)),
+                        reason: reason.unwrap_or_default().into()
+                    };
+
+                    let value = PromiseValue::from_future(async move {
+                        let mut sink = connection.sink.lock().await;
+
+                        // Server could close the connection already.
+                        if let Err(err) = sink.send(Message::Close(Some(frame))).await {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(?err, "failed to send websocket close frame");
+                        }
+
+                        let _ = sink.close().await;
+
+                        Ok(task_output(Ok(LuaValue::Nil)))
+                    });
+
+                    Promise::new(value)
+                        .into_lua(lua)
+                })?
+            },
+
+            lua
+        })
+    }
+
+    /// Create new lua table with API functions.
+    pub fn create_env(&self) -> Result<LuaTable, LuaError> {
+        let env = self.lua.create_table_with_capacity(0, 5)?;
+
+        env.raw_set("connect", &self.ws_connect)?;
+        env.raw_set("send", &self.ws_send)?;
+        env.raw_set("receive", &self.ws_receive)?;
+        env.raw_set("ping", &self.ws_ping)?;
+        env.raw_set("close", &self.ws_close)?;
+
+        Ok(env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn echo() -> Result<(), LuaError> {
+        let listener = tasks::block_on(tasks::net::TcpListener::bind("127.0.0.1:0"))?;
+        let address = listener.local_addr()?;
+
+        // Local echo server.
+        tasks::spawn(async move {
+            let Ok((socket, _)) = listener.accept().await else {
+                return;
+            };
+
+            let Ok(stream) = tokio_tungstenite::accept_async(socket).await else {
+                return;
+            };
+
+            let (mut sink, mut stream) = stream.split();
+
+            while let Some(Ok(message)) = stream.next().await {
+                if message.is_text() || message.is_binary() {
+                    let _ = sink.send(message).await;
+                }
+            }
+        });
+
+        let lua = Lua::new();
+        let api = WsApi::new(lua.clone(), Client::new())?;
+
+        lua.globals().raw_set("ws", api.create_env()?)?;
+        lua.globals().raw_set("address", format!("ws://{address}"))?;
+
+        lua.load(r#"
+            local handle = ws.connect(address):await()
+
+            ws.send(handle, "Hello, World!"):await()
+            ws.ping(handle):await()
+
+            local message = ws.receive(handle):await()
+
+            assert(message.type == "text")
+            assert(message.data == "Hello, World!")
+
+            ws.send(handle, { 1, 2, 3 }):await()
+
+            message = ws.receive(handle):await()
+
+            assert(message.type == "binary")
+            assert(#message.data:as_table() == 3)
+
+            ws.close(handle):await()
+        "#).exec()
+    }
+}
@@ -260,7 +260,8 @@ impl ModuleScope {
             api_scope["torrent"] = json!(self.allow_torrent_api);
         }
 
-        if cfg!(feature = "portal-api") {
+        #[cfg(feature = "portal-api")]
+        {
             api_scope["portal"] = json!(self.allow_portal_api);
         }
 
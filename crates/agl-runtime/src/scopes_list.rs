@@ -85,7 +85,8 @@ impl ScopesList {
             entry.allow_torrent_api |= scope.allow_torrent_api;
         }
 
-        if cfg!(feature = "portal-api") {
+        #[cfg(feature = "portal-api")]
+        {
             entry.allow_portal_api |= scope.allow_portal_api;
         }
 
@@ -33,6 +33,7 @@ use crate::scopes_list::ScopesList;
 
 use crate::module::{Module, ModuleScope};
 use crate::api::{ApiContext, ApiOptions};
+use crate::api::downloader_api::DownloaderTasks;
 use crate::runtime::{Runtime, RuntimeError, ModulePaths};
 
 #[cfg(feature = "packages-support")]
@@ -62,6 +63,13 @@ fn get_runtime() -> Result<Runtime, RuntimeError> {
         lua: Lua::new(),
         reqwest_client: reqwest::Client::new(),
 
+        http_client: reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("failed to build HTTP client"),
+
+        downloader_tasks: DownloaderTasks::new(),
+
         #[cfg(feature = "torrent-api")]
         torrent_server: None,
 
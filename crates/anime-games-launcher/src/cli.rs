@@ -0,0 +1,658 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::io::Read;
+use std::sync::Arc;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value as Json};
+
+use agl_core::tasks;
+use agl_packages::storage::Storage;
+use agl_runtime::mlua::prelude::*;
+use agl_runtime::scopes_list::ScopesList;
+use agl_runtime::api::bytes::Bytes;
+use agl_runtime::api::downloader_api::DownloaderTasks;
+use agl_runtime::api::portal_api::ToastOptions;
+use agl_runtime::runtime::{Runtime, ModulePaths};
+use agl_games::api::{GameIntegration, GameVariant, ProgressReport, LaunchPreset};
+
+use crate::{consts, config, cache, launch, saves, mods, disk, runtime};
+use crate::config::Config;
+use crate::games::GameLock;
+use crate::runtime::RuntimeHandlers;
+
+#[derive(Debug, Clone, PartialEq, Eq, Parser)]
+#[command(author = "Nikita Podvirnyi <krypt0nn@dawn.wine>", version, about)]
+pub struct Cli {
+    /// Launch the game with provided name without opening the launcher's
+    /// window. Used by the exported desktop entries and steam shortcuts.
+    #[arg(long, value_name = "NAME")]
+    pub launch: Option<String>,
+
+    /// Enable debug mode.
+    #[arg(long, hide = true)]
+    pub agl_debug: bool,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommands>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum CliCommands {
+    /// List games from the launcher's library.
+    List {
+        /// Print games list as JSON.
+        #[arg(long)]
+        json: bool
+    },
+
+    /// Launch the game and wait until it's closed.
+    Launch {
+        /// Name of the game from the library.
+        name: String,
+
+        /// Name of the game edition.
+        #[arg(short, long)]
+        edition: Option<String>,
+
+        /// Name of the launch preset. Selected one is used by default.
+        #[arg(short, long)]
+        preset: Option<String>
+    },
+
+    /// Run the game actions pipeline (install or update the game) without
+    /// asking anything. Pipelines which need user interaction are refused.
+    Pipeline {
+        /// Name of the game from the library.
+        name: String,
+
+        /// Name of the game edition.
+        #[arg(short, long)]
+        edition: Option<String>
+    },
+
+    /// Print status of the game.
+    Status {
+        /// Name of the game from the library.
+        name: String,
+
+        /// Name of the game edition.
+        #[arg(short, long)]
+        edition: Option<String>,
+
+        /// Print game status as JSON.
+        #[arg(long)]
+        json: bool
+    }
+}
+
+impl Cli {
+    /// Check if the launcher should work in command line mode instead of
+    /// opening its window.
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        self.launch.is_some() || self.command.is_some()
+    }
+
+    /// Execute requested command line mode.
+    pub fn run(self) -> anyhow::Result<()> {
+        if let Some(name) = &self.launch {
+            return launch_game(name, None, None);
+        }
+
+        match self.command {
+            Some(CliCommands::List { json }) => print_games_list(json),
+
+            Some(CliCommands::Launch { name, edition, preset }) => {
+                launch_game(&name, edition.as_deref(), preset.as_deref())
+            }
+
+            Some(CliCommands::Pipeline { name, edition }) => {
+                run_actions_pipeline(&name, edition.as_deref())
+            }
+
+            Some(CliCommands::Status { name, edition, json }) => {
+                print_game_status(&name, edition.as_deref(), json)
+            }
+
+            None => Ok(())
+        }
+    }
+}
+
+fn translate(str: agl_locale::string::LocalizableString) -> String {
+    let str = match config::startup().language() {
+        Ok(lang) => str.translate(&lang),
+        Err(_) => str.default_translation()
+    };
+
+    str.to_string()
+}
+
+/// Build packages runtime which doesn't depend on the launcher's UI. Toasts
+/// and dialogs are printed to the log, notifications are shown as usual.
+pub fn build_runtime(config: &Config) -> anyhow::Result<Runtime> {
+    let torrent_server = runtime::start_torrent_server(config);
+
+    runtime::build_runtime(config, DownloaderTasks::new(), torrent_server, RuntimeHandlers {
+        show_toast: Box::new(|options| {
+            let message = match options {
+                ToastOptions::Simple(message) |
+                ToastOptions::Activatable { message, .. } => translate(message)
+            };
+
+            tracing::info!("toast: {message}");
+        }),
+
+        show_notification: Box::new(|options| {
+            let mut notification = notify_rust::Notification::new();
+            let mut notification = notification.summary(&translate(options.title));
+
+            if let Some(message) = options.message {
+                notification = notification.body(&translate(message));
+            }
+
+            if let Some(icon) = options.icon {
+                notification = notification.icon(&icon);
+            }
+
+            if let Err(err) = notification.show() {
+                tracing::error!(?err, "failed to show system notification");
+            }
+        }),
+
+        show_dialog: Box::new(|options| {
+            let title = translate(options.title);
+            let message = translate(options.message);
+
+            tracing::warn!(?title, ?message, "dialogs are not supported in command line mode");
+        }),
+
+        translate
+    })
+}
+
+/// Read modules scopes lists cached by the launcher. Command line modes don't
+/// fetch them from the network.
+fn cached_scopes_list(config: &Config) -> ScopesList {
+    let mut scopes_list = ScopesList::default();
+
+    for url in &config.packages_scopes_lists {
+        let path = cache::get_path(url);
+
+        if !path.is_file() {
+            tracing::warn!(?url, "modules scopes list is not cached, skipping it");
+
+            continue;
+        }
+
+        let list = std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|list| Ok(serde_json::from_slice::<Json>(&list)?))
+            .and_then(|list| {
+                ScopesList::from_json(&list)
+                    .context("failed to deserialize modules scopes list")
+            });
+
+        match list {
+            Ok(list) => scopes_list.merge_with(list),
+            Err(err) => tracing::warn!(?err, ?url, "failed to read cached modules scopes list")
+        }
+    }
+
+    scopes_list
+}
+
+/// Load game integration of the locked game package into the runtime.
+pub fn load_integration(
+    runtime: &Runtime,
+    lock: &GameLock,
+    config: &Config
+) -> anyhow::Result<Arc<GameIntegration>> {
+    let storage = Storage::open(&config.packages_resources_path)
+        .context("failed to open packages storage")?;
+
+    let mut scopes_list = cached_scopes_list(config);
+
+    // Add game's scope to all the game integration resources.
+    if let Some(scope) = &lock.scope {
+        for hash in lock.lock.resources.keys() {
+            scopes_list.add_module_scope(*hash, scope.clone());
+        }
+    }
+
+    let paths = ModulePaths {
+        temp_dir: config.packages_temporary_path.clone(),
+        modules_dir: config.packages_modules_path.clone(),
+        persistent_dir: config.packages_persistent_path.clone()
+    };
+
+    runtime.load_packages(&lock.lock, &storage, &paths, &scopes_list)
+        .context("failed to load game package")?;
+
+    let module_key = lock.module_key()
+        .ok_or_else(|| anyhow::anyhow!("game integration module is missing in the package lock"))?;
+
+    let integration = runtime.get_value::<LuaTable>(module_key)?
+        .ok_or_else(|| anyhow::anyhow!("game integration module is missing in the runtime"))?
+        .raw_get::<LuaValue>("value")?;
+
+    let integration = GameIntegration::load(runtime.lua().clone(), &integration)
+        .context("failed to build game integration")?;
+
+    Ok(Arc::new(integration))
+}
+
+/// Forward output of the game process to the integration's handler.
+fn forward_output(
+    mut output: impl Read + Send + 'static,
+    handler: LuaFunction
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut buf = [0; 1024];
+
+        while let Ok(n) = output.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+
+            let bytes = Bytes::new(buf[..n].to_vec().into_boxed_slice());
+
+            let _ = handler.call::<()>(bytes);
+        }
+    })
+}
+
+/// Launch the game without opening the launcher's window and wait until it's
+/// closed. Selected launch preset and wrappers of the game are applied, and
+/// game saves are synchronized if it's enabled in the launcher config.
+///
+/// Provided edition and launch preset names take priority over the game's
+/// selected preset.
+pub fn launch_game(
+    game_name: &str,
+    edition: Option<&str>,
+    preset: Option<&str>
+) -> anyhow::Result<()> {
+    let config = config::startup();
+
+    let lock = tasks::block_on(GameLock::load(game_name))
+        .context("failed to load game package lock")?;
+
+    tracing::info!(?game_name, url = lock.url, "launching game from command line");
+
+    let runtime = build_runtime(config)?;
+    let integration = load_integration(&runtime, &lock, config)?;
+
+    let mut variant = GameVariant {
+        platform: *consts::CURRENT_PLATFORM,
+        edition: None
+    };
+
+    // Find selected launch preset, either user's or the integration's one.
+    let preset = match preset.or(lock.preset.as_deref()) {
+        Some(name) => {
+            let presets = integration.get_launch_presets(&variant)
+                .context("failed to request game launch presets")?
+                .map(Vec::from)
+                .unwrap_or_default();
+
+            let found = LaunchPreset::merge(presets, lock.presets.iter().cloned())
+                .into_iter()
+                .find(|preset| preset.name == name);
+
+            // Explicitly requested preset must exist, while the selected one
+            // could be removed from the game integration.
+            if found.is_none() {
+                if preset.is_some() {
+                    anyhow::bail!("launch preset '{name}' not found");
+                }
+
+                tracing::warn!(?name, "selected launch preset not found, default launch params will be used");
+            }
+
+            found
+        }
+
+        None => None
+    };
+
+    variant.edition = edition.map(String::from)
+        .or_else(|| preset.as_ref().and_then(|preset| preset.edition.clone()));
+
+    let mut info = integration.get_launch_info(&variant)
+        .context("failed to request game launch info")?
+        .ok_or_else(|| anyhow::anyhow!("game cannot be launched, perhaps it must be installed or updated from the launcher first"))?;
+
+    let saves_directories = integration.get_saves_directories(&variant)
+        .context("failed to request game saves directories")?
+        .unwrap_or_default();
+
+    let sync_saves = || {
+        if config.saves_enable && !saves_directories.is_empty() {
+            let result = saves::sync_game_saves(game_name, &saves_directories, config, |title, machine| {
+                tracing::warn!(?title, ?machine, "game saves conflict cannot be resolved in command line mode, open the launcher to resolve it");
+
+                None
+            });
+
+            if let Err(err) = result {
+                tracing::error!(?err, "failed to synchronize game saves");
+            }
+        }
+    };
+
+    // Synchronize game saves before launching the game.
+    sync_saves();
+
+    // Deploy enabled game mods while the game is running.
+    if let Err(err) = mods::deploy_game_mods(game_name) {
+        tracing::error!(?err, "failed to deploy game mods");
+    }
+
+    let undeploy_mods = || {
+        if let Err(err) = mods::undeploy_game_mods(game_name) {
+            tracing::error!(?err, "failed to undeploy game mods");
+        }
+    };
+
+    let wrappers = lock.wrappers_chain(config, preset.as_ref());
+
+    if let Some(preset) = &preset {
+        tracing::debug!(preset = preset.name.as_str(), "applying game launch preset");
+
+        preset.apply(&mut info);
+    }
+
+    let mut command = launch::build_command(&info, &wrappers);
+
+    tracing::info!(?command, "launching game");
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+
+        Err(err) => {
+            undeploy_mods();
+
+            return Err(anyhow::Error::new(err).context("failed to launch game"));
+        }
+    };
+
+    let mut handlers = Vec::with_capacity(2);
+
+    if let Some(handler) = info.stdout.clone() && let Some(stdout) = child.stdout.take() {
+        handlers.push(forward_output(stdout, handler));
+    }
+
+    if let Some(handler) = info.stderr.clone() && let Some(stderr) = child.stderr.take() {
+        handlers.push(forward_output(stderr, handler));
+    }
+
+    let status = child.wait();
+
+    for handler in handlers {
+        let _ = handler.join();
+    }
+
+    undeploy_mods();
+
+    let status = status.context("failed to wait for the game process")?;
+
+    tracing::info!(?status, "game closed");
+
+    // Synchronize game saves after closing the game.
+    sync_saves();
+
+    Ok(())
+}
+
+/// Read all the game package locks from the launcher's library.
+pub fn library_games() -> anyhow::Result<Vec<GameLock>> {
+    let config = config::startup();
+
+    let mut games = Vec::new();
+
+    if !config.games_path.is_dir() {
+        return Ok(games);
+    }
+
+    for entry in config.games_path.read_dir()?.flatten() {
+        let name = entry.file_name()
+            .to_string_lossy()
+            .to_string();
+
+        match tasks::block_on(GameLock::load(&name)) {
+            Ok(lock) => games.push(lock),
+            Err(err) => tracing::warn!(?err, ?name, "failed to load game package lock")
+        }
+    }
+
+    games.sort_by_key(GameLock::name);
+
+    Ok(games)
+}
+
+fn print_games_list(as_json: bool) -> anyhow::Result<()> {
+    let games = library_games()?;
+
+    if as_json {
+        let games = games.iter()
+            .map(|lock| {
+                json!({
+                    "name": lock.name(),
+                    "title": translate(lock.manifest.game.title.clone()),
+                    "developer": translate(lock.manifest.game.developer.clone()),
+                    "publisher": translate(lock.manifest.game.publisher.clone()),
+                    "url": lock.url,
+                    "preset": lock.preset
+                })
+            })
+            .collect::<Vec<_>>();
+
+        println!("{}", serde_json::to_string_pretty(&games)?);
+    }
+
+    else {
+        for lock in games {
+            println!("{}\t{}", lock.name(), translate(lock.manifest.game.title.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+fn print_game_status(
+    game_name: &str,
+    edition: Option<&str>,
+    as_json: bool
+) -> anyhow::Result<()> {
+    let config = config::startup();
+
+    let lock = tasks::block_on(GameLock::load(game_name))
+        .context("failed to load game package lock")?;
+
+    let runtime = build_runtime(config)?;
+    let integration = load_integration(&runtime, &lock, config)?;
+
+    let variant = GameVariant {
+        platform: *consts::CURRENT_PLATFORM,
+        edition: edition.map(String::from)
+    };
+
+    let editions = integration.get_editions(&consts::CURRENT_PLATFORM)
+        .context("failed to request game editions")?
+        .unwrap_or_default();
+
+    let launch_info = integration.get_launch_info(&variant)
+        .context("failed to request game launch info")?;
+
+    let pipeline = integration.get_actions_pipeline(&variant)
+        .context("failed to request game actions pipeline")?;
+
+    let presets = integration.get_launch_presets(&variant)
+        .context("failed to request game launch presets")?
+        .map(Vec::from)
+        .unwrap_or_default();
+
+    let presets = LaunchPreset::merge(presets, lock.presets.iter().cloned());
+
+    let status = json!({
+        "name": game_name,
+        "title": translate(lock.manifest.game.title.clone()),
+        "url": lock.url,
+        "edition": variant.edition,
+
+        "editions": editions.iter()
+            .map(|edition| json!({
+                "name": edition.name,
+                "title": translate(edition.title.clone())
+            }))
+            .collect::<Vec<_>>(),
+
+        "launch": launch_info.as_ref().map(|info| json!({
+            "status": info.status.to_string(),
+            "hint": info.hint.clone().map(translate)
+        })),
+
+        "pipeline": pipeline.as_ref().map(|pipeline| json!({
+            "title": translate(pipeline.title().clone()),
+            "description": pipeline.description().cloned().map(translate),
+            "interactive": pipeline.is_interactive(),
+            "actions": pipeline.actions().iter()
+                .map(|action| translate(action.title().clone()))
+                .collect::<Vec<_>>()
+        })),
+
+        "presets": presets.iter()
+            .map(|preset| json!({
+                "name": preset.name,
+                "title": translate(preset.title.clone())
+            }))
+            .collect::<Vec<_>>(),
+
+        "preset": lock.preset
+    });
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+
+        return Ok(());
+    }
+
+    println!("Name: {game_name}");
+    println!("Title: {}", translate(lock.manifest.game.title.clone()));
+
+    if let Some(edition) = &variant.edition {
+        println!("Edition: {edition}");
+    }
+
+    match &launch_info {
+        Some(info) => println!("Launch: available ({})", info.status),
+        None => println!("Launch: unavailable")
+    }
+
+    if let Some(hint) = launch_info.and_then(|info| info.hint) {
+        println!("Hint: {}", translate(hint));
+    }
+
+    match &pipeline {
+        Some(pipeline) => println!("Pipeline: {}", translate(pipeline.title().clone())),
+        None => println!("Pipeline: none")
+    }
+
+    if let Some(preset) = &lock.preset {
+        println!("Preset: {preset}");
+    }
+
+    Ok(())
+}
+
+fn report_progress(report: ProgressReport) {
+    let text = report.format().ok()
+        .flatten()
+        .map(translate)
+        .unwrap_or_else(|| format!("{:.2}%", report.fraction() * 100.0));
+
+    tracing::debug!(current = report.current(), total = report.total(), "{text}");
+}
+
+/// Run the game actions pipeline without opening the launcher's window.
+pub fn run_actions_pipeline(
+    game_name: &str,
+    edition: Option<&str>
+) -> anyhow::Result<()> {
+    let config = config::startup();
+
+    let lock = tasks::block_on(GameLock::load(game_name))
+        .context("failed to load game package lock")?;
+
+    let runtime = build_runtime(config)?;
+    let integration = load_integration(&runtime, &lock, config)?;
+
+    let variant = GameVariant {
+        platform: *consts::CURRENT_PLATFORM,
+        edition: edition.map(String::from)
+    };
+
+    let Some(pipeline) = integration.get_actions_pipeline(&variant)
+        .context("failed to request game actions pipeline")?
+    else {
+        println!("Nothing to do");
+
+        return Ok(());
+    };
+
+    // Dialogs can't be shown in command line mode.
+    if pipeline.is_interactive() {
+        anyhow::bail!("game actions pipeline needs user interaction, run it from the launcher's window instead");
+    }
+
+    let actions = pipeline.actions();
+
+    println!("{}", translate(pipeline.title().clone()));
+
+    for (i, action) in actions.iter().enumerate() {
+        let title = translate(action.title().clone());
+
+        println!("[{}/{}] {title}", i + 1, actions.len());
+
+        if let Some(estimate) = action.estimate()
+            .with_context(|| format!("failed to estimate '{title}' pipeline action"))?
+        {
+            disk::check_free_space(&estimate)?;
+        }
+
+        let before = action.before(report_progress)
+            .with_context(|| format!("failed to prepare '{title}' pipeline action"))?;
+
+        if before == Some(false) {
+            println!("[{}/{}] {title}: skipped", i + 1, actions.len());
+
+            continue;
+        }
+
+        action.perform(report_progress)
+            .with_context(|| format!("failed to perform '{title}' pipeline action"))?;
+    }
+
+    println!("Done");
+
+    Ok(())
+}
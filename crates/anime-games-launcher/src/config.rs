@@ -32,7 +32,40 @@ lazy_static::lazy_static! {
     static ref STARTUP_CONFIG: Config = tasks::block_on(get());
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Network connections which can be used to update games in background.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamesUpdatesNetwork {
+    /// Any network connection.
+    Any,
+
+    /// Only connections which are not metered (e.g. not mobile ones).
+    #[default]
+    Unmetered
+}
+
+impl std::fmt::Display for GamesUpdatesNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any       => f.write_str("any"),
+            Self::Unmetered => f.write_str("unmetered")
+        }
+    }
+}
+
+impl std::str::FromStr for GamesUpdatesNetwork {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any"       => Ok(Self::Any),
+            "unmetered" => Ok(Self::Unmetered),
+
+            _ => Err(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Config {
     /// Language of the launcher. If unset (`system`) - the system one is used.
     ///
@@ -136,6 +169,18 @@ pub struct Config {
     /// `packages.temporary.collect_garbage`
     pub packages_temporary_collect_garbage: bool,
 
+    /// Share packages resources with other launchers in the local network and
+    /// fetch resources from them before downloading them from the internet.
+    ///
+    /// `packages.lan_cache.enable`
+    pub packages_lan_cache_enable: bool,
+
+    /// TCP port of the local network resources server. If `0` is set then a
+    /// random port is used.
+    ///
+    /// `packages.lan_cache.port`
+    pub packages_lan_cache_port: u16,
+
     /// Maximal amount of memory in bytes allowed to be consumed by packages
     /// runtime (lua engine). If `0` is set then no limit is applied. Default is
     ///  `1073741824` (1 GiB).
@@ -174,6 +219,38 @@ pub struct Config {
     /// `runtime.torrent.blocklist_url`
     pub runtime_torrent_blocklist_url: Option<String>,
 
+    /// Path to the directory where torrents session is stored so added
+    /// torrents are resumed after the launcher restart. If unset, torrents are
+    /// forgotten when the launcher is closed.
+    ///
+    /// `runtime.torrent.session_path`
+    pub runtime_torrent_session_path: Option<PathBuf>,
+
+    /// Global download speed limit in bytes per second. If `0` is set then no
+    /// limit is applied.
+    ///
+    /// `runtime.torrent.download_limit`
+    pub runtime_torrent_download_limit: u32,
+
+    /// Global upload speed limit in bytes per second. If `0` is set then no
+    /// limit is applied.
+    ///
+    /// `runtime.torrent.upload_limit`
+    pub runtime_torrent_upload_limit: u32,
+
+    /// Stop seeding torrents when ratio of uploaded bytes to the torrent size
+    /// reaches this value. If `0` is set then no limit is applied. Torrents can
+    /// override this value.
+    ///
+    /// `runtime.torrent.seed_ratio`
+    pub runtime_torrent_seed_ratio: f64,
+
+    /// Stop seeding torrents after this duration. If `0` is set then no limit
+    /// is applied. Torrents can override this value.
+    ///
+    /// `runtime.torrent.seed_time`
+    pub runtime_torrent_seed_time: Duration,
+
     /// Path to the secrets API database file.
     ///
     /// `runtime.secrets.path`
@@ -187,7 +264,63 @@ pub struct Config {
     /// Path to the directory where game locks are stored.
     ///
     /// `games.path`
-    pub games_path: PathBuf
+    pub games_path: PathBuf,
+
+    /// Chain of commands used to wrap launch of every game, e.g.
+    /// `gamemoderun` or `gamescope -f --`. The first wrapper is the outermost
+    /// one. Games can disable this chain or extend it with their own wrappers.
+    ///
+    /// `games.wrappers`
+    pub games_wrappers: Vec<String>,
+
+    /// Periodically check installed games for updates and perform their
+    /// update pipelines in background. Pipelines which need user interaction
+    /// are not performed, the launcher only notifies about them.
+    ///
+    /// `games.updates.enable`
+    pub games_updates_enable: bool,
+
+    /// Interval between games updates checks. Default is 6 hours.
+    ///
+    /// `games.updates.interval`
+    pub games_updates_interval: Duration,
+
+    /// Local time of the day when games can be updated in background, as
+    /// minutes since midnight. Written as `HH:MM-HH:MM`, and can wrap over
+    /// midnight, e.g. `23:00-06:00`. If unset (`none`), games can be updated
+    /// at any time.
+    ///
+    /// `games.updates.window`
+    pub games_updates_window: Option<(u16, u16)>,
+
+    /// Network connections which can be used to update games in background.
+    /// Either `any` or `unmetered`. Default is `unmetered`.
+    ///
+    /// `games.updates.network`
+    pub games_updates_network: GamesUpdatesNetwork,
+
+    /// Synchronize games saves before launching and after closing games.
+    ///
+    /// `saves.enable`
+    pub saves_enable: bool,
+
+    /// Path to the directory where games saves snapshots are stored. It can be
+    /// a network-mounted folder shared between different machines.
+    ///
+    /// `saves.path`
+    pub saves_path: PathBuf,
+
+    /// Name of the current machine. It is stored in the games saves snapshots
+    /// to show where they were made. Default is the system hostname.
+    ///
+    /// `saves.machine`
+    pub saves_machine: String,
+
+    /// Amount of the latest snapshots kept for every game saves directory. If
+    /// `0` is set then all the snapshots are kept. Default is `20`.
+    ///
+    /// `saves.keep_snapshots`
+    pub saves_keep_snapshots: usize
 }
 
 impl Default for Config {
@@ -219,6 +352,9 @@ impl Default for Config {
             packages_temporary_path: DATA_DIR.join("packages").join("temporary"),
             packages_temporary_collect_garbage: true,
 
+            packages_lan_cache_enable: false,
+            packages_lan_cache_port: 0,
+
             runtime_memory_limit: 1024 * 1024 * 1024,
 
             runtime_private_paths: {
@@ -272,13 +408,36 @@ impl Default for Config {
             runtime_torrent_enable_upnp: false,
             runtime_torrent_trackers: vec![],
             runtime_torrent_blocklist_url: Some(String::from("https://raw.githubusercontent.com/Naunter/BT_BlockLists/master/bt_blocklists.gz")),
+            runtime_torrent_session_path: Some(DATA_DIR.join("torrents")),
+            runtime_torrent_download_limit: 0,
+            runtime_torrent_upload_limit: 0,
+            runtime_torrent_seed_ratio: 0.0,
+            runtime_torrent_seed_time: Duration::ZERO,
 
             runtime_secrets_path: DATA_DIR.join("secrets.db"),
 
             games_registries: vec![
                 String::from("https://raw.githubusercontent.com/an-anime-team/game-integrations/refs/heads/master/games/registry.json")
             ],
-            games_path: DATA_DIR.join("games")
+            games_path: DATA_DIR.join("games"),
+            games_wrappers: vec![],
+
+            games_updates_enable: false,
+            games_updates_interval: Duration::from_hours(6),
+            games_updates_window: None,
+            games_updates_network: GamesUpdatesNetwork::default(),
+
+            saves_enable: false,
+            saves_path: DATA_DIR.join("saves"),
+
+            saves_machine: std::fs::read_to_string("/etc/hostname")
+                .map(|hostname| hostname.trim().to_string())
+                .ok()
+                .filter(|hostname| !hostname.is_empty())
+                .or_else(|| std::env::var("HOSTNAME").ok())
+                .unwrap_or_else(|| String::from("unknown")),
+
+            saves_keep_snapshots: 20
         }
     }
 }
@@ -329,6 +488,10 @@ impl Config {
             path = (self.packages_temporary_path.to_string_lossy())
             collect_garbage = (self.packages_temporary_collect_garbage)
 
+            [packages.lan_cache]
+            enable = (self.packages_lan_cache_enable)
+            port = (self.packages_lan_cache_port)
+
             [runtime]
             memory_limit = (self.runtime_memory_limit)
             private_paths = (self.runtime_private_paths.iter()
@@ -341,6 +504,13 @@ impl Config {
             enable_upnp = (self.runtime_torrent_enable_upnp)
             trackers = (self.runtime_torrent_trackers.iter().map(|url| url.as_str()).collect::<Vec<_>>())
             blocklist_url = (self.runtime_torrent_blocklist_url.as_deref().unwrap_or("none"))
+            session_path = (self.runtime_torrent_session_path.as_ref()
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_else(|| String::from("none")))
+            download_limit = (self.runtime_torrent_download_limit)
+            upload_limit = (self.runtime_torrent_upload_limit)
+            seed_ratio = (self.runtime_torrent_seed_ratio)
+            seed_time = (self.runtime_torrent_seed_time.as_secs())
 
             [runtime.secrets]
             path = (self.runtime_secrets_path.to_string_lossy())
@@ -348,6 +518,23 @@ impl Config {
             [games]
             registries = (self.games_registries.iter().map(|url| url.as_str()).collect::<Vec<_>>())
             path = (self.games_path.to_string_lossy())
+            wrappers = (self.games_wrappers.iter().map(|wrapper| wrapper.as_str()).collect::<Vec<_>>())
+
+            [games.updates]
+            enable = (self.games_updates_enable)
+            interval = (self.games_updates_interval.as_secs())
+            window = (self.games_updates_window
+                .map(|(start, end)| {
+                    format!("{:02}:{:02}-{:02}:{:02}", start / 60, start % 60, end / 60, end % 60)
+                })
+                .unwrap_or_else(|| String::from("none")))
+            network = (self.games_updates_network.to_string())
+
+            [saves]
+            enable = (self.saves_enable)
+            path = (self.saves_path.to_string_lossy())
+            machine = (self.saves_machine.as_str())
+            keep_snapshots = (self.saves_keep_snapshots)
         }
     }
 
@@ -525,6 +712,21 @@ impl Config {
                     config.packages_temporary_collect_garbage = value;
                 }
             }
+
+            // `packages.lan_cache.*`
+            if let Some(lan_cache) = packages.get("lan_cache") {
+                // `packages.lan_cache.enable`
+                if let Some(value) = lan_cache.get("enable").and_then(Toml::as_bool) {
+                    config.packages_lan_cache_enable = value;
+                }
+
+                // `packages.lan_cache.port`
+                if let Some(port) = lan_cache.get("port").and_then(Toml::as_integer)
+                    && let Ok(port) = u16::try_from(port)
+                {
+                    config.packages_lan_cache_port = port;
+                }
+            }
         }
 
         // `runtime.*`
@@ -575,6 +777,41 @@ impl Config {
                         Some(blocklist_url.to_string())
                     };
                 }
+
+                // `runtime.torrent.session_path`
+                if let Some(session_path) = torrent.get("session_path").and_then(Toml::as_str) {
+                    config.runtime_torrent_session_path = if session_path == "none" {
+                        None
+                    } else {
+                        Some(PathBuf::from(session_path))
+                    };
+                }
+
+                // `runtime.torrent.download_limit`
+                if let Some(download_limit) = torrent.get("download_limit").and_then(Toml::as_integer)
+                    && let Ok(download_limit) = u32::try_from(download_limit)
+                {
+                    config.runtime_torrent_download_limit = download_limit;
+                }
+
+                // `runtime.torrent.upload_limit`
+                if let Some(upload_limit) = torrent.get("upload_limit").and_then(Toml::as_integer)
+                    && let Ok(upload_limit) = u32::try_from(upload_limit)
+                {
+                    config.runtime_torrent_upload_limit = upload_limit;
+                }
+
+                // `runtime.torrent.seed_ratio`
+                if let Some(seed_ratio) = torrent.get("seed_ratio").and_then(Toml::as_float) {
+                    config.runtime_torrent_seed_ratio = seed_ratio;
+                }
+
+                // `runtime.torrent.seed_time`
+                if let Some(seed_time) = torrent.get("seed_time").and_then(Toml::as_integer)
+                    && let Ok(seed_time) = u64::try_from(seed_time)
+                {
+                    config.runtime_torrent_seed_time = Duration::from_secs(seed_time);
+                }
             }
 
             // `runtime.secrets.*`
@@ -600,6 +837,74 @@ impl Config {
             if let Some(path) = games.get("path").and_then(Toml::as_str) {
                 config.games_path = PathBuf::from(path);
             }
+
+            // `games.wrappers`
+            if let Some(wrappers) = games.get("wrappers").and_then(Toml::as_array) {
+                config.games_wrappers = wrappers.iter()
+                    .flat_map(Toml::as_str)
+                    .map(String::from)
+                    .collect();
+            }
+
+            // `games.updates.*`
+            if let Some(updates) = games.get("updates") {
+                // `games.updates.enable`
+                if let Some(enable) = updates.get("enable").and_then(Toml::as_bool) {
+                    config.games_updates_enable = enable;
+                }
+
+                // `games.updates.interval`
+                if let Some(interval) = updates.get("interval").and_then(Toml::as_integer) {
+                    config.games_updates_interval = Duration::from_secs(interval as u64);
+                }
+
+                // `games.updates.window`
+                if let Some(window) = updates.get("window").and_then(Toml::as_str) {
+                    fn parse_time(time: &str) -> Option<u16> {
+                        let (hours, minutes) = time.trim().split_once(':')?;
+
+                        let hours = hours.parse::<u16>().ok()?;
+                        let minutes = minutes.parse::<u16>().ok()?;
+
+                        (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+                    }
+
+                    config.games_updates_window = window.split_once('-')
+                        .and_then(|(start, end)| {
+                            Some((parse_time(start)?, parse_time(end)?))
+                        });
+                }
+
+                // `games.updates.network`
+                if let Some(network) = updates.get("network").and_then(Toml::as_str)
+                    && let Ok(network) = network.parse()
+                {
+                    config.games_updates_network = network;
+                }
+            }
+        }
+
+        // `saves.*`
+        if let Some(saves) = value.get("saves") {
+            // `saves.enable`
+            if let Some(enable) = saves.get("enable").and_then(Toml::as_bool) {
+                config.saves_enable = enable;
+            }
+
+            // `saves.path`
+            if let Some(path) = saves.get("path").and_then(Toml::as_str) {
+                config.saves_path = PathBuf::from(path);
+            }
+
+            // `saves.machine`
+            if let Some(machine) = saves.get("machine").and_then(Toml::as_str) {
+                config.saves_machine = machine.to_string();
+            }
+
+            // `saves.keep_snapshots`
+            if let Some(keep_snapshots) = saves.get("keep_snapshots").and_then(Toml::as_integer) {
+                config.saves_keep_snapshots = keep_snapshots as usize;
+            }
         }
 
         config
@@ -131,6 +131,11 @@ lazy_static::lazy_static! {
     /// Default is `CONFIG_FOLDER/config.toml`.
     pub static ref CONFIG_FILE: PathBuf = CONFIG_DIR.join("config.toml");
 
+    /// Path to the downloads queue file.
+    ///
+    /// Default is `DATA_FOLDER/downloads.json`.
+    pub static ref DOWNLOADS_FILE: PathBuf = DATA_DIR.join("downloads.json");
+
     /// Path to the debug log file.
     ///
     /// Default is `DATA_FOLDER/debug.log`.
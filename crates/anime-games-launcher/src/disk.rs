@@ -0,0 +1,473 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf, Component};
+use std::io::{Read, Write};
+
+use agl_core::hashes::HashAlgorithm;
+use agl_packages::hash::Hash;
+use agl_games::api::SpaceEstimate;
+
+use crate::{utils, verify};
+
+/// Size of the buffer used to copy files.
+const COPY_CHUNK_LEN: usize = 1024 * 1024; // 1 MiB
+
+/// Algorithm used to compare content of the copied files.
+const VERIFY_HASH_ALGORITHM: HashAlgorithm = HashAlgorithm::Xxh3_128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelocateStage {
+    /// Copying files to the new location.
+    Copy,
+
+    /// Verifying copied files.
+    Verify,
+
+    /// Deleting files from the old location.
+    Delete
+}
+
+/// Get amount of free bytes available to the user on the filesystem of the
+/// given path. If the path doesn't exist, the closest existing parent is
+/// checked instead.
+pub fn free_space(path: impl AsRef<Path>) -> anyhow::Result<u64> {
+    let mut path = path.as_ref();
+
+    while !path.exists() {
+        path = path.parent()
+            .ok_or_else(|| anyhow::anyhow!("failed to find existing parent of the path"))?;
+    }
+
+    let stat = nix::sys::statvfs::statvfs(path)?;
+
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
+/// Check that there's enough free space for the pipeline action estimate.
+pub fn check_free_space(estimate: &SpaceEstimate) -> anyhow::Result<()> {
+    let available = free_space(&estimate.path)?;
+
+    if available < estimate.size {
+        anyhow::bail!(
+            "not enough free space in {:?}: {} needed, {} available",
+            estimate.path,
+            utils::pretty_bytes(estimate.size),
+            utils::pretty_bytes(available)
+        );
+    }
+
+    Ok(())
+}
+
+/// Calculate total size of the files stored in the given folder. Symlinks are
+/// not followed.
+pub fn disk_usage(path: impl AsRef<Path>) -> anyhow::Result<u64> {
+    let mut size = 0;
+    let mut folders = vec![path.as_ref().to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        for entry in folder.read_dir()? {
+            let entry = entry?;
+            let metadata = entry.path().symlink_metadata()?;
+
+            if metadata.is_dir() {
+                folders.push(entry.path());
+            } else {
+                size += metadata.len();
+            }
+        }
+    }
+
+    Ok(size)
+}
+
+/// Move folder to the new location.
+///
+/// If both paths are on the same filesystem, the folder is renamed. Otherwise
+/// files are copied, the copy is verified, and the old files are deleted.
+/// The `update` callback is called after the files are moved or copied and
+/// before the old files are deleted. If it fails, then the relocation is
+/// reverted.
+///
+/// Progress callback receives the current stage and amount of processed and
+/// total bytes.
+pub fn relocate(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+    progress: impl Fn(RelocateStage, u64, u64),
+    update: impl FnOnce() -> anyhow::Result<()>
+) -> anyhow::Result<()> {
+    let from = from.as_ref();
+    let to = to.as_ref();
+
+    if !from.is_dir() {
+        anyhow::bail!("source folder doesn't exist: {from:?}");
+    }
+
+    // Compare resolved paths so symlinks and relative components can't hide
+    // the target being inside of the source folder.
+    if canonicalize_nonexistent(to)?.starts_with(from.canonicalize()?) {
+        anyhow::bail!("can't move folder inside of itself");
+    }
+
+    if to.exists() && (!to.is_dir() || to.read_dir()?.next().is_some()) {
+        anyhow::bail!("target folder is not empty: {to:?}");
+    }
+
+    // Remember what existed before the relocation so reverting it doesn't
+    // delete anything which wasn't created by us.
+    let target_existed = to.exists();
+
+    let created_root = to.ancestors()
+        .take_while(|path| !path.exists())
+        .last()
+        .map(Path::to_path_buf);
+
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Renaming is instant on the same filesystem so try it first. Empty
+    // target folder is replaced by the rename.
+    if std::fs::rename(from, to).is_ok() {
+        if let Err(err) = update() {
+            std::fs::rename(to, from)?;
+
+            if let Err(err) = revert_target(to, target_existed, created_root.as_deref()) {
+                tracing::warn!(?err, ?to, "failed to revert relocation target folder");
+            }
+
+            return Err(err);
+        }
+
+        return Ok(());
+    }
+
+    let total = disk_usage(from)?;
+    let available = free_space(to)?;
+
+    if available < total {
+        anyhow::bail!(
+            "not enough free space in {to:?}: {} needed, {} available",
+            utils::pretty_bytes(total),
+            utils::pretty_bytes(available)
+        );
+    }
+
+    let result = copy_folder(from, to, |current| progress(RelocateStage::Copy, current, total))
+        .map_err(anyhow::Error::from)
+        .and_then(|_| verify_copy(from, to, &mut 0, &|current| progress(RelocateStage::Verify, current, total)))
+        .and_then(|_| update());
+
+    if let Err(err) = result {
+        if let Err(err) = revert_target(to, target_existed, created_root.as_deref()) {
+            tracing::warn!(?err, ?to, "failed to delete partially relocated folder");
+        }
+
+        return Err(err);
+    }
+
+    progress(RelocateStage::Delete, 0, 1);
+
+    std::fs::remove_dir_all(from)?;
+
+    progress(RelocateStage::Delete, 1, 1);
+
+    Ok(())
+}
+
+/// Canonicalize the path which may not exist yet by resolving its closest
+/// existing parent and appending the rest of the path to it.
+fn canonicalize_nonexistent(path: &Path) -> std::io::Result<PathBuf> {
+    let existing = path.ancestors()
+        .find(|path| path.exists())
+        .unwrap_or(Path::new(""));
+
+    let mut resolved = if existing.as_os_str().is_empty() {
+        std::env::current_dir()?
+    } else {
+        existing.canonicalize()?
+    };
+
+    // Missing folders can't be symlinks so the rest is resolved lexically.
+    let rest = path.strip_prefix(existing)
+        .unwrap_or(path);
+
+    for component in rest.components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+
+            Component::Normal(name) => resolved.push(name),
+
+            _ => ()
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Restore the relocation target to its state before the relocation.
+///
+/// If the target folder existed (it must have been empty), then only its
+/// content is deleted. Otherwise all the folders created for it are deleted.
+fn revert_target(
+    to: &Path,
+    target_existed: bool,
+    created_root: Option<&Path>
+) -> std::io::Result<()> {
+    if target_existed {
+        // Target folder is moved away if the source folder was renamed.
+        if !to.exists() {
+            return std::fs::create_dir(to);
+        }
+
+        for entry in to.read_dir()? {
+            let path = entry?.path();
+
+            if path.symlink_metadata()?.is_dir() {
+                std::fs::remove_dir_all(path)?;
+            } else {
+                std::fs::remove_file(path)?;
+            }
+        }
+    }
+
+    else if let Some(created_root) = created_root
+        && created_root.exists()
+    {
+        std::fs::remove_dir_all(created_root)?;
+    }
+
+    Ok(())
+}
+
+/// Move file, falling back to copying if it's on a different filesystem.
+pub fn move_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> std::io::Result<()> {
+    let from = from.as_ref();
+    let to = to.as_ref();
+
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // The source file is removed only when its copy is complete.
+    if std::fs::rename(from, to).is_err() {
+        copy_atomic(from, to)?;
+
+        std::fs::remove_file(from)?;
+    }
+
+    Ok(())
+}
+
+/// Write the file through a temporary one so it's never left half-written.
+pub fn write_atomic(path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let temp_path = path.with_extension(format!("tmp-{}", Hash::rand().to_base32()));
+
+    let result = std::fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    result?;
+
+    sync_parent(path)
+}
+
+/// Copy the file through a temporary one so it's never left half-written.
+pub fn copy_atomic(from: impl AsRef<Path>, to: impl AsRef<Path>) -> std::io::Result<()> {
+    let to = to.as_ref();
+
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let temp_path = to.with_extension(format!("tmp-{}", Hash::rand().to_base32()));
+
+    let result = std::fs::copy(from, &temp_path)
+        .and_then(|_| std::fs::OpenOptions::new().write(true).open(&temp_path))
+        .and_then(|file| file.sync_all())
+        .and_then(|_| std::fs::rename(&temp_path, to));
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    result?;
+
+    sync_parent(to)
+}
+
+/// Flush the parent folder of the path so the renamed entry is persisted.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            std::fs::File::open(parent)?.sync_all()
+        }
+
+        _ => std::fs::File::open(".")?.sync_all()
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Recursively copy folder keeping symlinks and permissions.
+///
+/// Progress callback receives amount of copied bytes. Total amount of copied
+/// bytes is returned.
+pub fn copy_folder(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+    progress: impl Fn(u64)
+) -> std::io::Result<u64> {
+    let mut copied = 0;
+
+    copy_folder_content(from.as_ref(), to.as_ref(), &mut copied, &progress)?;
+
+    Ok(copied)
+}
+
+fn copy_folder_content(
+    from: &Path,
+    to: &Path,
+    copied: &mut u64,
+    progress: &dyn Fn(u64)
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+
+    for entry in from.read_dir()? {
+        let entry = entry?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+
+        let metadata = source.symlink_metadata()?;
+
+        if metadata.is_symlink() {
+            copy_symlink(&source, &target)?;
+        }
+
+        else if metadata.is_dir() {
+            copy_folder_content(&source, &target, copied, progress)?;
+
+            std::fs::set_permissions(target, metadata.permissions())?;
+        }
+
+        else {
+            let mut source = std::fs::File::open(source)?;
+            let mut target_file = std::fs::File::create(&target)?;
+
+            let mut buf = vec![0; COPY_CHUNK_LEN];
+
+            loop {
+                let n = source.read(&mut buf)?;
+
+                if n == 0 {
+                    break;
+                }
+
+                target_file.write_all(&buf[..n])?;
+
+                *copied += n as u64;
+
+                progress(*copied);
+            }
+
+            target_file.flush()?;
+
+            std::fs::set_permissions(target, metadata.permissions())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source.read_link()?, target)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, _target: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("symlinks copying is not supported on this system: {source:?}")
+    ))
+}
+
+/// Check that all the files of the source folder were copied with the same
+/// content.
+fn verify_copy(
+    from: &Path,
+    to: &Path,
+    verified: &mut u64,
+    progress: &dyn Fn(u64)
+) -> anyhow::Result<()> {
+    for entry in from.read_dir()? {
+        let entry = entry?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+
+        let source_metadata = source.symlink_metadata()?;
+
+        let target_metadata = target.symlink_metadata()
+            .map_err(|err| anyhow::anyhow!("copied file is missing: {target:?}: {err}"))?;
+
+        if source_metadata.file_type() != target_metadata.file_type() {
+            anyhow::bail!("copied file doesn't match the original: {target:?}");
+        }
+
+        else if source_metadata.is_dir() {
+            verify_copy(&source, &target, verified, progress)?;
+        }
+
+        else if source_metadata.is_symlink() {
+            if source.read_link()? != target.read_link()? {
+                anyhow::bail!("copied symlink doesn't match the original: {target:?}");
+            }
+        }
+
+        else {
+            if source_metadata.len() != target_metadata.len()
+                || verify::hash_file(&source, VERIFY_HASH_ALGORITHM)? != verify::hash_file(&target, VERIFY_HASH_ALGORITHM)?
+            {
+                anyhow::bail!("copied file doesn't match the original: {target:?}");
+            }
+
+            *verified += source_metadata.len();
+
+            progress(*verified);
+        }
+    }
+
+    Ok(())
+}
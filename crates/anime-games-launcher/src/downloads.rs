@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Context;
+use serde_json::{json, Value as Json};
+
+use agl_packages::hash::Hasher;
+use agl_games::api::{GameVariant, PipelineAction};
+use agl_games::platform::Platform;
+
+use crate::disk;
+
+/// Information about a game component which should be installed or
+/// uninstalled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApplyComponentInfo {
+    pub name: String,
+    pub title: String
+}
+
+impl ApplyComponentInfo {
+    pub fn to_json(&self) -> Json {
+        json!({
+            "name": self.name,
+            "title": self.title
+        })
+    }
+
+    pub fn from_json(value: &Json) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: value.get("name")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("missing 'name' field in component info"))?,
+
+            title: value.get("title")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("missing 'title' field in component info"))?
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadKind {
+    /// Perform game actions pipeline. The pipeline itself is requested from
+    /// the game integration if it's not available (e.g. after the launcher
+    /// restart).
+    ActionsPipeline,
+
+    /// Install and uninstall game components. Applied components are removed
+    /// from these lists.
+    ApplyComponents {
+        install_components: Vec<ApplyComponentInfo>,
+        uninstall_components: Vec<ApplyComponentInfo>,
+
+        /// Delete game package after applying these components.
+        delete_game_package: bool
+    }
+}
+
+/// An entry of the launcher-wide downloads queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Download {
+    /// Unique identifier of the download within the queue.
+    pub id: u64,
+
+    /// Unique name of the game.
+    pub game_name: String,
+
+    /// Title of the game.
+    pub game_title: String,
+
+    /// Title of the download.
+    pub title: String,
+
+    /// Game variant for which the download should be performed.
+    pub variant: GameVariant,
+
+    /// What should be performed by the download.
+    pub kind: DownloadKind,
+
+    /// Identifiers of already performed actions of the actions pipeline, in
+    /// order. The pipeline is requested from the game integration again after
+    /// the launcher restart and can be different, so only its actions matching
+    /// these identifiers are skipped. See `pipeline_action_id`.
+    pub performed_actions: Vec<String>,
+
+    /// Whether the download was paused by the user.
+    pub paused: bool
+}
+
+impl Download {
+    pub fn to_json(&self) -> Json {
+        let kind = match &self.kind {
+            DownloadKind::ActionsPipeline => json!({
+                "type": "actions_pipeline"
+            }),
+
+            DownloadKind::ApplyComponents {
+                install_components,
+                uninstall_components,
+                delete_game_package
+            } => json!({
+                "type": "apply_components",
+                "install": install_components.iter()
+                    .map(ApplyComponentInfo::to_json)
+                    .collect::<Vec<_>>(),
+                "uninstall": uninstall_components.iter()
+                    .map(ApplyComponentInfo::to_json)
+                    .collect::<Vec<_>>(),
+                "delete_game_package": delete_game_package
+            })
+        };
+
+        json!({
+            "id": self.id,
+            "game_name": self.game_name,
+            "game_title": self.game_title,
+            "title": self.title,
+            "variant": {
+                "platform": self.variant.platform.to_string(),
+                "edition": self.variant.edition
+            },
+            "kind": kind,
+            "performed_actions": self.performed_actions,
+            "paused": self.paused
+        })
+    }
+
+    pub fn from_json(value: &Json) -> anyhow::Result<Self> {
+        fn get_components(kind: &Json, key: &str) -> anyhow::Result<Vec<ApplyComponentInfo>> {
+            kind.get(key)
+                .and_then(Json::as_array)
+                .map(|components| {
+                    components.iter()
+                        .map(ApplyComponentInfo::from_json)
+                        .collect::<anyhow::Result<Vec<_>>>()
+                })
+                .unwrap_or_else(|| Ok(Vec::new()))
+        }
+
+        let variant = value.get("variant")
+            .ok_or_else(|| anyhow::anyhow!("missing 'variant' field in download"))?;
+
+        let kind = value.get("kind")
+            .ok_or_else(|| anyhow::anyhow!("missing 'kind' field in download"))?;
+
+        Ok(Self {
+            id: value.get("id")
+                .and_then(Json::as_u64)
+                .ok_or_else(|| anyhow::anyhow!("missing 'id' field in download"))?,
+
+            game_name: value.get("game_name")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("missing 'game_name' field in download"))?,
+
+            game_title: value.get("game_title")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("missing 'game_title' field in download"))?,
+
+            title: value.get("title")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("missing 'title' field in download"))?,
+
+            variant: GameVariant {
+                platform: variant.get("platform")
+                    .and_then(Json::as_str)
+                    .and_then(|platform| Platform::from_str(platform).ok())
+                    .ok_or_else(|| anyhow::anyhow!("invalid 'variant.platform' field in download"))?,
+
+                edition: variant.get("edition")
+                    .and_then(Json::as_str)
+                    .map(String::from)
+            },
+
+            kind: match kind.get("type").and_then(Json::as_str) {
+                Some("actions_pipeline") => DownloadKind::ActionsPipeline,
+
+                Some("apply_components") => DownloadKind::ApplyComponents {
+                    install_components: get_components(kind, "install")?,
+                    uninstall_components: get_components(kind, "uninstall")?,
+
+                    delete_game_package: kind.get("delete_game_package")
+                        .and_then(Json::as_bool)
+                        .unwrap_or(false)
+                },
+
+                _ => anyhow::bail!("unsupported download kind")
+            },
+
+            performed_actions: value.get("performed_actions")
+                .and_then(Json::as_array)
+                .map(|actions| {
+                    actions.iter()
+                        .filter_map(Json::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+
+            paused: value.get("paused")
+                .and_then(Json::as_bool)
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// An entry of the downloads queue file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueEntry {
+    /// Download performed by the launcher.
+    Download(Download),
+
+    /// Torrent of the packages runtime's torrent server. Only its position in
+    /// the queue is stored, the torrent itself is restored by the server.
+    Torrent {
+        info_hash: String
+    }
+}
+
+impl QueueEntry {
+    pub fn to_json(&self) -> Json {
+        match self {
+            Self::Download(download) => download.to_json(),
+
+            Self::Torrent { info_hash } => json!({
+                "torrent": info_hash
+            })
+        }
+    }
+
+    pub fn from_json(value: &Json) -> anyhow::Result<Self> {
+        match value.get("torrent").and_then(Json::as_str) {
+            Some(info_hash) => Ok(Self::Torrent {
+                info_hash: info_hash.to_string()
+            }),
+
+            None => Download::from_json(value).map(Self::Download)
+        }
+    }
+}
+
+/// Get identifier of the pipeline action. It's used to find actions which were
+/// already performed when the actions pipeline is requested again.
+pub fn pipeline_action_id(index: usize, action: &PipelineAction) -> String {
+    let mut hasher = Hasher::default();
+
+    hasher.update(&(index as u64).to_le_bytes());
+    hasher.update(action.title().default_translation().as_bytes());
+
+    if let Some(description) = action.description() {
+        hasher.update(&[0]);
+        hasher.update(description.default_translation().as_bytes());
+    }
+
+    hasher.finish().to_base32()
+}
+
+/// Count leading actions of the pipeline which were already performed
+/// according to the list of their identifiers.
+pub fn count_performed_actions(actions: &[PipelineAction], performed_actions: &[String]) -> usize {
+    actions.iter()
+        .zip(performed_actions)
+        .enumerate()
+        .take_while(|(i, (action, id))| pipeline_action_id(*i, action) == **id)
+        .count()
+}
+
+/// Read downloads queue from the given file. Return empty queue if the file
+/// doesn't exist.
+pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Vec<QueueEntry>> {
+    let path = path.as_ref();
+
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let queue = std::fs::read(path)?;
+
+    let queue = serde_json::from_slice::<Json>(&queue)
+        .context("failed to decode json file with downloads queue")?;
+
+    if queue.get("format").and_then(Json::as_u64) != Some(1) {
+        anyhow::bail!("unsupported downloads queue file format");
+    }
+
+    let mut entries = Vec::new();
+
+    for entry in queue.get("downloads").and_then(Json::as_array).into_iter().flatten() {
+        match QueueEntry::from_json(entry) {
+            Ok(entry) => entries.push(entry),
+
+            Err(err) => tracing::warn!(?err, ?entry, "skip invalid downloads queue entry")
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Save downloads queue to the given file.
+pub fn save(path: impl AsRef<Path>, entries: &[QueueEntry]) -> anyhow::Result<()> {
+    let queue = json!({
+        "format": 1,
+        "downloads": entries.iter()
+            .map(QueueEntry::to_json)
+            .collect::<Vec<_>>()
+    });
+
+    disk::write_atomic(path, serde_json::to_vec_pretty(&queue)?)?;
+
+    Ok(())
+}
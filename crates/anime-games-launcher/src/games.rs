@@ -16,6 +16,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::sync::{Arc, RwLock};
+
 use anyhow::Context;
 use serde_json::{json, Value as Json};
 
@@ -23,14 +25,41 @@ use agl_core::export::hashes::seahash;
 use agl_core::tasks;
 use agl_core::network::downloader::{Downloader, DownloadOptions};
 use agl_packages::hash::Hash;
-use agl_packages::storage::Storage;
+use agl_packages::storage::{Storage, InstallOptions};
+use agl_packages::torrent::TorrentSource;
+use agl_packages::lan::LanCache;
 use agl_packages::lock::Lock as PackageLock;
 use agl_runtime::module::ModuleScope;
 use agl_games::manifest::GameManifest;
+use agl_games::api::LaunchPreset;
 
 use crate::config;
 use crate::cache;
 
+lazy_static::lazy_static! {
+    /// Source of the games packages resources which have torrent sources.
+    static ref TORRENT_SOURCE: RwLock<Option<Arc<dyn TorrentSource>>> = RwLock::new(None);
+
+    /// Local network cache of the games packages resources.
+    static ref LAN_CACHE: RwLock<Option<LanCache>> = RwLock::new(None);
+}
+
+/// Set source of the games packages resources which have torrent sources.
+/// If unset, all the resources are downloaded over HTTP.
+pub fn set_torrent_source(source: impl TorrentSource + 'static) {
+    if let Ok(mut torrent_source) = TORRENT_SOURCE.write() {
+        *torrent_source = Some(Arc::new(source));
+    }
+}
+
+/// Set local network cache which will be used to fetch games packages
+/// resources before downloading them from the internet.
+pub fn set_lan_cache(cache: LanCache) {
+    if let Ok(mut lan_cache) = LAN_CACHE.write() {
+        *lan_cache = Some(cache);
+    }
+}
+
 /// Get sanitized game name from optional manifest's `name` field and manifest
 /// downloading URL.
 pub fn get_name(name: Option<&str>, manifest_url: &str) -> String {
@@ -76,7 +105,21 @@ pub struct GameLock {
 
     /// Optional sandbox scope applied to all the runtime modules used by the
     /// game integration.
-    pub scope: Option<ModuleScope>
+    pub scope: Option<ModuleScope>,
+
+    /// Chain of commands used to wrap the game launch, e.g. `mangohud` or
+    /// `gamescope -f --`. Applied after the global wrappers chain.
+    pub wrappers: Vec<String>,
+
+    /// Apply global wrappers chain from the launcher config.
+    pub global_wrappers: bool,
+
+    /// Launch presets created by the user.
+    pub presets: Vec<LaunchPreset>,
+
+    /// Name of the selected launch preset, either user's or provided by the
+    /// game integration.
+    pub preset: Option<String>
 }
 
 impl GameLock {
@@ -87,7 +130,13 @@ impl GameLock {
             "manifest": self.manifest.to_json(),
             "lock": self.lock.to_json(),
             "scope": self.scope.as_ref()
-                .map(ModuleScope::to_json)
+                .map(ModuleScope::to_json),
+            "wrappers": self.wrappers,
+            "global_wrappers": self.global_wrappers,
+            "presets": self.presets.iter()
+                .map(LaunchPreset::to_json)
+                .collect::<Vec<_>>(),
+            "preset": self.preset
         })
     }
 
@@ -125,10 +174,108 @@ impl GameLock {
                     } else {
                         Some(ModuleScope::from_json(scope))
                     }
+                }),
+
+            wrappers: value.get("wrappers")
+                .and_then(Json::as_array)
+                .map(|wrappers| {
+                    wrappers.iter()
+                        .flat_map(Json::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+
+            global_wrappers: value.get("global_wrappers")
+                .and_then(Json::as_bool)
+                .unwrap_or(true),
+
+            presets: value.get("presets")
+                .and_then(Json::as_array)
+                .map(|presets| {
+                    presets.iter()
+                        .flat_map(LaunchPreset::from_json)
+                        .collect()
                 })
+                .unwrap_or_default(),
+
+            preset: value.get("preset")
+                .and_then(Json::as_str)
+                .map(String::from)
         })
     }
 
+    /// Read game lock file with provided name from the games directory.
+    pub async fn load(name: impl AsRef<str>) -> anyhow::Result<Self> {
+        let config = config::get().await;
+
+        let lock = tasks::fs::read(config.games_path.join(name.as_ref())).await?;
+
+        let lock = serde_json::from_slice::<Json>(&lock)
+            .context("failed to decode json file with game package lock")?;
+
+        Self::from_json(&lock)
+            .context("failed to deserialize game package lock")
+    }
+
+    /// Save game lock file to the games directory.
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let config = config::get().await;
+
+        tasks::fs::write(
+            config.games_path.join(self.name()),
+            serde_json::to_vec_pretty(&self.to_json())?
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Get full chain of commands which should wrap the game launch.
+    ///
+    /// Wrapper settings of the provided launch preset take priority over the
+    /// game's own ones.
+    pub fn wrappers_chain(
+        &self,
+        config: &config::Config,
+        preset: Option<&LaunchPreset>
+    ) -> Vec<String> {
+        let global_wrappers = preset.and_then(|preset| preset.global_wrappers)
+            .unwrap_or(self.global_wrappers);
+
+        let wrappers = preset.and_then(|preset| preset.wrappers.as_deref())
+            .unwrap_or(&self.wrappers);
+
+        let mut chain = Vec::with_capacity(config.games_wrappers.len() + wrappers.len());
+
+        if global_wrappers {
+            chain.extend(config.games_wrappers.iter().cloned());
+        }
+
+        chain.extend(wrappers.iter().cloned());
+
+        chain
+    }
+
+    /// Get key of the game integration module in the packages runtime.
+    ///
+    /// Return `None` if the module is missing in the package lock.
+    pub fn module_key(&self) -> Option<String> {
+        for hash in &self.lock.root {
+            #[allow(clippy::collapsible_if)]
+            if let Some(package) = self.lock.packages.get(hash) {
+                if let Some(output) = package.outputs.get(&self.manifest.package.output) {
+                    // TODO: can change in future. Better make some
+                    //       universal solution.
+                    let module_key = format!("{}#module", output.hash.to_base32());
+
+                    return Some(module_key);
+                }
+            }
+        }
+
+        None
+    }
+
     /// Get sanitized game name derived from either game manifest's `name` field
     /// or the manifest's download URL.
     #[inline]
@@ -182,9 +329,17 @@ impl GameLock {
             .context("failed to deserialize game manifest")?;
 
         // Install game package.
-        let result = storage.install_packages(&downloader, [
+        let options = InstallOptions {
+            torrent_source: TORRENT_SOURCE.read().ok()
+                .and_then(|torrent_source| torrent_source.clone()),
+
+            lan_cache: LAN_CACHE.read().ok()
+                .and_then(|lan_cache| lan_cache.clone())
+        };
+
+        let result = storage.install_packages_with_options(&downloader, [
             manifest.package.url.clone()
-        ]).await;
+        ], options).await;
 
         let lock = match result {
             Ok(lock) => lock,
@@ -198,7 +353,11 @@ impl GameLock {
             url: manifest_url,
             manifest,
             lock,
-            scope: None
+            scope: None,
+            wrappers: vec![],
+            global_wrappers: true,
+            presets: vec![],
+            preset: None
         })
     }
 }
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::process::{Command, Stdio, Child};
+use std::os::unix::process::CommandExt;
+use std::time::{Duration, Instant};
+
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+use agl_games::api::GameLaunchInfo;
+
+/// Time given to the game processes to close after the terminate signal
+/// before they're killed.
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(3);
+
+const TERMINATE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Split wrapper command string into separate arguments.
+///
+/// Arguments are separated by whitespaces. Single and double quotes can be
+/// used to keep whitespaces within an argument, and backslash escapes the
+/// following character (except within single quotes).
+///
+/// ```
+/// assert_eq!(split_command("gamescope -f --"), ["gamescope", "-f", "--"]);
+/// assert_eq!(split_command("taskset -c '0-3'"), ["taskset", "-c", "0-3"]);
+/// assert_eq!(split_command(r#"env "A=B C""#), ["env", "A=B C"]);
+/// ```
+pub fn split_command(command: impl AsRef<str>) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+
+    let mut has_arg = false;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for char in command.as_ref().chars() {
+        if escaped {
+            arg.push(char);
+
+            escaped = false;
+        }
+
+        else if quote == Some(char) {
+            quote = None;
+        }
+
+        else if char == '\\' && quote != Some('\'') {
+            has_arg = true;
+            escaped = true;
+        }
+
+        else if quote.is_some() {
+            arg.push(char);
+        }
+
+        else if char == '\'' || char == '"' {
+            has_arg = true;
+            quote = Some(char);
+        }
+
+        else if char.is_whitespace() {
+            if has_arg {
+                args.push(std::mem::take(&mut arg));
+
+                has_arg = false;
+            }
+        }
+
+        else {
+            has_arg = true;
+
+            arg.push(char);
+        }
+    }
+
+    if has_arg {
+        args.push(arg);
+    }
+
+    args
+}
+
+/// Join arguments into a single command string which can be split back by
+/// the `split_command` function.
+///
+/// ```
+/// assert_eq!(join_command(["taskset", "-c", "0-3"]), "taskset -c 0-3");
+/// assert_eq!(join_command(["env", "A=B C"]), "env 'A=B C'");
+/// ```
+pub fn join_command<T: AsRef<str>>(args: impl IntoIterator<Item = T>) -> String {
+    args.into_iter()
+        .map(|arg| {
+            let arg = arg.as_ref();
+
+            let special = arg.is_empty() || arg.chars().any(|char| {
+                char.is_whitespace() || char == '\\' || char == '"' || char == '\''
+            });
+
+            if !special {
+                return arg.to_string();
+            }
+
+            // Single quotes keep everything as is.
+            if !arg.contains('\'') {
+                return format!("'{arg}'");
+            }
+
+            let mut escaped = String::with_capacity(arg.len() + 2);
+
+            escaped.push('"');
+
+            for char in arg.chars() {
+                if char == '"' || char == '\\' {
+                    escaped.push('\\');
+                }
+
+                escaped.push(char);
+            }
+
+            escaped.push('"');
+
+            escaped
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Build game launching command from the game launch info, wrapping the game
+/// binary with provided chain of wrapper commands.
+///
+/// Wrappers are applied in the order they're listed, so the first one becomes
+/// the outermost command. For example, `["gamemoderun", "gamescope -f --"]`
+/// chain will result in `gamemoderun gamescope -f -- <binary> <args>` command.
+pub fn build_command<T: AsRef<str>>(
+    info: &GameLaunchInfo,
+    wrappers: &[T]
+) -> Command {
+    let mut wrappers = wrappers.iter()
+        .flat_map(split_command);
+
+    let mut command = match wrappers.next() {
+        Some(wrapper) => {
+            let mut command = Command::new(wrapper);
+
+            command.args(wrappers);
+            command.arg(&info.binary);
+
+            command
+        }
+
+        None => Command::new(&info.binary)
+    };
+
+    if let Some(parent_folder) = info.binary.parent()
+        && parent_folder.is_dir()
+    {
+        command.current_dir(parent_folder);
+    }
+
+    if let Some(args) = &info.args {
+        command.args(args);
+    }
+
+    if let Some(env) = &info.env {
+        command.envs(env);
+    }
+
+    if info.stdout.is_some() {
+        command.stdout(Stdio::piped());
+    } else {
+        command.stdout(Stdio::inherit());
+    }
+
+    if info.stderr.is_some() {
+        command.stderr(Stdio::piped());
+    } else {
+        command.stderr(Stdio::inherit());
+    }
+
+    // Spawn the game in its own process group so all the processes forked by
+    // it can be stopped together.
+    command.process_group(0);
+
+    command
+}
+
+/// Check if any process of the game's process group is still running.
+pub fn process_group_running(child: &Child) -> bool {
+    signal::killpg(Pid::from_raw(child.id() as i32), None).is_ok()
+}
+
+/// Terminate the game process with its whole process group. Processes are
+/// asked to close first and killed if they didn't in time.
+pub fn terminate_process(child: &mut Child) -> std::io::Result<()> {
+    let pid = Pid::from_raw(child.id() as i32);
+
+    if signal::killpg(pid, Signal::SIGTERM).is_ok() {
+        let started = Instant::now();
+
+        while started.elapsed() < TERMINATE_TIMEOUT {
+            // Reap the game process so it's not reported as a zombie member
+            // of the process group.
+            child.try_wait()?;
+
+            if !process_group_running(child) {
+                return Ok(());
+            }
+
+            std::thread::sleep(TERMINATE_CHECK_INTERVAL);
+        }
+
+        let _ = signal::killpg(pid, Signal::SIGKILL);
+    }
+
+    // Kill the game process itself if the group is already gone.
+    child.kill()?;
+    child.wait()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_quotes() {
+        assert_eq!(split_command("gamescope  -f   --"), ["gamescope", "-f", "--"]);
+        assert_eq!(split_command("taskset -c '0-3'"), ["taskset", "-c", "0-3"]);
+        assert_eq!(split_command(r#"env "A=B C" D='E F'"#), ["env", "A=B C", "D=E F"]);
+        assert_eq!(split_command(r#"echo "it's" 'say "hi"'"#), ["echo", "it's", "say \"hi\""]);
+    }
+
+    #[test]
+    fn split_escapes() {
+        assert_eq!(split_command(r#"echo a\ b"#), ["echo", "a b"]);
+        assert_eq!(split_command(r#"echo "a \"b\" \\ c""#), ["echo", r#"a "b" \ c"#]);
+        assert_eq!(split_command(r#"echo 'a\b'"#), ["echo", r#"a\b"#]);
+    }
+
+    #[test]
+    fn split_empty_args() {
+        assert!(split_command("").is_empty());
+        assert!(split_command("   ").is_empty());
+
+        assert_eq!(split_command(r#"env '' """#), ["env", "", ""]);
+    }
+
+    #[test]
+    fn split_unterminated_quote() {
+        assert_eq!(split_command("echo 'a b"), ["echo", "a b"]);
+        assert_eq!(split_command(r#"echo "a \"b"#), ["echo", "a \"b"]);
+        assert_eq!(split_command(r#"echo a\"#), ["echo", "a"]);
+    }
+
+    #[test]
+    fn join_quotes() {
+        assert_eq!(join_command(["taskset", "-c", "0-3"]), "taskset -c 0-3");
+        assert_eq!(join_command(["env", "A=B C", ""]), "env 'A=B C' ''");
+        assert_eq!(join_command(["echo", r#"it's "a\b""#]), r#"echo "it's \"a\\b\"""#);
+    }
+
+    #[test]
+    fn round_trip() {
+        let commands: &[&[&str]] = &[
+            &["gamescope", "-f", "--"],
+            &["env", "A=B C", "", " "],
+            &["echo", "it's", "say \"hi\"", r"a\b", "tab\there"],
+            &["'", "\"", "\\", "'\"\\"]
+        ];
+
+        for args in commands {
+            assert_eq!(split_command(join_command(*args)), *args);
+        }
+    }
+}
@@ -20,6 +20,8 @@ use std::fs::File;
 
 use relm4::prelude::*;
 
+use clap::Parser;
+
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::filter::*;
 
@@ -28,6 +30,15 @@ pub mod utils;
 pub mod config;
 pub mod cache;
 pub mod games;
+pub mod launch;
+pub mod saves;
+pub mod mods;
+pub mod verify;
+pub mod disk;
+pub mod runtime;
+pub mod downloads;
+pub mod shortcuts;
+pub mod cli;
 pub mod ui;
 
 lazy_static::lazy_static! {
@@ -156,6 +167,14 @@ fn main() -> anyhow::Result<()> {
         tracing::warn!("WINE_CANONICAL_HOLE={value} is not supported, please contact <https://github.com/NelloKudo> to fix it");
     }
 
+    // Run command line mode if it was requested instead of opening the main
+    // window.
+    let cli = cli::Cli::parse();
+
+    if cli.is_headless() {
+        return cli.run();
+    }
+
     adw::init().expect("failed to initializa libadwaita");
 
     // Register and include resources.
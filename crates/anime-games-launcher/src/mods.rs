@@ -0,0 +1,634 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde_json::{json, Value as Json};
+
+use agl_games::api::ModsDirectory;
+
+use crate::consts::DATA_DIR;
+use crate::disk;
+
+/// Mod added to the game mods directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameMod {
+    /// Unique name of the mod within the mods directory.
+    pub name: String,
+
+    /// Title of the mod displayed in UI.
+    pub title: String,
+
+    pub enabled: bool
+}
+
+/// File deployed to the game directory by a mod.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeployedFile {
+    /// Name of the mod which provided the file.
+    pub mod_name: String,
+
+    /// Whether the original game file was moved to the backup directory.
+    pub backup: bool
+}
+
+/// File provided by more than one enabled mod.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModsConflict {
+    /// Path to the file relative to the mods directory.
+    pub path: String,
+
+    /// Names of mods which provide the file in load order. The last one is
+    /// deployed.
+    pub mods: Vec<String>
+}
+
+/// Mods of a single game mods directory. Mods are stored separately from the
+/// game files and are deployed to the game directory only when requested.
+/// Original game files replaced by the mods are moved to the backup folder
+/// and restored when mods are undeployed.
+///
+/// ```text
+/// <DATA_DIR>/mods/<game name>/<directory name>/state.json
+/// <DATA_DIR>/mods/<game name>/<directory name>/files/<mod name>/...
+/// <DATA_DIR>/mods/<game name>/<directory name>/backup/...
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModsStorage {
+    path: PathBuf,
+    directory: ModsDirectory,
+
+    /// Mods in load order. Files of later mods override files of earlier ones.
+    mods: Vec<GameMod>,
+
+    /// Files deployed to the game directory with paths relative to it. Files
+    /// are listed here before they're deployed so an interrupted deployment
+    /// could be reverted.
+    deployed: BTreeMap<String, DeployedFile>,
+
+    /// Folders created in the game directory when deploying mods.
+    created_folders: Vec<String>
+}
+
+impl ModsStorage {
+    /// Open mods storage of the game mods directory, creating it if needed.
+    pub fn open(game_name: &str, directory: &ModsDirectory) -> anyhow::Result<Self> {
+        let path = DATA_DIR.join("mods")
+            .join(game_name)
+            .join(&directory.name);
+
+        let mut storage = if path.join("state.json").is_file() {
+            Self::load(&path)?
+        } else {
+            Self {
+                path,
+                directory: directory.clone(),
+                mods: Vec::new(),
+                deployed: BTreeMap::new(),
+                created_folders: Vec::new()
+            }
+        };
+
+        // Game integration could have changed the directory. Deployed files
+        // are kept at the old path until they're undeployed.
+        if storage.deployed.is_empty() {
+            storage.directory = directory.clone();
+        } else {
+            storage.directory.title = directory.title.clone();
+        }
+
+        storage.save()?;
+
+        Ok(storage)
+    }
+
+    /// Load mods storage from its folder.
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let state = std::fs::read(path.join("state.json"))?;
+
+        let state = serde_json::from_slice::<Json>(&state)
+            .context("failed to decode json file with mods state")?;
+
+        let directory = ModsDirectory {
+            name: state.get("name")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .context("missing mods directory name")?,
+
+            title: None,
+
+            path: state.get("path")
+                .and_then(Json::as_str)
+                .map(PathBuf::from)
+                .context("missing mods directory path")?,
+
+            extensions: state.get("extensions")
+                .and_then(Json::as_array)
+                .map(|extensions| {
+                    extensions.iter()
+                        .filter_map(Json::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+        };
+
+        let mods = state.get("mods")
+            .and_then(Json::as_array)
+            .map(|mods| {
+                mods.iter()
+                    .map(|game_mod| {
+                        Ok(GameMod {
+                            name: game_mod.get("name")
+                                .and_then(Json::as_str)
+                                .map(String::from)
+                                .context("missing mod name")?,
+
+                            title: game_mod.get("title")
+                                .and_then(Json::as_str)
+                                .map(String::from)
+                                .context("missing mod title")?,
+
+                            enabled: game_mod.get("enabled")
+                                .and_then(Json::as_bool)
+                                .unwrap_or(false)
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let deployed = state.get("deployed")
+            .and_then(Json::as_object)
+            .map(|deployed| {
+                deployed.iter()
+                    .map(|(path, file)| {
+                        Ok((path.clone(), DeployedFile {
+                            mod_name: file.get("mod")
+                                .and_then(Json::as_str)
+                                .map(String::from)
+                                .context("missing deployed file mod name")?,
+
+                            backup: file.get("backup")
+                                .and_then(Json::as_bool)
+                                .unwrap_or(false)
+                        }))
+                    })
+                    .collect::<anyhow::Result<BTreeMap<_, _>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let created_folders = state.get("created_folders")
+            .and_then(Json::as_array)
+            .map(|folders| {
+                folders.iter()
+                    .filter_map(Json::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            directory,
+            mods,
+            deployed,
+            created_folders
+        })
+    }
+
+    /// Save mods state to the storage folder.
+    fn save(&self) -> anyhow::Result<()> {
+        let mods = self.mods.iter()
+            .map(|game_mod| {
+                json!({
+                    "name": game_mod.name,
+                    "title": game_mod.title,
+                    "enabled": game_mod.enabled
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let deployed = self.deployed.iter()
+            .map(|(path, file)| {
+                (path.clone(), json!({
+                    "mod": file.mod_name,
+                    "backup": file.backup
+                }))
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        let state = json!({
+            "format": 1,
+            "name": self.directory.name,
+            "path": self.directory.path,
+            "extensions": self.directory.extensions,
+            "mods": mods,
+            "deployed": deployed,
+            "created_folders": self.created_folders
+        });
+
+        std::fs::create_dir_all(&self.path)?;
+
+        disk::write_atomic(self.path.join("state.json"), serde_json::to_vec_pretty(&state)?)?;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub const fn directory(&self) -> &ModsDirectory {
+        &self.directory
+    }
+
+    /// List of mods in load order.
+    #[inline(always)]
+    pub fn mods(&self) -> &[GameMod] {
+        &self.mods
+    }
+
+    /// Check if mods are currently deployed to the game directory.
+    #[inline(always)]
+    pub fn is_deployed(&self) -> bool {
+        !self.deployed.is_empty()
+    }
+
+    #[inline]
+    fn mod_path(&self, name: &str) -> PathBuf {
+        self.path.join("files").join(name)
+    }
+
+    /// Add mod from the given file or folder to the end of the load order.
+    /// Return name of the added mod.
+    pub fn add(&mut self, source: &Path) -> anyhow::Result<String> {
+        if self.is_deployed() {
+            anyhow::bail!("mods can't be changed while they're deployed");
+        }
+
+        let title = source.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .context("invalid mod path")?;
+
+        // Make unique name for the mod folder.
+        let base_name = title.chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+            .collect::<String>();
+
+        let mut name = base_name.clone();
+        let mut i = 1;
+
+        while self.mods.iter().any(|game_mod| game_mod.name == name) || self.mod_path(&name).exists() {
+            i += 1;
+
+            name = format!("{base_name}-{i}");
+        }
+
+        let mod_path = self.mod_path(&name);
+
+        if source.is_dir() {
+            disk::copy_folder(source, &mod_path, |_| ())?;
+        } else {
+            std::fs::create_dir_all(&mod_path)?;
+            std::fs::copy(source, mod_path.join(&title))?;
+        }
+
+        self.mods.push(GameMod {
+            name: name.clone(),
+            title,
+            enabled: true
+        });
+
+        self.save()?;
+
+        Ok(name)
+    }
+
+    /// Remove mod and its files.
+    pub fn remove(&mut self, name: &str) -> anyhow::Result<()> {
+        if self.is_deployed() {
+            anyhow::bail!("mods can't be changed while they're deployed");
+        }
+
+        self.mods.retain(|game_mod| game_mod.name != name);
+
+        let mod_path = self.mod_path(name);
+
+        if mod_path.exists() {
+            std::fs::remove_dir_all(mod_path)?;
+        }
+
+        self.save()
+    }
+
+    /// Enable or disable the mod.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> anyhow::Result<()> {
+        if let Some(game_mod) = self.mods.iter_mut().find(|game_mod| game_mod.name == name) {
+            game_mod.enabled = enabled;
+        }
+
+        self.save()
+    }
+
+    /// Move mod in the load order.
+    pub fn reorder(&mut self, from: usize, to: usize) -> anyhow::Result<()> {
+        if from >= self.mods.len() || to >= self.mods.len() {
+            return Ok(());
+        }
+
+        let game_mod = self.mods.remove(from);
+
+        self.mods.insert(to, game_mod);
+
+        self.save()
+    }
+
+    /// Get list of the mod files which can be deployed, with paths relative
+    /// to the mod folder.
+    pub fn mod_files(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let mod_path = self.mod_path(name);
+
+        let mut files = Vec::new();
+
+        if !mod_path.is_dir() {
+            return Ok(files);
+        }
+
+        let mut queue = vec![mod_path.clone()];
+
+        while let Some(current) = queue.pop() {
+            for entry in current.read_dir()? {
+                let entry = entry?;
+                let entry_path = entry.path();
+
+                let file_type = entry.file_type()?;
+
+                if file_type.is_dir() {
+                    queue.push(entry_path);
+                }
+
+                else if file_type.is_file() && self.directory.is_allowed(&entry_path) {
+                    files.push(entry_path.strip_prefix(&mod_path)?
+                        .to_string_lossy()
+                        .to_string());
+                }
+            }
+        }
+
+        files.sort();
+
+        Ok(files)
+    }
+
+    /// Get table of files provided by the enabled mods where the value is the
+    /// list of mods providing the file in load order.
+    fn enabled_files(&self) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
+        let mut files = BTreeMap::<String, Vec<String>>::new();
+
+        for game_mod in self.mods.iter().filter(|game_mod| game_mod.enabled) {
+            for file in self.mod_files(&game_mod.name)? {
+                files.entry(file)
+                    .or_default()
+                    .push(game_mod.name.clone());
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Get list of files provided by more than one enabled mod.
+    pub fn conflicts(&self) -> anyhow::Result<Vec<ModsConflict>> {
+        Ok(self.enabled_files()?
+            .into_iter()
+            .filter(|(_, mods)| mods.len() > 1)
+            .map(|(path, mods)| ModsConflict { path, mods })
+            .collect())
+    }
+
+    /// Deploy enabled mods to the game directory. Previously deployed mods
+    /// are undeployed first.
+    pub fn deploy(&mut self) -> anyhow::Result<()> {
+        self.undeploy()?;
+
+        let files = self.enabled_files()?;
+
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let game_path = self.directory.path.clone();
+        let backup_path = self.path.join("backup");
+
+        if !game_path.is_dir() {
+            anyhow::bail!("mods directory doesn't exist: {game_path:?}");
+        }
+
+        let mut result = Ok(());
+
+        for (path, mods) in files {
+            // Files of later mods override earlier ones.
+            let Some(mod_name) = mods.last() else {
+                continue;
+            };
+
+            let target = game_path.join(&path);
+
+            if let Some(parent) = target.parent()
+                && let Err(err) = self.create_folders(&game_path, parent)
+            {
+                result = Err(err.context(format!("failed to deploy mod file {path}")));
+
+                break;
+            }
+
+            // Keep original game file in the backup folder.
+            let backup = target.symlink_metadata().is_ok();
+
+            self.deployed.insert(path, DeployedFile {
+                mod_name: mod_name.clone(),
+                backup
+            });
+        }
+
+        // Store all the planned changes before touching the game files so
+        // they could be undeployed even if the launcher crashes in the middle
+        // of deployment.
+        if result.is_ok() {
+            result = self.save();
+        }
+
+        if result.is_ok() {
+            for (path, file) in &self.deployed {
+                let source = self.mod_path(&file.mod_name).join(path);
+                let target = game_path.join(path);
+
+                let deployed = (|| -> anyhow::Result<()> {
+                    if file.backup {
+                        disk::move_file(&target, backup_path.join(path))?;
+                    }
+
+                    // Hard links save disk space but don't work between
+                    // different filesystems.
+                    if std::fs::hard_link(&source, &target).is_err() {
+                        disk::copy_atomic(&source, &target)?;
+                    }
+
+                    Ok(())
+                })();
+
+                if let Err(err) = deployed {
+                    result = Err(err.context(format!("failed to deploy mod file {path}")));
+
+                    break;
+                }
+            }
+        }
+
+        if result.is_err() {
+            self.undeploy()?;
+        }
+
+        result
+    }
+
+    /// Remove deployed mods files from the game directory and restore the
+    /// original game files.
+    pub fn undeploy(&mut self) -> anyhow::Result<()> {
+        if self.deployed.is_empty() && self.created_folders.is_empty() {
+            return Ok(());
+        }
+
+        let game_path = self.directory.path.clone();
+        let backup_path = self.path.join("backup");
+
+        while let Some((path, file)) = self.deployed.pop_first() {
+            let target = game_path.join(&path);
+
+            let result = (|| -> anyhow::Result<()> {
+                let backup = backup_path.join(&path);
+
+                // Deployment could have been interrupted before the original
+                // game file was moved to the backup folder.
+                if file.backup && backup.symlink_metadata().is_err() {
+                    return Ok(());
+                }
+
+                if target.symlink_metadata().is_ok() {
+                    std::fs::remove_file(&target)?;
+                }
+
+                if file.backup {
+                    disk::move_file(backup, &target)?;
+                }
+
+                Ok(())
+            })();
+
+            if let Err(err) = result {
+                self.deployed.insert(path.clone(), file);
+
+                self.save()?;
+
+                return Err(err.context(format!("failed to undeploy mod file {path}")));
+            }
+        }
+
+        // Remove created folders from the deepest ones. Folders which still
+        // contain some files (e.g. created by the game) are kept.
+        while let Some(folder) = self.created_folders.pop() {
+            let _ = std::fs::remove_dir(game_path.join(folder));
+        }
+
+        if backup_path.exists() {
+            std::fs::remove_dir_all(backup_path)?;
+        }
+
+        self.save()
+    }
+
+    /// Create missing folders of the path within the game directory,
+    /// remembering them so they could be removed when mods are undeployed.
+    fn create_folders(&mut self, game_path: &Path, path: &Path) -> anyhow::Result<()> {
+        if path.exists() {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent()
+            && parent.starts_with(game_path)
+        {
+            self.create_folders(game_path, parent)?;
+        }
+
+        std::fs::create_dir(path)?;
+
+        self.created_folders.push(
+            path.strip_prefix(game_path)?
+                .to_string_lossy()
+                .to_string()
+        );
+
+        Ok(())
+    }
+}
+
+/// Open storages of all the mods directories used by the game before.
+pub fn list_storages(game_name: &str) -> anyhow::Result<Vec<ModsStorage>> {
+    let path = DATA_DIR.join("mods").join(game_name);
+
+    let mut storages = Vec::new();
+
+    if !path.is_dir() {
+        return Ok(storages);
+    }
+
+    for entry in path.read_dir()? {
+        let entry_path = entry?.path();
+
+        if entry_path.join("state.json").is_file() {
+            storages.push(ModsStorage::load(&entry_path)?);
+        }
+    }
+
+    Ok(storages)
+}
+
+/// Deploy enabled mods of the game to its directories.
+///
+/// Note: this is a blocking function. You likely want to run it from a
+/// different thread.
+pub fn deploy_game_mods(game_name: &str) -> anyhow::Result<()> {
+    for mut storage in list_storages(game_name)? {
+        storage.deploy()?;
+    }
+
+    Ok(())
+}
+
+/// Undeploy mods of the game from its directories, restoring the original
+/// game files.
+///
+/// Note: this is a blocking function. You likely want to run it from a
+/// different thread.
+pub fn undeploy_game_mods(game_name: &str) -> anyhow::Result<()> {
+    for mut storage in list_storages(game_name)? {
+        storage.undeploy()?;
+    }
+
+    Ok(())
+}
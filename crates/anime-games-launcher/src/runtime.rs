@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::Context;
+
+use agl_core::export::network::reqwest;
+use agl_locale::string::LocalizableString;
+use agl_runtime::mlua::prelude::*;
+use agl_runtime::api::{ApiOptions, ApiContext};
+use agl_runtime::api::downloader_api::DownloaderTasks;
+use agl_runtime::api::portal_api::{ToastOptions, NotificationOptions, DialogOptions};
+use agl_runtime::api::torrent_api::{TorrentServer, TorrentServerOptions, TorrentLimits};
+use agl_runtime::runtime::Runtime;
+
+use crate::games;
+use crate::config::Config;
+
+/// Handlers of the packages requests to interact with the user.
+pub struct RuntimeHandlers {
+    pub show_toast: Box<dyn Fn(ToastOptions) + Send>,
+    pub show_notification: Box<dyn Fn(NotificationOptions) + Send>,
+    pub show_dialog: Box<dyn Fn(DialogOptions) + Send>,
+    pub translate: fn(LocalizableString) -> String
+}
+
+/// Start torrent server if it's enabled in the launcher config and use it as
+/// the games torrent source.
+pub fn start_torrent_server(config: &Config) -> Option<TorrentServer> {
+    let torrent_server = config.runtime_torrent_enable.then(|| {
+        TorrentServer::start(TorrentServerOptions {
+            default_folder: config.packages_temporary_path.clone(),
+
+            socks_proxy: match config.general_network_proxy.clone() {
+                Some(proxy) if proxy.starts_with("socks") => Some(proxy),
+                _ => None
+            },
+
+            trackers: config.runtime_torrent_trackers.iter()
+                .cloned()
+                .collect(),
+
+            blocklist_url: config.runtime_torrent_blocklist_url.clone(),
+            enable_dht: config.runtime_torrent_enable_dht,
+            enable_upnp: config.runtime_torrent_enable_upnp,
+
+            persistence_folder: config.runtime_torrent_session_path.clone(),
+
+            limits: TorrentLimits {
+                download_speed: (config.runtime_torrent_download_limit > 0)
+                    .then_some(config.runtime_torrent_download_limit),
+
+                upload_speed: (config.runtime_torrent_upload_limit > 0)
+                    .then_some(config.runtime_torrent_upload_limit),
+
+                seed_ratio: (config.runtime_torrent_seed_ratio > 0.0)
+                    .then_some(config.runtime_torrent_seed_ratio),
+
+                seed_time: (!config.runtime_torrent_seed_time.is_zero())
+                    .then_some(config.runtime_torrent_seed_time.as_secs())
+            }
+        })
+    });
+
+    if let Some(torrent_server) = &torrent_server {
+        games::set_torrent_source(torrent_server.clone());
+    }
+
+    torrent_server
+}
+
+/// Build packages runtime from the launcher config.
+pub fn build_runtime(
+    config: &Config,
+    downloader_tasks: DownloaderTasks,
+    torrent_server: Option<TorrentServer>,
+    handlers: RuntimeHandlers
+) -> anyhow::Result<Runtime> {
+    let lua = Lua::new();
+
+    // Set runtime memory limit.
+    if config.runtime_memory_limit > 0 {
+        lua.set_memory_limit(config.runtime_memory_limit)
+            .context("failed to set packages runtime memory limit")?;
+    }
+
+    let reqwest_client = config.client_builder()?
+        .build()
+        .context("failed to build network client")?;
+
+    let http_client = config.client_builder()?
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .context("failed to build network client")?;
+
+    let options = ApiOptions {
+        lua,
+        reqwest_client,
+        http_client,
+        downloader_tasks,
+        torrent_server,
+
+        show_toast: handlers.show_toast,
+        show_notification: handlers.show_notification,
+        show_dialog: handlers.show_dialog,
+
+        secrets_file: config.runtime_secrets_path.clone(),
+
+        translate: handlers.translate
+    };
+
+    let context = ApiContext::default();
+
+    if let Ok(mut private_paths) = context.private_paths.write() {
+        private_paths.extend(config.runtime_private_paths.clone());
+    }
+
+    Ok(Runtime::new(options, context)?)
+}
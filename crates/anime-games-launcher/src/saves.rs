@@ -0,0 +1,1098 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf, Component};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use serde_json::{json, Value as Json};
+
+use agl_packages::hash::Hash;
+use agl_games::api::SavesDirectory;
+
+use crate::config::Config;
+use crate::consts::DATA_DIR;
+use crate::disk::{write_atomic, copy_atomic};
+use crate::ui::dialogs::{self, DialogAction};
+use crate::i18n;
+
+/// Unused file objects modified more recently than this are kept by the
+/// garbage collector because they could belong to a snapshot which is being
+/// created right now, possibly on another machine.
+const OBJECTS_GC_GRACE_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Information about a file stored in the saves snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SavesFile {
+    /// Hash of the file content.
+    pub hash: Hash,
+
+    /// Size of the file in bytes.
+    pub size: u64
+}
+
+/// Snapshot of a game saves directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavesSnapshot {
+    /// Unique identifier of the snapshot.
+    pub id: String,
+
+    /// Identifier of the snapshot this one was made from.
+    pub parent: Option<String>,
+
+    /// Name of the machine on which the snapshot was made.
+    pub machine: String,
+
+    /// UTC timestamp of the snapshot creation.
+    pub created_at: u64,
+
+    /// Files of the saves directory with paths relative to it.
+    pub files: BTreeMap<String, SavesFile>
+}
+
+impl SavesSnapshot {
+    pub fn to_json(&self) -> Json {
+        let files = self.files.iter()
+            .map(|(path, file)| {
+                (path.clone(), json!({
+                    "hash": file.hash.to_base32(),
+                    "size": file.size
+                }))
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "format": 1,
+            "id": self.id,
+            "parent": self.parent,
+            "machine": self.machine,
+            "created_at": self.created_at,
+            "files": files
+        })
+    }
+
+    pub fn from_json(value: &Json) -> anyhow::Result<Self> {
+        if value.get("format").and_then(Json::as_u64) != Some(1) {
+            anyhow::bail!("unsupported saves snapshot format");
+        }
+
+        let mut files = BTreeMap::new();
+
+        let files_json = value.get("files")
+            .and_then(Json::as_object)
+            .ok_or_else(|| anyhow::anyhow!("missing 'files' field in saves snapshot"))?;
+
+        for (path, file) in files_json {
+            let hash = file.get("hash")
+                .and_then(Json::as_str)
+                .and_then(Hash::from_base32)
+                .ok_or_else(|| anyhow::anyhow!("invalid 'hash' field value in saves snapshot file"))?;
+
+            let size = file.get("size")
+                .and_then(Json::as_u64)
+                .ok_or_else(|| anyhow::anyhow!("invalid 'size' field value in saves snapshot file"))?;
+
+            check_relative_path(path)?;
+
+            files.insert(path.clone(), SavesFile { hash, size });
+        }
+
+        Ok(Self {
+            id: value.get("id")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("missing 'id' field in saves snapshot"))?,
+
+            parent: value.get("parent")
+                .and_then(Json::as_str)
+                .map(String::from),
+
+            machine: value.get("machine")
+                .and_then(Json::as_str)
+                .map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("missing 'machine' field in saves snapshot"))?,
+
+            created_at: value.get("created_at")
+                .and_then(Json::as_u64)
+                .ok_or_else(|| anyhow::anyhow!("missing 'created_at' field in saves snapshot"))?,
+
+            files
+        })
+    }
+
+    /// Calculate total size of the snapshot files.
+    pub fn size(&self) -> u64 {
+        self.files.values()
+            .map(|file| file.size)
+            .sum()
+    }
+}
+
+/// Result of the game saves directory synchronization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SavesSyncStatus {
+    /// Local saves are the same as the latest snapshot.
+    UpToDate,
+
+    /// Local saves were changed and stored as a new latest snapshot.
+    Uploaded(SavesSnapshot),
+
+    /// Local saves were replaced by the latest snapshot made on another
+    /// machine.
+    Downloaded(SavesSnapshot),
+
+    /// Local saves and the latest snapshot were both changed since the last
+    /// synchronization. Local saves were stored as a separate snapshot which
+    /// is not marked as the latest one, and nothing was overwritten.
+    Conflict {
+        local: SavesSnapshot,
+        remote: SavesSnapshot
+    }
+}
+
+/// Way to resolve saves synchronization conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SavesConflictResolution {
+    /// Mark local saves snapshot as the latest one.
+    KeepLocal,
+
+    /// Replace local saves by the latest snapshot.
+    UseRemote
+}
+
+/// Content-addressed storage of the games saves snapshots.
+///
+/// ```text
+/// <path>/objects/<hash>
+/// <path>/snapshots/<game name>/<directory name>/<snapshot id>.json
+/// <path>/snapshots/<game name>/<directory name>/head
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SavesStorage {
+    path: PathBuf
+}
+
+impl SavesStorage {
+    /// Open saves storage in provided folder, creating it if needed.
+    pub fn open(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path: PathBuf = path.into();
+
+        std::fs::create_dir_all(path.join("objects"))?;
+        std::fs::create_dir_all(path.join("snapshots"))?;
+
+        Ok(Self { path })
+    }
+
+    /// Get path to the saves storage folder.
+    #[inline(always)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get path to the stored object with provided hash.
+    #[inline]
+    pub fn object_path(&self, hash: &Hash) -> PathBuf {
+        self.path.join("objects").join(hash.to_base32())
+    }
+
+    /// Get path to the folder with snapshots of the game saves directory.
+    #[inline]
+    pub fn snapshots_path(&self, game_name: &str, directory: &str) -> PathBuf {
+        self.path.join("snapshots")
+            .join(game_name)
+            .join(directory)
+    }
+
+    /// Read snapshot of the game saves directory.
+    pub fn get_snapshot(
+        &self,
+        game_name: &str,
+        directory: &str,
+        id: &str
+    ) -> anyhow::Result<SavesSnapshot> {
+        let path = self.snapshots_path(game_name, directory)
+            .join(format!("{id}.json"));
+
+        let snapshot = std::fs::read(path)
+            .context("failed to read saves snapshot")?;
+
+        let snapshot = serde_json::from_slice::<Json>(&snapshot)
+            .context("failed to decode json file with saves snapshot")?;
+
+        SavesSnapshot::from_json(&snapshot)
+    }
+
+    /// Get list of snapshots of the game saves directory, sorted from the
+    /// newest to the oldest.
+    pub fn list_snapshots(
+        &self,
+        game_name: &str,
+        directory: &str
+    ) -> anyhow::Result<Vec<SavesSnapshot>> {
+        let path = self.snapshots_path(game_name, directory);
+
+        if !path.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut snapshots = Vec::new();
+
+        for entry in path.read_dir()? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let snapshot = std::fs::read(&path)?;
+
+            let snapshot = serde_json::from_slice::<Json>(&snapshot)
+                .context("failed to decode json file with saves snapshot")
+                .and_then(|snapshot| SavesSnapshot::from_json(&snapshot));
+
+            match snapshot {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(err) => tracing::warn!(?err, ?path, "skipping invalid saves snapshot")
+            }
+        }
+
+        snapshots.sort_by(|a, b| {
+            b.created_at.cmp(&a.created_at)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+
+        Ok(snapshots)
+    }
+
+    /// Get the latest (head) snapshot of the game saves directory.
+    pub fn head(
+        &self,
+        game_name: &str,
+        directory: &str
+    ) -> anyhow::Result<Option<SavesSnapshot>> {
+        let path = self.snapshots_path(game_name, directory)
+            .join("head");
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let id = std::fs::read_to_string(path)?;
+
+        self.get_snapshot(game_name, directory, id.trim())
+            .map(Some)
+    }
+
+    /// Mark snapshot as the latest (head) one.
+    pub fn set_head(
+        &self,
+        game_name: &str,
+        directory: &str,
+        id: &str
+    ) -> std::io::Result<()> {
+        write_atomic(
+            self.snapshots_path(game_name, directory).join("head"),
+            id.as_bytes()
+        )
+    }
+
+    /// Make snapshot of the provided saves directory. Missing file objects are
+    /// copied to the storage. The snapshot is not marked as the latest one.
+    pub fn create_snapshot(
+        &self,
+        game_name: &str,
+        directory: &str,
+        path: &Path,
+        parent: Option<String>,
+        machine: &str
+    ) -> anyhow::Result<SavesSnapshot> {
+        let files = scan_directory(path)?;
+
+        for (relative_path, file) in &files {
+            let object_path = self.object_path(&file.hash);
+
+            if !object_path.is_file() {
+                copy_atomic(path.join(relative_path), &object_path)
+                    .context("failed to store saves file object")?;
+            }
+
+            // Refresh modification time of the existing object so it's not
+            // removed by the garbage collector before the snapshot is saved.
+            else if let Err(err) = std::fs::File::open(&object_path)
+                .and_then(|object| object.set_modified(SystemTime::now()))
+            {
+                tracing::warn!(?err, ?object_path, "failed to update saves file object modification time");
+            }
+        }
+
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)?
+            .as_secs();
+
+        let snapshot = SavesSnapshot {
+            id: format!("{created_at}-{}", Hash::rand().to_base32()),
+            parent,
+            machine: machine.to_string(),
+            created_at,
+            files
+        };
+
+        let snapshots_path = self.snapshots_path(game_name, directory);
+
+        std::fs::create_dir_all(&snapshots_path)?;
+
+        write_atomic(
+            snapshots_path.join(format!("{}.json", snapshot.id)),
+            &serde_json::to_vec_pretty(&snapshot.to_json())?
+        )?;
+
+        Ok(snapshot)
+    }
+
+    /// Replace content of the saves directory by the provided snapshot.
+    pub fn restore_snapshot(
+        &self,
+        snapshot: &SavesSnapshot,
+        path: &Path
+    ) -> anyhow::Result<()> {
+        // Snapshots can come from other machines so don't let them write
+        // anything outside of the saves directory.
+        for relative_path in snapshot.files.keys() {
+            check_relative_path(relative_path)?;
+        }
+
+        let current = scan_directory(path)?;
+
+        // Remove files which are not stored in the snapshot.
+        for relative_path in current.keys() {
+            if !snapshot.files.contains_key(relative_path) {
+                std::fs::remove_file(path.join(relative_path))?;
+            }
+        }
+
+        // Copy changed files from the storage.
+        for (relative_path, file) in &snapshot.files {
+            if current.get(relative_path) == Some(file) {
+                continue;
+            }
+
+            let object_path = self.object_path(&file.hash);
+
+            if !object_path.is_file() {
+                anyhow::bail!("saves file object {} is missing in the storage", file.hash);
+            }
+
+            copy_atomic(&object_path, path.join(relative_path))
+                .context("failed to restore saves file")?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove older snapshots of the game saves directory so only `keep` newest
+    /// ones are left. The head snapshot is never removed.
+    pub fn prune_snapshots(
+        &self,
+        game_name: &str,
+        directory: &str,
+        keep: usize
+    ) -> anyhow::Result<()> {
+        if keep == 0 {
+            return Ok(());
+        }
+
+        let head = self.head(game_name, directory)?
+            .map(|snapshot| snapshot.id);
+
+        let snapshots_path = self.snapshots_path(game_name, directory);
+
+        for snapshot in self.list_snapshots(game_name, directory)?.into_iter().skip(keep) {
+            if head.as_ref() == Some(&snapshot.id) {
+                continue;
+            }
+
+            tracing::trace!(id = snapshot.id, "remove old saves snapshot");
+
+            std::fs::remove_file(snapshots_path.join(format!("{}.json", snapshot.id)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove file objects which are not used by any snapshot. Recently
+    /// modified objects are kept to not break snapshots which are being
+    /// created at the same time.
+    pub fn collect_garbage(&self) -> anyhow::Result<()> {
+        let mut used = HashSet::new();
+
+        for game in self.path.join("snapshots").read_dir()? {
+            let game = game?;
+
+            if !game.path().is_dir() {
+                continue;
+            }
+
+            for directory in game.path().read_dir()? {
+                let directory = directory?;
+
+                if !directory.path().is_dir() {
+                    continue;
+                }
+
+                let snapshots = self.list_snapshots(
+                    &game.file_name().to_string_lossy(),
+                    &directory.file_name().to_string_lossy()
+                )?;
+
+                for snapshot in snapshots {
+                    used.extend(snapshot.files.values().map(|file| file.hash));
+                }
+            }
+        }
+
+        let now = SystemTime::now();
+
+        for entry in self.path.join("objects").read_dir()? {
+            let entry = entry?;
+
+            let is_used = Hash::from_base32(entry.file_name().to_string_lossy())
+                .map(|hash| used.contains(&hash))
+                .unwrap_or(false);
+
+            if is_used {
+                continue;
+            }
+
+            let is_recent = now.duration_since(entry.metadata()?.modified()?)
+                .map(|age| age < OBJECTS_GC_GRACE_PERIOD)
+                .unwrap_or(true);
+
+            if !is_recent {
+                tracing::trace!(path = ?entry.path(), "remove unused saves file object");
+
+                std::fs::remove_file(entry.path())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Synchronize the game saves directory with the storage.
+    ///
+    /// Identifier of the snapshot the local saves were synchronized with last
+    /// time is kept by the launcher separately for every machine. It is used
+    /// to detect whether local saves, the latest snapshot, or both were changed
+    /// since the previous synchronization.
+    pub fn sync(
+        &self,
+        game_name: &str,
+        directory: &SavesDirectory,
+        machine: &str
+    ) -> anyhow::Result<SavesSyncStatus> {
+        let mut state = SavesState::load(game_name)?;
+
+        let status = self.sync_state(&mut state, game_name, directory, machine)?;
+
+        state.save(game_name)?;
+
+        Ok(status)
+    }
+
+    /// Synchronize the game saves directory with the storage using provided
+    /// local synchronization state.
+    fn sync_state(
+        &self,
+        state: &mut SavesState,
+        game_name: &str,
+        directory: &SavesDirectory,
+        machine: &str
+    ) -> anyhow::Result<SavesSyncStatus> {
+        let base_id = state.get(&directory.name)
+            .map(String::from);
+
+        let base = match &base_id {
+            Some(id) => self.get_snapshot(game_name, &directory.name, id).ok(),
+            None => None
+        };
+
+        let local_files = scan_directory(&directory.path)?;
+
+        let local_changed = match &base {
+            Some(base) => base.files != local_files,
+            None => !local_files.is_empty()
+        };
+
+        let head = self.head(game_name, &directory.name)?;
+
+        let status = match head {
+            // Nothing was stored yet.
+            None if local_files.is_empty() => SavesSyncStatus::UpToDate,
+
+            None => {
+                let snapshot = self.create_snapshot(
+                    game_name,
+                    &directory.name,
+                    &directory.path,
+                    None,
+                    machine
+                )?;
+
+                self.set_head(game_name, &directory.name, &snapshot.id)?;
+
+                SavesSyncStatus::Uploaded(snapshot)
+            }
+
+            // Latest snapshot wasn't changed since the previous sync.
+            Some(head) if base_id.as_ref() == Some(&head.id) => {
+                if !local_changed {
+                    SavesSyncStatus::UpToDate
+                } else {
+                    let snapshot = self.create_snapshot(
+                        game_name,
+                        &directory.name,
+                        &directory.path,
+                        Some(head.id),
+                        machine
+                    )?;
+
+                    self.set_head(game_name, &directory.name, &snapshot.id)?;
+
+                    SavesSyncStatus::Uploaded(snapshot)
+                }
+            }
+
+            // Latest snapshot was changed on another machine.
+            Some(head) => {
+                if head.files == local_files {
+                    state.set(&directory.name, &head.id);
+
+                    SavesSyncStatus::UpToDate
+                }
+
+                else if !local_changed || local_files.is_empty() {
+                    self.restore_snapshot(&head, &directory.path)?;
+
+                    SavesSyncStatus::Downloaded(head)
+                }
+
+                else {
+                    // Reuse snapshot of the local saves made when the
+                    // conflict was found before if it's still unresolved.
+                    let pending = self.list_snapshots(game_name, &directory.name)?
+                        .into_iter()
+                        .find(|snapshot| {
+                            snapshot.parent == base_id
+                                && snapshot.machine == machine
+                                && snapshot.files == local_files
+                        });
+
+                    let snapshot = match pending {
+                        Some(snapshot) => snapshot,
+
+                        None => self.create_snapshot(
+                            game_name,
+                            &directory.name,
+                            &directory.path,
+                            base_id,
+                            machine
+                        )?
+                    };
+
+                    SavesSyncStatus::Conflict {
+                        local: snapshot,
+                        remote: head
+                    }
+                }
+            }
+        };
+
+        match &status {
+            SavesSyncStatus::Uploaded(snapshot) |
+            SavesSyncStatus::Downloaded(snapshot) => {
+                state.set(&directory.name, &snapshot.id);
+            }
+
+            SavesSyncStatus::UpToDate |
+            SavesSyncStatus::Conflict { .. } => ()
+        }
+
+        Ok(status)
+    }
+
+    /// Resolve saves synchronization conflict.
+    pub fn resolve_conflict(
+        &self,
+        game_name: &str,
+        directory: &SavesDirectory,
+        local: &SavesSnapshot,
+        remote: &SavesSnapshot,
+        resolution: SavesConflictResolution
+    ) -> anyhow::Result<()> {
+        let mut state = SavesState::load(game_name)?;
+
+        match resolution {
+            SavesConflictResolution::KeepLocal => {
+                self.set_head(game_name, &directory.name, &local.id)?;
+
+                state.set(&directory.name, &local.id);
+            }
+
+            SavesConflictResolution::UseRemote => {
+                self.restore_snapshot(remote, &directory.path)?;
+
+                state.set(&directory.name, &remote.id);
+            }
+        }
+
+        state.save(game_name)
+    }
+
+    /// Restore the game saves directory from provided snapshot and mark it
+    /// as the latest one. Current local saves are stored as a separate
+    /// snapshot before being replaced.
+    pub fn rollback(
+        &self,
+        game_name: &str,
+        directory: &SavesDirectory,
+        snapshot: &SavesSnapshot,
+        machine: &str
+    ) -> anyhow::Result<()> {
+        let mut state = SavesState::load(game_name)?;
+
+        self.create_snapshot(
+            game_name,
+            &directory.name,
+            &directory.path,
+            state.get(&directory.name).map(String::from),
+            machine
+        )?;
+
+        self.restore_snapshot(snapshot, &directory.path)?;
+        self.set_head(game_name, &directory.name, &snapshot.id)?;
+
+        state.set(&directory.name, &snapshot.id);
+        state.save(game_name)
+    }
+}
+
+/// Ask user to resolve the game saves synchronization conflict using a dialog.
+///
+/// Return `None` if the dialog was closed without choosing anything.
+///
+/// Note: this is a blocking function. You likely want to run it from a
+/// different thread.
+pub fn ask_conflict_resolution(
+    title: &str,
+    machine: &str
+) -> Option<SavesConflictResolution> {
+    let response = dialogs::present(
+        i18n!("game_saves_conflict_title")
+            .unwrap_or("Game saves conflict"),
+
+        i18n!("game_saves_conflict_message", { title => title, machine => machine })
+            .unwrap_or_else(|| format!("{title} saves were changed both on this machine and on {machine} since the last synchronization. Which saves do you want to keep? Other ones will stay available as a snapshot.")),
+
+        [
+            DialogAction::new(
+                "keep_local",
+                i18n!("game_saves_keep_local")
+                    .unwrap_or("Keep local saves")
+            ),
+
+            DialogAction::new(
+                "use_remote",
+                i18n!("game_saves_use_remote", { machine => machine })
+                    .unwrap_or_else(|| format!("Use {machine} saves"))
+            ).as_suggested()
+        ]
+    );
+
+    match response.as_deref() {
+        Some("keep_local") => Some(SavesConflictResolution::KeepLocal),
+        Some("use_remote") => Some(SavesConflictResolution::UseRemote),
+
+        _ => None
+    }
+}
+
+/// Synchronize all the game saves directories with the storage from the
+/// launcher config. Conflicts are resolved using the provided callback which
+/// accepts the saves directory title and the remote machine name. If it
+/// returns `None`, then the conflict is left unresolved: local saves are not
+/// overwritten and the conflict will be reported again next time.
+///
+/// Note: this is a blocking function. You likely want to run it from a
+/// different thread.
+pub fn sync_game_saves(
+    game_name: &str,
+    directories: &[SavesDirectory],
+    config: &Config,
+    mut resolve_conflict: impl FnMut(&str, &str) -> Option<SavesConflictResolution>
+) -> anyhow::Result<()> {
+    let storage = SavesStorage::open(&config.saves_path)
+        .context("failed to open saves storage")?;
+
+    let mut uploaded = false;
+
+    for directory in directories {
+        let status = storage.sync(game_name, directory, &config.saves_machine)
+            .with_context(|| format!("failed to synchronize '{}' saves directory", directory.name))?;
+
+        tracing::debug!(?game_name, directory = directory.name, ?status, "synchronized game saves directory");
+
+        match status {
+            SavesSyncStatus::UpToDate |
+            SavesSyncStatus::Downloaded(_) => (),
+
+            SavesSyncStatus::Uploaded(_) => uploaded = true,
+
+            SavesSyncStatus::Conflict { local, remote } => {
+                let title = directory.title.as_ref()
+                    .map(|title| {
+                        match config.language() {
+                            Ok(lang) => title.translate(&lang),
+                            Err(_) => title.default_translation()
+                        }
+                    })
+                    .unwrap_or(directory.name.as_str());
+
+                let Some(resolution) = resolve_conflict(title, &remote.machine) else {
+                    tracing::warn!(?game_name, directory = directory.name, "game saves conflict left unresolved");
+
+                    continue;
+                };
+
+                storage.resolve_conflict(game_name, directory, &local, &remote, resolution)
+                    .context("failed to resolve saves synchronization conflict")?;
+
+                uploaded |= resolution == SavesConflictResolution::KeepLocal;
+            }
+        }
+
+        storage.prune_snapshots(game_name, &directory.name, config.saves_keep_snapshots)
+            .context("failed to remove old saves snapshots")?;
+    }
+
+    if uploaded && config.saves_keep_snapshots > 0 {
+        storage.collect_garbage()
+            .context("failed to remove unused saves file objects")?;
+    }
+
+    Ok(())
+}
+
+/// Local synchronization state of the game saves directories. Stores
+/// identifiers of the snapshots each directory was synchronized with.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct SavesState(HashMap<String, String>);
+
+impl SavesState {
+    fn path(game_name: &str) -> PathBuf {
+        DATA_DIR.join("saves_state").join(game_name)
+    }
+
+    fn load(game_name: &str) -> anyhow::Result<Self> {
+        let path = Self::path(game_name);
+
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let state = std::fs::read(path)?;
+
+        let state = serde_json::from_slice::<HashMap<String, String>>(&state)
+            .context("failed to decode json file with saves state")?;
+
+        Ok(Self(state))
+    }
+
+    fn save(&self, game_name: &str) -> anyhow::Result<()> {
+        let path = Self::path(game_name);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        write_atomic(&path, &serde_json::to_vec_pretty(&self.0)?)?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn get(&self, directory: &str) -> Option<&str> {
+        self.0.get(directory).map(String::as_str)
+    }
+
+    #[inline]
+    fn set(&mut self, directory: &str, id: &str) {
+        self.0.insert(directory.to_string(), id.to_string());
+    }
+}
+
+/// Get list of files within the saves directory with their hashes and sizes.
+/// Return empty list if the directory doesn't exist.
+pub fn scan_directory(path: &Path) -> anyhow::Result<BTreeMap<String, SavesFile>> {
+    let mut files = BTreeMap::new();
+
+    if !path.is_dir() {
+        return Ok(files);
+    }
+
+    let mut queue = vec![path.to_path_buf()];
+
+    while let Some(current) = queue.pop() {
+        for entry in current.read_dir()? {
+            let entry = entry?;
+            let entry_path = entry.path();
+
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                queue.push(entry_path);
+            }
+
+            else if file_type.is_file() {
+                let relative_path = entry_path.strip_prefix(path)?
+                    .to_string_lossy()
+                    .to_string();
+
+                files.insert(relative_path, SavesFile {
+                    hash: Hash::digitize_path(&entry_path)?,
+                    size: entry.metadata()?.len()
+                });
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Check that the saves file path stays within the saves directory.
+fn check_relative_path(path: &str) -> anyhow::Result<()> {
+    let path = Path::new(path);
+
+    let is_valid = !path.has_root()
+        && path.components().next().is_some()
+        && path.components().all(|component| matches!(component, Component::Normal(_)));
+
+    if !is_valid {
+        anyhow::bail!("invalid saves file path in snapshot: {path:?}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_dir(name: &str) -> std::io::Result<PathBuf> {
+        let path = std::env::temp_dir()
+            .join(".agl-saves-test")
+            .join(name);
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+
+        std::fs::create_dir_all(&path)?;
+
+        Ok(path)
+    }
+
+    fn saves_directory(path: PathBuf) -> SavesDirectory {
+        SavesDirectory {
+            name: String::from("saves"),
+            title: None,
+            path
+        }
+    }
+
+    #[test]
+    fn snapshot_restore() -> anyhow::Result<()> {
+        let path = get_test_dir("snapshot_restore")?;
+        let saves_path = path.join("saves");
+
+        std::fs::create_dir_all(saves_path.join("slots"))?;
+        std::fs::write(saves_path.join("settings.ini"), "volume = 100")?;
+        std::fs::write(saves_path.join("slots/1.sav"), "slot 1")?;
+
+        let storage = SavesStorage::open(path.join("storage"))?;
+
+        let snapshot = storage.create_snapshot("game", "saves", &saves_path, None, "machine")?;
+
+        assert_eq!(snapshot.files.len(), 2);
+        assert_eq!(snapshot.size(), 18);
+        assert_eq!(snapshot.files, scan_directory(&saves_path)?);
+
+        for file in snapshot.files.values() {
+            assert!(storage.object_path(&file.hash).is_file());
+        }
+
+        assert_eq!(storage.get_snapshot("game", "saves", &snapshot.id)?, snapshot);
+        assert_eq!(storage.list_snapshots("game", "saves")?, vec![snapshot.clone()]);
+        assert_eq!(storage.head("game", "saves")?, None);
+
+        std::fs::write(saves_path.join("settings.ini"), "volume = 50")?;
+        std::fs::write(saves_path.join("slots/2.sav"), "slot 2")?;
+        std::fs::remove_file(saves_path.join("slots/1.sav"))?;
+
+        storage.restore_snapshot(&snapshot, &saves_path)?;
+
+        assert_eq!(scan_directory(&saves_path)?, snapshot.files);
+        assert_eq!(std::fs::read_to_string(saves_path.join("settings.ini"))?, "volume = 100");
+        assert_eq!(std::fs::read_to_string(saves_path.join("slots/1.sav"))?, "slot 1");
+        assert!(!saves_path.join("slots/2.sav").exists());
+
+        storage.set_head("game", "saves", &snapshot.id)?;
+
+        assert_eq!(storage.head("game", "saves")?, Some(snapshot));
+
+        Ok(())
+    }
+
+    #[test]
+    fn snapshot_path_traversal() -> anyhow::Result<()> {
+        let path = get_test_dir("snapshot_path_traversal")?;
+        let saves_path = path.join("saves");
+
+        std::fs::create_dir_all(&saves_path)?;
+        std::fs::write(saves_path.join("save.sav"), "save")?;
+
+        let storage = SavesStorage::open(path.join("storage"))?;
+
+        let mut snapshot = storage.create_snapshot("game", "saves", &saves_path, None, "machine")?;
+
+        let file = snapshot.files.remove("save.sav").unwrap();
+
+        snapshot.files.insert(String::from("../x"), file);
+
+        // Snapshot file can't be read.
+        std::fs::write(
+            storage.snapshots_path("game", "saves").join(format!("{}.json", snapshot.id)),
+            serde_json::to_vec(&snapshot.to_json())?
+        )?;
+
+        assert!(storage.get_snapshot("game", "saves", &snapshot.id).is_err());
+
+        // Snapshot can't be restored.
+        assert!(storage.restore_snapshot(&snapshot, &saves_path).is_err());
+
+        assert!(!path.join("x").exists());
+        assert!(saves_path.join("save.sav").exists());
+
+        for invalid in ["/x", "", ".", "a/../../x", "./x"] {
+            assert!(check_relative_path(invalid).is_err());
+        }
+
+        assert!(check_relative_path("slots/1.sav").is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sync_conflict() -> anyhow::Result<()> {
+        let path = get_test_dir("sync_conflict")?;
+
+        let storage = SavesStorage::open(path.join("storage"))?;
+
+        let directory_a = saves_directory(path.join("saves_a"));
+        let directory_b = saves_directory(path.join("saves_b"));
+
+        let mut state_a = SavesState::default();
+        let mut state_b = SavesState::default();
+
+        std::fs::create_dir_all(&directory_a.path)?;
+        std::fs::write(directory_a.path.join("1.sav"), "first")?;
+
+        let status = storage.sync_state(&mut state_a, "game", &directory_a, "a")?;
+
+        assert!(matches!(status, SavesSyncStatus::Uploaded(_)));
+        assert_eq!(storage.sync_state(&mut state_a, "game", &directory_a, "a")?, SavesSyncStatus::UpToDate);
+
+        let status = storage.sync_state(&mut state_b, "game", &directory_b, "b")?;
+
+        assert!(matches!(status, SavesSyncStatus::Downloaded(_)));
+        assert_eq!(std::fs::read_to_string(directory_b.path.join("1.sav"))?, "first");
+
+        // Change saves on both machines.
+        std::fs::write(directory_a.path.join("1.sav"), "second a")?;
+        std::fs::write(directory_b.path.join("1.sav"), "second b")?;
+
+        let status = storage.sync_state(&mut state_a, "game", &directory_a, "a")?;
+
+        assert!(matches!(status, SavesSyncStatus::Uploaded(_)));
+
+        let SavesSyncStatus::Conflict { local, remote } = storage.sync_state(&mut state_b, "game", &directory_b, "b")? else {
+            panic!("saves conflict expected");
+        };
+
+        assert_eq!(local.machine, "b");
+        assert_eq!(remote.machine, "a");
+        assert_eq!(storage.head("game", "saves")?, Some(remote.clone()));
+        assert_eq!(std::fs::read_to_string(directory_b.path.join("1.sav"))?, "second b");
+
+        let snapshots = storage.list_snapshots("game", "saves")?.len();
+
+        // Unresolved conflict must reuse the same local snapshot.
+        let status = storage.sync_state(&mut state_b, "game", &directory_b, "b")?;
+
+        assert_eq!(status, SavesSyncStatus::Conflict {
+            local: local.clone(),
+            remote: remote.clone()
+        });
+
+        assert_eq!(storage.list_snapshots("game", "saves")?.len(), snapshots);
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_garbage() -> anyhow::Result<()> {
+        let path = get_test_dir("collect_garbage")?;
+        let saves_path = path.join("saves");
+
+        std::fs::create_dir_all(&saves_path)?;
+        std::fs::write(saves_path.join("1.sav"), "slot 1")?;
+
+        let storage = SavesStorage::open(path.join("storage"))?;
+
+        let snapshot = storage.create_snapshot("game", "saves", &saves_path, None, "machine")?;
+
+        let used_object = storage.object_path(&snapshot.files["1.sav"].hash);
+        let old_object = storage.object_path(&Hash::rand());
+        let recent_object = storage.object_path(&Hash::rand());
+
+        std::fs::write(&old_object, "old")?;
+        std::fs::write(&recent_object, "recent")?;
+
+        let old = SystemTime::now() - OBJECTS_GC_GRACE_PERIOD * 2;
+
+        std::fs::File::open(&used_object)?.set_modified(old)?;
+        std::fs::File::open(&old_object)?.set_modified(old)?;
+
+        storage.collect_garbage()?;
+
+        assert!(used_object.is_file());
+        assert!(!old_object.exists());
+        assert!(recent_object.is_file());
+
+        Ok(())
+    }
+}
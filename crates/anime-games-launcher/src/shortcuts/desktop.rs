@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use agl_core::tasks;
+
+use crate::{consts, config};
+use crate::games::GameLock;
+
+/// Get path to the current user's applications folder.
+pub fn applications_dir() -> Option<PathBuf> {
+    std::env::var("XDG_DATA_HOME")
+        .map(|data| PathBuf::from(data).join("applications"))
+        .ok()
+        .or_else(|| {
+            consts::HOME_DIR.as_ref()
+                .map(|dir| dir.join(".local/share/applications"))
+        })
+}
+
+/// Get path to the desktop entry file of the game.
+pub fn entry_path(game_name: impl AsRef<str>) -> Option<PathBuf> {
+    let name = format!("{}.{}.desktop", consts::APP_ID, game_name.as_ref());
+
+    applications_dir().map(|dir| dir.join(name))
+}
+
+/// Check if desktop entry of the game exists.
+#[inline]
+pub fn has_entry(game_name: impl AsRef<str>) -> bool {
+    entry_path(game_name).is_some_and(|path| path.is_file())
+}
+
+/// Escape value of the desktop entry string key.
+fn escape_string(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
+
+/// Quote argument of the desktop entry `Exec` key if it's needed.
+fn quote_exec_arg(arg: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$',
+        '*', '?', '#', '(', ')', '`', '='
+    ];
+
+    let mut arg = arg.replace('%', "%%");
+
+    if arg.is_empty() || arg.contains(RESERVED) {
+        let mut quoted = String::with_capacity(arg.len() + 2);
+
+        quoted.push('"');
+
+        for char in arg.chars() {
+            if matches!(char, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+
+            quoted.push(char);
+        }
+
+        quoted.push('"');
+
+        arg = quoted;
+    }
+
+    arg
+}
+
+/// Build desktop entry file content which launches the game with provided
+/// program and arguments.
+pub fn build_entry(
+    title: &str,
+    icon: Option<&Path>,
+    program: &Path,
+    args: &[String]
+) -> String {
+    let exec = std::iter::once(program.to_string_lossy().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| quote_exec_arg(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut entry = String::from("[Desktop Entry]\n");
+
+    entry.push_str("Type=Application\n");
+    entry.push_str(&format!("Name={}\n", escape_string(title)));
+    entry.push_str(&format!("Exec={}\n", escape_string(&exec)));
+
+    if let Some(icon) = icon {
+        entry.push_str(&format!("Icon={}\n", escape_string(&icon.to_string_lossy())));
+    }
+
+    entry.push_str("Terminal=false\n");
+    entry.push_str("Categories=Game;\n");
+
+    entry
+}
+
+/// Create desktop entry for the game so it's shown in the applications menu.
+/// Return path to the created file.
+pub async fn create_entry(lock: &GameLock) -> anyhow::Result<PathBuf> {
+    let config = config::get().await;
+
+    let game_name = lock.name();
+
+    let path = entry_path(&game_name)
+        .ok_or_else(|| anyhow::anyhow!("failed to locate applications folder"))?;
+
+    let title = match config.language() {
+        Ok(lang) => lock.manifest.game.title.translate(&lang),
+        Err(_) => lock.manifest.game.title.default_translation()
+    };
+
+    // Entry is still useful without an icon.
+    let icon = match super::cache_icon(lock).await {
+        Ok(icon) => Some(icon),
+
+        Err(err) => {
+            tracing::warn!(?err, ?game_name, "failed to cache game icon");
+
+            None
+        }
+    };
+
+    let (program, args) = super::launch_command(&game_name)?;
+
+    let entry = build_entry(title, icon.as_deref(), &program, &args);
+
+    if let Some(parent) = path.parent() {
+        tasks::fs::create_dir_all(parent).await?;
+    }
+
+    tasks::fs::write(&path, entry).await
+        .context("failed to write desktop entry file")?;
+
+    tracing::info!(?game_name, ?path, "created game desktop entry");
+
+    Ok(path)
+}
+
+/// Remove desktop entry of the game if it exists.
+pub async fn remove_entry(game_name: impl AsRef<str>) -> anyhow::Result<()> {
+    if let Some(path) = entry_path(game_name) && path.is_file() {
+        tasks::fs::remove_file(&path).await
+            .context("failed to remove desktop entry file")?;
+
+        tracing::info!(?path, "removed game desktop entry");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use agl_core::tasks;
+use agl_core::network::downloader::{Downloader, DownloadOptions};
+
+use crate::{consts, config, cache};
+use crate::games::GameLock;
+
+pub mod desktop;
+pub mod steam;
+
+/// Path to the flatpak binary on the host system.
+const FLATPAK_PATH: &str = "/usr/bin/flatpak";
+
+/// Get program and arguments used to launch the game directly, without
+/// opening the launcher's main window.
+pub fn launch_command(game_name: impl AsRef<str>) -> anyhow::Result<(PathBuf, Vec<String>)> {
+    let game_name = game_name.as_ref().to_string();
+
+    // Flatpak applications must be started through the flatpak itself. Steam
+    // requires absolute executable paths.
+    if std::env::var("FLATPAK_ID").is_ok_and(|id| id == consts::APP_ID) {
+        return Ok((PathBuf::from(FLATPAK_PATH), vec![
+            String::from("run"),
+            String::from(consts::APP_ID),
+            String::from("--launch"),
+            game_name
+        ]));
+    }
+
+    let program = std::env::current_exe()
+        .context("failed to get launcher executable path")?;
+
+    Ok((program, vec![String::from("--launch"), game_name]))
+}
+
+/// Get path to the cached icon of the game.
+#[inline]
+pub fn icon_path(game_name: impl AsRef<str>) -> PathBuf {
+    consts::DATA_DIR.join("icons").join(game_name.as_ref())
+}
+
+/// Download game icon from its manifest to the launcher's data folder, or use
+/// already downloaded one if it's not expired.
+pub async fn cache_icon(lock: &GameLock) -> anyhow::Result<PathBuf> {
+    let config = config::get().await;
+
+    let path = icon_path(lock.name());
+
+    if !cache::is_expired(&path, config.cache_images_duration).await? {
+        return Ok(path);
+    }
+
+    if let Some(parent) = path.parent() {
+        tasks::fs::create_dir_all(parent).await?;
+    }
+
+    let client = config.client_builder()
+        .context("failed to create network client from the config values")?
+        .build()
+        .context("failed to build network client")?;
+
+    Downloader::from_client(client)
+        .download_with_options(&lock.manifest.game.images.icon, &path, DownloadOptions {
+            continue_download: false,
+            on_update: None,
+            on_finish: None
+        })
+        .wait().await
+        .context("failed to download game icon")?;
+
+    Ok(path)
+}
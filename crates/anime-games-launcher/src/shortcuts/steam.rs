@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use agl_core::tasks;
+use agl_core::vdf::{self, VdfValue};
+use agl_core::export::hashes::crc32;
+
+use crate::{consts, config};
+use crate::games::GameLock;
+
+/// Non-Steam game shortcut.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SteamShortcut {
+    /// Title of the shortcut.
+    pub app_name: String,
+
+    /// Path to the executable.
+    pub exe: PathBuf,
+
+    /// Working directory of the executable.
+    pub start_dir: PathBuf,
+
+    /// Optional path to the shortcut icon.
+    pub icon: Option<PathBuf>,
+
+    /// Arguments passed to the executable.
+    pub launch_options: String
+}
+
+impl SteamShortcut {
+    #[inline]
+    fn quoted_exe(&self) -> String {
+        format!("\"{}\"", self.exe.to_string_lossy())
+    }
+
+    /// Get Steam app id of the shortcut. Steam uses CRC32 checksum of the
+    /// quoted executable path and the shortcut title.
+    pub fn app_id(&self) -> u32 {
+        let key = format!("{}{}", self.quoted_exe(), self.app_name);
+
+        crc32::hash(key.as_bytes()) | 0x80000000
+    }
+
+    pub fn to_vdf(&self) -> VdfValue {
+        let icon = self.icon.as_ref()
+            .map(|icon| icon.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        VdfValue::Map(vec![
+            (String::from("appid"), VdfValue::Int(self.app_id())),
+            (String::from("AppName"), VdfValue::String(self.app_name.clone())),
+            (String::from("Exe"), VdfValue::String(self.quoted_exe())),
+            (String::from("StartDir"), VdfValue::String(format!("\"{}\"", self.start_dir.to_string_lossy()))),
+            (String::from("icon"), VdfValue::String(icon)),
+            (String::from("ShortcutPath"), VdfValue::String(String::new())),
+            (String::from("LaunchOptions"), VdfValue::String(self.launch_options.clone())),
+            (String::from("IsHidden"), VdfValue::Int(0)),
+            (String::from("AllowDesktopConfig"), VdfValue::Int(1)),
+            (String::from("AllowOverlay"), VdfValue::Int(1)),
+            (String::from("OpenVR"), VdfValue::Int(0)),
+            (String::from("Devkit"), VdfValue::Int(0)),
+            (String::from("DevkitGameID"), VdfValue::String(String::new())),
+            (String::from("DevkitOverrideAppID"), VdfValue::Int(0)),
+            (String::from("LastPlayTime"), VdfValue::Int(0)),
+            (String::from("FlatpakAppID"), VdfValue::String(String::new())),
+            (String::from("tags"), VdfValue::Map(vec![]))
+        ])
+    }
+
+    /// Check if the VDF value describes the same shortcut.
+    fn matches(&self, value: &VdfValue) -> bool {
+        let exe = value.get("Exe").and_then(VdfValue::as_str);
+        let launch_options = value.get("LaunchOptions").and_then(VdfValue::as_str);
+
+        exe == Some(self.quoted_exe().as_str())
+            && launch_options == Some(self.launch_options.as_str())
+    }
+}
+
+/// Get paths to the `shortcuts.vdf` files of all the Steam users on this
+/// machine. Files may not exist yet.
+pub fn shortcuts_files() -> Vec<PathBuf> {
+    let Some(home) = consts::HOME_DIR.as_ref() else {
+        return vec![];
+    };
+
+    let steam_dirs = [
+        home.join(".steam/steam"),
+        home.join(".local/share/Steam"),
+        home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam")
+    ];
+
+    let mut files = Vec::new();
+
+    for steam_dir in steam_dirs {
+        // `~/.steam/steam` is usually a symlink to one of the other folders.
+        let Ok(steam_dir) = steam_dir.canonicalize() else {
+            continue;
+        };
+
+        let Ok(users) = steam_dir.join("userdata").read_dir() else {
+            continue;
+        };
+
+        for user in users.flatten() {
+            let path = user.path().join("config/shortcuts.vdf");
+
+            // Skip the anonymous user and duplicated folders.
+            if user.file_name() != "0" && user.path().is_dir() && !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    files
+}
+
+/// Add shortcut to the `shortcuts.vdf` file, replacing the same one if it
+/// was already added before.
+pub fn add_shortcut(path: &Path, shortcut: &SteamShortcut) -> anyhow::Result<()> {
+    let mut shortcuts = read_shortcuts(path)?;
+
+    shortcuts.retain(|value| !shortcut.matches(value));
+    shortcuts.push(shortcut.to_vdf());
+
+    write_shortcuts(path, shortcuts)
+}
+
+fn read_shortcuts(path: &Path) -> anyhow::Result<Vec<VdfValue>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let bytes = std::fs::read(path)
+        .context("failed to read steam shortcuts file")?;
+
+    let entries = vdf::decode(&bytes)
+        .context("failed to decode steam shortcuts file")?;
+
+    let shortcuts = entries.into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("shortcuts"))
+        .map(|(_, value)| value);
+
+    match shortcuts {
+        Some(VdfValue::Map(shortcuts)) => Ok(shortcuts.into_iter()
+            .map(|(_, value)| value)
+            .collect()),
+
+        Some(_) => anyhow::bail!("invalid steam shortcuts file format"),
+        None => Ok(vec![])
+    }
+}
+
+fn write_shortcuts(path: &Path, shortcuts: Vec<VdfValue>) -> anyhow::Result<()> {
+    // Shortcuts are stored as a map with sequential indexes as keys.
+    let shortcuts = shortcuts.into_iter()
+        .enumerate()
+        .map(|(i, value)| (i.to_string(), value))
+        .collect();
+
+    let bytes = vdf::encode(&[
+        (String::from("shortcuts"), VdfValue::Map(shortcuts))
+    ]).context("failed to encode steam shortcuts file")?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, bytes)
+        .context("failed to write steam shortcuts file")?;
+
+    Ok(())
+}
+
+/// Build non-Steam shortcut for the game.
+pub async fn game_shortcut(lock: &GameLock) -> anyhow::Result<SteamShortcut> {
+    let config = config::get().await;
+
+    let game_name = lock.name();
+
+    let title = match config.language() {
+        Ok(lang) => lock.manifest.game.title.translate(&lang),
+        Err(_) => lock.manifest.game.title.default_translation()
+    };
+
+    let icon = match super::cache_icon(lock).await {
+        Ok(icon) => Some(icon),
+
+        Err(err) => {
+            tracing::warn!(?err, ?game_name, "failed to cache game icon");
+
+            None
+        }
+    };
+
+    let (program, args) = super::launch_command(&game_name)?;
+
+    let start_dir = program.parent()
+        .filter(|path| !path.as_os_str().is_empty())
+        .map(PathBuf::from)
+        .or_else(|| consts::HOME_DIR.clone())
+        .unwrap_or_else(|| PathBuf::from("/"));
+
+    Ok(SteamShortcut {
+        app_name: title.to_string(),
+        exe: program,
+        start_dir,
+        icon,
+        launch_options: crate::launch::join_command(args)
+    })
+}
+
+/// Add non-Steam shortcut for the game to all the Steam users on this machine.
+/// Return amount of updated users.
+///
+/// Steam must be restarted to see the changes.
+pub async fn create_shortcut(lock: &GameLock) -> anyhow::Result<usize> {
+    let files = shortcuts_files();
+
+    if files.is_empty() {
+        anyhow::bail!("no steam users found");
+    }
+
+    let shortcut = game_shortcut(lock).await?;
+
+    let game_name = lock.name();
+
+    tasks::spawn_blocking(move || -> anyhow::Result<usize> {
+        for path in &files {
+            add_shortcut(path, &shortcut)
+                .with_context(|| format!("failed to add steam shortcut to {path:?}"))?;
+
+            tracing::info!(?game_name, ?path, "added game steam shortcut");
+        }
+
+        Ok(files.len())
+    }).await?
+}
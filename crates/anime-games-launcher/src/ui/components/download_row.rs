@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use adw::prelude::*;
+use relm4::prelude::*;
+
+use crate::i18n;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadRowInit {
+    /// Download title.
+    pub title: String,
+
+    /// Download subtitle.
+    pub subtitle: Option<String>,
+
+    /// Download status text.
+    pub status: Option<String>,
+
+    /// Whether the download is paused.
+    pub paused: bool,
+
+    /// Show button to pause and resume the download.
+    pub pausable: bool,
+
+    /// Show buttons to move the download in the queue.
+    pub reorderable: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadRowMsg {
+    SetStatus(Option<String>),
+    SetPaused(bool),
+
+    /// Forbid removing the download.
+    SetLocked(bool),
+
+    /// Set progress bar value. Hide the progress bar if `None` is given.
+    SetProgress(Option<(String, f64)>),
+
+    EmitPauseOrResume,
+    EmitMoveUp,
+    EmitMoveDown,
+    EmitRemove
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadRowOutput {
+    PauseOrResume(DynamicIndex),
+    MoveUp(DynamicIndex),
+    MoveDown(DynamicIndex),
+    Remove(DynamicIndex)
+}
+
+/// A factory component that builds an `AdwActionRow` with a download status
+/// and buttons to pause, resume, reorder and remove it.
+#[derive(Debug)]
+pub struct DownloadRow {
+    title: String,
+    subtitle: Option<String>,
+    status: Option<String>,
+    progress: Option<(String, f64)>,
+
+    paused: bool,
+    locked: bool,
+    pausable: bool,
+    reorderable: bool,
+
+    index: DynamicIndex
+}
+
+#[relm4::factory(pub, async)]
+impl AsyncFactoryComponent for DownloadRow {
+    type Init = DownloadRowInit;
+    type Input = DownloadRowMsg;
+    type Output = DownloadRowOutput;
+    type CommandOutput = ();
+    type ParentWidget = adw::PreferencesGroup;
+
+    view! {
+        #[root]
+        adw::ActionRow {
+            set_title: &self.title,
+            set_subtitle?: &self.subtitle,
+
+            add_suffix = &gtk::Label {
+                add_css_class: "dim-label",
+
+                #[watch]
+                set_visible: self.status.is_some(),
+
+                #[watch]
+                set_label: self.status.as_deref().unwrap_or_default()
+            },
+
+            add_suffix = &gtk::ProgressBar {
+                set_valign: gtk::Align::Center,
+
+                set_show_text: true,
+
+                #[watch]
+                set_visible: self.progress.is_some(),
+
+                #[watch]
+                set_text: self.progress.as_ref().map(|(text, _)| text.as_str()),
+
+                #[watch]
+                set_fraction: self.progress.as_ref()
+                    .map(|(_, fraction)| *fraction)
+                    .unwrap_or_default()
+            },
+
+            add_suffix = &gtk::Button {
+                set_valign: gtk::Align::Center,
+
+                add_css_class: "flat",
+
+                set_visible: self.pausable,
+
+                #[watch]
+                set_icon_name: if self.paused {
+                    "media-playback-start-symbolic"
+                } else {
+                    "media-playback-pause-symbolic"
+                },
+
+                #[watch]
+                set_tooltip: if self.paused {
+                    i18n!("download_resume").unwrap_or("Resume")
+                } else {
+                    i18n!("download_pause").unwrap_or("Pause")
+                },
+
+                connect_clicked => DownloadRowMsg::EmitPauseOrResume
+            },
+
+            add_suffix = &gtk::Button {
+                set_valign: gtk::Align::Center,
+
+                add_css_class: "flat",
+
+                set_visible: self.reorderable,
+
+                set_icon_name: "go-up-symbolic",
+                set_tooltip: i18n!("download_move_up").unwrap_or("Move up"),
+
+                connect_clicked => DownloadRowMsg::EmitMoveUp
+            },
+
+            add_suffix = &gtk::Button {
+                set_valign: gtk::Align::Center,
+
+                add_css_class: "flat",
+
+                set_visible: self.reorderable,
+
+                set_icon_name: "go-down-symbolic",
+                set_tooltip: i18n!("download_move_down").unwrap_or("Move down"),
+
+                connect_clicked => DownloadRowMsg::EmitMoveDown
+            },
+
+            add_suffix = &gtk::Button {
+                set_valign: gtk::Align::Center,
+
+                add_css_class: "flat",
+
+                #[watch]
+                set_sensitive: !self.locked,
+
+                set_icon_name: "user-trash-symbolic",
+                set_tooltip: i18n!("download_remove").unwrap_or("Remove"),
+
+                connect_clicked => DownloadRowMsg::EmitRemove
+            }
+        }
+    }
+
+    #[inline]
+    async fn init_model(
+        init: Self::Init,
+        index: &DynamicIndex,
+        _sender: AsyncFactorySender<Self>,
+    ) -> Self {
+        Self {
+            title: init.title,
+            subtitle: init.subtitle,
+            status: init.status,
+            progress: None,
+
+            paused: init.paused,
+            locked: false,
+            pausable: init.pausable,
+            reorderable: init.reorderable,
+
+            index: index.clone()
+        }
+    }
+
+    async fn update(
+        &mut self,
+        msg: Self::Input,
+        sender: AsyncFactorySender<Self>
+    ) {
+        match msg {
+            DownloadRowMsg::SetStatus(status) => self.status = status,
+            DownloadRowMsg::SetPaused(paused) => self.paused = paused,
+            DownloadRowMsg::SetLocked(locked) => self.locked = locked,
+            DownloadRowMsg::SetProgress(progress) => self.progress = progress,
+
+            DownloadRowMsg::EmitPauseOrResume => {
+                let _ = sender.output(DownloadRowOutput::PauseOrResume(self.index.clone()));
+            }
+
+            DownloadRowMsg::EmitMoveUp => {
+                let _ = sender.output(DownloadRowOutput::MoveUp(self.index.clone()));
+            }
+
+            DownloadRowMsg::EmitMoveDown => {
+                let _ = sender.output(DownloadRowOutput::MoveDown(self.index.clone()));
+            }
+
+            DownloadRowMsg::EmitRemove => {
+                let _ = sender.output(DownloadRowOutput::Remove(self.index.clone()));
+            }
+        }
+    }
+}
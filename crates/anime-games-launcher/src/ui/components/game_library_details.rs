@@ -24,10 +24,12 @@ use adw::prelude::*;
 use agl_games::manifest::GameManifest;
 use agl_games::api::{
     GameVariant, ActionsPipeline, GameIntegration, GameLaunchInfo,
-    GameLaunchStatus, GameComponentsGroup, GameSettingsGroup, ToolButton
+    GameLaunchStatus, GameComponentsGroup, GameSettingsGroup, ToolButton,
+    SavesDirectory, ModsDirectory, LaunchPreset
 };
 
 use crate::{consts, config, i18n};
+use crate::games::GameLock;
 use crate::ui::dialogs;
 
 use super::lazy_picture::{
@@ -71,14 +73,23 @@ pub enum GameLibraryDetailsInput {
 
     ScheduleGameActionsPipeline,
     OpenGameComponentsWindow,
+    OpenGameModsWindow,
+    OpenGameVerifyWindow,
+    OpenGameStorageWindow,
     CallToolButton(usize),
     OpenGameSettingsWindow,
+
+    /// Select launch preset by its index in the dropdown. Index 0 is the
+    /// default (no preset).
+    SelectLaunchPreset(u32),
+
     LaunchGame
 }
 
 #[derive(Debug, Clone)]
 pub enum GameLibraryDetailsOutput {
     ScheduleGameActionsPipeline {
+        variant: GameVariant,
         game_name: String,
         game_title: String,
         actions_pipeline: Arc<ActionsPipeline>
@@ -92,15 +103,35 @@ pub enum GameLibraryDetailsOutput {
         layout: Box<[GameComponentsGroup]>
     },
 
+    OpenGameModsWindow {
+        game_name: String,
+        directories: Box<[ModsDirectory]>
+    },
+
+    OpenGameVerifyWindow {
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
+    OpenGameStorageWindow {
+        game_name: String,
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
     OpenGameSettingsWindow {
         integration: Arc<GameIntegration>,
         variant: GameVariant,
-        layout: Box<[GameSettingsGroup]>
+        game_name: String,
+        layout: Option<Box<[GameSettingsGroup]>>
     },
 
     LaunchGame {
+        game_name: String,
         game_title: String,
-        game_launch_info: GameLaunchInfo
+        game_launch_info: GameLaunchInfo,
+        launch_preset: Option<LaunchPreset>,
+        saves_directories: Box<[SavesDirectory]>
     }
 }
 
@@ -124,8 +155,62 @@ pub struct GameLibraryDetails {
     game_launch_info: Option<GameLaunchInfo>,
     game_actions_pipeline: Option<Arc<ActionsPipeline>>,
     game_components_layout: Option<Box<[GameComponentsGroup]>>,
+    game_mods_directories: Option<Box<[ModsDirectory]>>,
     game_tools_buttons: Vec<ToolButton>,
-    game_settings_layout: Option<Box<[GameSettingsGroup]>>
+    game_settings_layout: Option<Box<[GameSettingsGroup]>>,
+
+    game_presets: Vec<LaunchPreset>,
+    game_preset: Option<String>,
+
+    game_presets_model: gtk::StringList,
+    game_presets_dropdown: gtk::DropDown,
+    game_presets_handler: gtk::glib::SignalHandlerId
+}
+
+impl GameLibraryDetails {
+    /// Get currently selected launch preset.
+    fn selected_launch_preset(&self) -> Option<&LaunchPreset> {
+        let name = self.game_preset.as_ref()?;
+
+        self.game_presets.iter()
+            .find(|preset| &preset.name == name)
+    }
+
+    /// Get game variant used to launch the game. Launch presets can override
+    /// the game edition.
+    fn launch_variant(&self) -> Option<GameVariant> {
+        let variant = self.game_variant.as_ref()?;
+
+        let edition = self.selected_launch_preset()
+            .and_then(|preset| preset.edition.clone());
+
+        match edition {
+            Some(edition) => Some(GameVariant {
+                edition: Some(edition),
+                ..variant.clone()
+            }),
+
+            None => Some(variant.clone())
+        }
+    }
+
+    /// Update launch presets dropdown values without emitting selection events.
+    fn update_presets_dropdown(&self, titles: &[&str]) {
+        let selected = self.game_preset.as_ref()
+            .and_then(|name| {
+                self.game_presets.iter()
+                    .position(|preset| &preset.name == name)
+            })
+            .map(|index| index as u32 + 1)
+            .unwrap_or(0);
+
+        self.game_presets_dropdown.block_signal(&self.game_presets_handler);
+
+        self.game_presets_model.splice(0, self.game_presets_model.n_items(), titles);
+        self.game_presets_dropdown.set_selected(selected);
+
+        self.game_presets_dropdown.unblock_signal(&self.game_presets_handler);
+    }
 }
 
 #[relm4::component(pub, async)]
@@ -238,6 +323,18 @@ impl SimpleAsyncComponent for GameLibraryDetails {
                             connect_clicked => GameLibraryDetailsInput::LaunchGame
                         },
 
+                        // Launch preset selector.
+                        #[local_ref]
+                        game_presets_dropdown -> gtk::DropDown {
+                            set_valign: gtk::Align::Center,
+
+                            set_tooltip: i18n!("game_launch_preset")
+                                .unwrap_or("Launch preset"),
+
+                            #[watch]
+                            set_visible: model.game_launch_info.is_some() && !model.game_presets.is_empty()
+                        },
+
                         // Execute actions pipeline button.
                         gtk::Button {
                             #[watch]
@@ -304,7 +401,56 @@ impl SimpleAsyncComponent for GameLibraryDetails {
                             add_css_class: "pill",
 
                             #[watch]
-                            set_visible: model.game_settings_layout.is_some(),
+                            set_visible: model.game_mods_directories.is_some(),
+
+                            adw::ButtonContent {
+                                set_icon_name: "puzzle-piece-symbolic",
+
+                                set_label: i18n!("mods")
+                                    .unwrap_or("Mods")
+                            },
+
+                            connect_clicked => GameLibraryDetailsInput::OpenGameModsWindow
+                        },
+
+                        gtk::Button {
+                            add_css_class: "pill",
+
+                            #[watch]
+                            set_visible: model.game_integration.as_ref()
+                                .is_some_and(|integration| integration.can_verify()),
+
+                            adw::ButtonContent {
+                                set_icon_name: "system-search-symbolic",
+
+                                set_label: i18n!("verify_files")
+                                    .unwrap_or("Verify files")
+                            },
+
+                            connect_clicked => GameLibraryDetailsInput::OpenGameVerifyWindow
+                        },
+
+                        gtk::Button {
+                            add_css_class: "pill",
+
+                            #[watch]
+                            set_visible: model.game_integration.as_ref()
+                                .is_some_and(|integration| integration.can_relocate()),
+
+                            adw::ButtonContent {
+                                set_icon_name: "drive-harddisk-symbolic",
+
+                                set_label: i18n!("storage")
+                                    .unwrap_or("Storage")
+                            },
+
+                            connect_clicked => GameLibraryDetailsInput::OpenGameStorageWindow
+                        },
+
+                        // Game settings are always available because they
+                        // contain launch wrappers.
+                        gtk::Button {
+                            add_css_class: "pill",
 
                             adw::ButtonContent {
                                 set_icon_name: "settings-symbolic",
@@ -335,6 +481,21 @@ impl SimpleAsyncComponent for GameLibraryDetails {
         root: Self::Root,
         sender: AsyncComponentSender<Self>
     ) -> AsyncComponentParts<Self> {
+        let game_presets_model = gtk::StringList::new(&[]);
+
+        let game_presets_dropdown = gtk::DropDown::new(
+            Some(game_presets_model.clone()),
+            None::<gtk::Expression>
+        );
+
+        let game_presets_handler = {
+            let sender = sender.input_sender().clone();
+
+            game_presets_dropdown.connect_selected_notify(move |dropdown| {
+                sender.emit(GameLibraryDetailsInput::SelectLaunchPreset(dropdown.selected()));
+            })
+        };
+
         let model = Self {
             card: CardComponent::builder()
                 .launch(CardComponent::medium())
@@ -366,10 +527,20 @@ impl SimpleAsyncComponent for GameLibraryDetails {
             game_launch_info: None,
             game_actions_pipeline: None,
             game_components_layout: None,
+            game_mods_directories: None,
             game_tools_buttons: vec![],
-            game_settings_layout: None
+            game_settings_layout: None,
+
+            game_presets: vec![],
+            game_preset: None,
+
+            game_presets_model,
+            game_presets_dropdown,
+            game_presets_handler
         };
 
+        let game_presets_dropdown = &model.game_presets_dropdown;
+
         let widgets = view_output!();
 
         AsyncComponentParts { model, widgets }
@@ -395,8 +566,14 @@ impl SimpleAsyncComponent for GameLibraryDetails {
                 self.game_launch_info = None;
                 self.game_actions_pipeline = None;
                 self.game_components_layout = None;
+                self.game_mods_directories = None;
                 self.game_tools_buttons = vec![];
                 self.game_settings_layout = None;
+
+                self.game_presets = vec![];
+                self.game_preset = None;
+
+                self.update_presets_dropdown(&[]);
             }
 
             GameLibraryDetailsInput::SetGame {
@@ -472,11 +649,70 @@ impl SimpleAsyncComponent for GameLibraryDetails {
                 tools_layout,
                 settings_layout
             } => {
+                if launch_info
+                    && let Some(integration) = &self.game_integration
+                    && let Some(variant) = &self.game_variant
+                {
+                    let mut presets = match integration.get_launch_presets(variant) {
+                        Ok(presets) => presets.map(Vec::from).unwrap_or_default(),
+
+                        Err(err) => {
+                            tracing::error!(?err, "failed to request game launch presets");
+
+                            dialogs::error(
+                                i18n!("failed_request_game_launch_presets")
+                                    .unwrap_or("Failed to request game launch presets"),
+                                err.to_string()
+                            );
+
+                            vec![]
+                        }
+                    };
+
+                    let mut preset = None;
+
+                    if let Some(game_name) = &self.game_name {
+                        match GameLock::load(game_name).await {
+                            Ok(lock) => {
+                                presets = LaunchPreset::merge(presets, lock.presets);
+
+                                preset = lock.preset;
+                            }
+
+                            Err(err) => tracing::warn!(?err, ?game_name, "failed to load game lock, user launch presets are not available")
+                        }
+                    }
+
+                    // Selected preset could be removed from the list.
+                    self.game_preset = preset.filter(|name| {
+                        presets.iter().any(|preset| &preset.name == name)
+                    });
+
+                    self.game_presets = presets;
+
+                    let config = config::get().await;
+
+                    let lang = config.language().ok();
+
+                    let mut titles = Vec::with_capacity(self.game_presets.len() + 1);
+
+                    titles.push(i18n!("game_default_launch_preset").unwrap_or("Default"));
+
+                    for preset in &self.game_presets {
+                        titles.push(match &lang {
+                            Some(lang) => preset.title.translate(lang),
+                            None => preset.title.default_translation()
+                        });
+                    }
+
+                    self.update_presets_dropdown(&titles);
+                }
+
                 if let Some(integration) = &self.game_integration
                     && let Some(variant) = &self.game_variant
                 {
-                    if launch_info {
-                        match integration.get_launch_info(variant) {
+                    if launch_info && let Some(launch_variant) = self.launch_variant() {
+                        match integration.get_launch_info(launch_variant) {
                             Ok(launch_info) => self.game_launch_info = launch_info,
 
                             Err(err) => {
@@ -527,6 +763,27 @@ impl SimpleAsyncComponent for GameLibraryDetails {
                                 );
                             }
                         }
+
+                        // Mods directories can depend on installed game
+                        // components so they're updated together.
+                        match integration.get_mods_directories(variant) {
+                            Ok(directories) => {
+                                self.game_mods_directories = directories
+                                    .filter(|directories| !directories.is_empty());
+                            }
+
+                            Err(err) => {
+                                self.game_mods_directories = None;
+
+                                tracing::error!(?err, "failed to request game mods directories");
+
+                                dialogs::error(
+                                    i18n!("failed_request_game_mods_directories")
+                                        .unwrap_or("Failed to request game mods directories"),
+                                    err.to_string()
+                                );
+                            }
+                        }
                     }
 
                     if tools_layout {
@@ -606,11 +863,13 @@ impl SimpleAsyncComponent for GameLibraryDetails {
             }
 
             GameLibraryDetailsInput::ScheduleGameActionsPipeline => {
-                if let Some(game_name) = &self.game_name
+                if let Some(variant) = &self.game_variant
+                    && let Some(game_name) = &self.game_name
                     && let Some(game_title) = &self.game_title
                     && let Some(actions_pipeline) = &self.game_actions_pipeline
                 {
                     let _ = sender.output(GameLibraryDetailsOutput::ScheduleGameActionsPipeline {
+                        variant: variant.clone(),
                         game_name: game_name.clone(),
                         game_title: game_title.clone(),
                         actions_pipeline: actions_pipeline.clone()
@@ -635,6 +894,41 @@ impl SimpleAsyncComponent for GameLibraryDetails {
                 }
             }
 
+            GameLibraryDetailsInput::OpenGameModsWindow => {
+                if let Some(game_name) = &self.game_name
+                    && let Some(directories) = &self.game_mods_directories
+                {
+                    let _ = sender.output(GameLibraryDetailsOutput::OpenGameModsWindow {
+                        game_name: game_name.clone(),
+                        directories: directories.clone()
+                    });
+                }
+            }
+
+            GameLibraryDetailsInput::OpenGameVerifyWindow => {
+                if let Some(integration) = &self.game_integration
+                    && let Some(variant) = &self.game_variant
+                {
+                    let _ = sender.output(GameLibraryDetailsOutput::OpenGameVerifyWindow {
+                        integration: integration.clone(),
+                        variant: variant.clone()
+                    });
+                }
+            }
+
+            GameLibraryDetailsInput::OpenGameStorageWindow => {
+                if let Some(game_name) = &self.game_name
+                    && let Some(integration) = &self.game_integration
+                    && let Some(variant) = &self.game_variant
+                {
+                    let _ = sender.output(GameLibraryDetailsOutput::OpenGameStorageWindow {
+                        game_name: game_name.clone(),
+                        integration: integration.clone(),
+                        variant: variant.clone()
+                    });
+                }
+            }
+
             GameLibraryDetailsInput::CallToolButton(index) => {
                 if let Some(button) = self.game_tools_buttons.get(index) {
                     if let Err(err) = button.call() {
@@ -660,23 +954,88 @@ impl SimpleAsyncComponent for GameLibraryDetails {
             GameLibraryDetailsInput::OpenGameSettingsWindow => {
                 if let Some(integration) = &self.game_integration
                     && let Some(variant) = &self.game_variant
-                    && let Some(layout) = &self.game_settings_layout
+                    && let Some(game_name) = &self.game_name
                 {
                     let _ = sender.output(GameLibraryDetailsOutput::OpenGameSettingsWindow {
                         variant: variant.clone(),
                         integration: integration.clone(),
-                        layout: layout.clone()
+                        game_name: game_name.clone(),
+                        layout: self.game_settings_layout.clone()
                     });
                 }
             }
 
+            GameLibraryDetailsInput::SelectLaunchPreset(index) => {
+                let preset = (index as usize).checked_sub(1)
+                    .and_then(|index| self.game_presets.get(index))
+                    .map(|preset| preset.name.clone());
+
+                if preset == self.game_preset {
+                    return;
+                }
+
+                self.game_preset = preset.clone();
+
+                if let Some(game_name) = &self.game_name {
+                    let result = match GameLock::load(game_name).await {
+                        Ok(mut lock) => {
+                            lock.preset = preset;
+
+                            lock.save().await
+                        }
+
+                        Err(err) => Err(err)
+                    };
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch preset");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_launch_preset")
+                                .unwrap_or("Failed to update game launch preset"),
+                            err.to_string()
+                        );
+                    }
+                }
+
+                sender.input(GameLibraryDetailsInput::UpdateGameInfo {
+                    launch_info: true,
+                    actions_pipeline: false,
+                    components_layout: false,
+                    tools_layout: false,
+                    settings_layout: false
+                });
+            }
+
             GameLibraryDetailsInput::LaunchGame => {
-                if let Some(game_title) = &self.game_title
+                if let Some(game_name) = &self.game_name
+                    && let Some(game_title) = &self.game_title
                     && let Some(info) = &self.game_launch_info
+                    && let Some(integration) = &self.game_integration
+                    && let Some(variant) = self.launch_variant()
                 {
+                    let saves_directories = match integration.get_saves_directories(variant) {
+                        Ok(directories) => directories.unwrap_or_default(),
+
+                        Err(err) => {
+                            tracing::error!(?err, "failed to request game saves directories");
+
+                            dialogs::error(
+                                i18n!("failed_request_game_saves_directories")
+                                    .unwrap_or("Failed to request game saves directories"),
+                                err.to_string()
+                            );
+
+                            Box::default()
+                        }
+                    };
+
                     let _ = sender.output(GameLibraryDetailsOutput::LaunchGame {
+                        game_name: game_name.clone(),
                         game_title: game_title.clone(),
-                        game_launch_info: info.clone()
+                        game_launch_info: info.clone(),
+                        launch_preset: self.selected_launch_preset().cloned(),
+                        saves_directories
                     });
                 }
             }
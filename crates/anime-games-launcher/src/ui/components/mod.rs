@@ -21,6 +21,7 @@ pub mod card;
 pub mod graph;
 pub mod progress_bar_group;
 pub mod graph_progress_group;
+pub mod download_row;
 
 pub mod game_store_details;
 pub mod picture_carousel;
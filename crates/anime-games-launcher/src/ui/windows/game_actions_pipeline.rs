@@ -16,57 +16,42 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
 
 use adw::prelude::*;
 use relm4::prelude::*;
 
-use agl_core::tasks;
-use agl_games::api::ActionsPipeline;
-
-use crate::{consts, config, i18n};
-use crate::ui::dialogs;
+use crate::{consts, i18n};
 use crate::ui::components::graph_progress_group::{
     GraphProgressGroup, GraphProgressGroupInit, GraphProgressGroupMsg
 };
 
 #[derive(Debug, Clone)]
 pub enum GameActionsPipelineWindowInput {
-    SetActionsPipeline {
-        game_name: String,
-        game_title: String,
-        actions_pipeline: Arc<ActionsPipeline>
-    },
-
-    MarkStarted {
-        name: String
-    },
-
-    SetProgress {
-        name: String,
-        text: Option<String>,
-        fraction: f64
+    /// Show progress of the queued download with provided identifier.
+    SetDownload {
+        id: u64,
+        game_title: String
     },
 
-    MarkFinished {
-        name: String
+    /// Progress of the currently performed download.
+    Progress {
+        id: u64,
+        msg: GraphProgressGroupMsg
     },
 
-    EmitClose
-}
-
-#[derive(Debug, Clone)]
-pub enum GameActionsPipelineWindowOutput {
-    UpdateGameInfo(String)
+    DownloadStopped(u64)
 }
 
+/// Progress view of the actions pipeline download scheduled in the downloads
+/// queue. The download itself is performed by the downloads page, so
+/// this window can be closed at any time.
 #[derive(Debug)]
 pub struct GameActionsPipelineWindow {
     graph_group: AsyncController<GraphProgressGroup>,
 
     window: adw::Dialog,
 
-    game_name: Option<String>,
+    download_id: Option<u64>,
     game_title: Option<String>
 }
 
@@ -74,13 +59,13 @@ pub struct GameActionsPipelineWindow {
 impl SimpleAsyncComponent for GameActionsPipelineWindow {
     type Init = ();
     type Input = GameActionsPipelineWindowInput;
-    type Output = GameActionsPipelineWindowOutput;
+    type Output = ();
 
     view! {
         #[root]
         adw::Dialog {
             set_size_request: (800, 600),
-            set_can_close: false,
+            set_can_close: true,
 
             add_css_class?: consts::APP_DEBUG.then_some("devel"),
 
@@ -96,7 +81,6 @@ impl SimpleAsyncComponent for GameActionsPipelineWindow {
 
                 gtk::Label {
                     set_margin_top: 16,
-                    set_margin_bottom: 16,
 
                     #[watch]
                     set_label: match &model.game_title {
@@ -105,6 +89,16 @@ impl SimpleAsyncComponent for GameActionsPipelineWindow {
                     }
                 },
 
+                gtk::Label {
+                    set_margin_top: 4,
+                    set_margin_bottom: 16,
+
+                    add_css_class: "dim-label",
+
+                    set_label: i18n!("download_window_hint")
+                        .unwrap_or("Closing this window will not stop the download")
+                },
+
                 model.graph_group.widget(),
             }
         }
@@ -125,7 +119,7 @@ impl SimpleAsyncComponent for GameActionsPipelineWindow {
 
             window: root.clone(),
 
-            game_name: None,
+            download_id: None,
             game_title: None
         };
 
@@ -137,212 +131,35 @@ impl SimpleAsyncComponent for GameActionsPipelineWindow {
     async fn update(
         &mut self,
         msg: Self::Input,
-        sender: AsyncComponentSender<Self>
+        _sender: AsyncComponentSender<Self>
     ) {
         match msg {
-            GameActionsPipelineWindowInput::SetActionsPipeline {
-                game_name,
-                game_title,
-                actions_pipeline
-            } => {
-                let lang = config::get().await
-                    .language().ok();
-
-                let title = match &lang {
-                    Some(lang) => actions_pipeline.title().translate(lang),
-                    None => actions_pipeline.title().default_translation()
-                };
-
-                let description = actions_pipeline.description()
-                    .map(|description| {
-                        match &lang {
-                            Some(lang) => description.translate(lang),
-                            None => description.default_translation()
-                        }
-                    })
-                    .map(String::from);
-
+            GameActionsPipelineWindowInput::SetDownload { id, game_title } => {
                 self.graph_group.emit(GraphProgressGroupMsg::ClearGraph);
                 self.graph_group.emit(GraphProgressGroupMsg::ClearProgressRows);
 
-                self.graph_group.emit(GraphProgressGroupMsg::SetTitle(Some(title.to_string())));
-                self.graph_group.emit(GraphProgressGroupMsg::SetDescription(description));
+                self.graph_group.emit(GraphProgressGroupMsg::SetDescription(Some(
+                    i18n!("download_queued")
+                        .map(String::from)
+                        .unwrap_or_else(|| String::from("Queued"))
+                )));
 
-                self.game_name = Some(game_name);
+                self.download_id = Some(id);
                 self.game_title = Some(game_title);
-
-                let mut actions = Vec::new();
-
-                for (i, action) in actions_pipeline.actions().iter().enumerate() {
-                    let name = i.to_string();
-
-                    let title = match &lang {
-                        Some(lang) => action.title().translate(lang),
-                        None => action.title().default_translation()
-                    };
-
-                    let description = action.description()
-                        .map(|description| {
-                            match &lang {
-                                Some(lang) => description.translate(lang),
-                                None => description.default_translation()
-                            }
-                        })
-                        .map(String::from);
-
-                    self.graph_group.emit(GraphProgressGroupMsg::AddProgressRow {
-                        name: name.clone(),
-                        title: title.to_string(),
-                        description
-                    });
-
-                    actions.push((name, action.clone()));
-                }
-
-                tasks::spawn_blocking(move || {
-                    for (name, action) in actions {
-                        sender.input(GameActionsPipelineWindowInput::MarkStarted {
-                            name: name.clone()
-                        });
-
-                        let result = {
-                            let lang = lang.clone();
-                            let name = name.clone();
-                            let sender = sender.clone();
-
-                            action.before(move |progress| {
-                                let fraction = progress.fraction();
-
-                                let text = progress.format().ok()
-                                    .flatten()
-                                    .map(|text| {
-                                        let text = match &lang {
-                                            Some(lang) => text.translate(lang),
-                                            None => text.default_translation()
-                                        };
-
-                                        text.to_string()
-                                    })
-                                    .unwrap_or_else(|| {
-                                        format!("{:.2}%", fraction * 100.0)
-                                    });
-
-                                sender.input(GameActionsPipelineWindowInput::SetProgress {
-                                    name: name.clone(),
-                                    text: Some(text),
-                                    fraction
-                                });
-                            })
-                        };
-
-                        match result {
-                            Ok(Some(true)) | Ok(None) => {
-                                let lang = lang.clone();
-                                let name = name.clone();
-                                let sender = sender.clone();
-
-                                let result = action.perform(move |progress| {
-                                    let fraction = progress.fraction();
-
-                                    let text = progress.format().ok()
-                                        .flatten()
-                                        .map(|text| {
-                                            let text = match &lang {
-                                                Some(lang) => text.translate(lang),
-                                                None => text.default_translation()
-                                            };
-
-                                            text.to_string()
-                                        })
-                                        .unwrap_or_else(|| {
-                                            format!("{:.2}%", fraction * 100.0)
-                                        });
-
-                                    sender.input(GameActionsPipelineWindowInput::SetProgress {
-                                        name: name.clone(),
-                                        text: Some(text),
-                                        fraction
-                                    });
-                                });
-
-                                if let Err(err) = result {
-                                    tracing::error!(?err, "failed to perform pipeline action");
-
-                                    dialogs::error(
-                                        i18n!("failed_perform_pipeline_action")
-                                            .unwrap_or("Failed to perform pipeline action"),
-                                        err.to_string()
-                                    );
-
-                                    break;
-                                }
-                            }
-
-                            Ok(Some(false)) => (),
-
-                            Err(err) => {
-                                tracing::error!(?err, "failed to perform pipeline action");
-
-                                dialogs::error(
-                                    i18n!("failed_perform_pipeline_action")
-                                        .unwrap_or("Failed to perform pipeline action"),
-                                    err.to_string()
-                                );
-
-                                break;
-                            }
-                        }
-
-                        sender.input(GameActionsPipelineWindowInput::SetProgress {
-                            name: name.clone(),
-                            text: None,
-                            fraction: 1.0
-                        });
-
-                        sender.input(GameActionsPipelineWindowInput::MarkFinished {
-                            name
-                        });
-                    }
-
-                    sender.input(GameActionsPipelineWindowInput::EmitClose);
-                });
             }
 
-            GameActionsPipelineWindowInput::MarkStarted { name } => {
-                self.graph_group.emit(GraphProgressGroupMsg::MarkStarted {
-                    name
-                });
-            }
-
-            GameActionsPipelineWindowInput::SetProgress {
-                name,
-                text,
-                fraction
-            } => {
-                self.graph_group.emit(GraphProgressGroupMsg::SetProgress {
-                    name,
-                    text,
-                    fraction
-                });
-            }
-
-            GameActionsPipelineWindowInput::MarkFinished { name } => {
-                self.graph_group.emit(GraphProgressGroupMsg::MarkFinished {
-                    name
-                });
+            GameActionsPipelineWindowInput::Progress { id, msg } => {
+                if self.download_id == Some(id) {
+                    self.graph_group.emit(msg);
+                }
             }
 
-            GameActionsPipelineWindowInput::EmitClose => {
-                self.graph_group.emit(GraphProgressGroupMsg::ClearGraph);
-                self.graph_group.emit(GraphProgressGroupMsg::ClearProgressRows);
+            GameActionsPipelineWindowInput::DownloadStopped(id) => {
+                if self.download_id == Some(id) {
+                    self.download_id = None;
 
-                if let Some(name) = &self.game_name {
-                    let _ = sender.output(GameActionsPipelineWindowOutput::UpdateGameInfo(
-                        name.clone()
-                    ));
+                    self.window.close();
                 }
-
-                self.window.force_close();
             }
         }
     }
@@ -16,72 +16,42 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
 
 use adw::prelude::*;
 use relm4::prelude::*;
 
-use agl_core::tasks;
-use agl_games::api::{GameVariant, GameIntegration, ProgressReport};
-
-use crate::{consts, config, i18n};
-use crate::ui::dialogs;
+use crate::{consts, i18n};
 use crate::ui::components::graph_progress_group::{
     GraphProgressGroup, GraphProgressGroupInit, GraphProgressGroupMsg
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ApplyComponentInfo {
-    pub name: String,
-    pub title: String
-}
-
 #[derive(Debug, Clone)]
 pub enum GameApplyComponentsWindowInput {
-    SetComponents {
-        game_variant: GameVariant,
-        game_integration: Arc<GameIntegration>,
-
-        game_name: String,
-        game_title: String,
-
-        install_components: Box<[ApplyComponentInfo]>,
-        uninstall_components: Box<[ApplyComponentInfo]>,
-
-        /// Delete game package after applying these components.
-        delete_game_package: bool
+    /// Show progress of the queued download with provided identifier.
+    SetDownload {
+        id: u64,
+        game_title: String
     },
 
-    MarkStarted {
-        name: String
+    /// Progress of the currently performed download.
+    Progress {
+        id: u64,
+        msg: GraphProgressGroupMsg
     },
 
-    SetProgress {
-        name: String,
-        text: Option<String>,
-        fraction: f64
-    },
-
-    MarkFinished {
-        name: String
-    },
-
-    EmitClose
-}
-
-#[derive(Debug, Clone)]
-pub enum GameApplyComponentsWindowOutput {
-    UpdateGameInfo(String),
-    DeleteGamePackage(String)
+    DownloadStopped(u64)
 }
 
+/// Progress view of the components changes download scheduled in the
+/// downloads queue. The download itself is performed by the downloads page,
+/// so this window can be closed at any time.
 #[derive(Debug)]
 pub struct GameApplyComponentsWindow {
     graph_group: AsyncController<GraphProgressGroup>,
 
     window: adw::Dialog,
 
-    game_name: Option<String>,
+    download_id: Option<u64>,
     game_title: Option<String>
 }
 
@@ -89,13 +59,13 @@ pub struct GameApplyComponentsWindow {
 impl SimpleAsyncComponent for GameApplyComponentsWindow {
     type Init = ();
     type Input = GameApplyComponentsWindowInput;
-    type Output = GameApplyComponentsWindowOutput;
+    type Output = ();
 
     view! {
         #[root]
         adw::Dialog {
             set_size_request: (800, 600),
-            set_can_close: false,
+            set_can_close: true,
 
             add_css_class?: consts::APP_DEBUG.then_some("devel"),
 
@@ -111,7 +81,6 @@ impl SimpleAsyncComponent for GameApplyComponentsWindow {
 
                 gtk::Label {
                     set_margin_top: 16,
-                    set_margin_bottom: 16,
 
                     #[watch]
                     set_label: match &model.game_title {
@@ -120,6 +89,16 @@ impl SimpleAsyncComponent for GameApplyComponentsWindow {
                     }
                 },
 
+                gtk::Label {
+                    set_margin_top: 4,
+                    set_margin_bottom: 16,
+
+                    add_css_class: "dim-label",
+
+                    set_label: i18n!("download_window_hint")
+                        .unwrap_or("Closing this window will not stop the download")
+                },
+
                 model.graph_group.widget(),
             }
         }
@@ -141,7 +120,7 @@ impl SimpleAsyncComponent for GameApplyComponentsWindow {
 
             window: root.clone(),
 
-            game_name: None,
+            download_id: None,
             game_title: None
         };
 
@@ -153,207 +132,35 @@ impl SimpleAsyncComponent for GameApplyComponentsWindow {
     async fn update(
         &mut self,
         msg: Self::Input,
-        sender: AsyncComponentSender<Self>
+        _sender: AsyncComponentSender<Self>
     ) {
         match msg {
-            GameApplyComponentsWindowInput::SetComponents {
-                game_variant,
-                game_integration,
-                game_name,
-                game_title,
-                install_components,
-                uninstall_components,
-                delete_game_package
-            } => {
-                let lang = config::get().await
-                    .language().ok();
-
+            GameApplyComponentsWindowInput::SetDownload { id, game_title } => {
                 self.graph_group.emit(GraphProgressGroupMsg::ClearGraph);
                 self.graph_group.emit(GraphProgressGroupMsg::ClearProgressRows);
 
-                self.game_name = Some(game_name.clone());
-                self.game_title = Some(game_title);
+                self.graph_group.emit(GraphProgressGroupMsg::SetDescription(Some(
+                    i18n!("download_queued")
+                        .map(String::from)
+                        .unwrap_or_else(|| String::from("Queued"))
+                )));
 
-                if delete_game_package {
-                    self.graph_group.emit(GraphProgressGroupMsg::SetTitle(Some(
-                        i18n!("game_components_uninstall_all_title")
-                            .map(String::from)
-                            .unwrap_or_else(|| String::from("Uninstall game components"))
-                    )));
-                } else {
-                    self.graph_group.emit(GraphProgressGroupMsg::SetTitle(Some(
-                        i18n!("game_components_apply_changes_title")
-                            .map(String::from)
-                            .unwrap_or_else(|| String::from("Apply components changes"))
-                    )));
-                }
-
-                let mut actions = Vec::new();
-
-                for component in uninstall_components {
-                    let title = i18n!("game_component_uninstall_title", {
-                        component => component.title
-                    }).unwrap_or_else(|| {
-                        format!("Uninstall {}", component.title)
-                    });
-
-                    self.graph_group.emit(GraphProgressGroupMsg::AddProgressRow {
-                        name: component.name.clone(),
-                        title,
-                        description: None
-                    });
-
-                    actions.push((component.name, false));
-                }
-
-                for component in install_components {
-                    let title = i18n!("game_component_install_title", {
-                        component => component.title
-                    }).unwrap_or_else(|| {
-                        format!("Install {}", component.title)
-                    });
-
-                    self.graph_group.emit(GraphProgressGroupMsg::AddProgressRow {
-                        name: component.name.clone(),
-                        title,
-                        description: None
-                    });
-
-                    actions.push((component.name, true));
-                }
-
-                tasks::spawn_blocking(move || {
-                    for (name, is_install) in actions {
-                        sender.input(GameApplyComponentsWindowInput::MarkStarted {
-                            name: name.clone()
-                        });
-
-                        let updater = {
-                            let sender = sender.clone();
-                            let lang = lang.clone();
-                            let name = name.clone();
-
-                            move |progress: ProgressReport| {
-                                let fraction = progress.fraction();
-
-                                let text = progress.format().ok()
-                                    .flatten()
-                                    .map(|text| {
-                                        let text = match &lang {
-                                            Some(lang) => text.translate(lang),
-                                            None => text.default_translation()
-                                        };
-
-                                        text.to_string()
-                                    })
-                                    .unwrap_or_else(|| {
-                                        format!("{:.2}%", fraction * 100.0)
-                                    });
-
-                                sender.input(GameApplyComponentsWindowInput::SetProgress {
-                                    name: name.clone(),
-                                    text: Some(text),
-                                    fraction
-                                });
-                            }
-                        };
-
-                        let mut result = if is_install {
-                            game_integration.install_component(
-                                &game_variant,
-                                &name,
-                                updater
-                            )
-                        } else {
-                            game_integration.uninstall_component(
-                                &game_variant,
-                                &name,
-                                updater
-                            )
-                        };
-
-                        // If we've successfully installed/uninstalled the
-                        // component then save its enabled/disabled state.
-                        if result.is_ok() {
-                            result = game_integration.set_component_enabled(
-                                &game_variant,
-                                &name,
-                                is_install
-                            );
-                        }
-
-                        if let Err(err) = result {
-                            tracing::error!(
-                                ?err,
-                                component = ?name,
-                                "failed to apply game component"
-                            );
-
-                            dialogs::error(
-                                i18n!("failed_apply_game_component")
-                                    .unwrap_or("Failed to apply game component"),
-                                err.to_string()
-                            );
-
-                            break;
-                        }
-
-                        sender.input(GameApplyComponentsWindowInput::SetProgress {
-                            name: name.clone(),
-                            text: None,
-                            fraction: 1.0
-                        });
-
-                        sender.input(GameApplyComponentsWindowInput::MarkFinished {
-                            name
-                        });
-                    }
-
-                    if delete_game_package {
-                        let _ = sender.output(GameApplyComponentsWindowOutput::DeleteGamePackage(
-                            game_name
-                        ));
-                    }
-
-                    sender.input(GameApplyComponentsWindowInput::EmitClose);
-                });
-            }
-
-            GameApplyComponentsWindowInput::MarkStarted { name } => {
-                self.graph_group.emit(GraphProgressGroupMsg::MarkStarted {
-                    name
-                });
-            }
-
-            GameApplyComponentsWindowInput::SetProgress {
-                name,
-                text,
-                fraction
-            } => {
-                self.graph_group.emit(GraphProgressGroupMsg::SetProgress {
-                    name,
-                    text,
-                    fraction
-                });
+                self.download_id = Some(id);
+                self.game_title = Some(game_title);
             }
 
-            GameApplyComponentsWindowInput::MarkFinished { name } => {
-                self.graph_group.emit(GraphProgressGroupMsg::MarkFinished {
-                    name
-                });
+            GameApplyComponentsWindowInput::Progress { id, msg } => {
+                if self.download_id == Some(id) {
+                    self.graph_group.emit(msg);
+                }
             }
 
-            GameApplyComponentsWindowInput::EmitClose => {
-                self.graph_group.emit(GraphProgressGroupMsg::ClearGraph);
-                self.graph_group.emit(GraphProgressGroupMsg::ClearProgressRows);
+            GameApplyComponentsWindowInput::DownloadStopped(id) => {
+                if self.download_id == Some(id) {
+                    self.download_id = None;
 
-                if let Some(game_name) = &self.game_name {
-                    let _ = sender.output(GameApplyComponentsWindowOutput::UpdateGameInfo(
-                        game_name.clone()
-                    ));
+                    self.window.close();
                 }
-
-                self.window.force_close();
             }
         }
     }
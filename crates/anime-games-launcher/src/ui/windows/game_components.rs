@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use agl_core::tasks;
@@ -28,12 +28,13 @@ use agl_games::api::{
     GameIntegration,
     GameComponentsGroup,
     GameComponentsEntryValue,
-    GameComponentEntryValueStatus
+    GameComponentEntryValueStatus,
+    ComponentsResolver,
+    ComponentsResolveError
 };
 
-use super::game_apply_components::ApplyComponentInfo;
-
 use crate::{consts, config, i18n};
+use crate::downloads::ApplyComponentInfo;
 use crate::ui::dialogs;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -95,6 +96,7 @@ pub struct GameComponentsWindow {
 
     groups: Vec<adw::PreferencesGroup>,
     entries: HashMap<String, ComponentState>,
+    layout: Box<[GameComponentsGroup]>,
 
     game_integration: Option<Arc<GameIntegration>>,
     game_variant: Option<GameVariant>,
@@ -178,6 +180,7 @@ impl SimpleAsyncComponent for GameComponentsWindow {
             // Some random capacity values I took from my head.
             groups: Vec::with_capacity(2),
             entries: HashMap::with_capacity(5),
+            layout: Box::new([]),
 
             game_variant: None,
             game_integration: None,
@@ -269,6 +272,8 @@ impl SimpleAsyncComponent for GameComponentsWindow {
                     }
                 }
 
+                self.layout = layout.clone();
+
                 self.game_integration = Some(integration);
                 self.game_variant = Some(variant);
 
@@ -329,6 +334,21 @@ impl SimpleAsyncComponent for GameComponentsWindow {
                                     entry_widget.set_subtitle(description);
                                 }
 
+                                // Render component version and available
+                                // update.
+                                if let Some(version) = entry.version() {
+                                    let version_widget = gtk::Label::new(Some(version));
+
+                                    version_widget.add_css_class("dim-label");
+
+                                    if entry.has_update() && let Some(latest_version) = entry.latest_version() {
+                                        version_widget.set_label(&format!("{version} → {latest_version}"));
+                                        version_widget.add_css_class("warning");
+                                    }
+
+                                    entry_widget.add_suffix(&version_widget);
+                                }
+
                                 // Render component values.
                                 if component_state.entry_values.is_empty() {
                                     entry_widget.set_enable_expansion(false);
@@ -495,30 +515,108 @@ impl SimpleAsyncComponent for GameComponentsWindow {
                     && let Some(game_name) = &self.game_name
                     && let Some(game_title) = &self.game_title
                 {
-                    let install_components = self.entries.iter()
+                    let enabled = self.entries.iter()
+                        .filter(|(_, component)| component.prev_state)
+                        .map(|(name, _)| name.clone())
+                        .collect::<HashSet<_>>();
+
+                    let enable = self.entries.iter()
                         .filter(|(_, component)| {
                             !component.prev_state && component.curr_state
                         })
-                        .map(|(name, component)| {
-                            ApplyComponentInfo {
-                                name: name.clone(),
-                                title: component.title.clone()
-                            }
-                        })
-                        .collect::<Box<[_]>>();
+                        .map(|(name, _)| name.clone())
+                        .collect::<Vec<_>>();
 
-                    let uninstall_components = self.entries.iter()
+                    let disable = self.entries.iter()
                         .filter(|(_, component)| {
                             component.prev_state && !component.curr_state
                         })
-                        .map(|(name, component)| {
-                            ApplyComponentInfo {
-                                name: name.clone(),
-                                title: component.title.clone()
-                            }
-                        })
+                        .map(|(name, _)| name.clone())
+                        .collect::<Vec<_>>();
+
+                    // Resolve dependencies and conflicts of the changed
+                    // components.
+                    let plan = match ComponentsResolver::new(&self.layout).resolve(&enabled, &enable, &disable) {
+                        Ok(plan) => plan,
+
+                        Err(err) => {
+                            tracing::error!(?err, "failed to resolve game components changes");
+
+                            dialogs::error(
+                                i18n!("failed_resolve_game_components")
+                                    .unwrap_or("Failed to resolve game components changes"),
+                                self.resolve_error_message(&err)
+                            );
+
+                            return;
+                        }
+                    };
+
+                    if plan.is_empty() {
+                        self.window.close();
+
+                        return;
+                    }
+
+                    let install_components = plan.install.iter()
+                        .map(|name| self.component_info(name))
+                        .collect::<Box<[_]>>();
+
+                    let uninstall_components = plan.uninstall.iter()
+                        .map(|name| self.component_info(name))
                         .collect::<Box<[_]>>();
 
+                    // Show resolved plan before applying it since it can
+                    // contain components which were not toggled by the user.
+                    let mut message = Vec::with_capacity(2);
+
+                    if !uninstall_components.is_empty() {
+                        let components = uninstall_components.iter()
+                            .map(|component| component.title.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        message.push(i18n!("game_components_plan_uninstall", {
+                            components => components
+                        }).unwrap_or_else(|| format!("Uninstall: {components}")));
+                    }
+
+                    if !install_components.is_empty() {
+                        let components = install_components.iter()
+                            .map(|component| component.title.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        message.push(i18n!("game_components_plan_install", {
+                            components => components
+                        }).unwrap_or_else(|| format!("Install: {components}")));
+                    }
+
+                    let message = message.join("\n\n");
+
+                    let handle = tasks::spawn_blocking(move || {
+                        dialogs::present(
+                            i18n!("game_components_plan_title")
+                                .unwrap_or("Apply components changes?"),
+                            message,
+                            [
+                                dialogs::DialogAction::new(
+                                    "continue",
+                                    i18n!("continue").unwrap_or("Continue")
+                                ).as_suggested(),
+
+                                dialogs::DialogAction::new(
+                                    "cancel",
+                                    i18n!("cancel").unwrap_or("Cancel")
+                                )
+                            ]
+                        )
+                    });
+
+                    if !matches!(handle.await, Ok(Some(response)) if response == "continue") {
+                        return;
+                    }
+
                     let _ = sender.output(GameComponentsWindowOutput::ApplyChanges {
                         game_integration: game_integration.clone(),
                         game_variant: game_variant.clone(),
@@ -571,13 +669,16 @@ impl SimpleAsyncComponent for GameComponentsWindow {
                             "schedule game uninstallation"
                         );
 
-                        let uninstall_components = self.entries.iter()
-                            .map(|(name, component)| {
-                                ApplyComponentInfo {
-                                    name: name.clone(),
-                                    title: component.title.clone()
-                                }
-                            })
+                        let components = self.entries.keys()
+                            .cloned()
+                            .collect::<Vec<_>>();
+
+                        // Uninstall dependent components before their
+                        // dependencies.
+                        let uninstall_components = ComponentsResolver::new(&self.layout)
+                            .uninstall_order(&components)
+                            .iter()
+                            .map(|name| self.component_info(name))
                             .collect::<Box<[_]>>();
 
                         let _ = sender.output(GameComponentsWindowOutput::ApplyChanges {
@@ -600,3 +701,63 @@ impl SimpleAsyncComponent for GameComponentsWindow {
         }
     }
 }
+
+impl GameComponentsWindow {
+    fn component_info(&self, name: &str) -> ApplyComponentInfo {
+        ApplyComponentInfo {
+            name: name.to_string(),
+            title: self.entries.get(name)
+                .map(|component| component.title.clone())
+                .unwrap_or_else(|| name.to_string())
+        }
+    }
+
+    /// Build user-facing message of the components resolve error using the
+    /// components titles.
+    fn resolve_error_message(&self, err: &ComponentsResolveError) -> String {
+        let title = |name: &str| self.component_info(name).title;
+
+        match err {
+            ComponentsResolveError::UnknownComponent(_) => err.to_string(),
+
+            ComponentsResolveError::LockedComponent(component) => {
+                let component = title(component);
+
+                i18n!("game_component_locked", {
+                    component => component
+                }).unwrap_or_else(|| format!("{component} is locked and can't be uninstalled"))
+            }
+
+            ComponentsResolveError::RequiredComponent { component, dependency } => {
+                let component = title(component);
+                let dependency = title(dependency);
+
+                i18n!("game_component_required", {
+                    component => component,
+                    dependency => dependency
+                }).unwrap_or_else(|| format!("{component} requires {dependency}"))
+            }
+
+            ComponentsResolveError::ConflictingComponents { component, conflict } => {
+                let component = title(component);
+                let conflict = title(conflict);
+
+                i18n!("game_components_conflict", {
+                    component => component,
+                    conflict => conflict
+                }).unwrap_or_else(|| format!("{component} conflicts with {conflict}"))
+            }
+
+            ComponentsResolveError::DependencyCycle(cycle) => {
+                let components = cycle.iter()
+                    .map(|name| title(name))
+                    .collect::<Vec<_>>()
+                    .join(" → ");
+
+                i18n!("game_components_dependency_cycle", {
+                    components => components
+                }).unwrap_or_else(|| format!("Game components depend on each other: {components}"))
+            }
+        }
+    }
+}
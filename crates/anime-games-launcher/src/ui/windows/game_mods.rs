@@ -0,0 +1,467 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use agl_core::tasks;
+use relm4::prelude::*;
+use adw::prelude::*;
+
+use agl_games::api::ModsDirectory;
+
+use crate::{consts, config, i18n};
+use crate::mods::ModsStorage;
+use crate::ui::dialogs;
+
+#[derive(Debug)]
+pub enum GameModsWindowInput {
+    SetGame {
+        game_name: String,
+        directories: Box<[ModsDirectory]>
+    },
+
+    /// Re-render mods directories.
+    Render,
+
+    /// Open file chooser dialog to add a mod to the directory.
+    AddMod {
+        directory: usize,
+        folder: bool
+    },
+
+    SetModEnabled {
+        directory: usize,
+        name: String,
+        enabled: bool
+    },
+
+    MoveMod {
+        directory: usize,
+        from: usize,
+        to: usize
+    },
+
+    RemoveMod {
+        directory: usize,
+        name: String
+    }
+}
+
+#[derive(Debug)]
+pub struct GameModsWindow {
+    window: adw::PreferencesDialog,
+    page: adw::PreferencesPage,
+
+    groups: Vec<adw::PreferencesGroup>,
+
+    /// Storages of the game mods directories in the integration order.
+    storages: Vec<ModsStorage>
+}
+
+#[relm4::component(pub, async)]
+impl SimpleAsyncComponent for GameModsWindow {
+    type Init = ();
+    type Input = GameModsWindowInput;
+    type Output = ();
+
+    view! {
+        #[root]
+        adw::PreferencesDialog {
+            set_title: i18n!("game_mods_title")
+                .unwrap_or("Game mods"),
+
+            set_content_width: 800,
+            set_content_height: 600,
+            set_search_enabled: true,
+
+            add_css_class?: consts::APP_DEBUG.then_some("devel"),
+
+            #[local_ref]
+            add = page -> adw::PreferencesPage {
+                set_description: i18n!("game_mods_description")
+                    .unwrap_or("")
+            }
+        }
+    }
+
+    async fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        _sender: AsyncComponentSender<Self>
+    ) -> AsyncComponentParts<Self> {
+        let model = Self {
+            window: root.clone(),
+            page: adw::PreferencesPage::new(),
+
+            groups: Vec::new(),
+            storages: Vec::new()
+        };
+
+        let page = &model.page;
+
+        let widgets = view_output!();
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update(
+        &mut self,
+        msg: Self::Input,
+        sender: AsyncComponentSender<Self>
+    ) {
+        match msg {
+            GameModsWindowInput::SetGame {
+                game_name,
+                directories
+            } => {
+                self.storages.clear();
+
+                for directory in &directories {
+                    match ModsStorage::open(&game_name, directory) {
+                        Ok(storage) => self.storages.push(storage),
+
+                        Err(err) => {
+                            tracing::error!(?err, ?game_name, directory = directory.name, "failed to open game mods storage");
+
+                            dialogs::error(
+                                i18n!("failed_open_game_mods")
+                                    .unwrap_or("Failed to open game mods"),
+                                err.to_string()
+                            );
+                        }
+                    }
+                }
+
+                sender.input(GameModsWindowInput::Render);
+            }
+
+            GameModsWindowInput::Render => {
+                let lang = config::get().await
+                    .language().ok();
+
+                for group in self.groups.drain(..) {
+                    self.page.remove(&group);
+                }
+
+                for (i, storage) in self.storages.iter().enumerate() {
+                    let directory = storage.directory();
+                    let group = adw::PreferencesGroup::new();
+
+                    let title = match (&directory.title, &lang) {
+                        (Some(title), Some(lang)) => title.translate(lang),
+                        (Some(title), None) => title.default_translation(),
+                        (None, _) => directory.name.as_str()
+                    };
+
+                    group.set_title(title);
+
+                    // Deployed mods can't be changed until the game is closed.
+                    if storage.is_deployed() {
+                        group.set_sensitive(false);
+
+                        group.set_description(Some(
+                            i18n!("game_mods_deployed")
+                                .unwrap_or("Mods can't be changed while the game is running")
+                        ));
+                    } else {
+                        group.set_description(Some(directory.path.to_string_lossy().as_ref()));
+                    }
+
+                    let buttons = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+
+                    for (folder, icon, tooltip) in [
+                        (false, "document-open-symbolic", i18n!("game_mods_add_file").unwrap_or("Add mod file")),
+                        (true, "folder-open-symbolic", i18n!("game_mods_add_folder").unwrap_or("Add mod folder"))
+                    ] {
+                        let button = gtk::Button::from_icon_name(icon);
+
+                        button.add_css_class("flat");
+                        button.set_tooltip_text(Some(tooltip));
+
+                        let sender = sender.clone();
+
+                        button.connect_clicked(move |_| {
+                            sender.input(GameModsWindowInput::AddMod {
+                                directory: i,
+                                folder
+                            });
+                        });
+
+                        buttons.append(&button);
+                    }
+
+                    group.set_header_suffix(Some(&buttons));
+
+                    let conflicts = match storage.conflicts() {
+                        Ok(conflicts) => conflicts,
+
+                        Err(err) => {
+                            tracing::warn!(?err, "failed to check game mods conflicts");
+
+                            vec![]
+                        }
+                    };
+
+                    let mods = storage.mods();
+
+                    if mods.is_empty() {
+                        let row = adw::ActionRow::new();
+
+                        row.set_title(
+                            i18n!("game_mods_empty")
+                                .unwrap_or("No mods added")
+                        );
+
+                        row.add_css_class("dim-label");
+
+                        group.add(&row);
+                    }
+
+                    for (j, game_mod) in mods.iter().enumerate() {
+                        let row = adw::ActionRow::new();
+
+                        row.set_title(&game_mod.title);
+
+                        if *consts::APP_DEBUG {
+                            row.set_tooltip_text(Some(&game_mod.name));
+                        }
+
+                        // List mods with the same files. Mods later in the
+                        // load order override files of the earlier ones.
+                        let mut overrides = Vec::new();
+                        let mut overridden_by = Vec::new();
+
+                        for conflict in &conflicts {
+                            let Some(position) = conflict.mods.iter().position(|name| name == &game_mod.name) else {
+                                continue;
+                            };
+
+                            for (k, name) in conflict.mods.iter().enumerate() {
+                                let Some(other) = mods.iter().find(|other| &other.name == name) else {
+                                    continue;
+                                };
+
+                                if k < position && !overrides.contains(&other.title) {
+                                    overrides.push(other.title.clone());
+                                } else if k > position && !overridden_by.contains(&other.title) {
+                                    overridden_by.push(other.title.clone());
+                                }
+                            }
+                        }
+
+                        let mut subtitle = Vec::with_capacity(2);
+
+                        if !overrides.is_empty() {
+                            let mods = overrides.join(", ");
+
+                            subtitle.push(i18n!("game_mod_overrides", { mods => mods })
+                                .unwrap_or_else(|| format!("Overrides files of {mods}")));
+                        }
+
+                        if !overridden_by.is_empty() {
+                            let mods = overridden_by.join(", ");
+
+                            subtitle.push(i18n!("game_mod_overridden_by", { mods => mods })
+                                .unwrap_or_else(|| format!("Files are overridden by {mods}")));
+                        }
+
+                        if !subtitle.is_empty() {
+                            row.set_subtitle(&subtitle.join("\n"));
+
+                            let icon = gtk::Image::from_icon_name("dialog-warning-symbolic");
+
+                            icon.add_css_class("warning");
+
+                            row.add_suffix(&icon);
+                        }
+
+                        let checkbox = gtk::CheckButton::new();
+
+                        checkbox.set_valign(gtk::Align::Center);
+                        checkbox.set_active(game_mod.enabled);
+
+                        {
+                            let sender = sender.clone();
+                            let name = game_mod.name.clone();
+
+                            checkbox.connect_toggled(move |checkbox| {
+                                sender.input(GameModsWindowInput::SetModEnabled {
+                                    directory: i,
+                                    name: name.clone(),
+                                    enabled: checkbox.is_active()
+                                });
+                            });
+                        }
+
+                        row.add_prefix(&checkbox);
+
+                        let move_up = gtk::Button::from_icon_name("go-up-symbolic");
+                        let move_down = gtk::Button::from_icon_name("go-down-symbolic");
+                        let remove = gtk::Button::from_icon_name("user-trash-symbolic");
+
+                        move_up.set_sensitive(j > 0);
+                        move_down.set_sensitive(j + 1 < mods.len());
+
+                        move_up.set_tooltip_text(i18n!("download_move_up"));
+                        move_down.set_tooltip_text(i18n!("download_move_down"));
+                        remove.set_tooltip_text(i18n!("download_remove"));
+
+                        for button in [&move_up, &move_down, &remove] {
+                            button.add_css_class("flat");
+                            button.set_valign(gtk::Align::Center);
+
+                            row.add_suffix(button);
+                        }
+
+                        {
+                            let sender = sender.clone();
+
+                            move_up.connect_clicked(move |_| {
+                                sender.input(GameModsWindowInput::MoveMod {
+                                    directory: i,
+                                    from: j,
+                                    to: j.saturating_sub(1)
+                                });
+                            });
+                        }
+
+                        {
+                            let sender = sender.clone();
+
+                            move_down.connect_clicked(move |_| {
+                                sender.input(GameModsWindowInput::MoveMod {
+                                    directory: i,
+                                    from: j,
+                                    to: j + 1
+                                });
+                            });
+                        }
+
+                        {
+                            let sender = sender.clone();
+                            let name = game_mod.name.clone();
+
+                            remove.connect_clicked(move |_| {
+                                sender.input(GameModsWindowInput::RemoveMod {
+                                    directory: i,
+                                    name: name.clone()
+                                });
+                            });
+                        }
+
+                        group.add(&row);
+                    }
+
+                    self.page.add(&group);
+                    self.groups.push(group);
+                }
+            }
+
+            GameModsWindowInput::AddMod { directory, folder } => {
+                let Some(mut storage) = self.storages.get(directory).cloned() else {
+                    return;
+                };
+
+                let dialog = gtk::FileDialog::new();
+
+                let parent = self.window.root()
+                    .and_then(|root| root.downcast::<gtk::Window>().ok());
+
+                let result = if folder {
+                    dialog.select_folder_future(parent.as_ref()).await
+                } else {
+                    dialog.open_future(parent.as_ref()).await
+                };
+
+                // Dialog was closed without choosing anything.
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+
+                // Copying mods files can take some time.
+                let result = tasks::spawn_blocking(move || {
+                    storage.add(&path)?;
+
+                    Ok::<_, anyhow::Error>(storage)
+                }).await;
+
+                match result.map_err(|err| anyhow::anyhow!(err)).and_then(|result| result) {
+                    Ok(storage) => self.storages[directory] = storage,
+
+                    Err(err) => {
+                        tracing::error!(?err, "failed to add game mod");
+
+                        dialogs::error(
+                            i18n!("failed_add_game_mod")
+                                .unwrap_or("Failed to add game mod"),
+                            err.to_string()
+                        );
+                    }
+                }
+
+                sender.input(GameModsWindowInput::Render);
+            }
+
+            GameModsWindowInput::SetModEnabled { directory, name, enabled } => {
+                if let Some(storage) = self.storages.get_mut(directory)
+                    && let Err(err) = storage.set_enabled(&name, enabled)
+                {
+                    tracing::error!(?err, "failed to update game mods");
+
+                    dialogs::error(
+                        i18n!("failed_update_game_mods")
+                            .unwrap_or("Failed to update game mods"),
+                        err.to_string()
+                    );
+                }
+
+                sender.input(GameModsWindowInput::Render);
+            }
+
+            GameModsWindowInput::MoveMod { directory, from, to } => {
+                if let Some(storage) = self.storages.get_mut(directory)
+                    && let Err(err) = storage.reorder(from, to)
+                {
+                    tracing::error!(?err, "failed to update game mods");
+
+                    dialogs::error(
+                        i18n!("failed_update_game_mods")
+                            .unwrap_or("Failed to update game mods"),
+                        err.to_string()
+                    );
+                }
+
+                sender.input(GameModsWindowInput::Render);
+            }
+
+            GameModsWindowInput::RemoveMod { directory, name } => {
+                if let Some(storage) = self.storages.get_mut(directory)
+                    && let Err(err) = storage.remove(&name)
+                {
+                    tracing::error!(?err, "failed to remove game mod");
+
+                    dialogs::error(
+                        i18n!("failed_remove_game_mod")
+                            .unwrap_or("Failed to remove game mod"),
+                        err.to_string()
+                    );
+                }
+
+                sender.input(GameModsWindowInput::Render);
+            }
+        }
+    }
+}
@@ -24,8 +24,9 @@ use adw::prelude::*;
 
 use agl_core::tasks;
 use agl_core::export::tasks::tokio;
+use agl_games::api::SavesDirectory;
 
-use crate::{consts, utils, i18n};
+use crate::{consts, config, saves, mods, launch, utils, i18n};
 use crate::ui::dialogs;
 
 const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
@@ -33,7 +34,12 @@ const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 #[derive(Debug)]
 pub enum GameRunningWindowMsg {
     SetChild {
+        game_name: String,
         game_title: String,
+
+        /// Game saves directories synchronized after the game is closed.
+        saves_directories: Box<[SavesDirectory]>,
+
         child: Child
     },
 
@@ -46,7 +52,9 @@ pub enum GameRunningWindowMsg {
 pub struct GameRunningWindow {
     window: adw::Dialog,
 
+    game_name: Option<String>,
     game_title: Option<String>,
+    saves_directories: Box<[SavesDirectory]>,
 
     child: Option<Child>,
 
@@ -148,7 +156,9 @@ impl SimpleAsyncComponent for GameRunningWindow {
     ) -> AsyncComponentParts<Self> {
         let model = Self {
             window: root.clone(),
+            game_name: None,
             game_title: None,
+            saves_directories: Box::default(),
             child: None,
             running_since: None,
             running_time: None,
@@ -166,8 +176,15 @@ impl SimpleAsyncComponent for GameRunningWindow {
         sender: AsyncComponentSender<Self>
     ) {
         match msg {
-            GameRunningWindowMsg::SetChild { game_title, child } => {
+            GameRunningWindowMsg::SetChild {
+                game_name,
+                game_title,
+                saves_directories,
+                child
+            } => {
+                self.game_name = Some(game_name);
                 self.game_title = Some(game_title);
+                self.saves_directories = saves_directories;
                 self.child = Some(child);
 
                 self.running_since = Some(Instant::now());
@@ -188,8 +205,11 @@ impl SimpleAsyncComponent for GameRunningWindow {
                     self.running_time = Some(utils::pretty_seconds(running_time));
                 }
 
+                // Keep the window open while processes forked by the game
+                // are running.
                 if let Some(child) = &mut self.child
                     && matches!(child.try_wait(), Ok(Some(_)))
+                    && !launch::process_group_running(child)
                 {
                     sender.input(GameRunningWindowMsg::Kill);
                 }
@@ -199,9 +219,16 @@ impl SimpleAsyncComponent for GameRunningWindow {
                 self.running_since = None;
                 self.running_time = None;
 
-                #[allow(clippy::collapsible_if)]
                 if let Some(mut child) = self.child.take() {
-                    if let Err(err) = child.kill() {
+                    let result = tasks::spawn_blocking(move || {
+                        launch::terminate_process(&mut child)
+                    }).await;
+
+                    let result = result.unwrap_or_else(|err| {
+                        Err(std::io::Error::other(err))
+                    });
+
+                    if let Err(err) = result {
                         tracing::error!(?err, "failed to kill running game process");
 
                         dialogs::error(
@@ -216,6 +243,38 @@ impl SimpleAsyncComponent for GameRunningWindow {
                     handle.abort();
                 }
 
+                let saves_directories = std::mem::take(&mut self.saves_directories);
+
+                if let Some(game_name) = self.game_name.take() {
+                    let config = config::get().await;
+
+                    tasks::spawn_blocking(move || {
+                        // Restore original game files after the game is closed.
+                        if let Err(err) = mods::undeploy_game_mods(&game_name) {
+                            tracing::error!(?err, "failed to undeploy game mods");
+
+                            dialogs::error(
+                                i18n!("failed_undeploy_game_mods")
+                                    .unwrap_or("Failed to undeploy game mods"),
+                                err.to_string()
+                            );
+                        }
+
+                        // Synchronize game saves after the game is closed.
+                        if config.saves_enable && !saves_directories.is_empty()
+                            && let Err(err) = saves::sync_game_saves(&game_name, &saves_directories, &config, saves::ask_conflict_resolution)
+                        {
+                            tracing::error!(?err, "failed to synchronize game saves");
+
+                            dialogs::error(
+                                i18n!("failed_sync_game_saves")
+                                    .unwrap_or("Failed to synchronize game saves"),
+                                err.to_string()
+                            );
+                        }
+                    });
+                }
+
                 sender.input(GameRunningWindowMsg::Close);
             }
 
@@ -16,6 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use relm4::prelude::*;
@@ -26,13 +27,19 @@ use agl_locale::unic_langid::LanguageIdentifier;
 use agl_games::api::{
     GameVariant,
     GameIntegration,
+    GameEdition,
     GameSettingsEntry,
     GameSettingsEntryFormat,
     GameSettingsEntryReactivity,
-    GameSettingsGroup
+    GameSettingsGroup,
+    LaunchPreset
 };
 
-use crate::{consts, config, i18n};
+use agl_locale::string::LocalizableString;
+
+use crate::{consts, config, i18n, launch};
+use crate::games::GameLock;
+use crate::shortcuts::{desktop, steam};
 use crate::ui::dialogs;
 
 enum ParentWidget<'widget> {
@@ -450,12 +457,387 @@ fn render_entry(
     }
 }
 
+fn render_wrappers_group(
+    lock: &GameLock,
+    listener: relm4::Sender<GameSettingsWindowInput>
+) -> adw::PreferencesGroup {
+    let group_widget = adw::PreferencesGroup::new();
+
+    group_widget.set_title(i18n!("game_wrappers_title").unwrap_or("Launch wrappers"));
+    group_widget.set_description(Some(
+        i18n!("game_wrappers_description")
+            .unwrap_or("Commands used to wrap the game launch, like gamemoderun, mangohud or gamescope -f --. The first wrapper is the outermost one.")
+    ));
+
+    let global_wrappers = adw::SwitchRow::new();
+
+    global_wrappers.set_title(i18n!("game_global_wrappers_title").unwrap_or("Use global wrappers"));
+    global_wrappers.set_subtitle(
+        i18n!("game_global_wrappers_description")
+            .unwrap_or("Apply wrappers from the launcher config before the game ones")
+    );
+
+    global_wrappers.set_active(lock.global_wrappers);
+
+    {
+        let listener = listener.clone();
+
+        global_wrappers.connect_active_notify(move |widget| {
+            listener.emit(GameSettingsWindowInput::SetGlobalWrappers(widget.is_active()));
+        });
+    }
+
+    group_widget.add(&global_wrappers);
+
+    for (index, wrapper) in lock.wrappers.iter().enumerate() {
+        let widget = adw::EntryRow::new();
+
+        widget.set_show_apply_button(true);
+
+        widget.set_title(&i18n!("game_wrapper_title", { index => index + 1 })
+            .unwrap_or_else(|| format!("Wrapper #{}", index + 1)));
+
+        widget.set_text(wrapper);
+
+        let remove_button = gtk::Button::new();
+
+        remove_button.set_icon_name("user-trash-symbolic");
+        remove_button.set_valign(gtk::Align::Center);
+        remove_button.add_css_class("flat");
+
+        remove_button.set_tooltip(i18n!("game_remove_wrapper").unwrap_or("Remove wrapper"));
+
+        {
+            let listener = listener.clone();
+
+            remove_button.connect_clicked(move |_| {
+                listener.emit(GameSettingsWindowInput::RemoveWrapper(index));
+            });
+        }
+
+        widget.add_suffix(&remove_button);
+
+        {
+            let listener = listener.clone();
+
+            widget.connect_apply(move |widget| {
+                listener.emit(GameSettingsWindowInput::SetWrapper {
+                    index,
+                    value: widget.text().to_string()
+                });
+            });
+        }
+
+        group_widget.add(&widget);
+    }
+
+    let add_wrapper = adw::EntryRow::new();
+
+    add_wrapper.set_show_apply_button(true);
+    add_wrapper.set_title(i18n!("game_add_wrapper_title").unwrap_or("Add wrapper"));
+
+    add_wrapper.connect_apply(move |widget| {
+        listener.emit(GameSettingsWindowInput::AddWrapper(widget.text().to_string()));
+    });
+
+    group_widget.add(&add_wrapper);
+
+    group_widget
+}
+
+fn render_presets_group(
+    lock: &GameLock,
+    editions: &[GameEdition],
+    lang: Option<&LanguageIdentifier>,
+    listener: relm4::Sender<GameSettingsWindowInput>
+) -> adw::PreferencesGroup {
+    let group_widget = adw::PreferencesGroup::new();
+
+    group_widget.set_title(i18n!("game_launch_presets_title").unwrap_or("Launch presets"));
+    group_widget.set_description(Some(
+        i18n!("game_launch_presets_description")
+            .unwrap_or("Named sets of launch arguments, environment variables, game edition and wrappers which can be selected next to the play button.")
+    ));
+
+    for (index, preset) in lock.presets.iter().enumerate() {
+        let row = adw::ExpanderRow::new();
+
+        let title = match lang {
+            Some(lang) => preset.title.translate(lang),
+            None => preset.title.default_translation()
+        };
+
+        row.set_title(title);
+
+        if let Some(description) = &preset.description {
+            let description = match lang {
+                Some(lang) => description.translate(lang),
+                None => description.default_translation()
+            };
+
+            row.set_subtitle(description);
+        }
+
+        let remove_button = gtk::Button::new();
+
+        remove_button.set_icon_name("user-trash-symbolic");
+        remove_button.set_valign(gtk::Align::Center);
+        remove_button.add_css_class("flat");
+
+        remove_button.set_tooltip(i18n!("game_remove_launch_preset").unwrap_or("Remove preset"));
+
+        {
+            let listener = listener.clone();
+
+            remove_button.connect_clicked(move |_| {
+                listener.emit(GameSettingsWindowInput::RemovePreset(index));
+            });
+        }
+
+        row.add_suffix(&remove_button);
+
+        // Arguments.
+        let args = adw::EntryRow::new();
+
+        args.set_show_apply_button(true);
+        args.set_title(i18n!("game_launch_preset_args").unwrap_or("Arguments"));
+
+        if let Some(value) = &preset.args {
+            args.set_text(&launch::join_command(value));
+        }
+
+        {
+            let listener = listener.clone();
+
+            args.connect_apply(move |widget| {
+                listener.emit(GameSettingsWindowInput::SetPresetField {
+                    index,
+                    field: LaunchPresetField::Args(widget.text().to_string())
+                });
+            });
+        }
+
+        row.add_row(&args);
+
+        // Environment variables.
+        let env = adw::EntryRow::new();
+
+        env.set_show_apply_button(true);
+        env.set_title(i18n!("game_launch_preset_env").unwrap_or("Environment variables"));
+
+        if let Some(value) = &preset.env {
+            let mut value = value.iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>();
+
+            value.sort();
+
+            env.set_text(&launch::join_command(value));
+        }
+
+        {
+            let listener = listener.clone();
+
+            env.connect_apply(move |widget| {
+                listener.emit(GameSettingsWindowInput::SetPresetField {
+                    index,
+                    field: LaunchPresetField::Env(widget.text().to_string())
+                });
+            });
+        }
+
+        row.add_row(&env);
+
+        // Game edition.
+        if !editions.is_empty() {
+            let edition = adw::ComboRow::new();
+
+            edition.set_title(i18n!("game_launch_preset_edition").unwrap_or("Game edition"));
+
+            let model = gtk::StringList::new(&[
+                i18n!("game_launch_preset_default_edition").unwrap_or("Default")
+            ]);
+
+            let mut selected_index = 0;
+
+            for (i, value) in editions.iter().enumerate() {
+                let title = match lang {
+                    Some(lang) => value.title.translate(lang),
+                    None => value.title.default_translation()
+                };
+
+                model.append(title);
+
+                if preset.edition.as_ref() == Some(&value.name) {
+                    selected_index = i + 1;
+                }
+            }
+
+            edition.set_model(Some(&model));
+            edition.set_selected(selected_index as u32);
+
+            let listener = listener.clone();
+
+            let editions = editions.iter()
+                .map(|edition| edition.name.clone())
+                .collect::<Vec<_>>();
+
+            edition.connect_selected_notify(move |widget| {
+                let edition = (widget.selected() as usize).checked_sub(1)
+                    .and_then(|index| editions.get(index))
+                    .cloned();
+
+                listener.emit(GameSettingsWindowInput::SetPresetField {
+                    index,
+                    field: LaunchPresetField::Edition(edition)
+                });
+            });
+
+            row.add_row(&edition);
+        }
+
+        // Wrappers.
+        let wrappers = adw::EntryRow::new();
+
+        wrappers.set_show_apply_button(true);
+        wrappers.set_title(i18n!("game_launch_preset_wrappers").unwrap_or("Wrappers (replace the game ones)"));
+
+        if let Some(value) = &preset.wrappers {
+            wrappers.set_text(&value.join(" "));
+        }
+
+        {
+            let listener = listener.clone();
+
+            wrappers.connect_apply(move |widget| {
+                listener.emit(GameSettingsWindowInput::SetPresetField {
+                    index,
+                    field: LaunchPresetField::Wrappers(widget.text().to_string())
+                });
+            });
+        }
+
+        row.add_row(&wrappers);
+
+        // Global wrappers.
+        let global_wrappers = adw::SwitchRow::new();
+
+        global_wrappers.set_title(i18n!("game_global_wrappers_title").unwrap_or("Use global wrappers"));
+
+        global_wrappers.set_active(preset.global_wrappers.unwrap_or(lock.global_wrappers));
+
+        {
+            let listener = listener.clone();
+
+            global_wrappers.connect_active_notify(move |widget| {
+                listener.emit(GameSettingsWindowInput::SetPresetField {
+                    index,
+                    field: LaunchPresetField::GlobalWrappers(widget.is_active())
+                });
+            });
+        }
+
+        row.add_row(&global_wrappers);
+
+        group_widget.add(&row);
+    }
+
+    let add_preset = adw::EntryRow::new();
+
+    add_preset.set_show_apply_button(true);
+    add_preset.set_title(i18n!("game_add_launch_preset_title").unwrap_or("Add preset"));
+
+    add_preset.connect_apply(move |widget| {
+        listener.emit(GameSettingsWindowInput::AddPreset(widget.text().to_string()));
+    });
+
+    group_widget.add(&add_preset);
+
+    group_widget
+}
+
+fn render_shortcuts_group(
+    game_name: &str,
+    listener: relm4::Sender<GameSettingsWindowInput>
+) -> adw::PreferencesGroup {
+    let group_widget = adw::PreferencesGroup::new();
+
+    group_widget.set_title(i18n!("game_shortcuts_title").unwrap_or("Shortcuts"));
+
+    let desktop_entry = adw::SwitchRow::new();
+
+    desktop_entry.set_title(i18n!("game_desktop_entry_title").unwrap_or("Show in applications menu"));
+    desktop_entry.set_subtitle(
+        i18n!("game_desktop_entry_description")
+            .unwrap_or("Create a desktop entry which launches the game without opening the launcher")
+    );
+
+    desktop_entry.set_active(desktop::has_entry(game_name));
+
+    {
+        let listener = listener.clone();
+
+        desktop_entry.connect_active_notify(move |widget| {
+            listener.emit(GameSettingsWindowInput::SetDesktopEntry(widget.is_active()));
+        });
+    }
+
+    group_widget.add(&desktop_entry);
+
+    let steam_shortcut = adw::ActionRow::new();
+
+    steam_shortcut.set_title(i18n!("game_steam_shortcut_title").unwrap_or("Add to Steam"));
+    steam_shortcut.set_subtitle(
+        i18n!("game_steam_shortcut_description")
+            .unwrap_or("Add the game as a non-Steam shortcut. Steam must be restarted to show it")
+    );
+
+    let add_button = gtk::Button::new();
+
+    add_button.set_icon_name("list-add-symbolic");
+    add_button.set_valign(gtk::Align::Center);
+    add_button.add_css_class("flat");
+
+    add_button.connect_clicked(move |_| {
+        listener.emit(GameSettingsWindowInput::AddSteamShortcut);
+    });
+
+    steam_shortcut.add_suffix(&add_button);
+    steam_shortcut.set_activatable_widget(Some(&add_button));
+
+    group_widget.add(&steam_shortcut);
+
+    group_widget
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchPresetField {
+    /// Arguments command line string.
+    Args(String),
+
+    /// Whitespace separated `KEY=VALUE` pairs.
+    Env(String),
+
+    Edition(Option<String>),
+
+    /// Wrappers command line string.
+    Wrappers(String),
+
+    GlobalWrappers(bool)
+}
+
 #[derive(Debug)]
 pub enum GameSettingsWindowInput {
     SetGame {
         integration: Arc<GameIntegration>,
         variant: GameVariant,
-        layout: Box<[GameSettingsGroup]>
+
+        /// Unique game name. A game package lock filename is expected be used.
+        game_name: String,
+
+        /// Game settings layout. If `None`, only the launcher-managed settings
+        /// are shown.
+        layout: Option<Box<[GameSettingsGroup]>>
     },
 
     SetBoolProperty {
@@ -476,7 +858,30 @@ pub enum GameSettingsWindowInput {
         reactivity: GameSettingsEntryReactivity
     },
 
-    UpdateCurrentGameLayout
+    SetGlobalWrappers(bool),
+
+    SetWrapper {
+        index: usize,
+        value: String
+    },
+
+    AddWrapper(String),
+    RemoveWrapper(usize),
+
+    SetPresetField {
+        index: usize,
+        field: LaunchPresetField
+    },
+
+    /// Add new launch preset with provided title.
+    AddPreset(String),
+    RemovePreset(usize),
+
+    SetDesktopEntry(bool),
+    AddSteamShortcut,
+
+    UpdateCurrentGameLayout,
+    UpdateLaunchGroups
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -492,13 +897,14 @@ pub enum GameSettingsWindowOutput {
 
 #[derive(Debug, Clone)]
 pub struct GameSettingsWindow {
-    window: adw::PreferencesDialog,
     page: adw::PreferencesPage,
 
     groups: Vec<adw::PreferencesGroup>,
+    launch_groups: Vec<adw::PreferencesGroup>,
 
     game_integration: Option<Arc<GameIntegration>>,
-    game_variant: Option<GameVariant>
+    game_variant: Option<GameVariant>,
+    game_name: Option<String>
 }
 
 #[relm4::component(pub, async)]
@@ -529,14 +935,15 @@ impl SimpleAsyncComponent for GameSettingsWindow {
         _sender: AsyncComponentSender<Self>
     ) -> AsyncComponentParts<Self> {
         let model = Self {
-            window: root.clone(),
             page: adw::PreferencesPage::new(),
 
             // Some random capacity value I took from my head.
             groups: Vec::with_capacity(2),
+            launch_groups: Vec::with_capacity(3),
 
             game_variant: None,
-            game_integration: None
+            game_integration: None,
+            game_name: None
         };
 
         let page = &model.page;
@@ -590,10 +997,22 @@ impl SimpleAsyncComponent for GameSettingsWindow {
             }
         }
 
+        async fn update_lock(
+            game_name: &str,
+            callback: impl FnOnce(&mut GameLock)
+        ) -> anyhow::Result<()> {
+            let mut lock = GameLock::load(game_name).await?;
+
+            callback(&mut lock);
+
+            lock.save().await
+        }
+
         match msg {
             GameSettingsWindowInput::SetGame {
                 integration,
                 variant,
+                game_name,
                 layout
             } => {
                 let lang = config::get().await
@@ -603,8 +1022,17 @@ impl SimpleAsyncComponent for GameSettingsWindow {
 
                 self.game_integration = Some(integration);
                 self.game_variant = Some(variant);
+                self.game_name = Some(game_name);
+
+                // Launcher-managed groups are rendered after the game settings.
+                for group in self.launch_groups.drain(..) {
+                    self.page.remove(&group);
+                }
 
                 let groups = std::mem::take(&mut self.groups);
+                let layout = layout.unwrap_or_default();
+
+                let listener = sender.input_sender().clone();
 
                 let groups = gtk::glib::spawn_future_local(async move {
                     for group in groups {
@@ -641,7 +1069,7 @@ impl SimpleAsyncComponent for GameSettingsWindow {
                                 ParentWidget::Group(&group_widget),
                                 entry,
                                 lang.as_ref(),
-                                sender.input_sender().clone()
+                                listener.clone()
                             );
                         }
 
@@ -666,6 +1094,8 @@ impl SimpleAsyncComponent for GameSettingsWindow {
                         );
                     }
                 }
+
+                sender.input(GameSettingsWindowInput::UpdateLaunchGroups);
             }
 
             GameSettingsWindowInput::SetBoolProperty {
@@ -734,23 +1164,366 @@ impl SimpleAsyncComponent for GameSettingsWindow {
                 }
             }
 
+            GameSettingsWindowInput::SetGlobalWrappers(value) => {
+                if let Some(game_name) = &self.game_name {
+                    let result = update_lock(game_name, |lock| {
+                        lock.global_wrappers = value;
+                    }).await;
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch wrappers");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_wrappers")
+                                .unwrap_or("Failed to update game launch wrappers"),
+                            err.to_string()
+                        );
+                    }
+                }
+            }
+
+            GameSettingsWindowInput::SetWrapper { index, value } => {
+                if let Some(game_name) = &self.game_name {
+                    let value = value.trim();
+
+                    let result = update_lock(game_name, |lock| {
+                        if index < lock.wrappers.len() {
+                            // Empty wrapper is the same as removing it.
+                            if value.is_empty() {
+                                lock.wrappers.remove(index);
+                            } else {
+                                lock.wrappers[index] = value.to_string();
+                            }
+                        }
+                    }).await;
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch wrappers");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_wrappers")
+                                .unwrap_or("Failed to update game launch wrappers"),
+                            err.to_string()
+                        );
+                    }
+
+                    sender.input(GameSettingsWindowInput::UpdateLaunchGroups);
+                }
+            }
+
+            GameSettingsWindowInput::AddWrapper(value) => {
+                let value = value.trim();
+
+                if !value.is_empty() && let Some(game_name) = &self.game_name {
+                    let result = update_lock(game_name, |lock| {
+                        lock.wrappers.push(value.to_string());
+                    }).await;
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch wrappers");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_wrappers")
+                                .unwrap_or("Failed to update game launch wrappers"),
+                            err.to_string()
+                        );
+                    }
+
+                    sender.input(GameSettingsWindowInput::UpdateLaunchGroups);
+                }
+            }
+
+            GameSettingsWindowInput::RemoveWrapper(index) => {
+                if let Some(game_name) = &self.game_name {
+                    let result = update_lock(game_name, |lock| {
+                        if index < lock.wrappers.len() {
+                            lock.wrappers.remove(index);
+                        }
+                    }).await;
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch wrappers");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_wrappers")
+                                .unwrap_or("Failed to update game launch wrappers"),
+                            err.to_string()
+                        );
+                    }
+
+                    sender.input(GameSettingsWindowInput::UpdateLaunchGroups);
+                }
+            }
+
+            GameSettingsWindowInput::SetPresetField { index, field } => {
+                if let Some(game_name) = &self.game_name {
+                    let result = update_lock(game_name, |lock| {
+                        let Some(preset) = lock.presets.get_mut(index) else {
+                            return;
+                        };
+
+                        match field {
+                            LaunchPresetField::Args(value) => {
+                                let args = launch::split_command(value);
+
+                                preset.args = (!args.is_empty()).then_some(args);
+                            }
+
+                            LaunchPresetField::Env(value) => {
+                                let env = launch::split_command(value)
+                                    .into_iter()
+                                    .filter_map(|pair| {
+                                        let (key, value) = pair.split_once('=')?;
+
+                                        Some((key.to_string(), value.to_string()))
+                                    })
+                                    .collect::<HashMap<_, _>>();
+
+                                preset.env = (!env.is_empty()).then_some(env);
+                            }
+
+                            LaunchPresetField::Edition(edition) => preset.edition = edition,
+
+                            LaunchPresetField::Wrappers(value) => {
+                                let value = value.trim();
+
+                                preset.wrappers = (!value.is_empty())
+                                    .then(|| vec![value.to_string()]);
+                            }
+
+                            LaunchPresetField::GlobalWrappers(value) => {
+                                preset.global_wrappers = Some(value);
+                            }
+                        }
+                    }).await;
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch preset");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_launch_preset")
+                                .unwrap_or("Failed to update game launch preset"),
+                            err.to_string()
+                        );
+                    }
+
+                    let _ = sender.output(GameSettingsWindowOutput::ReloadGameInfo {
+                        launch_info: true,
+                        actions_pipeline: false,
+                        components_layout: false,
+                        tools_layout: false,
+                        settings_layout: false
+                    });
+                }
+            }
+
+            GameSettingsWindowInput::AddPreset(title) => {
+                let title = title.trim();
+
+                if !title.is_empty() && let Some(game_name) = &self.game_name {
+                    let result = update_lock(game_name, |lock| {
+                        // User presets are prefixed to not to collide with
+                        // the game integration ones.
+                        let mut name = format!("user:{title}");
+                        let mut i = 1;
+
+                        while lock.presets.iter().any(|preset| preset.name == name) {
+                            i += 1;
+
+                            name = format!("user:{title} ({i})");
+                        }
+
+                        lock.presets.push(LaunchPreset {
+                            name,
+                            title: LocalizableString::raw(title),
+                            description: None,
+                            edition: None,
+                            args: None,
+                            env: None,
+                            wrappers: None,
+                            global_wrappers: None
+                        });
+                    }).await;
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch preset");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_launch_preset")
+                                .unwrap_or("Failed to update game launch preset"),
+                            err.to_string()
+                        );
+                    }
+
+                    let _ = sender.output(GameSettingsWindowOutput::ReloadGameInfo {
+                        launch_info: true,
+                        actions_pipeline: false,
+                        components_layout: false,
+                        tools_layout: false,
+                        settings_layout: false
+                    });
+
+                    sender.input(GameSettingsWindowInput::UpdateLaunchGroups);
+                }
+            }
+
+            GameSettingsWindowInput::RemovePreset(index) => {
+                if let Some(game_name) = &self.game_name {
+                    let result = update_lock(game_name, |lock| {
+                        if index < lock.presets.len() {
+                            let preset = lock.presets.remove(index);
+
+                            if lock.preset.as_ref() == Some(&preset.name) {
+                                lock.preset = None;
+                            }
+                        }
+                    }).await;
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game launch preset");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_launch_preset")
+                                .unwrap_or("Failed to update game launch preset"),
+                            err.to_string()
+                        );
+                    }
+
+                    let _ = sender.output(GameSettingsWindowOutput::ReloadGameInfo {
+                        launch_info: true,
+                        actions_pipeline: false,
+                        components_layout: false,
+                        tools_layout: false,
+                        settings_layout: false
+                    });
+
+                    sender.input(GameSettingsWindowInput::UpdateLaunchGroups);
+                }
+            }
+
+            GameSettingsWindowInput::SetDesktopEntry(enabled) => {
+                if let Some(game_name) = &self.game_name {
+                    let result = if enabled {
+                        match GameLock::load(game_name).await {
+                            Ok(lock) => desktop::create_entry(&lock).await.map(|_| ()),
+                            Err(err) => Err(err)
+                        }
+                    } else {
+                        desktop::remove_entry(game_name).await
+                    };
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to update game desktop entry");
+
+                        dialogs::error(
+                            i18n!("failed_update_game_desktop_entry")
+                                .unwrap_or("Failed to update game desktop entry"),
+                            err.to_string()
+                        );
+                    }
+                }
+            }
+
+            GameSettingsWindowInput::AddSteamShortcut => {
+                if let Some(game_name) = &self.game_name {
+                    let result = match GameLock::load(game_name).await {
+                        Ok(lock) => steam::create_shortcut(&lock).await,
+                        Err(err) => Err(err)
+                    };
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to add game steam shortcut");
+
+                        dialogs::error(
+                            i18n!("failed_add_game_steam_shortcut")
+                                .unwrap_or("Failed to add game steam shortcut"),
+                            err.to_string()
+                        );
+                    }
+                }
+            }
+
+            GameSettingsWindowInput::UpdateLaunchGroups => {
+                for group in self.launch_groups.drain(..) {
+                    self.page.remove(&group);
+                }
+
+                if let Some(game_name) = &self.game_name {
+                    match GameLock::load(game_name).await {
+                        Ok(lock) => {
+                            let wrappers_group = render_wrappers_group(
+                                &lock,
+                                sender.input_sender().clone()
+                            );
+
+                            self.page.add(&wrappers_group);
+
+                            self.launch_groups.push(wrappers_group);
+
+                            let editions = match &self.game_integration {
+                                Some(integration) => integration.get_editions(&consts::CURRENT_PLATFORM)
+                                    .unwrap_or_else(|err| {
+                                        tracing::warn!(?err, "failed to request game editions");
+
+                                        None
+                                    })
+                                    .unwrap_or_default(),
+
+                                None => Box::default()
+                            };
+
+                            let lang = config::get().await
+                                .language().ok();
+
+                            let presets_group = render_presets_group(
+                                &lock,
+                                &editions,
+                                lang.as_ref(),
+                                sender.input_sender().clone()
+                            );
+
+                            self.page.add(&presets_group);
+
+                            self.launch_groups.push(presets_group);
+
+                            let shortcuts_group = render_shortcuts_group(
+                                game_name,
+                                sender.input_sender().clone()
+                            );
+
+                            self.page.add(&shortcuts_group);
+
+                            self.launch_groups.push(shortcuts_group);
+                        }
+
+                        Err(err) => {
+                            tracing::error!(?err, "failed to load game launch wrappers");
+
+                            dialogs::error(
+                                i18n!("failed_load_game_wrappers")
+                                    .unwrap_or("Failed to load game launch wrappers"),
+                                err.to_string()
+                            );
+                        }
+                    }
+                }
+            }
+
             GameSettingsWindowInput::UpdateCurrentGameLayout => {
-                if let Some(variant) = &self.game_variant &&
-                    let Some(integration) = &self.game_integration
+                if let Some(variant) = &self.game_variant
+                    && let Some(integration) = &self.game_integration
+                    && let Some(game_name) = &self.game_name
                 {
                     match integration.get_settings_layout(variant) {
-                        Ok(Some(layout)) => {
+                        Ok(layout) => {
                             sender.input(GameSettingsWindowInput::SetGame {
                                 variant: variant.clone(),
                                 integration: integration.clone(),
+                                game_name: game_name.clone(),
                                 layout
                             });
                         }
 
-                        Ok(None) => {
-                            self.window.close();
-                        }
-
                         Err(err) => {
                             tracing::error!(?err, "failed to update game settings layout");
 
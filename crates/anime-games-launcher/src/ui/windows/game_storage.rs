@@ -0,0 +1,462 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use agl_core::tasks;
+use relm4::prelude::*;
+use adw::prelude::*;
+
+use agl_games::api::{GameIntegration, GameVariant};
+
+use crate::{consts, disk, mods, utils, i18n};
+use crate::disk::RelocateStage;
+use crate::ui::dialogs;
+
+/// Game installation folder info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameStorageInfo {
+    pub path: PathBuf,
+
+    /// Total size of the game files in bytes.
+    pub disk_usage: u64,
+
+    /// Free space on the game's drive in bytes.
+    pub free_space: u64
+}
+
+#[derive(Debug)]
+pub enum GameStorageWindowInput {
+    SetGame {
+        game_name: String,
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
+    /// Open folder chooser dialog to move the game.
+    Move,
+
+    SetInfo {
+        task: u64,
+        result: Result<Option<GameStorageInfo>, String>
+    },
+
+    SetProgress {
+        task: u64,
+        stage: RelocateStage,
+        current: u64,
+        total: u64
+    },
+
+    MoveFinished {
+        task: u64,
+        result: Result<(), String>
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GameStorageWindowOutput {
+    ReloadGameInfo {
+        launch_info: bool,
+        actions_pipeline: bool,
+        components_layout: bool,
+        tools_layout: bool,
+        settings_layout: bool
+    }
+}
+
+#[derive(Debug)]
+pub struct GameStorageWindow {
+    window: adw::PreferencesDialog,
+
+    game_name: Option<String>,
+    integration: Option<Arc<GameIntegration>>,
+    variant: Option<GameVariant>,
+
+    info: Option<GameStorageInfo>,
+
+    loading: bool,
+
+    /// Current relocation stage and its progress.
+    progress: Option<(RelocateStage, u64, u64)>,
+
+    /// Identifier of the current background task. Messages of the previous
+    /// tasks are ignored.
+    task: u64
+}
+
+impl GameStorageWindow {
+    fn is_busy(&self) -> bool {
+        self.loading || self.progress.is_some()
+    }
+
+    fn progress_title(&self) -> String {
+        match self.progress {
+            Some((RelocateStage::Copy, _, _)) => i18n!("game_storage_copying")
+                .unwrap_or("Copying game files")
+                .to_string(),
+
+            Some((RelocateStage::Verify, _, _)) => i18n!("game_storage_verifying")
+                .unwrap_or("Verifying copied files")
+                .to_string(),
+
+            Some((RelocateStage::Delete, _, _)) => i18n!("game_storage_deleting")
+                .unwrap_or("Deleting old game files")
+                .to_string(),
+
+            None => String::new()
+        }
+    }
+
+    /// Request game folder info in background.
+    fn update_info(&mut self, sender: &AsyncComponentSender<Self>) {
+        let (Some(integration), Some(variant)) = (self.integration.clone(), self.variant.clone()) else {
+            return;
+        };
+
+        self.task += 1;
+        self.loading = true;
+
+        let task = self.task;
+        let sender = sender.clone();
+
+        tasks::spawn_blocking(move || {
+            let result = integration.get_storage_path(&variant)
+                .map_err(|err| anyhow::anyhow!(err.to_string()))
+                .and_then(|path| {
+                    let Some(path) = path.filter(|path| path.is_dir()) else {
+                        return Ok(None);
+                    };
+
+                    Ok(Some(GameStorageInfo {
+                        disk_usage: disk::disk_usage(&path)?,
+                        free_space: disk::free_space(&path)?,
+                        path
+                    }))
+                });
+
+            sender.input(GameStorageWindowInput::SetInfo {
+                task,
+                result: result.map_err(|err| err.to_string())
+            });
+        });
+    }
+}
+
+#[relm4::component(pub, async)]
+impl SimpleAsyncComponent for GameStorageWindow {
+    type Init = ();
+    type Input = GameStorageWindowInput;
+    type Output = GameStorageWindowOutput;
+
+    view! {
+        #[root]
+        adw::PreferencesDialog {
+            set_title: i18n!("game_storage_title")
+                .unwrap_or("Game storage"),
+
+            set_content_width: 700,
+            set_content_height: 400,
+
+            add_css_class?: consts::APP_DEBUG.then_some("devel"),
+
+            add = &adw::PreferencesPage {
+                set_description: i18n!("game_storage_description")
+                    .unwrap_or(""),
+
+                add = &adw::PreferencesGroup {
+                    #[wrap(Some)]
+                    set_header_suffix = &gtk::Button {
+                        add_css_class: "pill",
+
+                        #[watch]
+                        set_sensitive: !model.is_busy() && model.info.is_some(),
+
+                        set_label: i18n!("game_storage_move")
+                            .unwrap_or("Move"),
+
+                        connect_clicked => GameStorageWindowInput::Move
+                    },
+
+                    adw::ActionRow {
+                        set_title: i18n!("game_storage_location")
+                            .unwrap_or("Location"),
+
+                        add_css_class: "property",
+                        set_subtitle_selectable: true,
+
+                        #[watch]
+                        set_subtitle: &match &model.info {
+                            Some(info) => info.path.to_string_lossy().to_string(),
+
+                            None if model.loading => String::new(),
+
+                            None => i18n!("game_storage_not_installed")
+                                .unwrap_or("Game is not installed")
+                                .to_string()
+                        },
+
+                        add_suffix = &adw::Spinner {
+                            #[watch]
+                            set_visible: model.loading
+                        }
+                    },
+
+                    adw::ActionRow {
+                        set_title: i18n!("game_storage_disk_usage")
+                            .unwrap_or("Disk usage"),
+
+                        add_css_class: "property",
+
+                        #[watch]
+                        set_visible: model.info.is_some(),
+
+                        #[watch]
+                        set_subtitle: &model.info.as_ref()
+                            .map(|info| utils::pretty_bytes(info.disk_usage))
+                            .unwrap_or_default()
+                    },
+
+                    adw::ActionRow {
+                        set_title: i18n!("game_storage_free_space")
+                            .unwrap_or("Free space"),
+
+                        add_css_class: "property",
+
+                        #[watch]
+                        set_visible: model.info.is_some(),
+
+                        #[watch]
+                        set_subtitle: &model.info.as_ref()
+                            .map(|info| utils::pretty_bytes(info.free_space))
+                            .unwrap_or_default()
+                    },
+
+                    adw::ActionRow {
+                        #[watch]
+                        set_visible: model.progress.is_some(),
+
+                        #[watch]
+                        set_title: &model.progress_title(),
+
+                        add_suffix = &gtk::ProgressBar {
+                            set_valign: gtk::Align::Center,
+                            set_show_text: true,
+
+                            #[watch]
+                            set_text: model.progress
+                                .map(|(_, current, total)| {
+                                    format!("{} / {}", utils::pretty_bytes(current), utils::pretty_bytes(total))
+                                })
+                                .as_deref(),
+
+                            #[watch]
+                            set_fraction: match model.progress {
+                                Some((_, _, 0)) | None => 0.0,
+                                Some((_, current, total)) => current as f64 / total as f64
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: AsyncComponentSender<Self>
+    ) -> AsyncComponentParts<Self> {
+        let model = Self {
+            window: root.clone(),
+
+            game_name: None,
+            integration: None,
+            variant: None,
+
+            info: None,
+
+            loading: false,
+            progress: None,
+
+            task: 0
+        };
+
+        let widgets = view_output!();
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update(
+        &mut self,
+        msg: Self::Input,
+        sender: AsyncComponentSender<Self>
+    ) {
+        match msg {
+            GameStorageWindowInput::SetGame {
+                game_name,
+                integration,
+                variant
+            } => {
+                self.game_name = Some(game_name);
+                self.integration = Some(integration);
+                self.variant = Some(variant);
+
+                self.info = None;
+                self.progress = None;
+
+                self.update_info(&sender);
+            }
+
+            GameStorageWindowInput::Move => {
+                let (Some(game_name), Some(integration), Some(variant), Some(info)) = (
+                    self.game_name.clone(),
+                    self.integration.clone(),
+                    self.variant.clone(),
+                    self.info.clone()
+                ) else {
+                    return;
+                };
+
+                // Deployed mods mean that the game is running.
+                let deployed = mods::list_storages(&game_name)
+                    .map(|storages| storages.iter().any(|storage| storage.is_deployed()))
+                    .unwrap_or(false);
+
+                if deployed {
+                    dialogs::error(
+                        i18n!("failed_move_game")
+                            .unwrap_or("Failed to move game"),
+                        i18n!("game_storage_game_running")
+                            .unwrap_or("Game can't be moved while it's running")
+                    );
+
+                    return;
+                }
+
+                let dialog = gtk::FileDialog::new();
+
+                let parent = self.window.root()
+                    .and_then(|root| root.downcast::<gtk::Window>().ok());
+
+                // Dialog was closed without choosing anything.
+                let Some(folder) = dialog.select_folder_future(parent.as_ref()).await.ok()
+                    .and_then(|folder| folder.path())
+                else {
+                    return;
+                };
+
+                // Keep the game folder name in the chosen location.
+                let target = match info.path.file_name() {
+                    Some(name) => folder.join(name),
+                    None => folder
+                };
+
+                self.task += 1;
+                self.progress = Some((RelocateStage::Copy, 0, info.disk_usage));
+
+                let task = self.task;
+
+                tasks::spawn_blocking(move || {
+                    let progress = {
+                        let sender = sender.clone();
+
+                        move |stage: RelocateStage, current: u64, total: u64| {
+                            sender.input(GameStorageWindowInput::SetProgress {
+                                task,
+                                stage,
+                                current,
+                                total
+                            });
+                        }
+                    };
+
+                    let result = disk::relocate(&info.path, &target, progress, || {
+                        integration.set_storage_path(&variant, &target)
+                            .map_err(|err| anyhow::anyhow!(err.to_string()))
+                    });
+
+                    sender.input(GameStorageWindowInput::MoveFinished {
+                        task,
+                        result: result.map_err(|err| err.to_string())
+                    });
+                });
+            }
+
+            GameStorageWindowInput::SetInfo { task, result } => {
+                if task != self.task {
+                    return;
+                }
+
+                self.loading = false;
+
+                match result {
+                    Ok(info) => self.info = info,
+
+                    Err(err) => {
+                        self.info = None;
+
+                        tracing::error!(?err, "failed to request game storage info");
+
+                        dialogs::error(
+                            i18n!("failed_request_game_storage")
+                                .unwrap_or("Failed to request game storage info"),
+                            err
+                        );
+                    }
+                }
+            }
+
+            GameStorageWindowInput::SetProgress { task, stage, current, total } => {
+                if task == self.task {
+                    self.progress = Some((stage, current, total));
+                }
+            }
+
+            GameStorageWindowInput::MoveFinished { task, result } => {
+                if task != self.task {
+                    return;
+                }
+
+                self.progress = None;
+
+                if let Err(err) = result {
+                    tracing::error!(?err, "failed to move game");
+
+                    dialogs::error(
+                        i18n!("failed_move_game")
+                            .unwrap_or("Failed to move game"),
+                        err
+                    );
+                }
+
+                // Launch info and actions depend on the game folder.
+                let _ = sender.output(GameStorageWindowOutput::ReloadGameInfo {
+                    launch_info: true,
+                    actions_pipeline: true,
+                    components_layout: true,
+                    tools_layout: true,
+                    settings_layout: true
+                });
+
+                self.update_info(&sender);
+            }
+        }
+    }
+}
@@ -0,0 +1,491 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use agl_core::tasks;
+use relm4::prelude::*;
+use adw::prelude::*;
+
+use agl_games::api::{GameIntegration, GameVariant, ProgressReport};
+
+use crate::{consts, config, utils, i18n};
+use crate::verify::{self, VerifyResult};
+use crate::ui::dialogs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum VerifyStatus {
+    Idle,
+    Verifying,
+    Repairing,
+
+    /// Verify manifest is not provided by the game integration.
+    NotInstalled,
+
+    Finished(VerifyResult)
+}
+
+#[derive(Debug)]
+pub enum GameVerifyWindowInput {
+    SetGame {
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
+    Verify,
+    Repair,
+
+    SetProgress {
+        task: u64,
+        text: Option<String>,
+        fraction: f64
+    },
+
+    VerifyFinished {
+        task: u64,
+        result: Result<Option<VerifyResult>, String>
+    },
+
+    RepairFinished {
+        task: u64,
+        result: Result<(), String>
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GameVerifyWindowOutput {
+    ReloadGameInfo {
+        launch_info: bool,
+        actions_pipeline: bool,
+        components_layout: bool,
+        tools_layout: bool,
+        settings_layout: bool
+    }
+}
+
+#[derive(Debug)]
+pub struct GameVerifyWindow {
+    results_group: adw::PreferencesGroup,
+    results_rows: Vec<adw::ExpanderRow>,
+
+    integration: Option<Arc<GameIntegration>>,
+    variant: Option<GameVariant>,
+
+    status: VerifyStatus,
+
+    progress_text: Option<String>,
+    progress_fraction: f64,
+
+    /// Identifier of the current verification or repair task. Messages of
+    /// the previous tasks are ignored.
+    task: u64
+}
+
+impl GameVerifyWindow {
+    fn is_busy(&self) -> bool {
+        matches!(self.status, VerifyStatus::Verifying | VerifyStatus::Repairing)
+    }
+
+    fn can_repair(&self) -> bool {
+        let VerifyStatus::Finished(result) = &self.status else {
+            return false;
+        };
+
+        !result.is_valid() && self.integration.as_ref()
+            .is_some_and(|integration| integration.can_repair())
+    }
+
+    fn status_title(&self) -> String {
+        match &self.status {
+            VerifyStatus::Idle => String::new(),
+
+            VerifyStatus::Verifying => i18n!("game_verify_verifying")
+                .unwrap_or("Verifying game files")
+                .to_string(),
+
+            VerifyStatus::Repairing => i18n!("game_verify_repairing")
+                .unwrap_or("Repairing game files")
+                .to_string(),
+
+            VerifyStatus::NotInstalled => i18n!("game_verify_not_installed")
+                .unwrap_or("Game is not installed")
+                .to_string(),
+
+            VerifyStatus::Finished(result) if result.is_valid() => {
+                i18n!("game_verify_valid")
+                    .unwrap_or("All game files are valid")
+                    .to_string()
+            }
+
+            VerifyStatus::Finished(result) => {
+                let broken = result.missing.len() + result.corrupted.len();
+
+                i18n!("game_verify_broken", { files => broken })
+                    .unwrap_or_else(|| format!("{broken} game files are broken"))
+            }
+        }
+    }
+
+    /// Start verification task in background.
+    fn verify(&mut self, sender: &AsyncComponentSender<Self>) {
+        let (Some(integration), Some(variant)) = (self.integration.clone(), self.variant.clone()) else {
+            return;
+        };
+
+        self.task += 1;
+
+        self.status = VerifyStatus::Verifying;
+        self.progress_text = None;
+        self.progress_fraction = 0.0;
+
+        let task = self.task;
+        let sender = sender.clone();
+
+        tasks::spawn_blocking(move || {
+            let result = integration.get_verify_manifest(&variant)
+                .map_err(|err| anyhow::anyhow!(err.to_string()))
+                .and_then(|manifest| {
+                    let Some(manifest) = manifest else {
+                        return Ok(None);
+                    };
+
+                    let progress = {
+                        let sender = sender.clone();
+
+                        move |checked: u64, total: u64| {
+                            sender.input(GameVerifyWindowInput::SetProgress {
+                                task,
+                                text: Some(format!(
+                                    "{} / {}",
+                                    utils::pretty_bytes(checked),
+                                    utils::pretty_bytes(total)
+                                )),
+                                fraction: if total == 0 {
+                                    1.0
+                                } else {
+                                    checked as f64 / total as f64
+                                }
+                            });
+                        }
+                    };
+
+                    verify::verify_files(&manifest, progress).map(Some)
+                });
+
+            sender.input(GameVerifyWindowInput::VerifyFinished {
+                task,
+                result: result.map_err(|err| err.to_string())
+            });
+        });
+    }
+
+    /// Re-render verification results.
+    fn render_results(&mut self) {
+        for row in self.results_rows.drain(..) {
+            self.results_group.remove(&row);
+        }
+
+        let VerifyStatus::Finished(result) = &self.status else {
+            return;
+        };
+
+        for (files, title, icon) in [
+            (&result.missing, i18n!("game_verify_missing").unwrap_or("Missing files"), "dialog-error-symbolic"),
+            (&result.corrupted, i18n!("game_verify_corrupted").unwrap_or("Corrupted files"), "dialog-error-symbolic"),
+            (&result.extra, i18n!("game_verify_extra").unwrap_or("Extra files"), "dialog-information-symbolic")
+        ] {
+            if files.is_empty() {
+                continue;
+            }
+
+            let row = adw::ExpanderRow::new();
+
+            row.set_title(title);
+            row.set_subtitle(&files.len().to_string());
+            row.add_prefix(&gtk::Image::from_icon_name(icon));
+
+            for path in files {
+                let file_row = adw::ActionRow::new();
+
+                file_row.set_title(&path.to_string_lossy());
+                file_row.set_title_selectable(true);
+
+                row.add_row(&file_row);
+            }
+
+            self.results_group.add(&row);
+            self.results_rows.push(row);
+        }
+    }
+}
+
+#[relm4::component(pub, async)]
+impl SimpleAsyncComponent for GameVerifyWindow {
+    type Init = ();
+    type Input = GameVerifyWindowInput;
+    type Output = GameVerifyWindowOutput;
+
+    view! {
+        #[root]
+        adw::PreferencesDialog {
+            set_title: i18n!("game_verify_title")
+                .unwrap_or("Verify game files"),
+
+            set_content_width: 700,
+            set_content_height: 500,
+
+            add_css_class?: consts::APP_DEBUG.then_some("devel"),
+
+            add = &adw::PreferencesPage {
+                set_description: i18n!("game_verify_description")
+                    .unwrap_or(""),
+
+                add = &adw::PreferencesGroup {
+                    #[wrap(Some)]
+                    set_header_suffix = &gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_spacing: 8,
+
+                        gtk::Button {
+                            add_css_class: "pill",
+
+                            #[watch]
+                            set_sensitive: !model.is_busy() && model.integration.is_some(),
+
+                            set_label: i18n!("game_verify_start")
+                                .unwrap_or("Verify"),
+
+                            connect_clicked => GameVerifyWindowInput::Verify
+                        },
+
+                        gtk::Button {
+                            set_css_classes: &["pill", "suggested-action"],
+
+                            #[watch]
+                            set_visible: model.can_repair(),
+
+                            set_label: i18n!("game_verify_repair")
+                                .unwrap_or("Repair"),
+
+                            connect_clicked => GameVerifyWindowInput::Repair
+                        }
+                    },
+
+                    adw::ActionRow {
+                        #[watch]
+                        set_title: &model.status_title(),
+
+                        add_suffix = &adw::Spinner {
+                            #[watch]
+                            set_visible: model.is_busy() && model.progress_fraction == 0.0
+                        },
+
+                        add_suffix = &gtk::ProgressBar {
+                            set_valign: gtk::Align::Center,
+                            set_show_text: true,
+
+                            #[watch]
+                            set_visible: model.is_busy() && model.progress_fraction > 0.0,
+
+                            #[watch]
+                            set_text: model.progress_text.as_deref(),
+
+                            #[watch]
+                            set_fraction: model.progress_fraction
+                        }
+                    }
+                },
+
+                #[local_ref]
+                add = results_group -> adw::PreferencesGroup,
+            }
+        }
+    }
+
+    async fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: AsyncComponentSender<Self>
+    ) -> AsyncComponentParts<Self> {
+        let model = Self {
+            results_group: adw::PreferencesGroup::new(),
+            results_rows: Vec::new(),
+
+            integration: None,
+            variant: None,
+
+            status: VerifyStatus::Idle,
+
+            progress_text: None,
+            progress_fraction: 0.0,
+
+            task: 0
+        };
+
+        let results_group = &model.results_group;
+
+        let widgets = view_output!();
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update(
+        &mut self,
+        msg: Self::Input,
+        sender: AsyncComponentSender<Self>
+    ) {
+        match msg {
+            GameVerifyWindowInput::SetGame { integration, variant } => {
+                self.integration = Some(integration);
+                self.variant = Some(variant);
+
+                self.verify(&sender);
+                self.render_results();
+            }
+
+            GameVerifyWindowInput::Verify => {
+                if !self.is_busy() {
+                    self.verify(&sender);
+                    self.render_results();
+                }
+            }
+
+            GameVerifyWindowInput::Repair => {
+                let VerifyStatus::Finished(result) = &self.status else {
+                    return;
+                };
+
+                let (Some(integration), Some(variant)) = (self.integration.clone(), self.variant.clone()) else {
+                    return;
+                };
+
+                let files = result.broken();
+
+                let lang = config::get().await
+                    .language().ok();
+
+                self.task += 1;
+
+                self.status = VerifyStatus::Repairing;
+                self.progress_text = None;
+                self.progress_fraction = 0.0;
+
+                self.render_results();
+
+                let task = self.task;
+
+                tasks::spawn_blocking(move || {
+                    let updater = {
+                        let sender = sender.clone();
+
+                        move |progress: ProgressReport| {
+                            let fraction = progress.fraction();
+
+                            let text = progress.format().ok()
+                                .flatten()
+                                .map(|text| {
+                                    let text = match &lang {
+                                        Some(lang) => text.translate(lang),
+                                        None => text.default_translation()
+                                    };
+
+                                    text.to_string()
+                                })
+                                .unwrap_or_else(|| {
+                                    format!("{:.2}%", fraction * 100.0)
+                                });
+
+                            sender.input(GameVerifyWindowInput::SetProgress {
+                                task,
+                                text: Some(text),
+                                fraction
+                            });
+                        }
+                    };
+
+                    let result = integration.repair_files(&variant, &files, updater);
+
+                    sender.input(GameVerifyWindowInput::RepairFinished {
+                        task,
+                        result: result.map_err(|err| err.to_string())
+                    });
+                });
+            }
+
+            GameVerifyWindowInput::SetProgress { task, text, fraction } => {
+                if task == self.task {
+                    self.progress_text = text;
+                    self.progress_fraction = fraction;
+                }
+            }
+
+            GameVerifyWindowInput::VerifyFinished { task, result } => {
+                if task != self.task {
+                    return;
+                }
+
+                match result {
+                    Ok(Some(result)) => self.status = VerifyStatus::Finished(result),
+                    Ok(None) => self.status = VerifyStatus::NotInstalled,
+
+                    Err(err) => {
+                        self.status = VerifyStatus::Idle;
+
+                        tracing::error!(?err, "failed to verify game files");
+
+                        dialogs::error(
+                            i18n!("failed_verify_game_files")
+                                .unwrap_or("Failed to verify game files"),
+                            err
+                        );
+                    }
+                }
+
+                self.render_results();
+            }
+
+            GameVerifyWindowInput::RepairFinished { task, result } => {
+                if task != self.task {
+                    return;
+                }
+
+                if let Err(err) = result {
+                    tracing::error!(?err, "failed to repair game files");
+
+                    dialogs::error(
+                        i18n!("failed_repair_game_files")
+                            .unwrap_or("Failed to repair game files"),
+                        err
+                    );
+                }
+
+                // Repaired files can change game state (e.g. its version).
+                let _ = sender.output(GameVerifyWindowOutput::ReloadGameInfo {
+                    launch_info: true,
+                    actions_pipeline: true,
+                    components_layout: false,
+                    tools_layout: true,
+                    settings_layout: false
+                });
+
+                self.verify(&sender);
+                self.render_results();
+            }
+        }
+    }
+}
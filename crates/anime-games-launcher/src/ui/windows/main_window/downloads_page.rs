@@ -0,0 +1,1760 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Instant, Duration};
+
+use adw::prelude::*;
+use relm4::prelude::*;
+
+use agl_core::tasks;
+use agl_core::export::tasks::tokio;
+use agl_locale::string::LocalizableString;
+use agl_locale::unic_langid::LanguageIdentifier;
+use agl_runtime::api::task_api::CancellationToken;
+use agl_runtime::api::downloader_api::{DownloaderTasks, DownloaderTaskInfo};
+use agl_runtime::api::torrent_api::{TorrentServer, TorrentListInfo};
+use agl_runtime::api::portal_api::NotificationOptions;
+use agl_games::api::{
+    ActionsPipeline, GameIntegration, GameVariant, ProgressReport
+};
+
+use crate::{consts, config, downloads, disk, utils, i18n};
+use crate::config::{Config, GamesUpdatesNetwork};
+use crate::downloads::{Download, DownloadKind, ApplyComponentInfo, QueueEntry};
+use crate::ui::dialogs;
+use crate::ui::components::graph_progress_group::{
+    GraphProgressGroup, GraphProgressGroupInit, GraphProgressGroupMsg
+};
+use crate::ui::components::download_row::{
+    DownloadRow, DownloadRowInit, DownloadRowMsg, DownloadRowOutput
+};
+
+/// Interval between torrents and downloader tasks lists updates.
+const TRANSFERS_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval between checks whether games should be checked for updates.
+const UPDATES_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadResult {
+    /// All the download's work is done.
+    Finished,
+
+    /// Download was paused by the user. Pipeline actions after the given
+    /// amount of performed ones are left.
+    Paused {
+        performed_actions: usize
+    },
+
+    /// Download has failed. Pipeline actions after the given amount of
+    /// performed ones are left.
+    Failed {
+        performed_actions: usize
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LoadedGame {
+    pub title: String,
+    pub integration: Arc<GameIntegration>
+}
+
+#[derive(Debug)]
+struct QueuedDownload {
+    pub download: Download,
+    pub row_index: DynamicIndex,
+
+    /// Actions pipeline of the download. Requested from the game integration
+    /// when the download is started if not available.
+    pub actions_pipeline: Option<Arc<ActionsPipeline>>,
+
+    /// Whether the last download attempt has failed.
+    pub failed: bool
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum QueueItem {
+    /// Download performed by the launcher itself.
+    Download(QueuedDownload),
+
+    /// Torrent of the packages runtime's torrent server. Information is
+    /// missing until the torrents list is received from the server.
+    Torrent {
+        info_hash: String,
+        info: Option<TorrentListInfo>
+    },
+
+    /// File downloaded by a module with the downloader API.
+    DownloaderTask(DownloaderTaskInfo)
+}
+
+impl QueueItem {
+    #[inline]
+    fn download(&self) -> Option<&QueuedDownload> {
+        match self {
+            Self::Download(queued) => Some(queued),
+            _ => None
+        }
+    }
+
+    #[inline]
+    fn download_mut(&mut self) -> Option<&mut QueuedDownload> {
+        match self {
+            Self::Download(queued) => Some(queued),
+            _ => None
+        }
+    }
+
+    #[inline]
+    fn is_download(&self, id: u64) -> bool {
+        self.download().is_some_and(|queued| queued.download.id == id)
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum DownloadsPageInput {
+    /// Register loaded game integration so its downloads could be performed.
+    AddGame {
+        name: String,
+        title: String,
+        integration: Arc<GameIntegration>
+    },
+
+    /// Remove queued downloads of the deleted game.
+    RemoveGame(String),
+
+    ScheduleActionsPipeline {
+        variant: GameVariant,
+        game_name: String,
+        game_title: String,
+        actions_pipeline: Arc<ActionsPipeline>
+    },
+
+    ScheduleApplyComponents {
+        game_integration: Arc<GameIntegration>,
+        game_variant: GameVariant,
+
+        game_name: String,
+        game_title: String,
+
+        install_components: Box<[ApplyComponentInfo]>,
+        uninstall_components: Box<[ApplyComponentInfo]>,
+
+        /// Delete game package after applying these components.
+        delete_game_package: bool
+    },
+
+    QueueRow(DownloadRowOutput),
+
+    /// Start the first queued download if there's no currently performed one.
+    StartNext,
+
+    ShowActionsPipeline {
+        id: u64,
+        actions_pipeline: Arc<ActionsPipeline>,
+        performed_actions: usize
+    },
+
+    MarkStarted {
+        name: String
+    },
+
+    SetProgress {
+        name: String,
+        text: Option<String>,
+        fraction: f64
+    },
+
+    MarkFinished {
+        name: String
+    },
+
+    ComponentApplied {
+        id: u64,
+        name: String
+    },
+
+    DownloadStopped {
+        id: u64,
+        result: DownloadResult
+    },
+
+    UpdateTorrents(Box<[TorrentListInfo]>),
+    UpdateDownloaderTasks(Box<[DownloaderTaskInfo]>),
+
+    /// Check installed games for updates if it's time to do so.
+    CheckUpdates,
+
+    UpdateAvailable {
+        game_name: String,
+        variant: GameVariant,
+        actions_pipeline: Arc<ActionsPipeline>
+    },
+
+    UpdateNotAvailable {
+        game_name: String
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DownloadsPageOutput {
+    UpdateGameInfo(String),
+    DeleteGamePackage(String),
+    ShowNotification(NotificationOptions),
+
+    /// Actions pipeline download was scheduled by the user.
+    ActionsPipelineScheduled {
+        id: u64,
+        game_title: String
+    },
+
+    /// Components changes download was scheduled by the user.
+    ApplyComponentsScheduled {
+        id: u64,
+        game_title: String
+    },
+
+    /// Progress of the currently performed download.
+    DownloadProgress {
+        id: u64,
+        msg: GraphProgressGroupMsg
+    },
+
+    /// Download was finished, paused or has failed.
+    DownloadStopped(u64)
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadsPageInit {
+    /// Path to the downloads queue file.
+    pub queue_path: PathBuf,
+
+    /// Files downloaded by the packages runtime's downloader API which should
+    /// be displayed in the queue.
+    pub downloader_tasks: DownloaderTasks,
+
+    /// Torrent server whose torrents should be displayed in the queue.
+    pub torrent_server: Option<TorrentServer>
+}
+
+/// Launcher-wide downloads queue. Performs games actions pipelines and
+/// components changes one by one in the queue order. Torrents of the packages
+/// runtime's torrent server and files downloaded by its downloader API are
+/// listed in the same queue and can be paused, removed and reordered, but they
+/// are downloaded in parallel with other downloads.
+#[derive(Debug)]
+pub struct DownloadsPage {
+    graph_group: AsyncController<GraphProgressGroup>,
+    queue_rows: AsyncFactoryVecDeque<DownloadRow>,
+
+    queue: Vec<QueueItem>,
+
+    /// Table of loaded games where the key is the game name.
+    games: HashMap<String, LoadedGame>,
+
+    /// Identifier of the currently performed download and a token which is
+    /// cancelled to pause it.
+    current: Option<(u64, CancellationToken)>,
+
+    next_id: u64,
+
+    /// Time of the last games updates check.
+    last_updates_check: Option<Instant>,
+
+    /// Names of games with pending updates which need user interaction.
+    pending_updates: HashSet<String>,
+
+    /// Identifiers of downloads started by the background games updates.
+    unattended: HashSet<u64>,
+
+    queue_path: PathBuf,
+    downloader_tasks: DownloaderTasks,
+    torrent_server: Option<TorrentServer>
+}
+
+#[relm4::component(pub, async)]
+impl SimpleAsyncComponent for DownloadsPage {
+    type Init = DownloadsPageInit;
+    type Input = DownloadsPageInput;
+    type Output = DownloadsPageOutput;
+
+    view! {
+        #[root]
+        gtk::Box {
+            set_vexpand: true,
+            set_hexpand: true,
+
+            set_orientation: gtk::Orientation::Vertical,
+
+            model.graph_group.widget(),
+        }
+    }
+
+    async fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: AsyncComponentSender<Self>
+    ) -> AsyncComponentParts<Self> {
+        let mut model = Self {
+            graph_group: GraphProgressGroup::builder()
+                .launch(GraphProgressGroupInit {
+                    title: i18n!("downloads_idle")
+                        .map(String::from),
+                    description: None
+                })
+                .detach(),
+
+            queue_rows: AsyncFactoryVecDeque::builder()
+                .launch_default()
+                .forward(sender.input_sender(), DownloadsPageInput::QueueRow),
+
+            queue: Vec::new(),
+
+            games: HashMap::new(),
+
+            current: None,
+
+            next_id: 0,
+
+            last_updates_check: None,
+            pending_updates: HashSet::new(),
+            unattended: HashSet::new(),
+
+            queue_path: init.queue_path,
+            downloader_tasks: init.downloader_tasks,
+            torrent_server: init.torrent_server
+        };
+
+        model.queue_rows.widget().set_title(
+            i18n!("downloads_queue").unwrap_or("Queue")
+        );
+
+        model.graph_group.widget().add(model.queue_rows.widget());
+
+        // Restore downloads queue from the previous launcher session.
+        match downloads::load(&model.queue_path) {
+            Ok(queue) => {
+                for entry in queue {
+                    match entry {
+                        QueueEntry::Download(download) => {
+                            model.next_id = model.next_id.max(download.id + 1);
+
+                            model.push_download(download);
+                        }
+
+                        // Torrents are removed from the queue when the
+                        // server doesn't report them.
+                        QueueEntry::Torrent { info_hash } => {
+                            if model.torrent_server.is_some() {
+                                model.push_torrent(info_hash, None);
+                            }
+                        }
+                    }
+                }
+            }
+
+            Err(err) => {
+                tracing::error!(?err, path = ?model.queue_path, "failed to load downloads queue");
+
+                dialogs::error(
+                    i18n!("failed_load_downloads_queue")
+                        .unwrap_or("Failed to load downloads queue"),
+                    err.to_string()
+                );
+            }
+        }
+
+        model.update_description();
+
+        // Periodically update torrents and downloader tasks lists.
+        {
+            let sender = sender.clone();
+            let downloader_tasks = model.downloader_tasks.clone();
+            let mut torrent_server = model.torrent_server.clone();
+
+            tasks::spawn_blocking(move || {
+                loop {
+                    if let Some(server) = &torrent_server {
+                        match server.list() {
+                            Ok(torrents) => sender.input(DownloadsPageInput::UpdateTorrents(torrents)),
+
+                            // Server is stopped.
+                            Err(err) => {
+                                tracing::warn!(?err, "failed to list torrents");
+
+                                torrent_server = None;
+
+                                sender.input(DownloadsPageInput::UpdateTorrents(Box::new([])));
+                            }
+                        }
+                    }
+
+                    sender.input(DownloadsPageInput::UpdateDownloaderTasks(downloader_tasks.list()));
+
+                    std::thread::sleep(TRANSFERS_UPDATE_INTERVAL);
+                }
+            });
+        }
+
+        // Periodically check games for updates.
+        tasks::spawn(async move {
+            loop {
+                tokio::time::sleep(UPDATES_CHECK_INTERVAL).await;
+
+                sender.input(DownloadsPageInput::CheckUpdates);
+            }
+        });
+
+        let widgets = view_output!();
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update(
+        &mut self,
+        msg: Self::Input,
+        sender: AsyncComponentSender<Self>
+    ) {
+        match msg {
+            DownloadsPageInput::AddGame { name, title, integration } => {
+                self.games.insert(name, LoadedGame {
+                    title,
+                    integration
+                });
+
+                self.update_statuses();
+
+                sender.input(DownloadsPageInput::StartNext);
+            }
+
+            DownloadsPageInput::RemoveGame(name) => {
+                self.games.remove(&name);
+                self.pending_updates.remove(&name);
+
+                let current = self.current.as_ref()
+                    .map(|(id, _)| *id);
+
+                let mut i = 0;
+
+                while i < self.queue.len() {
+                    let remove = self.queue[i].download().is_some_and(|queued| {
+                        queued.download.game_name == name
+                            && Some(queued.download.id) != current
+                    });
+
+                    if remove {
+                        self.queue.remove(i);
+                        self.queue_rows.guard().remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+
+                self.update_description();
+                self.save_queue();
+            }
+
+            DownloadsPageInput::ScheduleActionsPipeline {
+                variant,
+                game_name,
+                game_title,
+                actions_pipeline
+            } => {
+                let id = self.schedule_actions_pipeline(
+                    variant,
+                    game_name,
+                    game_title.clone(),
+                    actions_pipeline
+                ).await;
+
+                let _ = sender.output(DownloadsPageOutput::ActionsPipelineScheduled {
+                    id,
+                    game_title
+                });
+
+                sender.input(DownloadsPageInput::StartNext);
+            }
+
+            DownloadsPageInput::ScheduleApplyComponents {
+                game_integration,
+                game_variant,
+                game_name,
+                game_title,
+                install_components,
+                uninstall_components,
+                delete_game_package
+            } => {
+                let title = if delete_game_package {
+                    i18n!("game_components_uninstall_all_title")
+                        .map(String::from)
+                        .unwrap_or_else(|| String::from("Uninstall game components"))
+                } else {
+                    i18n!("game_components_apply_changes_title")
+                        .map(String::from)
+                        .unwrap_or_else(|| String::from("Apply components changes"))
+                };
+
+                self.games.insert(game_name.clone(), LoadedGame {
+                    title: game_title.clone(),
+                    integration: game_integration
+                });
+
+                let id = self.push_download(Download {
+                    id: self.next_id,
+                    game_name,
+                    game_title: game_title.clone(),
+                    title,
+                    variant: game_variant,
+                    kind: DownloadKind::ApplyComponents {
+                        install_components: install_components.into_vec(),
+                        uninstall_components: uninstall_components.into_vec(),
+                        delete_game_package
+                    },
+                    performed_actions: Vec::new(),
+                    paused: false
+                });
+
+                self.next_id += 1;
+
+                self.update_description();
+                self.save_queue();
+
+                let _ = sender.output(DownloadsPageOutput::ApplyComponentsScheduled {
+                    id,
+                    game_title
+                });
+
+                sender.input(DownloadsPageInput::StartNext);
+            }
+
+            DownloadsPageInput::QueueRow(DownloadRowOutput::PauseOrResume(index)) => {
+                let i = index.current_index();
+
+                let queued = match self.queue.get_mut(i) {
+                    Some(QueueItem::Download(queued)) => queued,
+
+                    Some(QueueItem::Torrent { info_hash, info }) => {
+                        if let Some(torrent_server) = self.torrent_server.clone() {
+                            let info_hash = info_hash.clone();
+                            let pause = !info.as_ref().is_some_and(|info| info.paused);
+
+                            tasks::spawn_blocking(move || {
+                                if let Err(err) = torrent_server.pause_or_resume(&info_hash, pause) {
+                                    tracing::error!(?err, ?info_hash, "failed to pause or resume torrent");
+
+                                    dialogs::error(
+                                        i18n!("failed_pause_or_resume_torrent")
+                                            .unwrap_or("Failed to pause or resume torrent"),
+                                        err.to_string()
+                                    );
+                                }
+                            });
+                        }
+
+                        return;
+                    }
+
+                    // Downloader tasks can't be paused.
+                    Some(QueueItem::DownloaderTask(_)) | None => return
+                };
+
+                queued.download.paused = !queued.download.paused;
+                queued.failed = false;
+
+                // Stop currently performed download. If it's resumed before
+                // being stopped, then it will be started again.
+                if queued.download.paused
+                    && let Some((id, cancel)) = &self.current
+                    && *id == queued.download.id
+                {
+                    cancel.cancel();
+                }
+
+                self.queue_rows.guard().send(i, DownloadRowMsg::SetPaused(
+                    queued.download.paused
+                ));
+
+                self.update_statuses();
+                self.save_queue();
+
+                sender.input(DownloadsPageInput::StartNext);
+            }
+
+            DownloadsPageInput::QueueRow(DownloadRowOutput::MoveUp(index)) => {
+                let i = index.current_index();
+
+                if i > 0 && i < self.queue.len() {
+                    self.queue.swap(i, i - 1);
+                    self.queue_rows.guard().swap(i, i - 1);
+
+                    self.save_queue();
+                }
+            }
+
+            DownloadsPageInput::QueueRow(DownloadRowOutput::MoveDown(index)) => {
+                let i = index.current_index();
+
+                if i + 1 < self.queue.len() {
+                    self.queue.swap(i, i + 1);
+                    self.queue_rows.guard().swap(i, i + 1);
+
+                    self.save_queue();
+                }
+            }
+
+            DownloadsPageInput::QueueRow(DownloadRowOutput::Remove(index)) => {
+                let i = index.current_index();
+
+                let queued = match self.queue.get(i) {
+                    Some(QueueItem::Download(queued)) => queued,
+
+                    // Torrents and downloader tasks are removed from the queue
+                    // when their lists are updated.
+                    Some(QueueItem::Torrent { info_hash, .. }) => {
+                        if let Some(torrent_server) = self.torrent_server.clone() {
+                            let info_hash = info_hash.clone();
+
+                            tasks::spawn_blocking(move || {
+                                if let Err(err) = torrent_server.delete(&info_hash, false) {
+                                    tracing::error!(?err, ?info_hash, "failed to delete torrent");
+
+                                    dialogs::error(
+                                        i18n!("failed_delete_torrent")
+                                            .unwrap_or("Failed to delete torrent"),
+                                        err.to_string()
+                                    );
+                                }
+                            });
+                        }
+
+                        return;
+                    }
+
+                    Some(QueueItem::DownloaderTask(task)) => {
+                        self.downloader_tasks.abort(task.handle);
+
+                        return;
+                    }
+
+                    None => return
+                };
+
+                // Currently performed download can't be removed.
+                if let Some((id, _)) = &self.current
+                    && *id == queued.download.id
+                {
+                    return;
+                }
+
+                self.queue.remove(i);
+                self.queue_rows.guard().remove(i);
+
+                self.update_description();
+                self.save_queue();
+            }
+
+            DownloadsPageInput::StartNext => {
+                if self.current.is_some() {
+                    return;
+                }
+
+                let Some(i) = self.queue.iter().position(|item| {
+                    item.download().is_some_and(|queued| {
+                        !queued.download.paused
+                            && self.games.contains_key(&queued.download.game_name)
+                    })
+                }) else {
+                    self.graph_group.emit(GraphProgressGroupMsg::ClearGraph);
+                    self.graph_group.emit(GraphProgressGroupMsg::ClearProgressRows);
+
+                    self.graph_group.emit(GraphProgressGroupMsg::SetTitle(
+                        i18n!("downloads_idle").map(String::from)
+                    ));
+
+                    self.graph_group.emit(GraphProgressGroupMsg::SetDescription(None));
+
+                    return;
+                };
+
+                let Some(queued) = self.queue[i].download() else {
+                    return;
+                };
+
+                let Some(integration) = self.games.get(&queued.download.game_name)
+                    .map(|game| game.integration.clone())
+                else {
+                    return;
+                };
+
+                let id = queued.download.id;
+                let variant = queued.download.variant.clone();
+                let cancel = CancellationToken::new();
+
+                self.current = Some((id, cancel.clone()));
+
+                self.queue_rows.guard().send(i, DownloadRowMsg::SetLocked(true));
+
+                self.emit_progress(&sender, GraphProgressGroupMsg::ClearGraph);
+                self.emit_progress(&sender, GraphProgressGroupMsg::ClearProgressRows);
+
+                self.emit_progress(&sender, GraphProgressGroupMsg::SetTitle(Some(queued.download.title.clone())));
+                self.emit_progress(&sender, GraphProgressGroupMsg::SetDescription(Some(queued.download.game_title.clone())));
+
+                let lang = config::get().await
+                    .language().ok();
+
+                match &queued.download.kind {
+                    DownloadKind::ActionsPipeline => {
+                        let actions_pipeline = queued.actions_pipeline.clone();
+                        let performed_actions = queued.download.performed_actions.clone();
+
+                        tasks::spawn_blocking(move || {
+                            let actions_pipeline = match actions_pipeline {
+                                Some(actions_pipeline) => actions_pipeline,
+
+                                None => match integration.get_actions_pipeline(&variant) {
+                                    Ok(Some(actions_pipeline)) => Arc::new(actions_pipeline),
+
+                                    // Game integration doesn't need to do
+                                    // anything anymore.
+                                    Ok(None) => {
+                                        sender.input(DownloadsPageInput::DownloadStopped {
+                                            id,
+                                            result: DownloadResult::Finished
+                                        });
+
+                                        return;
+                                    }
+
+                                    Err(err) => {
+                                        tracing::error!(?err, "failed to request game actions pipeline");
+
+                                        dialogs::error(
+                                            i18n!("failed_request_game_actions_pipeline")
+                                                .unwrap_or("Failed to request game actions pipeline"),
+                                            err.to_string()
+                                        );
+
+                                        sender.input(DownloadsPageInput::DownloadStopped {
+                                            id,
+                                            result: DownloadResult::Failed {
+                                                performed_actions: 0
+                                            }
+                                        });
+
+                                        return;
+                                    }
+                                }
+                            };
+
+                            // Requested pipeline can differ from the one which
+                            // was partially performed before, so only skip its
+                            // actions which are known to be performed.
+                            let performed_actions = downloads::count_performed_actions(
+                                actions_pipeline.actions(),
+                                &performed_actions
+                            );
+
+                            sender.input(DownloadsPageInput::ShowActionsPipeline {
+                                id,
+                                actions_pipeline: actions_pipeline.clone(),
+                                performed_actions
+                            });
+
+                            let result = perform_actions_pipeline(
+                                &sender,
+                                lang,
+                                &actions_pipeline,
+                                performed_actions,
+                                &cancel
+                            );
+
+                            sender.input(DownloadsPageInput::DownloadStopped {
+                                id,
+                                result
+                            });
+                        });
+                    }
+
+                    DownloadKind::ApplyComponents {
+                        install_components,
+                        uninstall_components,
+                        ..
+                    } => {
+                        let mut components = Vec::with_capacity(
+                            install_components.len() + uninstall_components.len()
+                        );
+
+                        for component in uninstall_components {
+                            let title = i18n!("game_component_uninstall_title", {
+                                component => component.title
+                            }).unwrap_or_else(|| {
+                                format!("Uninstall {}", component.title)
+                            });
+
+                            self.emit_progress(&sender, GraphProgressGroupMsg::AddProgressRow {
+                                name: component.name.clone(),
+                                title,
+                                description: None
+                            });
+
+                            components.push((component.name.clone(), false));
+                        }
+
+                        for component in install_components {
+                            let title = i18n!("game_component_install_title", {
+                                component => component.title
+                            }).unwrap_or_else(|| {
+                                format!("Install {}", component.title)
+                            });
+
+                            self.emit_progress(&sender, GraphProgressGroupMsg::AddProgressRow {
+                                name: component.name.clone(),
+                                title,
+                                description: None
+                            });
+
+                            components.push((component.name.clone(), true));
+                        }
+
+                        tasks::spawn_blocking(move || {
+                            let result = apply_components(
+                                &sender,
+                                lang,
+                                id,
+                                &integration,
+                                &variant,
+                                components,
+                                &cancel
+                            );
+
+                            sender.input(DownloadsPageInput::DownloadStopped {
+                                id,
+                                result
+                            });
+                        });
+                    }
+                }
+
+                self.update_statuses();
+            }
+
+            DownloadsPageInput::ShowActionsPipeline {
+                id,
+                actions_pipeline,
+                performed_actions
+            } => {
+                let lang = config::get().await
+                    .language().ok();
+
+                let title = match &lang {
+                    Some(lang) => actions_pipeline.title().translate(lang),
+                    None => actions_pipeline.title().default_translation()
+                };
+
+                self.emit_progress(&sender, GraphProgressGroupMsg::SetTitle(Some(title.to_string())));
+
+                for (i, action) in actions_pipeline.actions().iter().enumerate() {
+                    let name = i.to_string();
+
+                    let title = match &lang {
+                        Some(lang) => action.title().translate(lang),
+                        None => action.title().default_translation()
+                    };
+
+                    let description = action.description()
+                        .map(|description| {
+                            match &lang {
+                                Some(lang) => description.translate(lang),
+                                None => description.default_translation()
+                            }
+                        })
+                        .map(String::from);
+
+                    self.emit_progress(&sender, GraphProgressGroupMsg::AddProgressRow {
+                        name: name.clone(),
+                        title: title.to_string(),
+                        description
+                    });
+
+                    if i < performed_actions {
+                        self.emit_progress(&sender, GraphProgressGroupMsg::MarkFinished {
+                            name
+                        });
+                    }
+                }
+
+                if let Some(queued) = self.find_download_mut(id) {
+                    queued.actions_pipeline = Some(actions_pipeline);
+                }
+            }
+
+            DownloadsPageInput::MarkStarted { name } => {
+                self.emit_progress(&sender, GraphProgressGroupMsg::MarkStarted {
+                    name
+                });
+            }
+
+            DownloadsPageInput::SetProgress {
+                name,
+                text,
+                fraction
+            } => {
+                self.emit_progress(&sender, GraphProgressGroupMsg::SetProgress {
+                    name,
+                    text,
+                    fraction
+                });
+            }
+
+            DownloadsPageInput::MarkFinished { name } => {
+                self.emit_progress(&sender, GraphProgressGroupMsg::MarkFinished {
+                    name
+                });
+            }
+
+            DownloadsPageInput::ComponentApplied { id, name } => {
+                if let Some(queued) = self.find_download_mut(id)
+                    && let DownloadKind::ApplyComponents {
+                        install_components,
+                        uninstall_components,
+                        ..
+                    } = &mut queued.download.kind
+                {
+                    install_components.retain(|component| component.name != name);
+                    uninstall_components.retain(|component| component.name != name);
+
+                    self.save_queue();
+                }
+            }
+
+            DownloadsPageInput::DownloadStopped { id, result } => {
+                self.current = None;
+
+                let _ = sender.output(DownloadsPageOutput::DownloadStopped(id));
+
+                if let Some(i) = self.queue.iter().position(|item| item.is_download(id))
+                    && let QueueItem::Download(queued) = &mut self.queue[i]
+                {
+                    // Report results of the background games updates.
+                    if !matches!(result, DownloadResult::Paused { .. }) && self.unattended.remove(&id) {
+                        let game_title = &queued.download.game_title;
+
+                        let title = if result == DownloadResult::Finished {
+                            i18n!("game_updated", {
+                                game => game_title
+                            }).unwrap_or_else(|| format!("{game_title} was updated"))
+                        } else {
+                            i18n!("game_update_failed", {
+                                game => game_title
+                            }).unwrap_or_else(|| format!("Failed to update {game_title}"))
+                        };
+
+                        let _ = sender.output(DownloadsPageOutput::ShowNotification(NotificationOptions {
+                            title: LocalizableString::raw(title),
+                            message: None,
+                            icon: Some(consts::APP_ID.to_string())
+                        }));
+                    }
+
+                    match result {
+                        DownloadResult::Finished => {
+                            let _ = sender.output(DownloadsPageOutput::UpdateGameInfo(
+                                queued.download.game_name.clone()
+                            ));
+
+                            if let DownloadKind::ApplyComponents { delete_game_package: true, .. } = queued.download.kind {
+                                let _ = sender.output(DownloadsPageOutput::DeleteGamePackage(
+                                    queued.download.game_name.clone()
+                                ));
+                            }
+
+                            self.queue.remove(i);
+                            self.queue_rows.guard().remove(i);
+                        }
+
+                        DownloadResult::Paused { performed_actions } => {
+                            queued.set_performed_actions(performed_actions);
+
+                            self.queue_rows.guard().send(i, DownloadRowMsg::SetLocked(false));
+                        }
+
+                        DownloadResult::Failed { performed_actions } => {
+                            queued.set_performed_actions(performed_actions);
+                            queued.download.paused = true;
+                            queued.failed = true;
+
+                            let mut guard = self.queue_rows.guard();
+
+                            guard.send(i, DownloadRowMsg::SetLocked(false));
+                            guard.send(i, DownloadRowMsg::SetPaused(true));
+
+                            // Game state could have been changed by the
+                            // performed actions.
+                            let _ = sender.output(DownloadsPageOutput::UpdateGameInfo(
+                                queued.download.game_name.clone()
+                            ));
+                        }
+                    }
+                }
+
+                self.update_description();
+                self.update_statuses();
+                self.save_queue();
+
+                sender.input(DownloadsPageInput::StartNext);
+            }
+
+            DownloadsPageInput::UpdateTorrents(torrents) => {
+                let mut changed = false;
+
+                // Remove deleted torrents from the queue.
+                let mut i = 0;
+
+                while i < self.queue.len() {
+                    let deleted = match &self.queue[i] {
+                        QueueItem::Torrent { info_hash, .. } => !torrents.iter()
+                            .any(|torrent| &torrent.info_hash == info_hash),
+
+                        _ => false
+                    };
+
+                    if deleted {
+                        self.queue.remove(i);
+                        self.queue_rows.guard().remove(i);
+
+                        changed = true;
+                    } else {
+                        i += 1;
+                    }
+                }
+
+                for torrent in torrents {
+                    let i = self.queue.iter().position(|item| {
+                        matches!(item, QueueItem::Torrent { info_hash, .. } if info_hash == &torrent.info_hash)
+                    });
+
+                    let i = match i {
+                        Some(i) => i,
+
+                        None => {
+                            changed = true;
+
+                            self.push_torrent(torrent.info_hash.clone(), Some(&torrent))
+                        }
+                    };
+
+                    let stats = &torrent.stats;
+
+                    let mut text = format!(
+                        "{} / {}",
+                        utils::pretty_bytes(stats.current),
+                        utils::pretty_bytes(stats.total)
+                    );
+
+                    if !torrent.paused && !torrent.finished {
+                        text = format!("{text} · {}/s", utils::pretty_bytes(stats.download_speed));
+                    }
+
+                    let fraction = if stats.total > 0 {
+                        stats.current as f64 / stats.total as f64
+                    } else {
+                        0.0
+                    };
+
+                    let status = if torrent.paused {
+                        i18n!("download_paused").unwrap_or("Paused").to_string()
+                    } else if torrent.finished {
+                        i18n!("download_seeding").unwrap_or("Seeding").to_string()
+                    } else {
+                        stats.eta.map(utils::pretty_seconds)
+                            .unwrap_or_default()
+                    };
+
+                    let mut guard = self.queue_rows.guard();
+
+                    guard.send(i, DownloadRowMsg::SetPaused(torrent.paused));
+                    guard.send(i, DownloadRowMsg::SetStatus(Some(status)));
+                    guard.send(i, DownloadRowMsg::SetProgress(Some((text, fraction))));
+
+                    drop(guard);
+
+                    if let QueueItem::Torrent { info, .. } = &mut self.queue[i] {
+                        *info = Some(torrent);
+                    }
+                }
+
+                if changed {
+                    self.update_description();
+                    self.save_queue();
+                }
+            }
+
+            DownloadsPageInput::UpdateDownloaderTasks(tasks) => {
+                let mut changed = false;
+
+                // Remove finished download tasks from the queue.
+                let mut i = 0;
+
+                while i < self.queue.len() {
+                    let finished = match &self.queue[i] {
+                        QueueItem::DownloaderTask(task) => !tasks.iter()
+                            .any(|info| info.handle == task.handle),
+
+                        _ => false
+                    };
+
+                    if finished {
+                        self.queue.remove(i);
+                        self.queue_rows.guard().remove(i);
+
+                        changed = true;
+                    } else {
+                        i += 1;
+                    }
+                }
+
+                for task in tasks {
+                    let i = self.queue.iter().position(|item| {
+                        matches!(item, QueueItem::DownloaderTask(info) if info.handle == task.handle)
+                    });
+
+                    let i = match i {
+                        Some(i) => i,
+
+                        None => {
+                            changed = true;
+
+                            self.push_downloader_task(&task)
+                        }
+                    };
+
+                    let text = format!(
+                        "{} / {}",
+                        utils::pretty_bytes(task.current),
+                        utils::pretty_bytes(task.total)
+                    );
+
+                    let fraction = if task.finished {
+                        1.0
+                    } else if task.total > 0 {
+                        task.current as f64 / task.total as f64
+                    } else {
+                        0.0
+                    };
+
+                    self.queue_rows.guard().send(i, DownloadRowMsg::SetProgress(Some((text, fraction))));
+
+                    self.queue[i] = QueueItem::DownloaderTask(task);
+                }
+
+                if changed {
+                    self.update_description();
+                }
+            }
+
+            DownloadsPageInput::CheckUpdates => {
+                let config = config::get().await;
+
+                if !config.games_updates_enable {
+                    return;
+                }
+
+                if let Some(last_check) = self.last_updates_check
+                    && last_check.elapsed() < config.games_updates_interval
+                {
+                    return;
+                }
+
+                if !background_updates_allowed(&config) {
+                    return;
+                }
+
+                self.last_updates_check = Some(Instant::now());
+
+                // Skip games which already have queued downloads.
+                let games = self.games.iter()
+                    .filter(|(name, _)| {
+                        !self.queue.iter()
+                            .filter_map(QueueItem::download)
+                            .any(|queued| &queued.download.game_name == *name)
+                    })
+                    .map(|(name, game)| (name.clone(), game.integration.clone()))
+                    .collect::<Vec<_>>();
+
+                tracing::debug!(games = games.len(), "checking games for updates");
+
+                tasks::spawn_blocking(move || {
+                    let variant = GameVariant {
+                        platform: *consts::CURRENT_PLATFORM,
+                        edition: None
+                    };
+
+                    for (game_name, integration) in games {
+                        // Games which can't be launched are not installed, so
+                        // their pipelines are not updates.
+                        match integration.get_launch_info(&variant) {
+                            Ok(Some(_)) => (),
+                            Ok(None) => continue,
+
+                            Err(err) => {
+                                tracing::warn!(?err, ?game_name, "failed to request game launch info");
+
+                                continue;
+                            }
+                        }
+
+                        match integration.get_actions_pipeline(&variant) {
+                            Ok(Some(actions_pipeline)) => {
+                                sender.input(DownloadsPageInput::UpdateAvailable {
+                                    game_name,
+                                    variant: variant.clone(),
+                                    actions_pipeline: Arc::new(actions_pipeline)
+                                });
+                            }
+
+                            Ok(None) => sender.input(DownloadsPageInput::UpdateNotAvailable {
+                                game_name
+                            }),
+
+                            Err(err) => {
+                                tracing::warn!(?err, ?game_name, "failed to request game actions pipeline");
+                            }
+                        }
+                    }
+                });
+            }
+
+            DownloadsPageInput::UpdateAvailable {
+                game_name,
+                variant,
+                actions_pipeline
+            } => {
+                let Some(game) = self.games.get(&game_name) else {
+                    return;
+                };
+
+                let game_title = game.title.clone();
+
+                // Leave pipelines which need user interaction to the user and
+                // notify about them only once.
+                if actions_pipeline.is_interactive() {
+                    if self.pending_updates.insert(game_name) {
+                        let title = i18n!("game_update_available", {
+                            game => game_title
+                        }).unwrap_or_else(|| format!("Update for {game_title} is available"));
+
+                        let _ = sender.output(DownloadsPageOutput::ShowNotification(NotificationOptions {
+                            title: LocalizableString::raw(title),
+                            message: None,
+                            icon: Some(consts::APP_ID.to_string())
+                        }));
+                    }
+
+                    return;
+                }
+
+                if self.queue.iter()
+                    .filter_map(QueueItem::download)
+                    .any(|queued| queued.download.game_name == game_name)
+                {
+                    return;
+                }
+
+                tracing::info!(?game_name, "performing game update in background");
+
+                self.pending_updates.remove(&game_name);
+
+                let id = self.schedule_actions_pipeline(
+                    variant,
+                    game_name,
+                    game_title,
+                    actions_pipeline
+                ).await;
+
+                self.unattended.insert(id);
+
+                sender.input(DownloadsPageInput::StartNext);
+            }
+
+            DownloadsPageInput::UpdateNotAvailable { game_name } => {
+                self.pending_updates.remove(&game_name);
+            }
+        }
+    }
+}
+
+impl QueuedDownload {
+    /// Remember first actions of the current actions pipeline as performed.
+    /// Nothing is changed if the pipeline wasn't requested yet.
+    fn set_performed_actions(&mut self, performed_actions: usize) {
+        if let Some(actions_pipeline) = &self.actions_pipeline {
+            self.download.performed_actions = actions_pipeline.actions()
+                .iter()
+                .take(performed_actions)
+                .enumerate()
+                .map(|(i, action)| downloads::pipeline_action_id(i, action))
+                .collect();
+        }
+    }
+}
+
+impl DownloadsPage {
+    /// Add download to the end of the queue, returning its identifier.
+    fn push_download(&mut self, download: Download) -> u64 {
+        let id = download.id;
+
+        let row_index = self.queue_rows.guard().push_back(DownloadRowInit {
+            title: download.title.clone(),
+            subtitle: Some(download.game_title.clone()),
+            status: None,
+            paused: download.paused,
+            pausable: true,
+            reorderable: true
+        });
+
+        self.queue.push(QueueItem::Download(QueuedDownload {
+            download,
+            row_index,
+            actions_pipeline: None,
+            failed: false
+        }));
+
+        self.update_statuses();
+
+        id
+    }
+
+    /// Add torrent to the end of the queue, returning its index.
+    fn push_torrent(&mut self, info_hash: String, info: Option<&TorrentListInfo>) -> usize {
+        self.queue_rows.guard().push_back(DownloadRowInit {
+            title: info.and_then(|info| info.name.clone())
+                .unwrap_or_else(|| info_hash.clone()),
+            subtitle: Some(info_hash.clone()),
+            status: None,
+            paused: info.is_some_and(|info| info.paused),
+            pausable: true,
+            reorderable: true
+        });
+
+        self.queue.push(QueueItem::Torrent {
+            info_hash,
+            info: info.cloned()
+        });
+
+        self.queue.len() - 1
+    }
+
+    /// Add downloader task to the end of the queue, returning its index.
+    fn push_downloader_task(&mut self, task: &DownloaderTaskInfo) -> usize {
+        let title = task.output_file.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| task.url.clone());
+
+        self.queue_rows.guard().push_back(DownloadRowInit {
+            title,
+            subtitle: Some(task.url.clone()),
+            status: None,
+            paused: false,
+            pausable: false,
+            reorderable: true
+        });
+
+        self.queue.push(QueueItem::DownloaderTask(task.clone()));
+
+        self.queue.len() - 1
+    }
+
+    /// Update progress of the currently performed download on the page and
+    /// send it to the download's window.
+    fn emit_progress(&self, sender: &AsyncComponentSender<Self>, msg: GraphProgressGroupMsg) {
+        if let Some((id, _)) = &self.current {
+            let _ = sender.output(DownloadsPageOutput::DownloadProgress {
+                id: *id,
+                msg: msg.clone()
+            });
+        }
+
+        self.graph_group.emit(msg);
+    }
+
+    /// Find queued download with the given identifier.
+    fn find_download_mut(&mut self, id: u64) -> Option<&mut QueuedDownload> {
+        self.queue.iter_mut()
+            .filter_map(QueueItem::download_mut)
+            .find(|queued| queued.download.id == id)
+    }
+
+    /// Add actions pipeline download to the end of the queue, returning its
+    /// identifier.
+    async fn schedule_actions_pipeline(
+        &mut self,
+        variant: GameVariant,
+        game_name: String,
+        game_title: String,
+        actions_pipeline: Arc<ActionsPipeline>
+    ) -> u64 {
+        let lang = config::get().await
+            .language().ok();
+
+        let title = match &lang {
+            Some(lang) => actions_pipeline.title().translate(lang),
+            None => actions_pipeline.title().default_translation()
+        };
+
+        let id = self.push_download(Download {
+            id: self.next_id,
+            game_name,
+            game_title,
+            title: title.to_string(),
+            variant,
+            kind: DownloadKind::ActionsPipeline,
+            performed_actions: Vec::new(),
+            paused: false
+        });
+
+        if let Some(queued) = self.find_download_mut(id) {
+            queued.actions_pipeline = Some(actions_pipeline);
+        }
+
+        self.next_id += 1;
+
+        self.update_description();
+        self.save_queue();
+
+        id
+    }
+
+    /// Update status labels of the queued downloads.
+    fn update_statuses(&mut self) {
+        let mut guard = self.queue_rows.guard();
+
+        for queued in self.queue.iter().filter_map(QueueItem::download) {
+            let current = self.current.as_ref()
+                .filter(|(id, _)| *id == queued.download.id);
+
+            let status = match current {
+                Some((_, cancel)) if cancel.is_cancelled() => i18n!("download_pausing")
+                    .unwrap_or("Pausing"),
+
+                Some(_) => i18n!("download_running")
+                    .unwrap_or("In progress"),
+
+                None if queued.failed => i18n!("download_failed")
+                    .unwrap_or("Failed"),
+
+                None if queued.download.paused => i18n!("download_paused")
+                    .unwrap_or("Paused"),
+
+                None if !self.games.contains_key(&queued.download.game_name) => i18n!("download_waiting_game")
+                    .unwrap_or("Waiting for the game to be loaded"),
+
+                None => i18n!("download_queued")
+                    .unwrap_or("Queued")
+            };
+
+            guard.send(
+                queued.row_index.current_index(),
+                DownloadRowMsg::SetStatus(Some(status.to_string()))
+            );
+        }
+    }
+
+    /// Update description of the queue group.
+    fn update_description(&self) {
+        self.queue_rows.widget().set_description(
+            self.queue.is_empty().then(|| {
+                i18n!("downloads_queue_empty")
+                    .unwrap_or("Nothing to download")
+            })
+        );
+    }
+
+    /// Save downloads queue to the disk so it could be restored after the
+    /// launcher restart.
+    fn save_queue(&self) {
+        let queue = self.queue.iter()
+            .filter_map(|item| match item {
+                QueueItem::Download(queued) => Some(QueueEntry::Download(queued.download.clone())),
+
+                QueueItem::Torrent { info_hash, .. } => Some(QueueEntry::Torrent {
+                    info_hash: info_hash.clone()
+                }),
+
+                // Downloader tasks are performed by the pipeline actions so
+                // they're started again when the actions are.
+                QueueItem::DownloaderTask(_) => None
+            })
+            .collect::<Vec<_>>();
+
+        if let Err(err) = downloads::save(&self.queue_path, &queue) {
+            tracing::error!(?err, path = ?self.queue_path, "failed to save downloads queue");
+        }
+    }
+}
+
+/// Check whether games can be updated in background right now according to
+/// the configured time window and network policy.
+fn background_updates_allowed(config: &Config) -> bool {
+    if let Some((start, end)) = config.games_updates_window {
+        let Ok(now) = gtk::glib::DateTime::now_local() else {
+            return false;
+        };
+
+        let now = (now.hour() * 60 + now.minute()) as u16;
+
+        let in_window = if start <= end {
+            (start..end).contains(&now)
+        } else {
+            now >= start || now < end
+        };
+
+        if !in_window {
+            return false;
+        }
+    }
+
+    let network = gtk::gio::NetworkMonitor::default();
+
+    if !network.is_network_available() {
+        return false;
+    }
+
+    match config.games_updates_network {
+        GamesUpdatesNetwork::Any => true,
+        GamesUpdatesNetwork::Unmetered => !network.is_network_metered()
+    }
+}
+
+/// Build progress report callback which forwards updates of the progress row
+/// with provided name to the downloads page.
+fn progress_updater(
+    sender: &AsyncComponentSender<DownloadsPage>,
+    lang: Option<LanguageIdentifier>,
+    name: String
+) -> impl Fn(ProgressReport) + Send + 'static {
+    let sender = sender.clone();
+
+    move |progress: ProgressReport| {
+        let fraction = progress.fraction();
+
+        let text = progress.format().ok()
+            .flatten()
+            .map(|text| {
+                let text = match &lang {
+                    Some(lang) => text.translate(lang),
+                    None => text.default_translation()
+                };
+
+                text.to_string()
+            })
+            .unwrap_or_else(|| {
+                format!("{:.2}%", fraction * 100.0)
+            });
+
+        sender.input(DownloadsPageInput::SetProgress {
+            name: name.clone(),
+            text: Some(text),
+            fraction
+        });
+    }
+}
+
+/// Perform actions of the pipeline, skipping the given amount of already
+/// performed ones. Stop when the cancellation token is cancelled: the token is
+/// passed to the actions so they can stop their downloads, otherwise the
+/// pipeline is stopped after the current action.
+fn perform_actions_pipeline(
+    sender: &AsyncComponentSender<DownloadsPage>,
+    lang: Option<LanguageIdentifier>,
+    actions_pipeline: &ActionsPipeline,
+    performed_actions: usize,
+    cancel: &CancellationToken
+) -> DownloadResult {
+    let actions = actions_pipeline.actions()
+        .iter()
+        .enumerate()
+        .skip(performed_actions);
+
+    for (i, action) in actions {
+        if cancel.is_cancelled() {
+            return DownloadResult::Paused {
+                performed_actions: i
+            };
+        }
+
+        let name = i.to_string();
+
+        sender.input(DownloadsPageInput::MarkStarted {
+            name: name.clone()
+        });
+
+        // Fail early instead of running out of space in the middle of the
+        // action.
+        match action.estimate() {
+            Ok(Some(estimate)) => {
+                if let Err(err) = disk::check_free_space(&estimate) {
+                    tracing::error!(?err, "not enough disk space for pipeline action");
+
+                    dialogs::error(
+                        i18n!("not_enough_disk_space")
+                            .unwrap_or("Not enough disk space"),
+                        err.to_string()
+                    );
+
+                    return DownloadResult::Failed {
+                        performed_actions: i
+                    };
+                }
+            }
+
+            Ok(None) => (),
+
+            Err(err) => tracing::warn!(?err, "failed to estimate pipeline action disk space")
+        }
+
+        let result = match action.before(progress_updater(sender, lang.clone(), name.clone())) {
+            Ok(Some(true)) | Ok(None) => {
+                action.perform_cancellable(progress_updater(sender, lang.clone(), name.clone()), cancel.clone())
+            }
+
+            Ok(Some(false)) => Ok(()),
+
+            Err(err) => Err(err)
+        };
+
+        // Interrupted action is performed again when the download is resumed.
+        if result.is_err() && cancel.is_cancelled() {
+            return DownloadResult::Paused {
+                performed_actions: i
+            };
+        }
+
+        if let Err(err) = result {
+            tracing::error!(?err, "failed to perform pipeline action");
+
+            dialogs::error(
+                i18n!("failed_perform_pipeline_action")
+                    .unwrap_or("Failed to perform pipeline action"),
+                err.to_string()
+            );
+
+            return DownloadResult::Failed {
+                performed_actions: i
+            };
+        }
+
+        sender.input(DownloadsPageInput::SetProgress {
+            name: name.clone(),
+            text: None,
+            fraction: 1.0
+        });
+
+        sender.input(DownloadsPageInput::MarkFinished {
+            name
+        });
+    }
+
+    DownloadResult::Finished
+}
+
+/// Install or uninstall game components from the given list. Stop when the
+/// cancellation token is cancelled: the token is passed to the integration so
+/// it can stop its downloads, otherwise changes are stopped after the current
+/// component.
+fn apply_components(
+    sender: &AsyncComponentSender<DownloadsPage>,
+    lang: Option<LanguageIdentifier>,
+    id: u64,
+    integration: &GameIntegration,
+    variant: &GameVariant,
+    components: Vec<(String, bool)>,
+    cancel: &CancellationToken
+) -> DownloadResult {
+    for (name, is_install) in components {
+        if cancel.is_cancelled() {
+            return DownloadResult::Paused {
+                performed_actions: 0
+            };
+        }
+
+        sender.input(DownloadsPageInput::MarkStarted {
+            name: name.clone()
+        });
+
+        let updater = progress_updater(sender, lang.clone(), name.clone());
+
+        let mut result = if is_install {
+            integration.install_component_cancellable(variant, &name, updater, cancel.clone())
+        } else {
+            integration.uninstall_component_cancellable(variant, &name, updater, cancel.clone())
+        };
+
+        // If we've successfully installed/uninstalled the component then save
+        // its enabled/disabled state.
+        if result.is_ok() {
+            result = integration.set_component_enabled(variant, &name, is_install);
+        }
+
+        // Interrupted component is applied again when the download is resumed.
+        if result.is_err() && cancel.is_cancelled() {
+            return DownloadResult::Paused {
+                performed_actions: 0
+            };
+        }
+
+        if let Err(err) = result {
+            tracing::error!(
+                ?err,
+                component = ?name,
+                "failed to apply game component"
+            );
+
+            dialogs::error(
+                i18n!("failed_apply_game_component")
+                    .unwrap_or("Failed to apply game component"),
+                err.to_string()
+            );
+
+            return DownloadResult::Failed {
+                performed_actions: 0
+            };
+        }
+
+        sender.input(DownloadsPageInput::SetProgress {
+            name: name.clone(),
+            text: None,
+            fraction: 1.0
+        });
+
+        sender.input(DownloadsPageInput::MarkFinished {
+            name: name.clone()
+        });
+
+        sender.input(DownloadsPageInput::ComponentApplied {
+            id,
+            name
+        });
+    }
+
+    DownloadResult::Finished
+}
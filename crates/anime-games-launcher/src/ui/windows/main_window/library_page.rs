@@ -25,11 +25,13 @@ use adw::prelude::*;
 use agl_core::tasks;
 use agl_games::api::{
     ActionsPipeline, GameComponentsGroup, GameEdition, GameIntegration,
-    GameLaunchInfo, GameSettingsGroup, GameVariant
+    GameLaunchInfo, GameSettingsGroup, GameVariant, SavesDirectory,
+    ModsDirectory, LaunchPreset
 };
 
 use crate::{consts, config, i18n};
 use crate::games::GameLock;
+use crate::shortcuts::desktop;
 use crate::ui::dialogs;
 use crate::ui::components::lazy_picture::ImagePath;
 use crate::ui::components::cards_list::{
@@ -107,6 +109,7 @@ pub enum LibraryPageInput {
     },
 
     ScheduleGameActionsPipeline {
+        variant: GameVariant,
         game_name: String,
         game_title: String,
         actions_pipeline: Arc<ActionsPipeline>
@@ -120,21 +123,42 @@ pub enum LibraryPageInput {
         layout: Box<[GameComponentsGroup]>
     },
 
+    OpenGameModsWindow {
+        game_name: String,
+        directories: Box<[ModsDirectory]>
+    },
+
+    OpenGameVerifyWindow {
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
+    OpenGameStorageWindow {
+        game_name: String,
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
     OpenGameSettingsWindow {
         integration: Arc<GameIntegration>,
         variant: GameVariant,
-        layout: Box<[GameSettingsGroup]>
+        game_name: String,
+        layout: Option<Box<[GameSettingsGroup]>>
     },
 
     LaunchGame {
+        game_name: String,
         game_title: String,
-        game_launch_info: GameLaunchInfo
+        game_launch_info: GameLaunchInfo,
+        launch_preset: Option<LaunchPreset>,
+        saves_directories: Box<[SavesDirectory]>
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum LibraryPageOutput {
     ScheduleGameActionsPipeline {
+        variant: GameVariant,
         game_name: String,
         game_title: String,
         actions_pipeline: Arc<ActionsPipeline>
@@ -148,15 +172,35 @@ pub enum LibraryPageOutput {
         layout: Box<[GameComponentsGroup]>
     },
 
+    OpenGameModsWindow {
+        game_name: String,
+        directories: Box<[ModsDirectory]>
+    },
+
+    OpenGameVerifyWindow {
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
+    OpenGameStorageWindow {
+        game_name: String,
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
     OpenGameSettingsWindow {
         integration: Arc<GameIntegration>,
         variant: GameVariant,
-        layout: Box<[GameSettingsGroup]>
+        game_name: String,
+        layout: Option<Box<[GameSettingsGroup]>>
     },
 
     LaunchGame {
+        game_name: String,
         game_title: String,
-        game_launch_info: GameLaunchInfo
+        game_launch_info: GameLaunchInfo,
+        launch_preset: Option<LaunchPreset>,
+        saves_directories: Box<[SavesDirectory]>
     }
 }
 
@@ -249,17 +293,26 @@ impl SimpleAsyncComponent for LibraryPage {
             game_details: GameLibraryDetails::builder()
                 .launch(())
                 .forward(sender.input_sender(), |msg| match msg {
-                    GameLibraryDetailsOutput::ScheduleGameActionsPipeline { game_name, game_title, actions_pipeline }
-                        => LibraryPageInput::ScheduleGameActionsPipeline { game_name, game_title, actions_pipeline },
+                    GameLibraryDetailsOutput::ScheduleGameActionsPipeline { variant, game_name, game_title, actions_pipeline }
+                        => LibraryPageInput::ScheduleGameActionsPipeline { variant, game_name, game_title, actions_pipeline },
 
                     GameLibraryDetailsOutput::OpenGameComponentsWindow { integration, variant, game_name, game_title, layout }
                         => LibraryPageInput::OpenGameComponentsWindow { integration, variant, game_name, game_title, layout },
 
-                    GameLibraryDetailsOutput::OpenGameSettingsWindow { integration, variant, layout }
-                        => LibraryPageInput::OpenGameSettingsWindow { integration, variant, layout },
+                    GameLibraryDetailsOutput::OpenGameModsWindow { game_name, directories }
+                        => LibraryPageInput::OpenGameModsWindow { game_name, directories },
+
+                    GameLibraryDetailsOutput::OpenGameVerifyWindow { integration, variant }
+                        => LibraryPageInput::OpenGameVerifyWindow { integration, variant },
+
+                    GameLibraryDetailsOutput::OpenGameStorageWindow { game_name, integration, variant }
+                        => LibraryPageInput::OpenGameStorageWindow { game_name, integration, variant },
 
-                    GameLibraryDetailsOutput::LaunchGame { game_title, game_launch_info }
-                        => LibraryPageInput::LaunchGame { game_title, game_launch_info }
+                    GameLibraryDetailsOutput::OpenGameSettingsWindow { integration, variant, game_name, layout }
+                        => LibraryPageInput::OpenGameSettingsWindow { integration, variant, game_name, layout },
+
+                    GameLibraryDetailsOutput::LaunchGame { game_name, game_title, game_launch_info, launch_preset, saves_directories }
+                        => LibraryPageInput::LaunchGame { game_name, game_title, game_launch_info, launch_preset, saves_directories }
                 }),
 
             games: HashMap::new()
@@ -404,6 +457,10 @@ impl SimpleAsyncComponent for LibraryPage {
                             err.to_string()
                         );
                     }
+
+                    if let Err(err) = desktop::remove_entry(&name).await {
+                        tracing::warn!(?err, ?name, "failed to remove game desktop entry");
+                    }
                 }
             }
 
@@ -506,11 +563,13 @@ impl SimpleAsyncComponent for LibraryPage {
             }
 
             LibraryPageInput::ScheduleGameActionsPipeline {
+                variant,
                 game_name,
                 game_title,
                 actions_pipeline
             } => {
                 let _ = sender.output(LibraryPageOutput::ScheduleGameActionsPipeline {
+                    variant,
                     game_name,
                     game_title,
                     actions_pipeline
@@ -533,22 +592,59 @@ impl SimpleAsyncComponent for LibraryPage {
                 });
             }
 
+            LibraryPageInput::OpenGameModsWindow {
+                game_name,
+                directories
+            } => {
+                let _ = sender.output(LibraryPageOutput::OpenGameModsWindow {
+                    game_name,
+                    directories
+                });
+            }
+
+            LibraryPageInput::OpenGameVerifyWindow {
+                integration,
+                variant
+            } => {
+                let _ = sender.output(LibraryPageOutput::OpenGameVerifyWindow {
+                    integration,
+                    variant
+                });
+            }
+
+            LibraryPageInput::OpenGameStorageWindow {
+                game_name,
+                integration,
+                variant
+            } => {
+                let _ = sender.output(LibraryPageOutput::OpenGameStorageWindow {
+                    game_name,
+                    integration,
+                    variant
+                });
+            }
+
             LibraryPageInput::OpenGameSettingsWindow {
                 integration,
                 variant,
+                game_name,
                 layout
             } => {
                 let _ = sender.output(LibraryPageOutput::OpenGameSettingsWindow {
                     integration,
                     variant,
+                    game_name,
                     layout
                 });
             }
 
-            LibraryPageInput::LaunchGame { game_title, game_launch_info } => {
+            LibraryPageInput::LaunchGame { game_name, game_title, game_launch_info, launch_preset, saves_directories } => {
                 let _ = sender.output(LibraryPageOutput::LaunchGame {
+                    game_name,
                     game_title,
-                    game_launch_info
+                    game_launch_info,
+                    launch_preset,
+                    saves_directories
                 });
             }
         }
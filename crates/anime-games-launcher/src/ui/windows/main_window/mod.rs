@@ -20,7 +20,6 @@ use std::io::Read;
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::path::Path;
-use std::process::{Command, Stdio};
 
 use adw::prelude::*;
 
@@ -35,11 +34,11 @@ use agl_core::network::downloader::{Downloader, DownloadOptions};
 use agl_locale::string::LocalizableString;
 use agl_packages::hash::Hash;
 use agl_packages::storage::Storage;
+use agl_packages::lan::LanCache;
 use agl_runtime::mlua::prelude::*;
 use agl_runtime::scopes_list::ScopesList;
-use agl_runtime::api::{ApiOptions, ApiContext};
 use agl_runtime::api::bytes::Bytes;
-use agl_runtime::api::torrent_api::{TorrentServer, TorrentServerOptions};
+use agl_runtime::api::downloader_api::DownloaderTasks;
 use agl_runtime::api::portal_api::{
     ToastOptions, NotificationOptions, DialogOptions, DialogButtonStatus
 };
@@ -47,41 +46,57 @@ use agl_runtime::runtime::{Runtime, ModulePaths};
 use agl_games::manifest::{GamesRegistryManifest, GameManifest};
 use agl_games::api::{
     GameVariant, GameIntegration, ActionsPipeline, GameLaunchInfo,
-    GameComponentsGroup, GameSettingsGroup
+    GameComponentsGroup, GameSettingsGroup, SavesDirectory,
+    ModsDirectory, LaunchPreset
 };
 
-use crate::{consts, config, cache, games, i18n};
+use crate::{consts, config, cache, games, launch, saves, mods, runtime, i18n};
 use crate::games::GameLock;
+use crate::runtime::RuntimeHandlers;
+use crate::downloads::ApplyComponentInfo;
 use crate::ui::dialogs;
+use crate::ui::components::graph_progress_group::GraphProgressGroupMsg;
 use crate::ui::windows::about::AboutWindow;
+use crate::ui::windows::game_actions_pipeline::{
+    GameActionsPipelineWindow,
+    GameActionsPipelineWindowInput
+};
+use crate::ui::windows::game_apply_components::{
+    GameApplyComponentsWindow,
+    GameApplyComponentsWindowInput
+};
 use crate::ui::windows::game_components::{
     GameComponentsWindow,
     GameComponentsWindowInput,
     GameComponentsWindowOutput
 };
-use crate::ui::windows::game_apply_components::{
-    GameApplyComponentsWindow,
-    GameApplyComponentsWindowInput,
-    GameApplyComponentsWindowOutput,
-    ApplyComponentInfo
+use crate::ui::windows::game_mods::{GameModsWindow, GameModsWindowInput};
+use crate::ui::windows::game_storage::{
+    GameStorageWindow,
+    GameStorageWindowInput,
+    GameStorageWindowOutput
+};
+use crate::ui::windows::game_verify::{
+    GameVerifyWindow,
+    GameVerifyWindowInput,
+    GameVerifyWindowOutput
 };
 use crate::ui::windows::game_settings::{
     GameSettingsWindow,
     GameSettingsWindowInput,
     GameSettingsWindowOutput
 };
-use crate::ui::windows::game_actions_pipeline::{
-    GameActionsPipelineWindow,
-    GameActionsPipelineWindowInput,
-    GameActionsPipelineWindowOutput
-};
 use crate::ui::windows::game_running::{GameRunningWindow, GameRunningWindowMsg};
 
 pub mod store_page;
 pub mod library_page;
+pub mod downloads_page;
 
 use store_page::{StorePage, StorePageInput, StorePageOutput};
 use library_page::{LibraryPage, LibraryPageInput, LibraryPageOutput};
+use downloads_page::{
+    DownloadsPage, DownloadsPageInit, DownloadsPageInput, DownloadsPageOutput
+};
 
 relm4::new_action_group!(WindowActionGroup, "win");
 
@@ -136,6 +151,7 @@ pub enum MainWindowMsg {
     ShowLibraryGameWithUrl(String),
 
     ScheduleGameActionsPipeline {
+        game_variant: GameVariant,
         game_name: String,
         game_title: String,
         actions_pipeline: Arc<ActionsPipeline>
@@ -155,6 +171,23 @@ pub enum MainWindowMsg {
         delete_game_package: bool
     },
 
+    OpenGameActionsPipelineWindow {
+        download_id: u64,
+        game_title: String
+    },
+
+    OpenGameApplyComponentsWindow {
+        download_id: u64,
+        game_title: String
+    },
+
+    DownloadProgress {
+        download_id: u64,
+        msg: GraphProgressGroupMsg
+    },
+
+    DownloadStopped(u64),
+
     OpenGameComponentsWindow {
         integration: Arc<GameIntegration>,
         variant: GameVariant,
@@ -163,15 +196,35 @@ pub enum MainWindowMsg {
         layout: Box<[GameComponentsGroup]>
     },
 
+    OpenGameModsWindow {
+        game_name: String,
+        directories: Box<[ModsDirectory]>
+    },
+
+    OpenGameVerifyWindow {
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
+    OpenGameStorageWindow {
+        game_name: String,
+        integration: Arc<GameIntegration>,
+        variant: GameVariant
+    },
+
     OpenGameSettingsWindow {
         integration: Arc<GameIntegration>,
         variant: GameVariant,
-        layout: Box<[GameSettingsGroup]>
+        game_name: String,
+        layout: Option<Box<[GameSettingsGroup]>>
     },
 
     LaunchGame {
+        game_name: String,
         game_title: String,
-        game_launch_info: GameLaunchInfo
+        game_launch_info: GameLaunchInfo,
+        launch_preset: Option<LaunchPreset>,
+        saves_directories: Box<[SavesDirectory]>
     },
 
     ReloadSelectedLibraryGameInfo {
@@ -187,10 +240,14 @@ pub struct MainWindow {
     about_window: AsyncController<AboutWindow>,
     store_page: AsyncController<StorePage>,
     library_page: AsyncController<LibraryPage>,
+    downloads_page: AsyncController<DownloadsPage>,
+    game_actions_pipeline_window: AsyncController<GameActionsPipelineWindow>,
     game_components_window: AsyncController<GameComponentsWindow>,
     game_apply_components_window: AsyncController<GameApplyComponentsWindow>,
+    game_mods_window: AsyncController<GameModsWindow>,
+    game_verify_window: AsyncController<GameVerifyWindow>,
+    game_storage_window: AsyncController<GameStorageWindow>,
     game_settings_window: AsyncController<GameSettingsWindow>,
-    game_actions_pipeline_window: AsyncController<GameActionsPipelineWindow>,
     game_running_window: AsyncController<GameRunningWindow>,
 
     window: adw::ApplicationWindow,
@@ -212,10 +269,14 @@ impl std::fmt::Debug for MainWindow {
             .field("about_window", &self.about_window)
             .field("store_page", &self.store_page)
             .field("library_page", &self.library_page)
+            .field("downloads_page", &self.downloads_page)
+            .field("game_actions_pipeline_window", &self.game_actions_pipeline_window)
             .field("game_components_window", &self.game_components_window)
             .field("game_apply_components_window", &self.game_apply_components_window)
+            .field("game_mods_window", &self.game_mods_window)
+            .field("game_verify_window", &self.game_verify_window)
+            .field("game_storage_window", &self.game_storage_window)
             .field("game_settings_window", &self.game_settings_window)
-            .field("game_actions_pipeline_window", &self.game_actions_pipeline_window)
             .field("game_running_window", &self.game_running_window)
             .field("window", &self.window)
             .field("toast_overlay", &self.toast_overlay)
@@ -336,6 +397,19 @@ impl SimpleAsyncComponent for MainWindow {
 
                                 set_name: Some("library"),
                                 set_icon_name: Some("applications-games-symbolic")
+                            },
+
+                            add = &gtk::Box {
+                                set_vexpand: true,
+                                set_hexpand: true,
+
+                                model.downloads_page.widget(),
+                            } -> {
+                                set_title: Some(i18n!("downloads")
+                                    .unwrap_or("Downloads")),
+
+                                set_name: Some("downloads"),
+                                set_icon_name: Some("folder-download-symbolic")
                             }
                         }
                     }
@@ -351,42 +425,63 @@ impl SimpleAsyncComponent for MainWindow {
     ) -> AsyncComponentParts<Self> {
         let config = config::startup();
 
-        let lua = Lua::new();
+        let storage = Storage::open(&config.packages_resources_path)
+            .expect("failed to open packages storage");
 
-        // Set runtime memory limit.
-        if config.runtime_memory_limit > 0 {
-            lua.set_memory_limit(config.runtime_memory_limit)
-                .expect("failed to set packages runtime memory limit");
+        if config.packages_lan_cache_enable {
+            match LanCache::start(storage.clone(), config.packages_lan_cache_port) {
+                Ok(lan_cache) => games::set_lan_cache(lan_cache),
+                Err(err) => tracing::error!(?err, "failed to start packages LAN cache")
+            }
         }
 
-        let reqwest_client = config.client_builder()
-            .and_then(|client| {
-                client.build()
-                    .map_err(|err| anyhow::anyhow!(err))
+        let downloader_tasks = DownloaderTasks::new();
+        let torrent_server = runtime::start_torrent_server(config);
+
+        let downloads_page = DownloadsPage::builder()
+            .launch(DownloadsPageInit {
+                queue_path: consts::DOWNLOADS_FILE.clone(),
+                downloader_tasks: downloader_tasks.clone(),
+                torrent_server: torrent_server.clone()
             })
-            .expect("failed to build network client");
+            .forward(sender.input_sender(), |msg| match msg {
+                // FIXME: this is a hack
+                DownloadsPageOutput::UpdateGameInfo(_)
+                    => MainWindowMsg::ReloadSelectedLibraryGameInfo {
+                        launch_info: true,
+                        actions_pipeline: true,
+                        components_layout: true,
+                        tools_layout: true,
+                        settings_layout: true
+                    },
 
-        let storage = Storage::open(&config.packages_resources_path)
-            .expect("failed to open packages storage");
+                DownloadsPageOutput::DeleteGamePackage(name)
+                    => MainWindowMsg::DeleteGamePackage(name),
 
-        let torrent_server = config.runtime_torrent_enable.then(|| {
-            TorrentServer::start(TorrentServerOptions {
-                default_folder: config.packages_temporary_path.clone(),
+                DownloadsPageOutput::ShowNotification(options)
+                    => MainWindowMsg::ShowNotification(options),
 
-                socks_proxy: match config.general_network_proxy.clone() {
-                    Some(proxy) if proxy.starts_with("socks") => Some(proxy),
-                    _ => None
-                },
+                DownloadsPageOutput::ActionsPipelineScheduled { id, game_title }
+                    => MainWindowMsg::OpenGameActionsPipelineWindow {
+                        download_id: id,
+                        game_title
+                    },
 
-                trackers: config.runtime_torrent_trackers.iter()
-                    .cloned()
-                    .collect(),
+                DownloadsPageOutput::ApplyComponentsScheduled { id, game_title }
+                    => MainWindowMsg::OpenGameApplyComponentsWindow {
+                        download_id: id,
+                        game_title
+                    },
 
-                blocklist_url: config.runtime_torrent_blocklist_url.clone(),
-                enable_dht: config.runtime_torrent_enable_dht,
-                enable_upnp: config.runtime_torrent_enable_upnp
-            })
-        });
+                DownloadsPageOutput::DownloadProgress { id, msg }
+                    => MainWindowMsg::DownloadProgress {
+                        download_id: id,
+                        msg
+                    },
+
+                DownloadsPageOutput::DownloadStopped(id)
+                    => MainWindowMsg::DownloadStopped(id)
+            });
 
         fn translate(str: LocalizableString) -> String {
             let config = tasks::block_on(config::get());
@@ -399,11 +494,7 @@ impl SimpleAsyncComponent for MainWindow {
             str.to_string()
         }
 
-        let options = ApiOptions {
-            lua,
-            reqwest_client,
-            torrent_server,
-
+        let handlers = RuntimeHandlers {
             show_toast: {
                 let sender = sender.clone();
 
@@ -428,18 +519,10 @@ impl SimpleAsyncComponent for MainWindow {
                 })
             },
 
-            secrets_file: config.runtime_secrets_path.clone(),
-
             translate
         };
 
-        let context = ApiContext::default();
-
-        if let Ok(mut private_paths) = context.private_paths.write() {
-            private_paths.extend(config.runtime_private_paths.clone());
-        }
-
-        let runtime = Runtime::new(options, context)
+        let runtime = runtime::build_runtime(config, downloader_tasks, torrent_server, handlers)
             .expect("failed to initialize packages runtime");
 
         let model = Self {
@@ -463,19 +546,34 @@ impl SimpleAsyncComponent for MainWindow {
             library_page: LibraryPage::builder()
                 .launch(())
                 .forward(sender.input_sender(), |msg| match msg {
-                    LibraryPageOutput::ScheduleGameActionsPipeline { game_name, game_title, actions_pipeline }
-                        => MainWindowMsg::ScheduleGameActionsPipeline { game_name, game_title, actions_pipeline },
+                    LibraryPageOutput::ScheduleGameActionsPipeline { variant, game_name, game_title, actions_pipeline }
+                        => MainWindowMsg::ScheduleGameActionsPipeline { game_variant: variant, game_name, game_title, actions_pipeline },
 
                     LibraryPageOutput::OpenGameComponentsWindow { integration, variant, game_name, game_title, layout }
                         => MainWindowMsg::OpenGameComponentsWindow { integration, variant, game_name, game_title, layout },
 
-                    LibraryPageOutput::OpenGameSettingsWindow { integration, variant, layout }
-                        => MainWindowMsg::OpenGameSettingsWindow { integration, variant, layout },
+                    LibraryPageOutput::OpenGameModsWindow { game_name, directories }
+                        => MainWindowMsg::OpenGameModsWindow { game_name, directories },
+
+                    LibraryPageOutput::OpenGameVerifyWindow { integration, variant }
+                        => MainWindowMsg::OpenGameVerifyWindow { integration, variant },
 
-                    LibraryPageOutput::LaunchGame { game_title, game_launch_info }
-                        => MainWindowMsg::LaunchGame { game_title, game_launch_info }
+                    LibraryPageOutput::OpenGameStorageWindow { game_name, integration, variant }
+                        => MainWindowMsg::OpenGameStorageWindow { game_name, integration, variant },
+
+                    LibraryPageOutput::OpenGameSettingsWindow { integration, variant, game_name, layout }
+                        => MainWindowMsg::OpenGameSettingsWindow { integration, variant, game_name, layout },
+
+                    LibraryPageOutput::LaunchGame { game_name, game_title, game_launch_info, launch_preset, saves_directories }
+                        => MainWindowMsg::LaunchGame { game_name, game_title, game_launch_info, launch_preset, saves_directories }
                 }),
 
+            downloads_page,
+
+            game_actions_pipeline_window: GameActionsPipelineWindow::builder()
+                .launch(())
+                .detach(),
+
             game_components_window: GameComponentsWindow::builder()
                 .launch(())
                 .forward(sender.input_sender(), |msg| match msg {
@@ -500,25 +598,34 @@ impl SimpleAsyncComponent for MainWindow {
 
             game_apply_components_window: GameApplyComponentsWindow::builder()
                 .launch(())
-                .forward(sender.input_sender(), |msg| match msg {
-                    // FIXME: this is a hack
-                    GameApplyComponentsWindowOutput::UpdateGameInfo(_)
-                        => MainWindowMsg::ReloadSelectedLibraryGameInfo {
-                            launch_info: true,
-                            actions_pipeline: true,
-                            components_layout: true,
-                            tools_layout: true,
-                            settings_layout: true
-                        },
+                .detach(),
+
+            game_mods_window: GameModsWindow::builder()
+                .launch(())
+                .detach(),
 
-                    GameApplyComponentsWindowOutput::DeleteGamePackage(name)
-                        => MainWindowMsg::DeleteGamePackage(name)
+            game_verify_window: GameVerifyWindow::builder()
+                .launch(())
+                .forward(sender.input_sender(), |msg| match msg {
+                    GameVerifyWindowOutput::ReloadGameInfo {
+                        launch_info,
+                        actions_pipeline,
+                        components_layout,
+                        tools_layout,
+                        settings_layout
+                    } => MainWindowMsg::ReloadSelectedLibraryGameInfo {
+                        launch_info,
+                        actions_pipeline,
+                        components_layout,
+                        tools_layout,
+                        settings_layout
+                    }
                 }),
 
-            game_settings_window: GameSettingsWindow::builder()
+            game_storage_window: GameStorageWindow::builder()
                 .launch(())
                 .forward(sender.input_sender(), |msg| match msg {
-                    GameSettingsWindowOutput::ReloadGameInfo {
+                    GameStorageWindowOutput::ReloadGameInfo {
                         launch_info,
                         actions_pipeline,
                         components_layout,
@@ -533,18 +640,22 @@ impl SimpleAsyncComponent for MainWindow {
                     }
                 }),
 
-            game_actions_pipeline_window: GameActionsPipelineWindow::builder()
+            game_settings_window: GameSettingsWindow::builder()
                 .launch(())
                 .forward(sender.input_sender(), |msg| match msg {
-                    // FIXME: this is a hack
-                    GameActionsPipelineWindowOutput::UpdateGameInfo(_)
-                        => MainWindowMsg::ReloadSelectedLibraryGameInfo {
-                            launch_info: true,
-                            actions_pipeline: true,
-                            components_layout: true,
-                            tools_layout: true,
-                            settings_layout: true
-                        }
+                    GameSettingsWindowOutput::ReloadGameInfo {
+                        launch_info,
+                        actions_pipeline,
+                        components_layout,
+                        tools_layout,
+                        settings_layout
+                    } => MainWindowMsg::ReloadSelectedLibraryGameInfo {
+                        launch_info,
+                        actions_pipeline,
+                        components_layout,
+                        tools_layout,
+                        settings_layout
+                    }
                 }),
 
             game_running_window: GameRunningWindow::builder()
@@ -994,8 +1105,13 @@ impl SimpleAsyncComponent for MainWindow {
                             .unwrap_or_else(|| format!("Updating {title} game package"))
                     )));
 
-                    // Keep package scope from the current package lock.
+                    // Keep package scope, user-defined wrappers and launch
+                    // presets from the current package lock.
                     let prev_scope = lock.scope.clone();
+                    let prev_wrappers = lock.wrappers.clone();
+                    let prev_global_wrappers = lock.global_wrappers;
+                    let prev_presets = lock.presets.clone();
+                    let prev_preset = lock.preset.clone();
 
                     // Try to update game package.
                     match GameLock::download(&lock.url, &storage).await {
@@ -1003,6 +1119,10 @@ impl SimpleAsyncComponent for MainWindow {
                         // new one.
                         Ok(mut new_lock) => {
                             new_lock.scope = prev_scope;
+                            new_lock.wrappers = prev_wrappers;
+                            new_lock.global_wrappers = prev_global_wrappers;
+                            new_lock.presets = prev_presets;
+                            new_lock.preset = prev_preset;
 
                             tasks::fs::write(
                                 expected_path,
@@ -1281,24 +1401,7 @@ impl SimpleAsyncComponent for MainWindow {
                     return;
                 }
 
-                fn find_module_key(lock: &GameLock) -> Option<String> {
-                    for hash in &lock.lock.root {
-                        #[allow(clippy::collapsible_if)]
-                        if let Some(package) = lock.lock.packages.get(hash) {
-                            if let Some(output) = package.outputs.get(&lock.manifest.package.output) {
-                                // TODO: can change in future. Better make some
-                                //       universal solution.
-                                let module_key = format!("{}#module", output.hash.to_base32());
-
-                                return Some(module_key);
-                            }
-                        }
-                    }
-
-                    None
-                }
-
-                let Some(module_key) = find_module_key(&lock) else {
+                let Some(module_key) = lock.module_key() else {
                     tracing::error!(
                         url = lock.url,
                         ?name,
@@ -1393,6 +1496,12 @@ impl SimpleAsyncComponent for MainWindow {
                     }
                 };
 
+                self.downloads_page.emit(DownloadsPageInput::AddGame {
+                    name: name.clone(),
+                    title: title.to_string(),
+                    integration: game_integration.clone()
+                });
+
                 self.library_page.emit(LibraryPageInput::AddGame {
                     name,
                     package: lock,
@@ -1402,6 +1511,7 @@ impl SimpleAsyncComponent for MainWindow {
             }
 
             MainWindowMsg::DeleteGamePackage(name) => {
+                self.downloads_page.emit(DownloadsPageInput::RemoveGame(name.clone()));
                 self.library_page.emit(LibraryPageInput::DeleteGamePackage(name));
             }
 
@@ -1558,18 +1668,19 @@ impl SimpleAsyncComponent for MainWindow {
             }
 
             MainWindowMsg::ScheduleGameActionsPipeline {
+                game_variant,
                 game_name,
                 game_title,
                 actions_pipeline
             } => {
-                self.game_actions_pipeline_window.emit(GameActionsPipelineWindowInput::SetActionsPipeline {
+                self.downloads_page.emit(DownloadsPageInput::ScheduleActionsPipeline {
+                    variant: game_variant,
                     game_name,
                     game_title,
                     actions_pipeline
                 });
 
-                self.game_actions_pipeline_window.widget()
-                    .present(Some(&self.window));
+                self.view_stack.set_visible_child_name("downloads");
             }
 
             MainWindowMsg::ScheduleApplyGameComponents {
@@ -1581,9 +1692,9 @@ impl SimpleAsyncComponent for MainWindow {
                 uninstall_components,
                 delete_game_package
             } => {
-                self.game_apply_components_window.emit(GameApplyComponentsWindowInput::SetComponents {
-                    game_variant,
+                self.downloads_page.emit(DownloadsPageInput::ScheduleApplyComponents {
                     game_integration,
+                    game_variant,
                     game_name,
                     game_title,
                     install_components,
@@ -1591,10 +1702,46 @@ impl SimpleAsyncComponent for MainWindow {
                     delete_game_package
                 });
 
+                self.view_stack.set_visible_child_name("downloads");
+            }
+
+            MainWindowMsg::OpenGameActionsPipelineWindow { download_id, game_title } => {
+                self.game_actions_pipeline_window.emit(GameActionsPipelineWindowInput::SetDownload {
+                    id: download_id,
+                    game_title
+                });
+
+                self.game_actions_pipeline_window.widget()
+                    .present(Some(&self.window));
+            }
+
+            MainWindowMsg::OpenGameApplyComponentsWindow { download_id, game_title } => {
+                self.game_apply_components_window.emit(GameApplyComponentsWindowInput::SetDownload {
+                    id: download_id,
+                    game_title
+                });
+
                 self.game_apply_components_window.widget()
                     .present(Some(&self.window));
             }
 
+            MainWindowMsg::DownloadProgress { download_id, msg } => {
+                self.game_actions_pipeline_window.emit(GameActionsPipelineWindowInput::Progress {
+                    id: download_id,
+                    msg: msg.clone()
+                });
+
+                self.game_apply_components_window.emit(GameApplyComponentsWindowInput::Progress {
+                    id: download_id,
+                    msg
+                });
+            }
+
+            MainWindowMsg::DownloadStopped(download_id) => {
+                self.game_actions_pipeline_window.emit(GameActionsPipelineWindowInput::DownloadStopped(download_id));
+                self.game_apply_components_window.emit(GameApplyComponentsWindowInput::DownloadStopped(download_id));
+            }
+
             MainWindowMsg::OpenGameComponentsWindow {
                 integration,
                 variant,
@@ -1614,14 +1761,57 @@ impl SimpleAsyncComponent for MainWindow {
                     .present(Some(&self.window));
             }
 
+            MainWindowMsg::OpenGameModsWindow {
+                game_name,
+                directories
+            } => {
+                self.game_mods_window.emit(GameModsWindowInput::SetGame {
+                    game_name,
+                    directories
+                });
+
+                self.game_mods_window.widget()
+                    .present(Some(&self.window));
+            }
+
+            MainWindowMsg::OpenGameVerifyWindow {
+                integration,
+                variant
+            } => {
+                self.game_verify_window.emit(GameVerifyWindowInput::SetGame {
+                    integration,
+                    variant
+                });
+
+                self.game_verify_window.widget()
+                    .present(Some(&self.window));
+            }
+
+            MainWindowMsg::OpenGameStorageWindow {
+                game_name,
+                integration,
+                variant
+            } => {
+                self.game_storage_window.emit(GameStorageWindowInput::SetGame {
+                    game_name,
+                    integration,
+                    variant
+                });
+
+                self.game_storage_window.widget()
+                    .present(Some(&self.window));
+            }
+
             MainWindowMsg::OpenGameSettingsWindow {
                 integration,
                 variant,
+                game_name,
                 layout
             } => {
                 self.game_settings_window.emit(GameSettingsWindowInput::SetGame {
                     variant,
                     integration,
+                    game_name,
                     layout
                 });
 
@@ -1629,35 +1819,79 @@ impl SimpleAsyncComponent for MainWindow {
                     .present(Some(&self.window));
             }
 
-            MainWindowMsg::LaunchGame { game_title, game_launch_info } => {
-                let mut command = &mut Command::new(&game_launch_info.binary);
+            MainWindowMsg::LaunchGame {
+                game_name,
+                game_title,
+                mut game_launch_info,
+                launch_preset,
+                saves_directories
+            } => {
+                let config = config::get().await;
+
+                // Synchronize game saves before launching the game.
+                if config.saves_enable && !saves_directories.is_empty() {
+                    let game_name = game_name.clone();
+                    let saves_directories = saves_directories.clone();
+                    let config = config.clone();
 
-                if let Some(parent_folder) = game_launch_info.binary.parent()
-                    && parent_folder.is_dir()
-                {
-                    command = command.current_dir(parent_folder);
-                }
+                    let result = tasks::spawn_blocking(move || {
+                        saves::sync_game_saves(&game_name, &saves_directories, &config, saves::ask_conflict_resolution)
+                    }).await;
 
-                if let Some(args) = &game_launch_info.args {
-                    command = command.args(args);
-                }
+                    let result = result.map_err(|err| anyhow::anyhow!(err))
+                        .and_then(|result| result);
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to synchronize game saves");
 
-                if let Some(env) = &game_launch_info.env {
-                    command = command.envs(env);
+                        dialogs::error(
+                            i18n!("failed_sync_game_saves")
+                                .unwrap_or("Failed to synchronize game saves"),
+                            err.to_string()
+                        );
+                    }
                 }
 
-                if game_launch_info.stdout.is_some() {
-                    command = command.stdout(Stdio::piped());
-                } else {
-                    command = command.stdout(Stdio::inherit());
+                // Deploy enabled game mods while the game is running.
+                {
+                    let game_name = game_name.clone();
+
+                    let result = tasks::spawn_blocking(move || {
+                        mods::deploy_game_mods(&game_name)
+                    }).await;
+
+                    let result = result.map_err(|err| anyhow::anyhow!(err))
+                        .and_then(|result| result);
+
+                    if let Err(err) = result {
+                        tracing::error!(?err, "failed to deploy game mods");
+
+                        dialogs::error(
+                            i18n!("failed_deploy_game_mods")
+                                .unwrap_or("Failed to deploy game mods"),
+                            err.to_string()
+                        );
+                    }
                 }
 
-                if game_launch_info.stderr.is_some() {
-                    command = command.stderr(Stdio::piped());
-                } else {
-                    command = command.stderr(Stdio::inherit());
+                let wrappers = match GameLock::load(&game_name).await {
+                    Ok(lock) => lock.wrappers_chain(&config, launch_preset.as_ref()),
+
+                    Err(err) => {
+                        tracing::warn!(?err, ?game_name, "failed to load game lock, only global wrappers will be used");
+
+                        config.games_wrappers.clone()
+                    }
+                };
+
+                if let Some(preset) = &launch_preset {
+                    tracing::debug!(preset = preset.name.as_str(), "applying game launch preset");
+
+                    preset.apply(&mut game_launch_info);
                 }
 
+                let mut command = launch::build_command(&game_launch_info, &wrappers);
+
                 tracing::info!(?command, "launching game");
 
                 match command.spawn() {
@@ -1707,7 +1941,9 @@ impl SimpleAsyncComponent for MainWindow {
                         }
 
                         self.game_running_window.emit(GameRunningWindowMsg::SetChild {
+                            game_name,
                             game_title,
+                            saves_directories,
                             child
                         });
 
@@ -1715,7 +1951,15 @@ impl SimpleAsyncComponent for MainWindow {
                             .present(Some(&self.window));
                     }
 
-                    Err(err) => tracing::error!(?err, "failed to launch game")
+                    Err(err) => {
+                        tracing::error!(?err, "failed to launch game");
+
+                        tasks::spawn_blocking(move || {
+                            if let Err(err) = mods::undeploy_game_mods(&game_name) {
+                                tracing::error!(?err, "failed to undeploy game mods");
+                            }
+                        });
+                    }
                 }
             }
 
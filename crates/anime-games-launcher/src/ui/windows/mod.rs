@@ -20,6 +20,9 @@ pub mod main_window;
 pub mod game_actions_pipeline;
 pub mod game_components;
 pub mod game_apply_components;
+pub mod game_mods;
+pub mod game_verify;
+pub mod game_storage;
 pub mod game_settings;
 pub mod game_running;
 pub mod about;
@@ -52,3 +52,28 @@ pub fn pretty_seconds(mut seconds: u64) -> String {
 
     format!("{hours}:{minutes}:{seconds}")
 }
+
+/// Generate pretty size output from given bytes amount.
+///
+/// ```
+/// assert_eq!(pretty_bytes(512),         "512 B");
+/// assert_eq!(pretty_bytes(1536),        "1.50 KiB");
+/// assert_eq!(pretty_bytes(1073741824),  "1.00 GiB");
+/// ```
+pub fn pretty_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.2} {}", UNITS[unit])
+}
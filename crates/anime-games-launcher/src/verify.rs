@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+//
+// anime-games-launcher
+// Copyright (C) 2025 - 2026  Nikita Podvirnyi <krypt0nn@dawn.wine>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::io::{Read, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use agl_core::hashes::{Hasher, HashAlgorithm};
+use agl_games::api::{VerifyManifest, VerifyFile};
+
+/// Size of the buffer used to read verified files.
+const READ_CHUNK_LEN: usize = 1024 * 1024; // 1 MiB
+
+/// Result of the game files verification. Paths are relative to the verified
+/// game directory.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyResult {
+    /// Files listed in the manifest but missing in the game directory.
+    pub missing: Vec<PathBuf>,
+
+    /// Files which size or hash doesn't match the manifest.
+    pub corrupted: Vec<PathBuf>,
+
+    /// Files in the game directory which are not listed in the manifest.
+    pub extra: Vec<PathBuf>
+}
+
+impl VerifyResult {
+    /// List of files which should be repaired.
+    pub fn broken(&self) -> Vec<PathBuf> {
+        self.missing.iter()
+            .chain(self.corrupted.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Check whether all the game files are in place and not corrupted.
+    /// Extra files are not considered a problem.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.corrupted.is_empty()
+    }
+}
+
+/// Verify game files from the manifest using all the available CPU cores.
+///
+/// Progress callback receives amount of checked and total bytes. Files with
+/// unknown size are counted as empty.
+pub fn verify_files(
+    manifest: &VerifyManifest,
+    progress: impl Fn(u64, u64) + Send + Sync
+) -> anyhow::Result<VerifyResult> {
+    let total = manifest.files.iter()
+        .filter_map(|file| file.size)
+        .sum::<u64>();
+
+    let checked = AtomicU64::new(0);
+    let next_file = AtomicUsize::new(0);
+
+    let missing = Mutex::new(Vec::new());
+    let corrupted = Mutex::new(Vec::new());
+
+    let workers = std::thread::available_parallelism()
+        .map(|workers| workers.get())
+        .unwrap_or(1)
+        .min(manifest.files.len())
+        .max(1);
+
+    std::thread::scope(|scope| -> anyhow::Result<()> {
+        let mut handles = Vec::with_capacity(workers);
+
+        for _ in 0..workers {
+            handles.push(scope.spawn(|| -> anyhow::Result<()> {
+                loop {
+                    let Some(file) = manifest.files.get(next_file.fetch_add(1, Ordering::Relaxed)) else {
+                        return Ok(());
+                    };
+
+                    let path = manifest.path.join(&file.path);
+
+                    if !path.is_file() {
+                        missing.lock()
+                            .map_err(|err| anyhow::anyhow!("failed to lock missing files list: {err}"))?
+                            .push(file.path.clone());
+                    }
+
+                    else if !check_file(manifest, file, &path)? {
+                        corrupted.lock()
+                            .map_err(|err| anyhow::anyhow!("failed to lock corrupted files list: {err}"))?
+                            .push(file.path.clone());
+                    }
+
+                    if let Some(size) = file.size {
+                        progress(checked.fetch_add(size, Ordering::Relaxed) + size, total);
+                    }
+                }
+            }));
+        }
+
+        for handle in handles {
+            handle.join()
+                .map_err(|_| anyhow::anyhow!("game files verification thread panicked"))??;
+        }
+
+        Ok(())
+    })?;
+
+    let mut result = VerifyResult {
+        missing: missing.into_inner()
+            .map_err(|err| anyhow::anyhow!("failed to lock missing files list: {err}"))?,
+
+        corrupted: corrupted.into_inner()
+            .map_err(|err| anyhow::anyhow!("failed to lock corrupted files list: {err}"))?,
+
+        extra: extra_files(manifest)?
+    };
+
+    result.missing.sort();
+    result.corrupted.sort();
+    result.extra.sort();
+
+    Ok(result)
+}
+
+/// Check size and hash of the existing game file.
+fn check_file(
+    manifest: &VerifyManifest,
+    file: &VerifyFile,
+    path: &Path
+) -> anyhow::Result<bool> {
+    if let Some(size) = file.size
+        && path.metadata()?.len() != size
+    {
+        return Ok(false);
+    }
+
+    let Some(expected_hash) = &file.hash else {
+        return Ok(true);
+    };
+
+    Ok(&hash_file(path, manifest.algorithm)? == expected_hash)
+}
+
+/// Calculate hex-encoded hash of the file's content.
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Hasher::new(algorithm);
+
+    let mut buf = vec![0; READ_CHUNK_LEN];
+
+    loop {
+        let n = file.read(&mut buf)?;
+
+        if n == 0 {
+            break;
+        }
+
+        hasher.write_all(&buf[..n])?;
+    }
+
+    hasher.flush()?;
+
+    Ok(hasher.finalize().0
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Find files in the game directory which are not listed in the manifest.
+fn extra_files(manifest: &VerifyManifest) -> anyhow::Result<Vec<PathBuf>> {
+    let expected = manifest.files.iter()
+        .map(|file| file.path.as_path())
+        .collect::<HashSet<_>>();
+
+    let mut extra = Vec::new();
+    let mut folders = vec![manifest.path.clone()];
+
+    while let Some(folder) = folders.pop() {
+        if !folder.is_dir() {
+            continue;
+        }
+
+        for entry in folder.read_dir()? {
+            let path = entry?.path();
+
+            let Ok(relative_path) = path.strip_prefix(&manifest.path) else {
+                continue;
+            };
+
+            if manifest.is_ignored(relative_path) {
+                continue;
+            }
+
+            if path.is_dir() && !path.is_symlink() {
+                folders.push(path);
+            }
+
+            else if !expected.contains(relative_path) {
+                extra.push(relative_path.to_path_buf());
+            }
+        }
+    }
+
+    Ok(extra)
+}
@@ -17,6 +17,7 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 use tracing_subscriber::prelude::*;
@@ -33,15 +34,21 @@ use agl_locale::string::LocalizableString;
 use agl_locale::SYSTEM_LANG;
 use agl_packages::hash::Hash;
 use agl_packages::format::ResourceFormat;
-use agl_packages::storage::Storage;
+use agl_packages::storage::{Storage, InstallOptions};
+use agl_packages::torrent::TorrentSource;
+use agl_packages::lan::LanCache;
 use agl_packages::lock::Lock;
 use agl_runtime::mlua::prelude::*;
 use agl_runtime::runtime::{Runtime, ModulePaths};
 use agl_runtime::module::{Module, ModuleScope};
 use agl_runtime::scopes_list::ScopesList;
 use agl_runtime::api::{ApiContext, ApiOptions};
+use agl_runtime::api::downloader_api::DownloaderTasks;
 use agl_runtime::api::portal_api::ToastOptions;
-use agl_runtime::api::torrent_api::{TorrentServer, TorrentServerOptions};
+use agl_runtime::api::torrent_api::{
+    TorrentServer, TorrentServerOptions, TorrentAddOptions, TorrentLimits
+};
+use agl_runtime::api::torrent_file::{TorrentFileOptions, TorrentVersion};
 
 pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -49,7 +56,7 @@ pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-#[derive(Debug, Clone, PartialEq, Eq, Parser)]
+#[derive(Debug, Clone, PartialEq, Parser)]
 #[command(author = "Nikita Podvirnyi <krypt0nn@dawn.wine>")]
 struct Cli {
     #[arg(long, alias = "resources")]
@@ -87,7 +94,7 @@ struct Cli {
     pub command: CliCommands
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Parser)]
+#[derive(Debug, Clone, PartialEq, Parser)]
 #[command(author = "Nikita Podvirnyi <krypt0nn@dawn.wine>")]
 enum CliCommands {
     /// Packages manager commands.
@@ -96,10 +103,14 @@ enum CliCommands {
 
     /// Luau modules runtime commands.
     #[command(subcommand)]
-    Module(CliModuleCommands)
+    Module(CliModuleCommands),
+
+    /// BitTorrent commands.
+    #[command(subcommand)]
+    Torrent(CliTorrentCommands)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Parser)]
+#[derive(Debug, Clone, PartialEq, Parser)]
 enum CliPackageCommands {
     /// Download packages.
     Download {
@@ -115,7 +126,24 @@ enum CliPackageCommands {
 
         /// Name of the output lock file.
         #[arg(short, long, alias = "lock", alias = "name")]
-        lock_name: Option<String>
+        lock_name: Option<String>,
+
+        /// Download resources which have torrent sources over BitTorrent
+        /// protocol.
+        #[arg(long)]
+        use_torrents: bool,
+
+        /// Share resources with other launchers in the local network and
+        /// fetch resources from them.
+        #[arg(long)]
+        lan_cache: bool,
+
+        /// TCP port of the local network resources server.
+        #[arg(long, default_value_t = 0)]
+        lan_cache_port: u16,
+
+        #[command(flatten)]
+        torrent: TorrentOptionsCli
     },
 
     /// Run luau modules stored as outputs of the package lock file in the
@@ -139,7 +167,7 @@ enum CliPackageCommands {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Parser)]
+#[derive(Debug, Clone, PartialEq, Parser)]
 enum CliModuleCommands {
     /// Run luau module in the modules runtime.
     Run {
@@ -162,6 +190,47 @@ enum CliModuleCommands {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Parser)]
+enum CliTorrentCommands {
+    /// Create torrent file and magnet link from a local file or folder.
+    Create {
+        /// Path to the file or folder.
+        path: PathBuf,
+
+        /// Path to the output torrent file. `<name>.torrent` is used if unset.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Name of the torrent. Name of the file or folder is used if unset.
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Size of a torrent piece in bytes. Must be a power of two not lower
+        /// than 16 KiB. Chosen automatically if unset.
+        #[arg(long)]
+        piece_size: Option<u32>,
+
+        /// URL to the torrent tracker.
+        #[arg(long)]
+        tracker: Vec<String>,
+
+        /// Mark torrent as private.
+        #[arg(long)]
+        private: bool,
+
+        /// Version of the torrent file: `v1`, `v2` or `hybrid`.
+        #[arg(long, default_value = "v1")]
+        torrent_version: TorrentVersion,
+
+        /// Seed created torrent until the process is stopped.
+        #[arg(long)]
+        seed: bool,
+
+        #[command(flatten)]
+        torrent: TorrentOptionsCli
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Parser)]
 struct CliModuleScope {
     /// Allow module to access string API.
@@ -364,7 +433,7 @@ impl From<CliModuleScope> for ModuleScope {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Parser)]
+#[derive(Debug, Clone, PartialEq, Parser)]
 struct TorrentOptionsCli {
     /// Default path to the folder where torrents should be downloaded.
     #[arg(long)]
@@ -390,7 +459,28 @@ struct TorrentOptionsCli {
 
     /// URL to the torrent tracker.
     #[arg(long)]
-    pub torrent_tracker: Vec<String>
+    pub torrent_tracker: Vec<String>,
+
+    /// Path to the folder where torrents session should be stored. Added
+    /// torrents are not remembered if unset.
+    #[arg(long)]
+    pub torrent_session_path: Option<PathBuf>,
+
+    /// Download speed limit in bytes per second.
+    #[arg(long)]
+    pub torrent_download_limit: Option<u32>,
+
+    /// Upload speed limit in bytes per second.
+    #[arg(long)]
+    pub torrent_upload_limit: Option<u32>,
+
+    /// Stop seeding torrents when upload/download ratio reaches this value.
+    #[arg(long)]
+    pub torrent_seed_ratio: Option<f64>,
+
+    /// Stop seeding torrents after this amount of seconds.
+    #[arg(long)]
+    pub torrent_seed_time: Option<u64>
 }
 
 fn translate(str: LocalizableString) -> String {
@@ -400,11 +490,16 @@ fn translate(str: LocalizableString) -> String {
 fn build_client(
     proxy: Option<String>,
     user_agent: Option<String>,
-    timeout: Option<Duration>
+    timeout: Option<Duration>,
+    follow_redirects: bool
 ) -> anyhow::Result<reqwest::Client> {
     let mut client = reqwest::ClientBuilder::new()
         .user_agent(user_agent.unwrap_or_else(|| format!("anirun/v{APP_VERSION}")));
 
+    if !follow_redirects {
+        client = client.redirect(reqwest::redirect::Policy::none());
+    }
+
     if let Some(proxy) = &proxy {
         let proxy = reqwest::Proxy::all(proxy)
             .context("failed to build proxy")?;
@@ -419,34 +514,55 @@ fn build_client(
     client.build().context("failed to build HTTP client")
 }
 
+fn start_torrent_server(
+    temp_dir: &Path,
+    proxy: Option<String>,
+    options: TorrentOptionsCli
+) -> TorrentServer {
+    TorrentServer::start(TorrentServerOptions {
+        default_folder: options.torrent_folder
+            .unwrap_or_else(|| temp_dir.to_path_buf()),
+
+        socks_proxy: proxy.and_then(|proxy| {
+            proxy.starts_with("socks")
+                .then_some(proxy)
+        }),
+
+        trackers: options.torrent_tracker.into_iter().collect(),
+        blocklist_url: options.torrent_blocklist_url,
+
+        enable_dht: options.torrent_enable_dht,
+        enable_upnp: options.torrent_enable_upnp,
+
+        persistence_folder: options.torrent_session_path,
+
+        limits: TorrentLimits {
+            download_speed: options.torrent_download_limit,
+            upload_speed: options.torrent_upload_limit,
+            seed_ratio: options.torrent_seed_ratio,
+            seed_time: options.torrent_seed_time
+        }
+    })
+}
+
 fn build_runtime(
     temp_dir: &Path,
     secrets_file: PathBuf,
     proxy: Option<String>,
     torrent: Option<TorrentOptionsCli>,
-    reqwest_client: reqwest::Client
+    reqwest_client: reqwest::Client,
+    http_client: reqwest::Client
 ) -> anyhow::Result<Runtime> {
     let options = ApiOptions {
         lua: Lua::new(),
 
         reqwest_client,
+        http_client,
 
-        torrent_server: torrent.map(|options| {
-            TorrentServer::start(TorrentServerOptions {
-                default_folder: options.torrent_folder
-                    .unwrap_or_else(|| temp_dir.to_path_buf()),
-
-                socks_proxy: proxy.and_then(|proxy| {
-                    proxy.starts_with("socks")
-                        .then_some(proxy)
-                }),
+        downloader_tasks: DownloaderTasks::new(),
 
-                trackers: options.torrent_tracker.into_iter().collect(),
-                blocklist_url: options.torrent_blocklist_url,
-
-                enable_dht: options.torrent_enable_dht,
-                enable_upnp: options.torrent_enable_upnp
-            })
+        torrent_server: torrent.map(|options| {
+            start_torrent_server(temp_dir, proxy, options)
         }),
 
         show_toast: Box::new(|options| {
@@ -596,25 +712,59 @@ fn main() -> anyhow::Result<()> {
         *path = path.canonicalize()?;
     }
 
-    // Build reqwest client.
+    // Build reqwest clients.
     let client = build_client(
+        cli.proxy.clone(),
+        cli.user_agent.clone(),
+        cli.timeout.map(Duration::from_millis),
+        true
+    )?;
+
+    let http_client = build_client(
         cli.proxy.clone(),
         cli.user_agent,
-        cli.timeout.map(Duration::from_millis)
+        cli.timeout.map(Duration::from_millis),
+        false
     )?;
 
     // Process the parsed command.
     match cli.command {
         CliCommands::Package(command) => match command {
-            CliPackageCommands::Download { source, lock_name } => {
+            CliPackageCommands::Download {
+                source,
+                lock_name,
+                use_torrents,
+                lan_cache,
+                lan_cache_port,
+                torrent
+            } => {
                 let storage = Storage::open(&resources_dir)
                     .context("failed to open resources storage")?;
 
                 let downloader = Downloader::from_client(client.clone());
 
+                let options = InstallOptions {
+                    torrent_source: use_torrents.then(|| {
+                        Arc::new(start_torrent_server(&temp_dir, cli.proxy.clone(), torrent)) as Arc<dyn TorrentSource>
+                    }),
+
+                    lan_cache: if lan_cache {
+                        let lan_cache = LanCache::start(storage.clone(), lan_cache_port)
+                            .context("failed to start LAN cache")?;
+
+                        // Give other launchers some time to respond to our
+                        // announcement.
+                        std::thread::sleep(Duration::from_secs(2));
+
+                        Some(lan_cache)
+                    } else {
+                        None
+                    }
+                };
+
                 tracing::info!("downloading packages");
 
-                let lock = tasks::block_on(storage.install_packages(&downloader, source))
+                let lock = tasks::block_on(storage.install_packages_with_options(&downloader, source, options))
                     .context("failed to install packages")?;
 
                 let lock_name = lock_name.unwrap_or_else(|| {
@@ -676,7 +826,8 @@ fn main() -> anyhow::Result<()> {
                     secret_file,
                     cli.proxy.clone(),
                     scope.torrent_api.and_then(|enabled| enabled.then_some(torrent)),
-                    client
+                    client,
+                    http_client
                 )?;
 
                 tracing::info!("preparing allow list");
@@ -754,7 +905,8 @@ fn main() -> anyhow::Result<()> {
                     secret_file,
                     cli.proxy.clone(),
                     scope.torrent_api.and_then(|enabled| enabled.then_some(torrent)),
-                    client
+                    client,
+                    http_client
                 )?;
 
                 let module = Module {
@@ -785,6 +937,88 @@ fn main() -> anyhow::Result<()> {
                 );
             }
         }
+
+        CliCommands::Torrent(command) => match command {
+            CliTorrentCommands::Create {
+                path,
+                output,
+                name,
+                piece_size,
+                tracker,
+                private,
+                torrent_version,
+                seed,
+                torrent
+            } => {
+                if seed && !torrent_version.has_v1() {
+                    anyhow::bail!("v2 torrents cannot be seeded, use hybrid torrents instead");
+                }
+
+                let path = path.canonicalize()
+                    .context("failed to resolve torrent path")?;
+
+                tracing::info!(?path, version = %torrent_version, "creating torrent");
+
+                let torrent_file = agl_runtime::api::torrent_file::create_torrent(&path, &TorrentFileOptions {
+                    name,
+                    piece_size,
+                    trackers: tracker,
+                    private,
+                    version: torrent_version
+                }).context("failed to create torrent")?;
+
+                let output = output.unwrap_or_else(|| {
+                    PathBuf::from(format!("{}.torrent", torrent_file.name))
+                });
+
+                std::fs::write(&output, &torrent_file.content)
+                    .context("failed to save torrent file")?;
+
+                tracing::info!(
+                    ?output,
+                    info_hash = %torrent_file.info_hash(),
+                    magnet = %torrent_file.magnet(),
+                    "torrent created"
+                );
+
+                if seed {
+                    let torrent_server = start_torrent_server(&temp_dir, cli.proxy, torrent);
+
+                    // Single file torrents are stored under their name in the
+                    // output folder.
+                    let output_folder = if path.is_file() {
+                        path.parent()
+                            .map(PathBuf::from)
+                            .unwrap_or_default()
+                    } else {
+                        path
+                    };
+
+                    let info_hash = torrent_server.add_torrent(
+                        torrent_file.content.into_boxed_slice(),
+                        output_folder,
+                        TorrentAddOptions::default()
+                    ).context("failed to seed torrent")?;
+
+                    tracing::info!(%info_hash, "seeding torrent, press Ctrl+C to stop");
+
+                    loop {
+                        std::thread::sleep(Duration::from_secs(30));
+
+                        let torrents = torrent_server.list()
+                            .context("failed to list torrents")?;
+
+                        if let Some(info) = torrents.iter().find(|info| info.info_hash == info_hash) {
+                            tracing::info!(
+                                uploaded = info.stats.uploaded,
+                                upload_speed = info.stats.upload_speed,
+                                "seeding torrent"
+                            );
+                        }
+                    }
+                }
+            }
+        }
     }
 
     Ok(())